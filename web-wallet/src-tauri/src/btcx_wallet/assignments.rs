@@ -21,7 +21,7 @@
 //! Wire DTOs are snake_case to match the node RPCs exactly — the Angular
 //! forging page consumes either backend through one interface.
 
-use bdk_wallet::{SignOptions, TxOrdering};
+use bdk_wallet::TxOrdering;
 use bitcoin::hashes::Hash;
 use bitcoin::script::PushBytesBuf;
 use bitcoin::{ScriptBuf, Sequence, Transaction};
@@ -31,6 +31,7 @@ use electrum_btcx::{ElectrumBackend, SendFee};
 use wallet_btcx::BdkWalletBackend;
 
 use super::config::WalletNetwork;
+use super::spend;
 use super::state::SharedBtcxWalletState;
 
 /// Assignment OP_RETURN marker ("Proof of Capacity neXt").
@@ -119,12 +120,8 @@ pub struct AssignmentStatusDto {
     pub revocation_effective_height: Option<u64>,
 }
 
-/// Build, sign and broadcast one marker transaction (assignment or
-/// revocation): forces the largest plot-address UTXO in as the ownership
-/// proof, puts the single-push OP_RETURN at `output[0]`
-/// (`TxOrdering::Untouched` — bdk's default shuffle would displace it),
-/// lets bdk add fee inputs/change, signs RBF (parity with the node wallet,
-/// which broadcasts assignments replaceable).
+/// Build, sign and broadcast one marker transaction (see
+/// [`build_marker`]). Returns `(txid, hex)`.
 fn build_and_send_marker(
     state: &SharedBtcxWalletState,
     backend: &BdkWalletBackend,
@@ -132,35 +129,75 @@ fn build_and_send_marker(
     payload: Vec<u8>,
     fee_rate_sat_vb: Option<f64>,
 ) -> Result<(String, String), String> {
-    state.ensure_first_sync()?;
+    let tx = build_marker(state, backend, plot_spk, payload, fee_rate_sat_vb)?;
+
+    // Broadcast home-first with view fallback, then fold our own tx into
+    // the cache and persist (see `spend`).
+    let txid = spend::broadcast_and_record(state, backend, &tx)?;
+
+    let hex = bitcoin::consensus::encode::serialize_hex(&tx);
+    Ok((txid, hex))
+}
+
+/// Build and sign one marker transaction (assignment or revocation):
+/// forces the largest plot-address UTXO in as the ownership proof, puts
+/// the single-push OP_RETURN at `output[0]` (`TxOrdering::Untouched` —
+/// bdk's default shuffle would displace it), lets bdk add fee
+/// inputs/change, signs RBF (parity with the node wallet, which
+/// broadcasts assignments replaceable).
+fn build_marker(
+    state: &SharedBtcxWalletState,
+    backend: &BdkWalletBackend,
+    plot_spk: &ScriptBuf,
+    payload: Vec<u8>,
+    fee_rate_sat_vb: Option<f64>,
+) -> Result<Transaction, String> {
+    let control = spend::coin_control(state)?;
 
     let fee = match fee_rate_sat_vb {
         Some(rate) => SendFee::RatePerKvb((rate * 1000.0).round().max(0.0) as u64),
         None => SendFee::Target(6),
     };
-    // sat/kvB → sat/kwu, the same rounding wallet-btcx uses.
-    let feerate_kvb = backend
-        .chain()
-        .resolve_send_fee(fee)
-        .map_err(|e| format!("{e:#}"))?;
-    let feerate = bitcoin::FeeRate::from_sat_per_kwu((feerate_kvb + 2) / 4);
+    let feerate = spend::resolve_feerate(backend, fee)?;
 
     let push = PushBytesBuf::try_from(payload)
         .map_err(|_| "marker payload exceeds push limits".to_string())?;
 
-    let tx = state.with_entry(|entry| {
+    state.with_entry(|entry| {
         // Ownership proof: the LARGEST spendable UTXO on the plot address
-        // (mirrors the node wallet's coin selection for assignments).
-        let plot_utxo = entry
+        // (mirrors the node wallet's coin selection for assignments). Frozen
+        // coins are never picked — not as the proof, not as fee inputs;
+        // unverified receives may be the proof but never pay the fee; coins
+        // a live preview holds are left to its commit.
+        let plot_utxos: Vec<_> = entry
             .wallet
             .list_unspent()
             .filter(|u| u.txout.script_pubkey == *plot_spk)
+            .collect();
+        let plot_utxo = plot_utxos
+            .iter()
+            .filter(|u| {
+                !control.frozen.contains(&u.outpoint) && !control.reserved.contains(&u.outpoint)
+            })
             .max_by_key(|u| u.txout.value)
             .ok_or_else(|| {
-                "The plot address has no spendable coins in this wallet — send a small \
-                 amount to it first (the assignment must be signed by a coin on the plot \
-                 address itself)"
-                    .to_string()
+                if plot_utxos.is_empty() {
+                    "The plot address has no spendable coins in this wallet — send a small \
+                     amount to it first (the assignment must be signed by a coin on the plot \
+                     address itself)"
+                        .to_string()
+                } else if plot_utxos
+                    .iter()
+                    .all(|u| control.frozen.contains(&u.outpoint))
+                {
+                    "Every coin on the plot address is frozen — unfreeze one to sign the \
+                     assignment with it"
+                        .to_string()
+                } else {
+                    "Every coin on the plot address is held by a pending preview — commit or \
+                     discard it first"
+                        .to_string()
+                }
             })?;
 
         let mut builder = entry.wallet.build_tx();
@@ -172,20 +209,11 @@ fn build_and_send_marker(
         builder
             .add_utxo(plot_utxo.outpoint)
             .map_err(|e| format!("selecting the plot UTXO: {e}"))?;
-        let mut psbt = builder
+        builder.unspendable(control.unspendable(&[plot_utxo.outpoint]));
+        let psbt = builder
             .finish()
             .map_err(|e| format!("building the transaction: {e}"))?;
-
-        let done = entry
-            .wallet
-            .sign(&mut psbt, SignOptions::default())
-            .map_err(|e| format!("signing: {e}"))?;
-        if !done {
-            return Err("wallet could not finalize the transaction".to_string());
-        }
-        let tx = psbt
-            .extract_tx()
-            .map_err(|e| format!("extracting the transaction: {e}"))?;
+        let tx = spend::sign_and_extract(entry, psbt)?;
 
         // Consensus sanity before anything leaves this process: exactly one
         // marker OP_RETURN, at output[0], and the ownership input present.
@@ -198,61 +226,38 @@ fn build_and_send_marker(
             return Err("internal error: malformed marker transaction".to_string());
         }
         Ok(tx)
-    })?;
+    })
+}
 
-    // Broadcast home-first with view fallback (any acceptance wins), then
-    // fold our own tx into the cache and persist — broadcast-before-persist,
-    // like wallet-btcx sends: a crash re-learns the tx from our own spk
-    // history on the next sync.
-    let txid = fan_broadcast(backend, &tx)?;
-    state.with_entry(|entry| {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-        entry.wallet.apply_unconfirmed_txs([(tx.clone(), now)]);
-        entry
-            .wallet
-            .persist(&mut entry.conn)
-            .map_err(|e| format!("persisting wallet: {e}"))?;
-        Ok(())
-    })?;
-    let _ = state.poke();
+/// The plot script and OP_RETURN payload of an assignment.
+fn assignment_marker(
+    network: WalletNetwork,
+    plot_address: &str,
+    forging_address: &str,
+) -> Result<(ScriptBuf, Vec<u8>), String> {
+    let plot20 = p2wpkh_program(network, plot_address, "plot address")?;
+    let forge20 = p2wpkh_program(network, forging_address, "forging address")?;
+    let plot_spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array(plot20));
 
-    let hex = bitcoin::consensus::encode::serialize_hex(&tx);
-    Ok((txid, hex))
+    let mut payload = Vec::with_capacity(44);
+    payload.extend_from_slice(ASSIGN_MARKER);
+    payload.extend_from_slice(&plot20);
+    payload.extend_from_slice(&forge20);
+    Ok((plot_spk, payload))
 }
 
-/// Broadcast through the home server, falling over to the healthy views —
-/// any acceptance wins (the wallet-btcx `broadcast_fan` pattern; that
-/// method is private, so it is reproduced over the backend's public
-/// chain/views accessors).
-fn fan_broadcast(backend: &BdkWalletBackend, tx: &Transaction) -> Result<String, String> {
-    let mut last_err: Option<String> = None;
-    let mut tried = false;
-    for server in std::iter::once(backend.chain()).chain(backend.views().iter()) {
-        if !server.health().available() {
-            continue;
-        }
-        tried = true;
-        match server.broadcast(tx) {
-            Ok(txid) => return Ok(txid.to_string()),
-            Err(e) => last_err = Some(format!("{e:#}")),
-        }
-    }
-    if !tried {
-        // Everything is inside a backoff window: dial the home anyway
-        // rather than stranding a signed transaction.
-        return backend
-            .chain()
-            .broadcast(tx)
-            .map(|txid| txid.to_string())
-            .map_err(|e| format!("{e:#}"));
-    }
-    Err(format!(
-        "Transaction rejected — {}",
-        last_err.expect("tried at least one server")
-    ))
+/// The plot script and OP_RETURN payload of a revocation.
+fn revocation_marker(
+    network: WalletNetwork,
+    plot_address: &str,
+) -> Result<(ScriptBuf, Vec<u8>), String> {
+    let plot20 = p2wpkh_program(network, plot_address, "plot address")?;
+    let plot_spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array(plot20));
+
+    let mut payload = Vec::with_capacity(24);
+    payload.extend_from_slice(REVOKE_MARKER);
+    payload.extend_from_slice(&plot20);
+    Ok((plot_spk, payload))
 }
 
 /// Create a forging assignment: delegate `plot_address`'s forging rights to
@@ -264,14 +269,7 @@ pub fn create_assignment(
     fee_rate_sat_vb: Option<f64>,
 ) -> Result<CreateAssignmentDto, String> {
     let network = state.get_config().network;
-    let plot20 = p2wpkh_program(network, plot_address, "plot address")?;
-    let forge20 = p2wpkh_program(network, forging_address, "forging address")?;
-    let plot_spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array(plot20));
-
-    let mut payload = Vec::with_capacity(44);
-    payload.extend_from_slice(ASSIGN_MARKER);
-    payload.extend_from_slice(&plot20);
-    payload.extend_from_slice(&forge20);
+    let (plot_spk, payload) = assignment_marker(network, plot_address, forging_address)?;
 
     let backend = state.backend()?;
     let (txid, hex) = build_and_send_marker(state, &backend, &plot_spk, payload, fee_rate_sat_vb)?;
@@ -290,12 +288,7 @@ pub fn revoke_assignment(
     fee_rate_sat_vb: Option<f64>,
 ) -> Result<RevokeAssignmentDto, String> {
     let network = state.get_config().network;
-    let plot20 = p2wpkh_program(network, plot_address, "plot address")?;
-    let plot_spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array(plot20));
-
-    let mut payload = Vec::with_capacity(24);
-    payload.extend_from_slice(REVOKE_MARKER);
-    payload.extend_from_slice(&plot20);
+    let (plot_spk, payload) = revocation_marker(network, plot_address)?;
 
     let backend = state.backend()?;
     let (txid, hex) = build_and_send_marker(state, &backend, &plot_spk, payload, fee_rate_sat_vb)?;
@@ -306,6 +299,32 @@ pub fn revoke_assignment(
     })
 }
 
+/// Build and sign an assignment without broadcasting it (the preview
+/// step, see `preview`).
+pub fn build_assignment(
+    state: &SharedBtcxWalletState,
+    plot_address: &str,
+    forging_address: &str,
+    fee_rate_sat_vb: Option<f64>,
+) -> Result<Transaction, String> {
+    let network = state.get_config().network;
+    let (plot_spk, payload) = assignment_marker(network, plot_address, forging_address)?;
+    let backend = state.backend()?;
+    build_marker(state, &backend, &plot_spk, payload, fee_rate_sat_vb)
+}
+
+/// Build and sign a revocation without broadcasting it (the preview step).
+pub fn build_revocation(
+    state: &SharedBtcxWalletState,
+    plot_address: &str,
+    fee_rate_sat_vb: Option<f64>,
+) -> Result<Transaction, String> {
+    let network = state.get_config().network;
+    let (plot_spk, payload) = revocation_marker(network, plot_address)?;
+    let backend = state.backend()?;
+    build_marker(state, &backend, &plot_spk, payload, fee_rate_sat_vb)
+}
+
 /// One marker found in a transaction output.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Marker {
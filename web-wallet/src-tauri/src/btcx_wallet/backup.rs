@@ -0,0 +1,589 @@
+//! Encrypted full-wallet backup bundles.
+//!
+//! A nodeless wallet is spread over the registry in
+//! `btcx_wallet_config.json` (policy, source, group membership) and each
+//! member's data dir (`seed.mnemonic` + `seed.passphrase`, or
+//! `descriptor.secret` / `descriptor.public`, plus the `wallet/` side
+//! files). A bundle captures one wallet group — or every wallet of a
+//! network — in ONE passphrase-sealed file, so moving wallets between
+//! machines no longer means copying directories by hand.
+//!
+//! ## Format note (one line)
+//!
+//! `PHXBACKUPv1:<salt>:<nonce>:<ct>` — the `descstore` passphrase scheme
+//! (scrypt N=2^15, r=8, p=1 + ChaCha20-Poly1305, hex fields) under its own
+//! magic. The plaintext is the JSON [`BackupBundle`]; key material inside
+//! it is PLAINTEXT (mnemonics, private descriptors), which is why the
+//! bundle passphrase is mandatory.
+//!
+//! Restoring recreates the registry entries, group membership, key files
+//! and side files, then reopens the selected wallet WITHOUT a restore
+//! probe: the bundle already says which branch every compartment is on.
+//! The bdk stores are not bundled — they rebuild on the first sync.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use seedstore::SeedStore;
+
+use super::config::{self, BtcxWalletConfig, WalletMeta, WalletNetwork, WalletSourceCfg};
+use super::descstore::{self, DescStore, DescriptorPayload};
+use super::labels::LABELS_FILE;
+use super::payreq::PAYMENT_REQUESTS_FILE;
+use super::replacements::REPLACEMENTS_FILE;
+use super::state::{BtcxWalletStatus, SharedBtcxWalletState};
+
+/// Magic of a sealed backup bundle.
+const BACKUP_MAGIC: &str = "PHXBACKUPv1";
+
+/// Format version of the bundle JSON.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// The `wallet/` side files a bundle carries — and the ONLY names a
+/// restore writes (a crafted bundle cannot place files elsewhere).
+const SIDE_FILES: [&str; 3] = [LABELS_FILE, REPLACEMENTS_FILE, PAYMENT_REQUESTS_FILE];
+
+/// One wallet's key material, by source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum BackupKeys {
+    /// BIP39 mnemonic plus the (possibly empty) 25th word.
+    Seed {
+        mnemonic: String,
+        #[serde(default)]
+        bip39_passphrase: String,
+    },
+    /// Imported private descriptors.
+    Descriptor { payload: DescriptorPayload },
+    /// Watch-only public descriptors.
+    WatchOnly { payload: DescriptorPayload },
+}
+
+/// One wallet of a bundle: its registry entry, keys, and side files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupWallet {
+    pub name: String,
+    /// Registry entry; `group` always set explicitly, balance snapshot
+    /// dropped (stale the moment it lands elsewhere).
+    pub meta: WalletMeta,
+    pub keys: BackupKeys,
+    /// `wallet/` side file name → contents (only files that exist).
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+/// The plaintext of a backup file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupBundle {
+    pub version: u32,
+    pub network: WalletNetwork,
+    /// Unix seconds at export (display only).
+    pub created_at: u64,
+    /// The wallet that was active at export, when it is in the bundle —
+    /// restored as the active wallet.
+    #[serde(default)]
+    pub active: Option<String>,
+    pub wallets: Vec<BackupWallet>,
+}
+
+impl BackupBundle {
+    /// Seal the bundle under `passphrase` (required, non-empty).
+    pub fn seal(&self, passphrase: &str) -> Result<String, String> {
+        if passphrase.is_empty() {
+            return Err("A backup passphrase is required".into());
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("serializing backup: {e}"))?;
+        descstore::seal_with_passphrase(BACKUP_MAGIC, &json, passphrase)
+    }
+
+    /// Open a sealed bundle.
+    pub fn open(sealed: &str, passphrase: &str) -> Result<Self, String> {
+        let json = descstore::open_with_passphrase(BACKUP_MAGIC, sealed, passphrase)
+            .map_err(|e| format!("Cannot open backup: {e}"))?;
+        let bundle: Self =
+            serde_json::from_str(&json).map_err(|e| format!("parsing backup: {e}"))?;
+        if bundle.version != BACKUP_VERSION {
+            return Err(format!(
+                "Unsupported backup version {} (this build reads {BACKUP_VERSION})",
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+}
+
+/// Outcome of a bundle restore.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupRestoreResult {
+    pub status: BtcxWalletStatus,
+    /// Every restored wallet name, bundle order.
+    pub restored: Vec<String>,
+    /// The wallet now active.
+    pub active: String,
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Read one member's key material. The ACTIVE wallet reads through the
+/// state's held store (an unlocked wallet needs no passphrase again); the
+/// others open standalone and, when passphrase-encrypted, need
+/// `wallet_passphrase` (a group shares one at-rest passphrase).
+fn read_keys(
+    state: &SharedBtcxWalletState,
+    config: &BtcxWalletConfig,
+    name: &str,
+    source: WalletSourceCfg,
+    wallet_passphrase: Option<&str>,
+) -> Result<BackupKeys, String> {
+    let network = config.network;
+    let root = BtcxWalletConfig::wallet_root(network, name);
+    let active = name == config.active_wallet_name();
+    let locked = || format!("'{name}' is passphrase-encrypted — supply its passphrase");
+    match source {
+        WalletSourceCfg::Seed => {
+            let held = active
+                .then(|| state.with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}"))))
+                .and_then(Result::ok);
+            let mnemonic = match held {
+                Some(mnemonic) => mnemonic,
+                None => {
+                    let mut store = SeedStore::open(&root, None)
+                        .map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+                    match store.mnemonic() {
+                        Ok(mnemonic) => mnemonic,
+                        Err(_) => {
+                            let pass = wallet_passphrase.ok_or_else(locked)?;
+                            store
+                                .unlock(pass)
+                                .map_err(|e| format!("Unlocking '{name}': {e:#}"))?;
+                            store.mnemonic().map_err(|e| format!("{e:#}"))?
+                        }
+                    }
+                }
+            };
+            Ok(BackupKeys::Seed {
+                mnemonic,
+                bip39_passphrase: descstore::read_bip39_passphrase(&root)?,
+            })
+        }
+        WalletSourceCfg::Descriptor => {
+            let held = active
+                .then(|| state.with_desc(|d| d.payload()))
+                .and_then(Result::ok);
+            let payload = match held {
+                Some(payload) => payload,
+                None => {
+                    let mut store = DescStore::open(&root)?;
+                    match store.payload() {
+                        Ok(payload) => payload,
+                        Err(_) => {
+                            let pass = wallet_passphrase.ok_or_else(locked)?;
+                            store
+                                .unlock(pass)
+                                .map_err(|e| format!("Unlocking '{name}': {e}"))?;
+                            store.payload()?
+                        }
+                    }
+                }
+            };
+            Ok(BackupKeys::Descriptor { payload })
+        }
+        WalletSourceCfg::WatchOnly => Ok(BackupKeys::WatchOnly {
+            payload: descstore::read_public_descriptors(&root)?,
+        }),
+    }
+}
+
+/// Collect one wallet group (`group`) — or every wallet of the active
+/// network (`None`) — into a bundle.
+pub fn export_bundle(
+    state: &SharedBtcxWalletState,
+    group: Option<&str>,
+    wallet_passphrase: Option<&str>,
+) -> Result<BackupBundle, String> {
+    let config = state.get_config();
+    let network = config.network;
+    let names = match group {
+        Some(group) => config.group_members(network, group),
+        None => config.wallet_names(network),
+    };
+    if names.is_empty() {
+        return Err(match group {
+            Some(group) => format!("No wallet group '{group}' on {}", network.as_str()),
+            None => format!("No wallets on {}", network.as_str()),
+        });
+    }
+
+    let mut wallets = Vec::with_capacity(names.len());
+    for name in &names {
+        let mut meta = config
+            .wallet_meta(network, name)
+            .ok_or_else(|| format!("No wallet named '{name}'"))?;
+        meta.group = config.group_of(network, name);
+        meta.balance_snapshot = None;
+        let keys = read_keys(state, &config, name, meta.source, wallet_passphrase)?;
+        let side_dir = BtcxWalletConfig::wallet_root(network, name).join("wallet");
+        let mut files = BTreeMap::new();
+        for file in SIDE_FILES {
+            match std::fs::read_to_string(side_dir.join(file)) {
+                Ok(text) => {
+                    files.insert(file.to_string(), text);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("reading {file} of '{name}': {e}")),
+            }
+        }
+        wallets.push(BackupWallet {
+            name: name.clone(),
+            meta,
+            keys,
+            files,
+        });
+    }
+
+    let active = config.active_wallet_name();
+    Ok(BackupBundle {
+        version: BACKUP_VERSION,
+        network,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        active: names.contains(&active).then_some(active),
+        wallets,
+    })
+}
+
+/// Export a sealed bundle (see [`export_bundle`]).
+pub fn export_backup(
+    state: &SharedBtcxWalletState,
+    group: Option<&str>,
+    backup_passphrase: &str,
+    wallet_passphrase: Option<&str>,
+) -> Result<String, String> {
+    export_bundle(state, group, wallet_passphrase)?.seal(backup_passphrase)
+}
+
+// ============================================================================
+// Restore
+// ============================================================================
+
+/// Refuse a bundle that cannot land cleanly: wrong network, an invalid or
+/// taken wallet name (case-insensitive, registry or on-disk leftover), a
+/// group name already in use here, or a duplicate inside the bundle.
+/// Nothing is written before this passes.
+fn check_restorable(config: &BtcxWalletConfig, bundle: &BackupBundle) -> Result<(), String> {
+    let network = config.network;
+    if bundle.network != network {
+        return Err(format!(
+            "This backup is for {} — switch the wallet to that network first",
+            bundle.network.as_str()
+        ));
+    }
+    if bundle.wallets.is_empty() {
+        return Err("The backup holds no wallets".into());
+    }
+    let existing = config.wallet_names(network);
+    let groups: Vec<String> = existing
+        .iter()
+        .map(|n| config.group_of(network, n))
+        .collect();
+    let mut seen = Vec::new();
+    for wallet in &bundle.wallets {
+        config::validate_wallet_name(&wallet.name)?;
+        let lower = wallet.name.to_ascii_lowercase();
+        if let Some(taken) = existing.iter().find(|n| n.to_ascii_lowercase() == lower) {
+            return Err(format!(
+                "A wallet named '{taken}' already exists on {}",
+                network.as_str()
+            ));
+        }
+        if super::commands::has_leftover_store(network, &wallet.name) {
+            return Err(format!(
+                "A wallet already exists on disk at '{}'",
+                wallet.name
+            ));
+        }
+        if groups.contains(&wallet.meta.group) {
+            return Err(format!(
+                "A wallet group '{}' already exists on {}",
+                wallet.meta.group,
+                network.as_str()
+            ));
+        }
+        if seen.contains(&lower) {
+            return Err(format!("The backup lists '{}' twice", wallet.name));
+        }
+        seen.push(lower);
+        if let Some(file) = wallet
+            .files
+            .keys()
+            .find(|f| !SIDE_FILES.contains(&f.as_str()))
+        {
+            return Err(format!("Unexpected file '{file}' in backup"));
+        }
+    }
+    Ok(())
+}
+
+/// Where a restored wallet is written before the restore commits: a
+/// dot-named sibling of its data dir (never a valid wallet name).
+fn staging_root(network: WalletNetwork, name: &str) -> PathBuf {
+    BtcxWalletConfig::wallet_root(network, name).with_file_name(format!(".restore-{name}"))
+}
+
+/// Write one wallet's key and side files into `root`.
+/// `passphrase` re-encrypts seeds and private descriptors at rest.
+fn write_wallet(
+    root: &Path,
+    wallet: &BackupWallet,
+    passphrase: Option<&str>,
+) -> Result<(), String> {
+    match &wallet.keys {
+        BackupKeys::Seed {
+            mnemonic,
+            bip39_passphrase,
+        } => {
+            let mut store = SeedStore::open(root, None)
+                .map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+            store
+                .import_seed(mnemonic, passphrase)
+                .map(|_| ())
+                .map_err(|e| format!("{e:#}"))?;
+            descstore::write_bip39_passphrase(root, bip39_passphrase)?;
+        }
+        BackupKeys::Descriptor { payload } => {
+            DescStore::open(root)?.import(payload, passphrase)?;
+        }
+        BackupKeys::WatchOnly { payload } => {
+            std::fs::create_dir_all(root)
+                .map_err(|e| format!("creating {}: {e}", root.display()))?;
+            descstore::write_public_descriptors(root, payload)?;
+        }
+    }
+    if !wallet.files.is_empty() {
+        let side_dir = root.join("wallet");
+        std::fs::create_dir_all(&side_dir)
+            .map_err(|e| format!("creating {}: {e}", side_dir.display()))?;
+        for (file, contents) in &wallet.files {
+            descstore::write_atomic(&side_dir.join(file), contents)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stage every wallet of `bundle` into its staging dir, move the staged
+/// dirs into place and register them all in ONE registry write. Every dir
+/// created is pushed to `created`, for the caller to remove on error.
+fn restore_wallets(
+    state: &SharedBtcxWalletState,
+    network: WalletNetwork,
+    bundle: &BackupBundle,
+    passphrase: Option<&str>,
+    created: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    for wallet in &bundle.wallets {
+        let staging = staging_root(network, &wallet.name);
+        // A leftover of an earlier, interrupted restore.
+        let _ = std::fs::remove_dir_all(&staging);
+        created.push(staging.clone());
+        write_wallet(&staging, wallet, passphrase)
+            .map_err(|e| format!("Restoring '{}': {e}", wallet.name))?;
+    }
+    for wallet in &bundle.wallets {
+        let root = BtcxWalletConfig::wallet_root(network, &wallet.name);
+        // An empty dir is harmless; anything else was refused as a leftover.
+        let _ = std::fs::remove_dir(&root);
+        std::fs::rename(staging_root(network, &wallet.name), &root)
+            .map_err(|e| format!("Restoring '{}': moving into place: {e}", wallet.name))?;
+        created.push(root);
+    }
+    state.update_config(|c| {
+        for wallet in &bundle.wallets {
+            c.set_wallet_meta(network, &wallet.name, wallet.meta.clone());
+        }
+    })?;
+    Ok(())
+}
+
+/// Restore a sealed bundle: recreate every wallet's key files, side files
+/// and registry entry (group membership included), select the bundle's
+/// active wallet (else its first), and open it — no restore probe.
+/// `wallet_passphrase` encrypts the restored key material at rest, as on
+/// create. All or nothing: a failure leaves no wallet dir and no registry
+/// entry behind.
+pub fn import_backup(
+    state: &SharedBtcxWalletState,
+    app: Option<AppHandle>,
+    sealed: &str,
+    backup_passphrase: &str,
+    wallet_passphrase: Option<&str>,
+) -> Result<BackupRestoreResult, String> {
+    let bundle = BackupBundle::open(sealed, backup_passphrase)?;
+    let config = state.get_config();
+    check_restorable(&config, &bundle)?;
+    let network = config.network;
+    let passphrase = wallet_passphrase.filter(|p| !p.is_empty());
+
+    let mut created = Vec::new();
+    if let Err(e) = restore_wallets(state, network, &bundle, passphrase, &mut created) {
+        for dir in &created {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+        return Err(e);
+    }
+    for wallet in &bundle.wallets {
+        log::info!(
+            "btcx wallet: '{}' restored from backup into group '{}'",
+            wallet.name,
+            wallet.meta.group
+        );
+    }
+
+    let active = bundle
+        .active
+        .clone()
+        .filter(|a| bundle.wallets.iter().any(|w| &w.name == a))
+        .unwrap_or_else(|| bundle.wallets[0].name.clone());
+    state.close_runtime();
+    state.drop_seed_passphrase();
+    state.update_config(|c| {
+        c.set_active_wallet(network, &active);
+        c.active = true;
+    })?;
+    if let Some(pass) = passphrase {
+        match state.active_source(&state.get_config()) {
+            WalletSourceCfg::Seed => {
+                state.with_seed(|s| s.unlock(pass).map_err(|e| format!("{e:#}")))?
+            }
+            WalletSourceCfg::Descriptor => state.with_desc(|d| d.unlock(pass))?,
+            WalletSourceCfg::WatchOnly => {}
+        }
+    }
+    state.open_runtime(app)?;
+
+    Ok(BackupRestoreResult {
+        status: state.status()?,
+        restored: bundle.wallets.iter().map(|w| w.name.clone()).collect(),
+        active,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btcx_wallet::config::{DescriptorKindCfg, DescriptorPolicy};
+
+    fn meta(group: &str, source: WalletSourceCfg) -> WalletMeta {
+        WalletMeta {
+            policy: DescriptorPolicy {
+                kind: DescriptorKindCfg::Bip84,
+                coin_type: keys_btcx::COIN_BTCX,
+            },
+            created_at: Some(1_700_000_000),
+            source,
+            single_address: false,
+            v30_migrated: true,
+            group: group.to_string(),
+            balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
+        }
+    }
+
+    fn bundle() -> BackupBundle {
+        BackupBundle {
+            version: BACKUP_VERSION,
+            network: WalletNetwork::Regtest,
+            created_at: 1_700_000_000,
+            active: Some("bk-main".into()),
+            wallets: vec![
+                BackupWallet {
+                    name: "bk-main".into(),
+                    meta: meta("bk-main", WalletSourceCfg::Seed),
+                    keys: BackupKeys::Seed {
+                        mnemonic: "abandon ".repeat(11) + "about",
+                        bip39_passphrase: "25th".into(),
+                    },
+                    files: BTreeMap::from([(LABELS_FILE.to_string(), "{}\n".to_string())]),
+                },
+                BackupWallet {
+                    name: "bk-watch".into(),
+                    meta: meta("bk-watch", WalletSourceCfg::WatchOnly),
+                    keys: BackupKeys::WatchOnly {
+                        payload: DescriptorPayload::new("wpkh(xpub/0/*)".into(), None),
+                    },
+                    files: BTreeMap::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn sealed_bundle_roundtrips_and_is_never_plaintext() {
+        let sealed = bundle().seal("correct horse").unwrap();
+        assert!(sealed.starts_with("PHXBACKUPv1:"));
+        assert!(!sealed.contains("abandon"));
+        assert_eq!(
+            BackupBundle::open(&sealed, "correct horse").unwrap(),
+            bundle()
+        );
+        assert!(BackupBundle::open(&sealed, "wrong").is_err());
+        assert!(bundle().seal("").is_err(), "passphrase is mandatory");
+        // A descriptor store is not a backup, even under the same scheme.
+        let desc = descstore::seal_with_passphrase("PHXDESCv1", "{}", "pw").unwrap();
+        assert!(BackupBundle::open(&desc, "pw").is_err());
+    }
+
+    #[test]
+    fn restore_refuses_collisions_and_foreign_files() {
+        let mut config = BtcxWalletConfig {
+            network: WalletNetwork::Regtest,
+            ..Default::default()
+        };
+        assert!(check_restorable(&config, &bundle()).is_ok());
+
+        config.set_wallet_meta(
+            WalletNetwork::Regtest,
+            "BK-MAIN",
+            meta("x", WalletSourceCfg::Seed),
+        );
+        assert!(check_restorable(&config, &bundle()).is_err(), "name taken");
+
+        config.remove_wallet_meta(WalletNetwork::Regtest, "BK-MAIN");
+        config.set_wallet_meta(
+            WalletNetwork::Regtest,
+            "other",
+            meta("bk-watch", WalletSourceCfg::Seed),
+        );
+        assert!(check_restorable(&config, &bundle()).is_err(), "group taken");
+
+        config.remove_wallet_meta(WalletNetwork::Regtest, "other");
+        let mut sneaky = bundle();
+        sneaky.wallets[1]
+            .files
+            .insert("../../seed.mnemonic".into(), String::new());
+        assert!(check_restorable(&config, &sneaky).is_err());
+
+        let mut foreign = bundle();
+        foreign.network = WalletNetwork::Mainnet;
+        assert!(check_restorable(&config, &foreign).is_err());
+    }
+
+    #[test]
+    fn staging_dirs_sit_next_to_the_wallet_and_are_never_wallet_names() {
+        let root = BtcxWalletConfig::wallet_root(WalletNetwork::Regtest, "bk-main");
+        let staging = staging_root(WalletNetwork::Regtest, "bk-main");
+        assert_eq!(staging.parent(), root.parent());
+        let name = staging.file_name().unwrap().to_str().unwrap();
+        assert!(config::validate_wallet_name(name).is_err(), "{name}");
+    }
+}
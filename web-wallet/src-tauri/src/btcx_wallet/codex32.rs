@@ -0,0 +1,407 @@
+//! codex32 (BIP-93) threshold shares of a seed.
+//!
+//! A k-of-n split: the secret becomes the `s` share of a degree-(k-1)
+//! polynomial over GF(32), k-1 shares are random, and the rest are
+//! interpolated — any k of them interpolate back to `s`. Each share is an
+//! `ms1…` bech32-alphabet string with its own 13-character BCH checksum
+//! (the BIP-93 short code), so a mistyped share is caught before combining.
+//!
+//! Only the codec lives here. What gets split is the wallet's BIP39
+//! ENTROPY (16–32 bytes), so the shares restore the same mnemonic — and
+//! therefore the same wallet, through the regular restore path. Note this
+//! differs from treating the codex32 secret as a BIP32 master seed (how
+//! codex32-native wallets read it): shares made here belong back in a
+//! BIP39 wallet.
+
+use rand::Rng;
+
+/// Human-readable part of every codex32 string.
+const HRP: &str = "ms";
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// BIP-93 short-code checksum parameters.
+const GENERATOR: [u128; 5] = [
+    0x19dc500ce73fde210,
+    0x1bfae00def77fe529,
+    0x1fbd920fffe7bee52,
+    0x1739640bdeee3fdad,
+    0x07729a039cfc75f5a,
+];
+const TARGET_RESIDUE: u128 = 0x10ce0795c2fd1e62a;
+const CHECKSUM_LEN: usize = 13;
+/// Longest data part (header + payload + checksum) of a short code.
+const SHORT_CODE_MAX: usize = 93;
+/// Threshold char, 4-char identifier, share index.
+const HEADER_LEN: usize = 6;
+/// The secret's share index, `s`.
+const SECRET_INDEX: u8 = 16;
+/// Indices handed out to shares (`s` is never one), in alphabetical order.
+const SHARE_INDICES: &[u8] = b"023456789acdefghjklmnpqrtuvwxyz";
+/// Most shares one split can produce.
+pub const MAX_SHARES: usize = SHARE_INDICES.len();
+/// Highest threshold codex32 can express (a single digit).
+pub const MAX_THRESHOLD: usize = 9;
+
+fn fe(c: u8) -> Option<u8> {
+    CHARSET.iter().position(|&x| x == c).map(|i| i as u8)
+}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u128 {
+    let mut residue: u128 = 1;
+    for v in values {
+        let top = residue >> 60;
+        residue = ((residue & 0x0fff_ffff_ffff_ffff) << 5) ^ u128::from(v);
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                residue ^= g;
+            }
+        }
+    }
+    residue
+}
+
+fn hrp_expanded() -> impl Iterator<Item = u8> {
+    HRP.bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(HRP.bytes().map(|c| c & 31))
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let residue = polymod(
+        hrp_expanded()
+            .chain(data.iter().copied())
+            .chain([0; CHECKSUM_LEN]),
+    ) ^ TARGET_RESIDUE;
+    std::array::from_fn(|i| ((residue >> (5 * (CHECKSUM_LEN - 1 - i))) & 31) as u8)
+}
+
+/// Multiplication in GF(32) modulo x^5 + x^3 + 1 (the bech32 field).
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut r: u16 = 0;
+    for i in 0..5 {
+        if (b >> i) & 1 == 1 {
+            r ^= u16::from(a) << i;
+        }
+    }
+    for i in (5..10).rev() {
+        if (r >> i) & 1 == 1 {
+            r ^= 0b10_1001 << (i - 5);
+        }
+    }
+    r as u8
+}
+
+fn gf_inv(a: u8) -> u8 {
+    (1..32).find(|&x| gf_mul(a, x) == 1).unwrap_or(0)
+}
+
+/// Lagrange-interpolate whole shares (header and checksum included — both
+/// are affine in the data, so the result is itself a valid share) at `at`.
+fn interpolate(points: &[&[u8]], at: u8) -> Vec<u8> {
+    let mut out = vec![0u8; points[0].len()];
+    for (i, pi) in points.iter().enumerate() {
+        let xi = pi[HEADER_LEN - 1];
+        let (mut num, mut den) = (1u8, 1u8);
+        for (j, pj) in points.iter().enumerate() {
+            if i != j {
+                let xj = pj[HEADER_LEN - 1];
+                num = gf_mul(num, at ^ xj);
+                den = gf_mul(den, xi ^ xj);
+            }
+        }
+        let weight = gf_mul(num, gf_inv(den));
+        for (o, &v) in out.iter_mut().zip(pi.iter()) {
+            *o ^= gf_mul(weight, v);
+        }
+    }
+    out
+}
+
+fn bytes_to_fes(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &b in bytes {
+        acc = (acc << 8) | u32::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(((acc >> bits) & 31) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    out
+}
+
+/// Whole bytes of `fes`; the trailing padding bits (< 5) are dropped.
+fn fes_to_bytes(fes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(fes.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &v in fes {
+        acc = (acc << 5) | u32::from(v);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    out
+}
+
+fn encode(data: &[u8]) -> String {
+    let mut s = format!("{HRP}1");
+    s.extend(data.iter().map(|&v| CHARSET[v as usize] as char));
+    s
+}
+
+/// Parse and checksum-verify one share into its data-part values.
+fn parse(share: &str) -> Result<Vec<u8>, String> {
+    let share = share.trim();
+    if share.chars().any(|c| c.is_ascii_lowercase())
+        && share.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err("A codex32 share must not mix upper and lower case".into());
+    }
+    let share = share.to_ascii_lowercase();
+    let (hrp, data) = share
+        .rsplit_once('1')
+        .ok_or("Not a codex32 share (missing the 'ms1' prefix)")?;
+    if hrp != HRP {
+        return Err("Not a codex32 share (missing the 'ms1' prefix)".into());
+    }
+    let data = data
+        .bytes()
+        .map(|c| fe(c).ok_or_else(|| format!("Invalid character '{}' in share", c as char)))
+        .collect::<Result<Vec<u8>, String>>()?;
+    if data.len() < HEADER_LEN + CHECKSUM_LEN + bytes_to_fes(&[0; 16]).len()
+        || data.len() > SHORT_CODE_MAX
+    {
+        return Err("Share has the wrong length".into());
+    }
+    if polymod(hrp_expanded().chain(data.iter().copied())) != TARGET_RESIDUE {
+        return Err(format!(
+            "Share '{}' has a bad checksum — check for typos",
+            encode(&data[..HEADER_LEN])
+        ));
+    }
+    let threshold = CHARSET[data[0] as usize];
+    if !(threshold == b'0' || (b'2'..=b'9').contains(&threshold)) {
+        return Err("Share has an invalid threshold".into());
+    }
+    if threshold == b'0' && data[HEADER_LEN - 1] != SECRET_INDEX {
+        return Err("An unshared (threshold 0) codex32 string must have index 's'".into());
+    }
+    Ok(data)
+}
+
+/// Split `secret` (16–32 bytes) into `count` shares, any `threshold` of
+/// which recover it. Threshold 1 is the unshared form: one `ms10…s` string.
+pub fn split(
+    rng: &mut impl Rng,
+    secret: &[u8],
+    threshold: usize,
+    count: usize,
+) -> Result<Vec<String>, String> {
+    if !(16..=32).contains(&secret.len()) {
+        return Err("codex32 shares hold a 16 to 32 byte secret".into());
+    }
+    if !(1..=MAX_THRESHOLD).contains(&threshold) {
+        return Err(format!("The threshold must be 1 to {MAX_THRESHOLD}"));
+    }
+    if threshold == 1 && count != 1 {
+        return Err("A threshold of 1 makes a single unshared string".into());
+    }
+    if !(threshold..=MAX_SHARES).contains(&count) {
+        return Err(format!(
+            "The share count must be {threshold} to {MAX_SHARES}"
+        ));
+    }
+    let k = if threshold == 1 {
+        b'0'
+    } else {
+        b'0' + threshold as u8
+    };
+    let mut header = [0u8; HEADER_LEN];
+    header[0] = fe(k).ok_or("bad threshold")?;
+    for v in &mut header[1..HEADER_LEN - 1] {
+        *v = rng.gen_range(0..32);
+    }
+    let with_checksum = |mut data: Vec<u8>| {
+        let sum = checksum(&data);
+        data.extend(sum);
+        data
+    };
+    let share_with = |index: u8, payload: Vec<u8>| {
+        let mut data = header.to_vec();
+        data[HEADER_LEN - 1] = index;
+        data.extend(payload);
+        with_checksum(data)
+    };
+
+    let payload = bytes_to_fes(secret);
+    let secret_share = share_with(SECRET_INDEX, payload.clone());
+    if threshold == 1 {
+        return Ok(vec![encode(&secret_share)]);
+    }
+    let mut shares: Vec<Vec<u8>> = SHARE_INDICES[..threshold - 1]
+        .iter()
+        .map(|&c| {
+            let random = (0..payload.len()).map(|_| rng.gen_range(0..32)).collect();
+            share_with(fe(c).unwrap_or(0), random)
+        })
+        .collect();
+    let mut points: Vec<&[u8]> = shares.iter().map(Vec::as_slice).collect();
+    points.push(&secret_share);
+    let derived: Vec<Vec<u8>> = SHARE_INDICES[threshold - 1..count]
+        .iter()
+        .map(|&c| interpolate(&points, fe(c).unwrap_or(0)))
+        .collect();
+    shares.extend(derived);
+    Ok(shares.iter().map(|s| encode(s)).collect())
+}
+
+/// Recover the secret from `shares` (at least the threshold of one split;
+/// any extra shares must agree with the rest).
+pub fn combine(shares: &[String]) -> Result<Vec<u8>, String> {
+    let parsed = shares
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| parse(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = parsed.first().ok_or("Enter at least one share")?;
+    let mut distinct: Vec<&[u8]> = Vec::with_capacity(parsed.len());
+    for share in &parsed {
+        if share.len() != first.len() || share[..HEADER_LEN - 1] != first[..HEADER_LEN - 1] {
+            return Err(
+                "The shares belong to different backups (threshold, identifier or length differ)"
+                    .into(),
+            );
+        }
+        match distinct
+            .iter()
+            .find(|d| d[HEADER_LEN - 1] == share[HEADER_LEN - 1])
+        {
+            Some(d) if **d != share[..] => {
+                return Err("Two different shares carry the same index".into())
+            }
+            Some(_) => {}
+            None => distinct.push(share),
+        }
+    }
+    let threshold = match CHARSET[first[0] as usize] {
+        b'0' => 1,
+        k => usize::from(k - b'0'),
+    };
+    if distinct.len() < threshold {
+        return Err(format!(
+            "{threshold} shares are needed, {} given",
+            distinct.len()
+        ));
+    }
+    let (basis, extra) = distinct.split_at(threshold);
+    let secret = if threshold == 1 {
+        basis[0].to_vec()
+    } else {
+        interpolate(basis, SECRET_INDEX)
+    };
+    for share in extra {
+        if interpolate(basis, share[HEADER_LEN - 1]) != **share {
+            return Err("The shares do not agree — one of them is from another backup".into());
+        }
+    }
+    Ok(fes_to_bytes(
+        &secret[HEADER_LEN..secret.len() - CHECKSUM_LEN],
+    ))
+}
+
+/// The 4-character identifier shared by every share of one split.
+pub fn identifier(share: &str) -> Result<String, String> {
+    let data = parse(share)?;
+    Ok(data[1..HEADER_LEN - 1]
+        .iter()
+        .map(|&v| CHARSET[v as usize] as char)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn bip93_vector_1_unshared() {
+        let secret = combine(&strings(&[
+            "ms10testsxxxxxxxxxxxxxxxxxxxxxxxxxx4nzvca9cmczlw",
+        ]))
+        .unwrap();
+        assert_eq!(hex::encode(secret), "318c6318c6318c6318c6318c6318c631");
+    }
+
+    #[test]
+    fn bip93_vector_2_two_of_n() {
+        let secret = combine(&strings(&[
+            "MS12NAMEA320ZYXWVUTSRQPNMLKJHGFEDCAXRPP870HKKQRM",
+            "MS12NAMECACDEFGHJKLMNPQRSTUVWXYZ023FTR2GDZMPY6PN",
+        ]))
+        .unwrap();
+        assert_eq!(hex::encode(secret), "d1808e096b35b209ca12132b264662a5");
+        assert_eq!(
+            identifier("MS12NAMEA320ZYXWVUTSRQPNMLKJHGFEDCAXRPP870HKKQRM").unwrap(),
+            "name"
+        );
+    }
+
+    #[test]
+    fn split_then_any_k_shares_recover() {
+        let mut rng = rand::thread_rng();
+        for len in [16, 32] {
+            let secret: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let shares = split(&mut rng, &secret, 3, 5).unwrap();
+            assert_eq!(shares.len(), 5);
+            for pick in [[0, 1, 2], [2, 3, 4], [0, 2, 4], [4, 1, 3]] {
+                let subset: Vec<String> = pick.iter().map(|&i| shares[i].clone()).collect();
+                assert_eq!(combine(&subset).unwrap(), secret);
+            }
+            // Extra shares are cross-checked, too few are refused.
+            assert_eq!(combine(&shares).unwrap(), secret);
+            assert!(combine(&shares[..2]).is_err());
+        }
+        let secret = [7u8; 16];
+        let single = split(&mut rng, &secret, 1, 1).unwrap();
+        assert!(single[0].starts_with("ms10"));
+        assert_eq!(combine(&single).unwrap(), secret);
+    }
+
+    #[test]
+    fn bad_and_mismatched_shares_are_refused() {
+        let mut rng = rand::thread_rng();
+        let a = split(&mut rng, &[1u8; 16], 2, 3).unwrap();
+        let b = split(&mut rng, &[2u8; 16], 2, 3).unwrap();
+
+        // One typo breaks the checksum.
+        let mut typo = a[0].clone().into_bytes();
+        typo[10] = if typo[10] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(combine(&[typo, a[1].clone()]).is_err());
+
+        // Shares of two splits never combine (identifier differs, or the
+        // third share disagrees when the identifiers happen to collide).
+        if identifier(&a[0]).unwrap() != identifier(&b[1]).unwrap() {
+            assert!(combine(&[a[0].clone(), b[1].clone()]).is_err());
+        }
+        assert!(combine(&[a[0].clone(), a[1].clone(), b[2].clone()]).is_err());
+
+        assert!(combine(&[a[0].to_uppercase(), a[1].clone()]).is_ok());
+        let mixed = format!("MS{}", &a[0][2..]);
+        assert!(combine(&[mixed, a[1].clone()]).is_err());
+        assert!(split(&mut rng, &[0u8; 16], 1, 3).is_err());
+        assert!(split(&mut rng, &[0u8; 16], 4, 3).is_err());
+        assert!(split(&mut rng, &[0u8; 64], 2, 3).is_err());
+    }
+}
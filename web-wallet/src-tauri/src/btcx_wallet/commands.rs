@@ -12,14 +12,26 @@ use keys_btcx::WalletSeed;
 use seedstore::SeedStore;
 use wallet_btcx::WalletTxInfo;
 
+use super::backup::BackupRestoreResult;
+use super::codex32;
 use super::config::{
-    self, BtcxWalletConfig, DescriptorKindCfg, DescriptorPolicy, WalletMeta, WalletNetwork,
-    WalletSourceCfg, TRASH_SUBDIR,
+    self, BtcxWalletConfig, DescriptorKindCfg, DescriptorPolicy, HeaderConsensusMode,
+    MultisigShape, ServerDiscoveryMode, WalletMeta, WalletNetwork, WalletSourceCfg, TRASH_SUBDIR,
 };
+use super::consensus::{ConsensusReport, ServerConsensus};
 use super::descriptors::{self, ImportValidation};
 use super::descstore::{self, DescStore, DescriptorPayload};
+use super::discovery::DiscoveryReport;
+use super::fees::{eta_secs, resolve_send_fee, FeeEstimateMode, FeeEstimator, FeeSource, TARGETS};
+use super::labels::{LabelImportSummary, LabelRecord, LabelType};
 use super::manager::{self, BranchHit};
+use super::message::{MessageFormat, MessageVerification};
+use super::payreq::{PaymentRequestStatus, PaymentUri};
+use super::preview::{PreviewKind, TxPreview};
+use super::rewrap::PassphraseChange;
+use super::seedcheck::{BackupChallenge, BackupVerification, MnemonicReveal};
 use super::state::{BtcxWalletStatus, SharedBtcxWalletState};
+use super::trash::TrashedWallet;
 
 /// Run a blocking wallet operation off the async runtime.
 async fn blocking<T: Send + 'static>(
@@ -87,12 +99,15 @@ fn resolve_new_wallet_name(
     Ok(name)
 }
 
-/// Whether a wallet directory holds leftover key material (a seed OR an
-/// imported descriptor store) — e.g. a wallet restored from `.trash` by
-/// hand. Creating/renaming onto it would mix unrelated wallets.
-fn has_leftover_store(network: WalletNetwork, name: &str) -> bool {
+/// Whether a wallet directory holds a leftover wallet store (a seed, an
+/// imported descriptor store, or a watch-only public descriptor file) —
+/// e.g. a wallet restored from `.trash` by hand. Creating/renaming onto it
+/// would mix unrelated wallets.
+pub(crate) fn has_leftover_store(network: WalletNetwork, name: &str) -> bool {
     let root = BtcxWalletConfig::wallet_root(network, name);
-    root.join(seedstore::SEED_FILE).exists() || root.join(descstore::DESCRIPTOR_FILE).exists()
+    root.join(seedstore::SEED_FILE).exists()
+        || root.join(descstore::DESCRIPTOR_FILE).exists()
+        || root.join(descstore::PUBLIC_DESCRIPTOR_FILE).exists()
 }
 
 /// Write `mnemonic` into the named wallet's OWN data dir (standalone store
@@ -490,7 +505,7 @@ pub struct BtcxV30MigrationResult {
 /// A free wallet name for a counterpart being CREATED: `<base><suffix>`,
 /// truncated to the 32-char limit and disambiguated with `-2`, `-3`, … if
 /// the plain form is taken in the registry or holds a leftover on-disk store.
-fn resolve_counterpart_name(
+pub(crate) fn resolve_counterpart_name(
     state: &SharedBtcxWalletState,
     network: WalletNetwork,
     base: &str,
@@ -642,6 +657,8 @@ fn register_sibling_wallet(
                 v30_migrated: true,
                 group: group.to_string(),
                 balance_snapshot: None,
+                multisig: None,
+                backup_verified_at: None,
             },
         );
     })?;
@@ -774,7 +791,10 @@ pub fn rescan_legacy_impl(
         return migration_result(state, V30MigrationOutcome::Noop, None, None);
     }
 
-    if state.active_source(&config) == WalletSourceCfg::Descriptor {
+    if matches!(
+        state.active_source(&config),
+        WalletSourceCfg::Descriptor | WalletSourceCfg::WatchOnly
+    ) {
         return migration_result(
             state,
             V30MigrationOutcome::Noop,
@@ -855,6 +875,9 @@ pub struct BtcxImportResult {
     /// Single-address (`wpkh(WIF)`) wallet: one keychain, change returns
     /// to the same address — the success screen notes it.
     pub single_address: bool,
+    /// k-of-n shape of a multisig import.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigShape>,
 }
 
 /// The full descriptor-import flow (parse/validate → resolve name → store
@@ -862,6 +885,11 @@ pub struct BtcxImportResult {
 /// open runtime), shared by the `btcx_wallet_import_descriptor` command and
 /// the regtest integration tests (which run it without an `AppHandle`).
 ///
+/// A PUBLIC paste (xpub-family descriptors or a bare zpub/vpub) registers a
+/// WATCH-ONLY wallet instead: the pair goes to `descriptor.public`, there is
+/// nothing to encrypt (a passphrase is refused), and every signing path is
+/// gated by [`ensure_signing_wallet`].
+///
 /// Unlike restore there is no branch probing and no Electrum requirement —
 /// the descriptors say exactly which scripts the wallet owns; the fresh
 /// store's first sync gap-scans them the same way a restored branch is
@@ -878,13 +906,24 @@ pub fn import_descriptor_wallet_impl(
     let network = config.network;
     let name = resolve_new_wallet_name(&config, network, name)?;
     let parsed = descriptors::parse_import(input, network).map_err(|e| e.message)?;
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    if parsed.watch_only && passphrase.is_some() {
+        return Err(
+            "A watch-only wallet holds no keys — there is nothing to encrypt; \
+             leave the passphrase empty"
+                .into(),
+        );
+    }
 
     let root = BtcxWalletConfig::wallet_root(network, &name);
-    let mut store = DescStore::open(&root)?;
-    store.import(
-        &DescriptorPayload::new(parsed.external.clone(), parsed.internal.clone()),
-        passphrase,
-    )?;
+    let payload = DescriptorPayload::new(parsed.external.clone(), parsed.internal.clone());
+    let source = if parsed.watch_only {
+        descstore::write_public_descriptors(&root, &payload)?;
+        WalletSourceCfg::WatchOnly
+    } else {
+        DescStore::open(&root)?.import(&payload, passphrase)?;
+        WalletSourceCfg::Descriptor
+    };
 
     // Adopt the wallet: close the old runtime, select the new name, record
     // its classification, and re-hold the passphrase so an encrypted store
@@ -908,7 +947,7 @@ pub fn import_descriptor_wallet_impl(
                     .duration_since(std::time::UNIX_EPOCH)
                     .ok()
                     .map(|d| d.as_secs()),
-                source: WalletSourceCfg::Descriptor,
+                source,
                 single_address: parsed.single_address(),
                 // Descriptor-imported wallets are never part of the seed
                 // v30→v31 migration — mark them done so it never re-checks.
@@ -916,11 +955,13 @@ pub fn import_descriptor_wallet_impl(
                 // Imported wallets are always singleton groups.
                 group: name.clone(),
                 balance_snapshot: None,
+                multisig: parsed.multisig,
+                backup_verified_at: None,
             },
         );
         c.active = true;
     })?;
-    if let Some(pass) = passphrase.filter(|p| !p.is_empty()) {
+    if let Some(pass) = passphrase {
         state.with_desc(|d| d.unlock(pass))?;
     }
     // No Electrum server configured yet is fine — the runtime opens later,
@@ -933,6 +974,7 @@ pub fn import_descriptor_wallet_impl(
         inferred_internal: parsed.inferred_internal,
         from_multipath: parsed.from_multipath,
         single_address: parsed.single_address(),
+        multisig: parsed.multisig,
     })
 }
 
@@ -941,10 +983,14 @@ pub fn import_descriptor_wallet_impl(
 /// standard descriptor infers its `/0/*`↔`/1/*` sibling; a multipath
 /// `<0;1>` descriptor carries both branches; a `wpkh(WIF)` descriptor
 /// imports as a SINGLE-ADDRESS wallet (one keychain, change returns to the
-/// same address — vanity/plot identities). Public-only (xpub) material is
-/// rejected — watch-only wallets are not supported yet. The optional
+/// same address — vanity/plot identities); `wsh(sortedmulti(k, …))` over
+/// our key plus the cosigners' xpubs imports as a MULTISIG wallet (spends
+/// through the cosigner PSBT flow). PUBLIC descriptors (or a bare
+/// SLIP-132 zpub/vpub, imported as the BIP84 pair) register a WATCH-ONLY
+/// wallet: balances, history and receive addresses, plus unsigned PSBTs
+/// via `btcx_wallet_create_funded_psbt` — never signing. The optional
 /// passphrase encrypts the stored descriptors at rest (same scheme as the
-/// seed store). `name` picks the named wallet (default: the active one);
+/// seed store); a watch-only import refuses one. `name` picks the named wallet (default: the active one);
 /// the imported wallet becomes active.
 #[tauri::command]
 pub async fn btcx_wallet_import_descriptor(
@@ -977,6 +1023,284 @@ pub fn btcx_wallet_validate_import(
     )))
 }
 
+// ============================================================================
+// Multisig
+// ============================================================================
+
+/// Our BIP-48 multisig account key `(private, public)` from the ACTIVE
+/// seed wallet — the key we contribute to a `wsh(sortedmulti)` wallet.
+fn own_multisig_keys(
+    state: &SharedBtcxWalletState,
+    config: &BtcxWalletConfig,
+) -> Result<(String, String), String> {
+    if state.active_source(config) != WalletSourceCfg::Seed {
+        return Err(
+            "Select a seed wallet first — our multisig key derives from its seed (BIP-48)".into(),
+        );
+    }
+    let mnemonic = state
+        .with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}")))
+        .map_err(|_| "The wallet seed is locked — unlock it, then retry".to_string())?;
+    let bip39_passphrase = descstore::read_bip39_passphrase(&config.active_wallet_root())?;
+    let seed = WalletSeed::from_mnemonic(&mnemonic, &bip39_passphrase)
+        .map_err(|e| format!("Failed to derive wallet seed: {e:#}"))?;
+    manager::multisig_account_keys(
+        &seed,
+        config.network.asset_coin_type(),
+        descriptors::expected_network_kind(config.network),
+    )
+    .map_err(|e| format!("{e:#}"))
+}
+
+/// Our multisig cosigner key to hand to the other cosigners:
+/// `[fingerprint/48'/coin'/0'/2']xpub…` of the active seed wallet.
+#[tauri::command]
+pub async fn btcx_wallet_multisig_xpub(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || own_multisig_keys(&state, &state.get_config()).map(|(_, public)| public)).await
+}
+
+/// Create a `threshold`-of-n `wsh(sortedmulti)` wallet from our own BIP-48
+/// key (derived from the active seed wallet) plus the `cosigners`'
+/// account-level xpubs, then run the descriptor-import flow on the pair —
+/// the new wallet holds our private key only and becomes active. Its
+/// descriptors carry a key of the seed, so a passphrase-encrypted seed
+/// needs `passphrase` too: the key must not end up less protected than the
+/// seed it came from. Shared with the tests like
+/// [`import_descriptor_wallet_impl`].
+pub fn create_multisig_wallet_impl(
+    state: &SharedBtcxWalletState,
+    app: Option<AppHandle>,
+    threshold: u32,
+    cosigners: &[String],
+    passphrase: Option<&str>,
+    name: Option<String>,
+) -> Result<BtcxImportResult, String> {
+    let config = state.get_config();
+    let (private, public) = own_multisig_keys(state, &config)?;
+    let seed_file = std::fs::read_to_string(config.active_wallet_root().join(seedstore::SEED_FILE))
+        .unwrap_or_default();
+    if super::state::seed_needs_passphrase(&seed_file)
+        && passphrase.filter(|p| !p.is_empty()).is_none()
+    {
+        return Err(
+            "The seed wallet is passphrase-protected — give the multisig wallet a passphrase \
+             too; it stores a key derived from that seed"
+                .into(),
+        );
+    }
+    let own_xpub = public.rsplit_once(']').map_or(public.as_str(), |(_, k)| k);
+    if cosigners.iter().any(|c| c.contains(own_xpub)) {
+        return Err("Our own key is already in the list — give only the OTHER cosigners".into());
+    }
+    let mut keys = vec![private];
+    keys.extend(cosigners.iter().cloned());
+    let (external, internal) =
+        descriptors::multisig_descriptors(threshold, &keys).map_err(|e| e.message)?;
+    import_descriptor_wallet_impl(
+        state,
+        app,
+        &format!("{external}\n{internal}"),
+        passphrase,
+        name,
+    )
+}
+
+/// Create a multisig wallet (see [`create_multisig_wallet_impl`]). The
+/// passphrase encrypts its stored descriptors at rest — required when the
+/// seed wallet has one.
+#[tauri::command]
+pub async fn btcx_wallet_create_multisig(
+    threshold: u32,
+    cosigners: Vec<String>,
+    passphrase: Option<String>,
+    name: Option<String>,
+    app: AppHandle,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<BtcxImportResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        create_multisig_wallet_impl(
+            &state,
+            Some(app),
+            threshold,
+            &cosigners,
+            passphrase.as_deref(),
+            name,
+        )
+    })
+    .await
+}
+
+/// The stored descriptor pair of the active wallet, from whichever store
+/// its source uses (the descriptor store must be unlocked).
+fn active_descriptor_payload(
+    state: &SharedBtcxWalletState,
+    config: &BtcxWalletConfig,
+) -> Result<DescriptorPayload, String> {
+    match state.active_source(config) {
+        WalletSourceCfg::Seed => Err("Seed wallets have no stored descriptors".into()),
+        WalletSourceCfg::Descriptor => state.with_desc(|d| d.payload()),
+        WalletSourceCfg::WatchOnly => {
+            descstore::read_public_descriptors(&config.active_wallet_root())
+        }
+    }
+}
+
+/// Export the active multisig wallet's backup: threshold, every cosigner's
+/// fingerprint + derivation + xpub, the public descriptor pair, and a
+/// Coldcard/Sparrow-style setup file. Public data only.
+#[tauri::command]
+pub async fn btcx_wallet_multisig_backup(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<descriptors::MultisigBackup, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        let payload = active_descriptor_payload(&state, &config)?;
+        let internal = payload
+            .internal
+            .as_deref()
+            .ok_or("The open wallet is not a multisig wallet")?;
+        descriptors::multisig_backup(&config.active_wallet_name(), &payload.external, internal)
+    })
+    .await
+}
+
+/// The active wallet's PUBLIC descriptor pair, never read through the key
+/// stores: the open bdk wallet's own descriptors, else a watch-only
+/// wallet's stored public copy.
+fn public_source_descriptors(
+    state: &SharedBtcxWalletState,
+    config: &BtcxWalletConfig,
+) -> Result<(String, Option<String>), String> {
+    if state.open_wallet_name() == Some((config.network, config.active_wallet_name())) {
+        return state.with_entry(|entry| {
+            use bdk_wallet::KeychainKind;
+            let wallet = &entry.wallet;
+            let external = wallet.public_descriptor(KeychainKind::External).to_string();
+            // Single-address stores have one keychain.
+            let internal = (wallet.keychains().count() > 1)
+                .then(|| wallet.public_descriptor(KeychainKind::Internal).to_string());
+            Ok((external, internal))
+        });
+    }
+    match state.active_source(config) {
+        WalletSourceCfg::WatchOnly => {
+            let payload = descstore::read_public_descriptors(&config.active_wallet_root())?;
+            Ok((payload.external, payload.internal))
+        }
+        _ => Err("Open the wallet to export its public descriptors".into()),
+    }
+}
+
+/// The active wallet's descriptor pair for an export. Public exports come
+/// from [`public_source_descriptors`]; PRIVATE ones are gated like the
+/// mnemonic reveal — an encrypted seed or descriptor store needs its
+/// at-rest `passphrase`, verified afresh even while the wallet is open, an
+/// unencrypted one `confirm`.
+fn export_source_descriptors(
+    state: &SharedBtcxWalletState,
+    config: &BtcxWalletConfig,
+    private: bool,
+    passphrase: Option<&str>,
+    confirm: bool,
+) -> Result<(String, Option<String>), String> {
+    if !private {
+        return public_source_descriptors(state, config);
+    }
+    let payload = match state.active_source(config) {
+        WalletSourceCfg::Seed => {
+            let mnemonic = super::seedcheck::gated_mnemonic(state, passphrase, confirm)?.0;
+            let bip39_passphrase = descstore::read_bip39_passphrase(&config.active_wallet_root())?;
+            let seed = WalletSeed::from_mnemonic(&mnemonic, &bip39_passphrase)
+                .map_err(|e| format!("Failed to derive wallet seed: {e:#}"))?;
+            let policy = config.policy();
+            let kind = policy
+                .kind
+                .kind()
+                .ok_or("This wallet's branch has no seed derivation")?;
+            let (external, internal) = seed
+                .wallet_descriptors(kind, policy.coin_type)
+                .map_err(|e| format!("{e:#}"))?;
+            DescriptorPayload::new(external, Some(internal))
+        }
+        WalletSourceCfg::Descriptor => {
+            let mut store = DescStore::open(&config.active_wallet_root())?;
+            if store.status().encrypted {
+                let pass = passphrase
+                    .filter(|p| !p.is_empty())
+                    .ok_or("Enter the wallet passphrase to export its descriptors")?;
+                store.unlock(pass)?;
+            } else if !confirm {
+                return Err(
+                    "This wallet has no passphrase — anyone at this computer can read \
+                     its private descriptors; confirm to export them"
+                        .into(),
+                );
+            }
+            store.payload()?
+        }
+        WalletSourceCfg::WatchOnly => {
+            return Err("A watch-only wallet holds no private keys to export".into());
+        }
+    };
+    Ok((payload.external, payload.internal))
+}
+
+/// Export the active wallet's descriptors for another tool (see
+/// [`descriptors::descriptor_export`]); `include_private` adds the private
+/// descriptors, gated by `passphrase` / `confirm` as in
+/// [`export_source_descriptors`].
+pub fn export_descriptors_impl(
+    state: &SharedBtcxWalletState,
+    include_private: bool,
+    passphrase: Option<&str>,
+    confirm: bool,
+) -> Result<descriptors::DescriptorExport, String> {
+    let config = state.get_config();
+    let (external, internal) =
+        export_source_descriptors(state, &config, include_private, passphrase, confirm)?;
+    let export = descriptors::descriptor_export(
+        config.network,
+        &external,
+        internal.as_deref(),
+        include_private,
+    )?;
+    if include_private {
+        log::info!(
+            "btcx wallet: exported the private descriptors of '{}'",
+            config.active_wallet_name()
+        );
+    }
+    Ok(export)
+}
+
+/// Export the active wallet's public descriptors, account xpub (standard +
+/// SLIP-132) and key origin, plus a Core `importdescriptors` request — and
+/// its private descriptors with `include_private` (gated by `passphrase`,
+/// or `confirm` for an unencrypted wallet).
+#[tauri::command]
+pub async fn btcx_wallet_export_descriptors(
+    include_private: Option<bool>,
+    passphrase: Option<String>,
+    confirm: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<descriptors::DescriptorExport, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        export_descriptors_impl(
+            &state,
+            include_private.unwrap_or(false),
+            passphrase.as_deref(),
+            confirm.unwrap_or(false),
+        )
+    })
+    .await
+}
+
 /// Supply the passphrase of an encrypted seed — or of an imported
 /// wallet's encrypted descriptor store — (verified by trial decryption)
 /// and open the wallet.
@@ -993,6 +1317,9 @@ pub async fn btcx_wallet_unlock(
                 state.with_seed(|s| s.unlock(&passphrase).map_err(|e| format!("{e:#}")))?
             }
             WalletSourceCfg::Descriptor => state.with_desc(|d| d.unlock(&passphrase))?,
+            WalletSourceCfg::WatchOnly => {
+                return Err("A watch-only wallet has nothing to unlock".to_string());
+            }
         }
         state.open_runtime(Some(app))?;
         state.status()
@@ -1012,6 +1339,201 @@ pub fn btcx_wallet_lock(
     state.status()
 }
 
+/// Change, add, or remove the at-rest passphrase of the active wallet's
+/// seed or descriptor store — and of every same-source sibling in its
+/// group (see `rewrap`). `current_passphrase` unlocks an encrypted store;
+/// an empty `new_passphrase` removes the passphrase and needs
+/// `confirm_remove`.
+#[tauri::command]
+pub async fn btcx_wallet_change_passphrase(
+    current_passphrase: Option<String>,
+    new_passphrase: Option<String>,
+    confirm_remove: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<PassphraseChange, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        super::rewrap::change_passphrase(
+            &state,
+            current_passphrase.as_deref(),
+            new_passphrase.as_deref(),
+            confirm_remove.unwrap_or(false),
+        )
+    })
+    .await
+}
+
+/// Re-display the active seed wallet's mnemonic (see `seedcheck`). An
+/// encrypted seed needs `passphrase`, an unencrypted one `confirm`; the
+/// returned token re-reveals without either for a short while.
+#[tauri::command]
+pub async fn btcx_wallet_reveal_mnemonic(
+    passphrase: Option<String>,
+    token: Option<String>,
+    confirm: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<MnemonicReveal, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        super::seedcheck::reveal(
+            &state,
+            passphrase.as_deref(),
+            token.as_deref(),
+            confirm.unwrap_or(false),
+        )
+    })
+    .await
+}
+
+/// Start a paper-backup check: random word positions of the active seed
+/// for the user to fill in.
+#[tauri::command]
+pub fn btcx_wallet_backup_challenge(
+    count: Option<usize>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<BackupChallenge, String> {
+    super::seedcheck::challenge(state.inner(), count)
+}
+
+/// Answer the pending backup check — `words` in the challenge's position
+/// order. Returns only the outcome, never the seed.
+#[tauri::command]
+pub fn btcx_wallet_verify_backup(
+    words: Vec<String>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<BackupVerification, String> {
+    super::seedcheck::verify(state.inner(), &words)
+}
+
+// ============================================================================
+// Split-Seed Backups (codex32)
+// ============================================================================
+
+/// A k-of-n codex32 split of the active wallet's seed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedShares {
+    /// `ms1…` strings, one per share (index order).
+    pub shares: Vec<String>,
+    /// How many shares recover the seed.
+    pub threshold: usize,
+    /// The 4-character identifier every share of this split carries.
+    pub identifier: String,
+    /// A BIP39 25th word is set — the shares alone do NOT recover the
+    /// funds, the passphrase is needed too.
+    pub has_bip39_passphrase: bool,
+}
+
+/// Split the active seed wallet's mnemonic into `count` codex32 shares,
+/// any `threshold` of which restore it (see `codex32`). Gated like the
+/// mnemonic reveal: an encrypted seed needs its at-rest `passphrase`, an
+/// unencrypted one `confirm`.
+pub fn split_seed_impl(
+    state: &SharedBtcxWalletState,
+    passphrase: Option<&str>,
+    confirm: bool,
+    threshold: usize,
+    count: usize,
+) -> Result<SeedShares, String> {
+    let (mnemonic, has_bip39_passphrase) =
+        super::seedcheck::gated_mnemonic(state, passphrase, confirm)?;
+    let entropy = bip39::Mnemonic::parse_normalized(mnemonic.trim())
+        .map_err(|e| format!("The stored seed is not a BIP39 mnemonic: {e}"))?
+        .to_entropy();
+    let shares = codex32::split(&mut rand::thread_rng(), &entropy, threshold, count)?;
+    let identifier = codex32::identifier(&shares[0])?;
+    log::info!(
+        "btcx wallet: split the seed of '{}' into {threshold}-of-{count} codex32 shares ({identifier})",
+        state.get_config().active_wallet_name()
+    );
+    Ok(SeedShares {
+        shares,
+        threshold,
+        identifier,
+        has_bip39_passphrase,
+    })
+}
+
+/// Split the active wallet's seed into codex32 shares — the treasury
+/// threshold backup. `passphrase` unlocks an encrypted seed; an
+/// unencrypted one needs `confirm`.
+#[tauri::command]
+pub async fn btcx_wallet_split_seed(
+    threshold: usize,
+    share_count: usize,
+    passphrase: Option<String>,
+    confirm: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<SeedShares, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        split_seed_impl(
+            &state,
+            passphrase.as_deref(),
+            confirm.unwrap_or(false),
+            threshold,
+            share_count,
+        )
+    })
+    .await
+}
+
+/// Combine codex32 `shares` back into the mnemonic and restore it through
+/// [`restore_wallet_impl`] — the same branch probe and compartment
+/// materialization as a typed-in phrase.
+#[allow(clippy::too_many_arguments)]
+pub fn restore_from_shares_impl(
+    state: &SharedBtcxWalletState,
+    app: Option<AppHandle>,
+    shares: &[String],
+    passphrase: Option<&str>,
+    bip39_passphrase: &str,
+    name: Option<String>,
+    kind: Option<DescriptorKindCfg>,
+) -> Result<BtcxRestoreResult, String> {
+    let entropy = codex32::combine(shares)?;
+    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)
+        .map_err(|e| format!("The shares do not hold a BIP39 seed: {e}"))?
+        .to_string();
+    restore_wallet_impl(
+        state,
+        app,
+        &mnemonic,
+        passphrase,
+        bip39_passphrase,
+        name,
+        kind,
+    )
+}
+
+/// Restore a wallet from at least `threshold` codex32 shares. Arguments
+/// past `shares` are `btcx_wallet_restore`'s.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn btcx_wallet_restore_from_shares(
+    shares: Vec<String>,
+    passphrase: Option<String>,
+    bip39_passphrase: Option<String>,
+    name: Option<String>,
+    kind: Option<DescriptorKindCfg>,
+    app: AppHandle,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<BtcxRestoreResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        restore_from_shares_impl(
+            &state,
+            Some(app),
+            &shares,
+            passphrase.as_deref(),
+            bip39_passphrase.as_deref().unwrap_or(""),
+            name,
+            kind,
+        )
+    })
+    .await
+}
+
 // ============================================================================
 // Named-Wallet Registry
 // ============================================================================
@@ -1050,6 +1572,12 @@ pub struct BtcxWalletSummary {
     pub balance_sat: Option<u64>,
     /// Wallet-selector group this wallet belongs to (one group = one seed).
     pub group: String,
+    /// k-of-n shape of a multisig wallet — the switcher's "2-of-3" badge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigShape>,
+    /// When the mnemonic backup was last checked (`btcx_wallet_verify_backup`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_verified_at: Option<u64>,
 }
 
 /// List the registered wallets of the active network.
@@ -1090,6 +1618,7 @@ fn wallet_summaries(state: &SharedBtcxWalletState) -> Result<Vec<BtcxWalletSumma
             WalletSourceCfg::Descriptor => descstore::is_passphrase_descriptor_file(
                 &std::fs::read_to_string(root.join(descstore::DESCRIPTOR_FILE)).unwrap_or_default(),
             ),
+            WalletSourceCfg::WatchOnly => false,
         };
         let is_active = name == active_name;
         let is_open = is_active && runtime_open;
@@ -1116,6 +1645,8 @@ fn wallet_summaries(state: &SharedBtcxWalletState) -> Result<Vec<BtcxWalletSumma
             seed_locked: needs_passphrase && !(is_active && active_unlocked),
             balance_sat,
             group,
+            multisig: meta.multisig,
+            backup_verified_at: meta.backup_verified_at,
         });
     }
     Ok(out)
@@ -1168,6 +1699,7 @@ fn compartment_rank(policy: DescriptorPolicy, network: WalletNetwork) -> u8 {
         (false, DescriptorKindCfg::Bip84) => 2,
         (false, DescriptorKindCfg::Bip86) => 3,
         (_, DescriptorKindCfg::Legacy) => 4,
+        (_, DescriptorKindCfg::Multisig) => 5,
     }
 }
 
@@ -1365,7 +1897,7 @@ pub async fn btcx_wallet_delete(
         // The cached seed store holds no OS file handle, but drop it anyway
         // so nothing references the moved directory.
         state.drop_seed_passphrase();
-        delete_wallet_core(&state, network, &name)
+        delete_wallet_core(&state, network, &name, unix_now())
     })
     .await
 }
@@ -1375,7 +1907,7 @@ pub async fn btcx_wallet_delete(
 /// outlive the close by a moment — on Windows that fails the move with a
 /// sharing violation (the PR #156 failure class). A bounded retry (~2s)
 /// absorbs the straggler instead of surfacing a spurious "access denied".
-fn move_wallet_dir(old: &std::path::Path, new: &std::path::Path) -> Result<(), String> {
+pub(crate) fn move_wallet_dir(old: &std::path::Path, new: &std::path::Path) -> Result<(), String> {
     let mut last_err = None;
     for _ in 0..20 {
         match std::fs::rename(old, new) {
@@ -1392,15 +1924,26 @@ fn move_wallet_dir(old: &std::path::Path, new: &std::path::Path) -> Result<(), S
     ))
 }
 
+/// Current unix time in seconds (trash timestamps).
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// The delete core (trash move + registry removal), shared by the
 /// per-wallet delete and the group delete. Caller has validated the name,
-/// confirmed intent, and dropped the cached passphrase.
+/// confirmed intent, and dropped the cached passphrase. `deleted_at` is
+/// shared by one group delete's members — the trash's batch marker.
 fn delete_wallet_core(
     state: &SharedBtcxWalletState,
     network: WalletNetwork,
     name: &str,
+    deleted_at: u64,
 ) -> Result<(), String> {
     let root = BtcxWalletConfig::wallet_root(network, name);
+    let meta = state.get_config().wallet_meta(network, name);
     if root.exists() {
         // A just-closed wallet's worker may still hold its sqlite (blocking
         // connect) — wait for the real release before moving the directory.
@@ -1410,10 +1953,7 @@ fn delete_wallet_core(
             .join(TRASH_SUBDIR);
         std::fs::create_dir_all(&trash)
             .map_err(|e| format!("creating {}: {e}", trash.display()))?;
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        let ts = deleted_at;
         let mut dest = trash.join(format!("{name}-{ts}"));
         let mut n = 0;
         while dest.exists() {
@@ -1422,6 +1962,19 @@ fn delete_wallet_core(
         }
         move_wallet_dir(&root, &dest)?;
         log::info!("btcx wallet '{name}' moved to {}", dest.display());
+        // The registry entry travels with the dir so the trash browser can
+        // list and restore it. Best effort: the move already happened.
+        if let Some(mut meta) = meta {
+            meta.group = state.get_config().group_of(network, name);
+            let manifest = super::trash::TrashManifest {
+                name: name.to_string(),
+                deleted_at,
+                meta,
+            };
+            if let Err(e) = super::trash::write_manifest(&dest, &manifest) {
+                log::warn!("btcx wallet: writing the trash manifest of '{name}' failed: {e}");
+            }
+        }
     }
     state.update_config(|c| c.remove_wallet_meta(network, name))?;
     Ok(())
@@ -1522,7 +2075,7 @@ pub async fn btcx_wallet_rename(
 /// the new group name, a `<group><rest>` sibling keeps its `<rest>` tail,
 /// and a member whose name never contained the group name keeps it (only
 /// its `group` field moves).
-fn renamed_member(member: &str, group: &str, new_group: &str) -> String {
+pub(crate) fn renamed_member(member: &str, group: &str, new_group: &str) -> String {
     match member.strip_prefix(group) {
         Some(rest) => format!("{new_group}{rest}"),
         None => member.to_string(),
@@ -1652,8 +2205,9 @@ pub fn delete_group_impl(
         return Err("Cannot delete the open wallet's group — close it first".into());
     }
     state.drop_seed_passphrase();
+    let deleted_at = unix_now();
     for name in &members {
-        delete_wallet_core(state, network, name)?;
+        delete_wallet_core(state, network, name, deleted_at)?;
     }
     Ok(())
 }
@@ -1670,6 +2224,82 @@ pub async fn btcx_wallet_delete_group(
     blocking(move || delete_group_impl(&state, &group, &confirm_name)).await
 }
 
+// ============================================================================
+// Trash (deleted wallets)
+// ============================================================================
+
+/// The active network's trashed wallets, newest first: source, policy,
+/// group, deletion time and last balance snapshot where known.
+#[tauri::command]
+pub fn btcx_wallet_trash_list(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<TrashedWallet>, String> {
+    super::trash::list_in(&super::trash::trash_dir(state.get_config().network))
+}
+
+/// Restore one trashed wallet into the registry under `name` (default: its
+/// old name), disambiguated `-2`, `-3`, … when taken. Returns the name it
+/// got.
+#[tauri::command]
+pub async fn btcx_wallet_trash_restore(
+    id: String,
+    name: Option<String>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || super::trash::restore_wallet(&state, &id, name.as_deref())).await
+}
+
+/// Restore the whole group `id` was deleted with (same group, same delete
+/// batch), moving to a free group id when the old one is taken. Returns
+/// the restored names.
+#[tauri::command]
+pub async fn btcx_wallet_trash_restore_group(
+    id: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<String>, String> {
+    let state = state.inner().clone();
+    blocking(move || super::trash::restore_group(&state, &id)).await
+}
+
+/// PERMANENTLY remove one trashed wallet — the only delete that destroys
+/// key material. Demands the wallet name typed back (`confirm_name`).
+#[tauri::command]
+pub async fn btcx_wallet_trash_purge(
+    id: String,
+    confirm_name: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<(), String> {
+    let network = state.get_config().network;
+    blocking(move || super::trash::purge_in(&super::trash::trash_dir(network), &id, &confirm_name))
+        .await
+}
+
+/// Set (or clear, `None`) the trash retention in days and apply it to the
+/// active network right away. Returns the purged entry ids.
+#[tauri::command]
+pub async fn btcx_wallet_set_trash_retention(
+    days: Option<u32>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<String>, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        if days == Some(0) {
+            return Err("Retention must be at least one day".into());
+        }
+        let config = state.update_config(|c| c.trash_retention_days = days)?;
+        match days {
+            Some(days) => super::trash::purge_expired_in(
+                &super::trash::trash_dir(config.network),
+                days,
+                unix_now(),
+            ),
+            None => Ok(Vec::new()),
+        }
+    })
+    .await
+}
+
 // ============================================================================
 // Wallet Operations
 // ============================================================================
@@ -1746,6 +2376,10 @@ pub struct BtcxBalance {
     pub untrusted_pending_sat: u64,
     /// Immature coinbase outputs.
     pub immature_sat: u64,
+    /// Reported confirmed but not yet SPV-verified (see `spv`) — counted in
+    /// the pending buckets above instead of `confirmed_sat`. Always 0
+    /// without an independent second server.
+    pub unverified_sat: u64,
     /// confirmed + trusted pending — what a send can use.
     pub spendable_sat: u64,
     /// Everything the wallet knows about.
@@ -1755,14 +2389,26 @@ pub struct BtcxBalance {
 /// Current balance, served from the background-synced bdk cache.
 #[tauri::command]
 pub fn btcx_wallet_balance(state: State<'_, SharedBtcxWalletState>) -> Result<BtcxBalance, String> {
+    // Cached consensus report only: a balance read never dials servers.
+    super::consensus::gate(&state, false)?;
+    // A single-server wallet trusts its home server (see spv).
+    let spv = super::spv::holds_back(&state).then(|| super::spv::read_active(&state));
     state.with_entry(|entry| {
         let balance = entry.wallet.balance();
+        // Confirmations the SPV pass has not proven yet count as pending:
+        // our own change as trusted, receives as untrusted.
+        let (change, receives) = spv.as_ref().map_or((0, 0), |spv| {
+            super::spv::unverified_confirmed(&entry.wallet, spv)
+        });
+        let trusted_pending = balance.trusted_pending.to_sat() + change;
+        let confirmed = balance.confirmed.to_sat().saturating_sub(change + receives);
         Ok(BtcxBalance {
-            confirmed_sat: balance.confirmed.to_sat(),
-            trusted_pending_sat: balance.trusted_pending.to_sat(),
-            untrusted_pending_sat: balance.untrusted_pending.to_sat(),
+            confirmed_sat: confirmed,
+            trusted_pending_sat: trusted_pending,
+            untrusted_pending_sat: balance.untrusted_pending.to_sat() + receives,
             immature_sat: balance.immature.to_sat(),
-            spendable_sat: balance.trusted_spendable().to_sat(),
+            unverified_sat: change + receives,
+            spendable_sat: confirmed + trusted_pending,
             total_sat: balance.total().to_sat(),
         })
     })
@@ -1779,6 +2425,9 @@ pub struct BtcxWalletTxDto {
     /// has a bech32 display form (e.g. an OP_RETURN-only counterparty).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
+    /// The user's BIP-329 `tx` label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
 /// Derive the display address of one activity entry from its tx outputs:
@@ -1835,6 +2484,7 @@ pub fn wallet_transactions_impl(
     offset: Option<usize>,
 ) -> Result<BtcxWalletTxPage, String> {
     let network = state.get_config().network;
+    let labels = super::labels::display_labels(state);
     state.with_entry(|entry| {
         use bdk_wallet::chain::ChainPosition;
         let tip = entry.wallet.latest_checkpoint().height();
@@ -1907,7 +2557,12 @@ pub fn wallet_transactions_impl(
                     timestamp,
                 };
                 let address = tx_display_address(entry, network, &info, &tx);
-                BtcxWalletTxDto { info, address }
+                let label = labels.label(LabelType::Tx, &info.txid).map(str::to_string);
+                BtcxWalletTxDto {
+                    info,
+                    address,
+                    label,
+                }
             })
             .collect();
         Ok(BtcxWalletTxPage { items, total })
@@ -1934,6 +2589,9 @@ pub struct BtcxWalletAddressDto {
     pub used: bool,
     /// Currently holds an unspent output.
     pub funded: bool,
+    /// The user's BIP-329 `addr` label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
 /// Every revealed external address with used/funded flags — the remote
@@ -1945,6 +2603,7 @@ pub fn btcx_wallet_addresses(
 ) -> Result<Vec<BtcxWalletAddressDto>, String> {
     use bdk_wallet::KeychainKind;
     let network = state.get_config().network;
+    let labels = super::labels::display_labels(&state);
     state.with_entry(|entry| {
         let last = entry.wallet.derivation_index(KeychainKind::External);
         // Non-wildcard (single-address WIF) descriptors reveal no index —
@@ -1970,8 +2629,10 @@ pub fn btcx_wallet_addresses(
                     .peek_address(KeychainKind::External, i)
                     .address
                     .script_pubkey();
+                let address = super::psbt::spk_to_address(network, &spk).unwrap_or_default();
                 BtcxWalletAddressDto {
-                    address: super::psbt::spk_to_address(network, &spk).unwrap_or_default(),
+                    label: labels.label(LabelType::Addr, &address).map(str::to_string),
+                    address,
                     index: i,
                     used: received.contains(&spk),
                     funded: funded.contains(&spk),
@@ -2040,7 +2701,14 @@ pub struct BtcxTxDetail {
     pub timestamp: Option<u64>,
     pub block_height: Option<u32>,
     pub block_hash: Option<String>,
+    /// The confirmation is SPV-verified against its block header (see
+    /// `spv`); always false while unconfirmed.
+    pub verified: bool,
     pub rbf: bool,
+    /// The replacement (fee bump or cancel) that took this tx's place.
+    pub replaced_by: Option<String>,
+    /// The tx this one replaced.
+    pub replaces: Option<String>,
     pub inputs: Vec<BtcxTxDetailInput>,
     pub outputs: Vec<BtcxTxDetailOutput>,
 }
@@ -2052,26 +2720,39 @@ pub fn btcx_wallet_tx_detail(
 ) -> Result<BtcxTxDetail, String> {
     let network = state.get_config().network;
     let txid: bitcoin::Txid = txid.parse().map_err(|e| format!("invalid txid: {e}"))?;
+    let links = super::replacements::read_active(&state);
+    let spv = super::spv::read_active(&state);
     state.with_entry(|entry| {
         use bdk_wallet::chain::ChainPosition;
-        let wtx = entry
-            .wallet
-            .get_tx(txid)
-            .ok_or_else(|| "transaction not found in wallet history".to_string())?;
         let tip = entry.wallet.latest_checkpoint().height();
-        let (confirmations, timestamp, block_height, block_hash) = match wtx.chain_position {
-            ChainPosition::Confirmed { anchor, .. } => (
-                u64::from((tip + 1).saturating_sub(anchor.block_id.height)),
-                Some(anchor.confirmation_time),
-                Some(anchor.block_id.height),
-                Some(anchor.block_id.hash.to_string()),
-            ),
-            ChainPosition::Unconfirmed {
-                first_seen,
-                last_seen,
-            } => (0, first_seen.or(last_seen), None, None),
-        };
-        let tx = wtx.tx_node.tx.clone();
+        let (tx, (confirmations, timestamp, block_height, block_hash)) =
+            match entry.wallet.get_tx(txid) {
+                Some(wtx) => (
+                    wtx.tx_node.tx.clone(),
+                    match wtx.chain_position {
+                        ChainPosition::Confirmed { anchor, .. } => (
+                            u64::from((tip + 1).saturating_sub(anchor.block_id.height)),
+                            Some(anchor.confirmation_time),
+                            Some(anchor.block_id.height),
+                            Some(anchor.block_id.hash.to_string()),
+                        ),
+                        ChainPosition::Unconfirmed {
+                            first_seen,
+                            last_seen,
+                        } => (0, first_seen.or(last_seen), None, None),
+                    },
+                ),
+                // Evicted from the canonical history (replaced) — the graph
+                // still holds it, so a "replaced by" link stays followable.
+                None => (
+                    entry
+                        .wallet
+                        .tx_graph()
+                        .get_tx(txid)
+                        .ok_or_else(|| "transaction not found in wallet history".to_string())?,
+                    (0, None, None, None),
+                ),
+            };
         let (sent, received) = entry.wallet.sent_and_received(&tx);
         let (sent, received) = (sent.to_sat(), received.to_sat());
         let fee = entry.wallet.calculate_fee(&tx).ok().map(|a| a.to_sat());
@@ -2127,8 +2808,13 @@ pub fn btcx_wallet_tx_detail(
             confirmations,
             timestamp,
             block_height,
+            verified: block_hash
+                .as_deref()
+                .is_some_and(|hash| spv.is_verified(&txid.to_string(), hash)),
             block_hash,
             rbf: tx.is_explicitly_rbf(),
+            replaced_by: links.replaced_by(&txid.to_string()).map(str::to_string),
+            replaces: links.replaces(&txid.to_string()).map(str::to_string),
             inputs,
             outputs,
         })
@@ -2150,7 +2836,8 @@ pub fn btcx_wallet_transactions(
 
 /// A send request. Exactly one of `amount_sat` / `send_all` must be given.
 /// Fee: an explicit `fee_rate_sat_vb` wins over `fee_target` (confirmation
-/// target in blocks); with neither, the market estimate at 6 blocks.
+/// target in blocks); with neither, the market estimate at 6 blocks. A
+/// target is estimated per `fee_mode` (see `fees`).
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BtcxSendRequest {
@@ -2166,19 +2853,62 @@ pub struct BtcxSendRequest {
     /// Explicit feerate in sat/vB (decimals carry: 1.08 → 1080 sat/kvB).
     #[serde(default)]
     pub fee_rate_sat_vb: Option<f64>,
+    /// Which estimator answers `fee_target` (default `auto`).
+    #[serde(default)]
+    pub fee_mode: FeeEstimateMode,
+    /// Coin control: spend exactly these outpoints ("txid:vout"). With
+    /// `send_all`, sweeps just these coins.
+    #[serde(default)]
+    pub inputs: Option<Vec<String>>,
 }
 
 impl BtcxSendRequest {
     fn fee(&self) -> SendFee {
-        match self.fee_rate_sat_vb {
-            // sat/vB → sat/kvB, the estimator's native integer resolution.
-            Some(rate) => SendFee::RatePerKvb((rate * 1000.0).round().max(0.0) as u64),
-            None => SendFee::Target(self.fee_target.unwrap_or(6)),
-        }
+        send_fee(self.fee_rate_sat_vb, self.fee_target)
+    }
+}
+
+/// The send commands' fee choice: an explicit rate wins over a target;
+/// neither = the market estimate at 6 blocks.
+fn send_fee(fee_rate_sat_vb: Option<f64>, fee_target: Option<u16>) -> SendFee {
+    match fee_rate_sat_vb {
+        // sat/vB → sat/kvB, the estimator's native integer resolution.
+        Some(rate) => SendFee::RatePerKvb((rate * 1000.0).round().max(0.0) as u64),
+        None => SendFee::Target(fee_target.unwrap_or(6)),
+    }
+}
+
+/// Refuse signing operations (send, bump, PSBT signing, assignments) on a
+/// WATCH-ONLY wallet: it holds no keys, so fail up front with a capability
+/// error instead of building a transaction bdk can never sign. Its spend
+/// path is an unsigned PSBT signed elsewhere.
+pub fn ensure_signing_wallet(source: WalletSourceCfg) -> Result<(), String> {
+    match source {
+        WalletSourceCfg::Seed | WalletSourceCfg::Descriptor => Ok(()),
+        WalletSourceCfg::WatchOnly => Err(
+            "The open wallet is watch-only — it holds no keys and cannot sign; compose an \
+             unsigned PSBT (Transaction Builder) and sign it on the key-holding machine"
+                .into(),
+        ),
+    }
+}
+
+/// Refuse one-shot sign-and-broadcast operations (send, bump) on a multisig
+/// wallet: our signature alone never completes a k-of-n spend. Its spend
+/// path is the cosigner PSBT flow (compose → sign → combine → finalize).
+pub fn ensure_single_sig_wallet(policy: DescriptorPolicy) -> Result<(), String> {
+    if policy.kind == DescriptorKindCfg::Multisig {
+        return Err(
+            "The open wallet is multisig — compose a PSBT, collect the cosigners' signatures, \
+             then combine and finalize it"
+                .into(),
+        );
     }
+    Ok(())
 }
 
 /// Send `amount_sat` (or sweep everything) to `address`, RBF-signaling.
+/// Frozen coins are never selected; `inputs` pins the exact coins to spend.
 /// Returns the txid.
 #[tauri::command]
 pub async fn btcx_wallet_send(
@@ -2187,24 +2917,173 @@ pub async fn btcx_wallet_send(
 ) -> Result<String, String> {
     let state = state.inner().clone();
     blocking(move || {
-        let backend = state.backend()?;
-        let fee = request.fee();
-        match (request.send_all, request.amount_sat) {
-            (true, None) => backend
-                .wallet_send_all(&request.address, fee)
-                .map_err(|e| format!("{e:#}")),
-            (false, Some(amount_sat)) => backend
-                .wallet_send(&request.address, amount_sat, fee)
-                .map_err(|e| format!("{e:#}")),
-            (true, Some(_)) => Err("Give either amountSat or sendAll, not both".to_string()),
-            (false, None) => Err("Missing amountSat (or set sendAll)".to_string()),
-        }
+        let amount_sat = check_send_request(&state, &request)?;
+        super::spend::send(
+            &state,
+            &request.address,
+            amount_sat,
+            resolve_send_fee(&state, request.fee(), request.fee_mode)?,
+            request.inputs.as_deref(),
+        )
+    })
+    .await
+}
+
+/// The send commands' guards and request validation; returns the amount
+/// (`None` = sweep).
+fn check_send_request(
+    state: &SharedBtcxWalletState,
+    request: &BtcxSendRequest,
+) -> Result<Option<u64>, String> {
+    let config = state.get_config();
+    ensure_signing_wallet(state.active_source(&config))?;
+    ensure_single_sig_wallet(config.policy())?;
+    let amount_sat = match (request.send_all, request.amount_sat) {
+        (true, None) => None,
+        (false, Some(amount_sat)) => Some(amount_sat),
+        (true, Some(_)) => return Err("Give either amountSat or sendAll, not both".into()),
+        (false, None) => return Err("Missing amountSat (or set sendAll)".into()),
+    };
+    if request.inputs.as_ref().is_some_and(|i| i.is_empty()) {
+        return Err("Give at least one input, or leave coin selection to the wallet".into());
+    }
+    Ok(amount_sat)
+}
+
+/// Preview a send or sweep: build and sign exactly the transaction
+/// [`btcx_wallet_send`] would broadcast, park it, and return its summary
+/// (fee, inputs, change, vsize) with a short-lived handle. Nothing is
+/// broadcast until [`btcx_wallet_commit_preview`].
+#[tauri::command]
+pub async fn btcx_wallet_preview_send(
+    request: BtcxSendRequest,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<TxPreview, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let amount_sat = check_send_request(&state, &request)?;
+        let (tx, _) = super::spend::build_send(
+            &state,
+            &request.address,
+            amount_sat,
+            resolve_send_fee(&state, request.fee(), request.fee_mode)?,
+            request.inputs.as_deref(),
+        )?;
+        let kind = match amount_sat {
+            Some(_) => PreviewKind::Send,
+            None => PreviewKind::Sweep,
+        };
+        super::preview::stage(&state, kind, tx)
+    })
+    .await
+}
+
+/// Broadcast exactly the transaction a preview parked — never a rebuilt
+/// one. Fails (preview again) when it expired or one of its coins is gone
+/// or frozen. Returns the txid, the one the preview announced.
+#[tauri::command]
+pub async fn btcx_wallet_commit_preview(
+    handle: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || super::preview::commit(&state, &handle)).await
+}
+
+/// Drop a preview the user cancelled, handing its change address back.
+#[tauri::command]
+pub fn btcx_wallet_discard_preview(
+    handle: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<(), String> {
+    super::preview::discard(&state, &handle);
+    Ok(())
+}
+
+/// A batch send: N recipients, one transaction. Fee as in
+/// [`BtcxSendRequest`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcxBatchSendRequest {
+    pub recipients: Vec<super::spend::BatchRecipient>,
+    #[serde(default)]
+    pub fee_target: Option<u16>,
+    #[serde(default)]
+    pub fee_rate_sat_vb: Option<f64>,
+    #[serde(default)]
+    pub fee_mode: FeeEstimateMode,
+    /// Coin control: spend exactly these outpoints ("txid:vout").
+    #[serde(default)]
+    pub inputs: Option<Vec<String>>,
+    /// Build and sign, report fee/change/vsize, but do not broadcast.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Pay every recipient in one RBF transaction (pool payouts). With
+/// `dry_run` the identical transaction is built and signed but not
+/// broadcast — the confirmation screen's fee, change and vsize.
+#[tauri::command]
+pub async fn btcx_wallet_send_batch(
+    request: BtcxBatchSendRequest,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::spend::BatchSendResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_single_sig_wallet(config.policy())?;
+        super::spend::batch_send(
+            &state,
+            &request.recipients,
+            resolve_send_fee(
+                &state,
+                send_fee(request.fee_rate_sat_vb, request.fee_target),
+                request.fee_mode,
+            )?,
+            request.inputs.as_deref(),
+            request.dry_run,
+        )
+    })
+    .await
+}
+
+/// Parse and validate a payout CSV (`address,amount[,label]`, amounts in
+/// coins) against the active network. Pure — builds nothing; the UI shows
+/// the row errors and only offers the batch send when there are none.
+#[tauri::command]
+pub fn btcx_wallet_parse_payout_csv(
+    csv: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::spend::PayoutCsv, String> {
+    Ok(super::spend::parse_payout_csv(
+        state.get_config().network,
+        &csv,
+    ))
+}
+
+/// Child-pays-for-parent: speed up an unconfirmed INCOMING transaction by
+/// spending our output of it at a fee that lifts the package to
+/// `package_fee_rate_sat_vb`. Returns the child plus the effective package
+/// feerate.
+#[tauri::command]
+pub async fn btcx_wallet_cpfp(
+    txid: String,
+    package_fee_rate_sat_vb: f64,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::spend::CpfpResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_single_sig_wallet(config.policy())?;
+        super::spend::cpfp(&state, &txid, package_fee_rate_sat_vb)
     })
     .await
 }
 
 /// RBF-bump a wallet-owned transaction to `fee_rate_sat_vb`; returns the
-/// replacement txid.
+/// replacement txid (and records the replacement link).
 #[tauri::command]
 pub async fn btcx_wallet_bumpfee(
     txid: String,
@@ -2213,11 +3092,34 @@ pub async fn btcx_wallet_bumpfee(
 ) -> Result<String, String> {
     let state = state.inner().clone();
     blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_single_sig_wallet(config.policy())?;
         let feerate_sat_kvb = (fee_rate_sat_vb * 1000.0).round().max(0.0) as u64;
-        state
+        let replacement = state
             .backend()?
             .wallet_bumpfee(&txid, feerate_sat_kvb)
-            .map_err(|e| format!("{e:#}"))
+            .map_err(|e| format!("{e:#}"))?;
+        super::replacements::record_active(&state, &txid, &replacement);
+        Ok(replacement)
+    })
+    .await
+}
+
+/// Cancel an unconfirmed wallet send: replace it with a transaction that
+/// spends the same inputs back to a fresh internal address, at the minimum
+/// fee the replacement rules accept.
+#[tauri::command]
+pub async fn btcx_wallet_cancel_tx(
+    txid: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::spend::CancelResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_single_sig_wallet(config.policy())?;
+        super::spend::cancel(&state, &txid)
     })
     .await
 }
@@ -2237,9 +3139,24 @@ pub async fn btcx_broadcast_tx(
     blocking(move || state.broadcast_tx(&tx_hex, network)).await
 }
 
+/// Run the local standardness and policy checks on a raw transaction
+/// (hex) without broadcasting it — a client-side `testmempoolaccept` with
+/// structured rejection reasons. Chain-only like [`btcx_broadcast_tx`];
+/// with the wallet open on `network` its own unconfirmed graph is checked
+/// for double-spends too.
+#[tauri::command]
+pub async fn btcx_test_mempool_accept(
+    tx_hex: String,
+    network: Option<WalletNetwork>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::policy::PolicyCheck, String> {
+    let state = state.inner().clone();
+    blocking(move || super::policy::test_accept(&state, &tx_hex, network)).await
+}
+
 /// Fee estimates for the send form, decimal sat/vB at the estimator's full
-/// sat/kvB resolution. `None` where the estimator has no data (fall back
-/// to `min_sat_per_vb`).
+/// sat/kvB resolution. `None` where the mode has no estimate (fall back to
+/// `min_sat_per_vb`); every value carries its source.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BtcxFeeEstimates {
@@ -2251,28 +3168,329 @@ pub struct BtcxFeeEstimates {
     pub normal: Option<f64>,
     /// 144-block target.
     pub slow: Option<f64>,
+    pub fast_source: Option<FeeSource>,
+    pub normal_source: Option<FeeSource>,
+    pub slow_source: Option<FeeSource>,
+    /// The 1/3/6/12-block targets (`fees::TARGETS`).
+    pub targets: Vec<BtcxFeeTarget>,
+    /// The estimation mode the values were made in.
+    pub mode: FeeEstimateMode,
+}
+
+/// One confirmation target of [`BtcxFeeEstimates::targets`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BtcxFeeTarget {
+    pub blocks: u16,
+    pub sat_per_vb: Option<f64>,
+    pub source: Option<FeeSource>,
+    /// Expected wait at the PoCX block spacing.
+    pub eta_secs: u64,
 }
 
-/// Market fee estimates from the wallet's home Electrum server.
+/// Fee estimates from the wallet's home Electrum server: its estimator,
+/// its mempool fee histogram, or the estimator with the histogram filling
+/// the gaps (`mode`, default `auto`).
 #[tauri::command]
 pub async fn btcx_wallet_fee_estimates(
+    mode: Option<FeeEstimateMode>,
     state: State<'_, SharedBtcxWalletState>,
 ) -> Result<BtcxFeeEstimates, String> {
     let state = state.inner().clone();
     blocking(move || {
         let backend = state.backend()?;
-        let chain = backend.chain();
-        let vb = |kvb: Option<u64>| kvb.map(|rate| rate as f64 / 1000.0);
+        let mode = mode.unwrap_or_default();
+        let mut estimator = FeeEstimator::new(&state, &backend, mode);
+        let mut estimate = |blocks: u16| -> Result<(Option<f64>, Option<FeeSource>), String> {
+            Ok(match estimator.estimate(blocks)? {
+                Some((kvb, source)) => (Some(kvb as f64 / 1000.0), Some(source)),
+                None => (None, None),
+            })
+        };
+        let (fast, fast_source) = estimate(1)?;
+        let (normal, normal_source) = estimate(6)?;
+        let (slow, slow_source) = estimate(144)?;
+        let targets = TARGETS
+            .iter()
+            .map(|&blocks| {
+                let (sat_per_vb, source) = estimate(blocks)?;
+                Ok(BtcxFeeTarget {
+                    blocks,
+                    sat_per_vb,
+                    source,
+                    eta_secs: eta_secs(blocks),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
         Ok(BtcxFeeEstimates {
             min_sat_per_vb: backend.params().min_feerate_sat_kvb as f64 / 1000.0,
-            fast: vb(chain.fee_estimate_kvb(1).map_err(|e| format!("{e:#}"))?),
-            normal: vb(chain.fee_estimate_kvb(6).map_err(|e| format!("{e:#}"))?),
-            slow: vb(chain.fee_estimate_kvb(144).map_err(|e| format!("{e:#}"))?),
+            fast,
+            normal,
+            slow,
+            fast_source,
+            normal_source,
+            slow_source,
+            targets,
+            mode,
         })
     })
     .await
 }
 
+// ============================================================================
+// Labels (BIP-329)
+// ============================================================================
+
+/// Every label record of the active wallet, in (type, ref) order.
+#[tauri::command]
+pub fn btcx_wallet_labels(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<LabelRecord>, String> {
+    Ok(super::labels::read_active(&state)?
+        .records()
+        .cloned()
+        .collect())
+}
+
+/// The label of one txid / address / outpoint (`txid:vout`) / xpub or
+/// descriptor, if any.
+#[tauri::command]
+pub fn btcx_wallet_get_label(
+    kind: LabelType,
+    reference: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Option<String>, String> {
+    let reference = super::labels::normalize_ref(kind, &reference)?;
+    Ok(super::labels::read_active(&state)?
+        .label(kind, &reference)
+        .map(str::to_string))
+}
+
+/// Set (or replace) a label. An empty label is refused — clearing goes
+/// through `btcx_wallet_delete_label`.
+#[tauri::command]
+pub fn btcx_wallet_set_label(
+    kind: LabelType,
+    reference: String,
+    label: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<LabelRecord, String> {
+    super::labels::update_active(&state, |labels| labels.set(kind, &reference, &label))
+}
+
+/// Remove a label. Returns whether there was one.
+#[tauri::command]
+pub fn btcx_wallet_delete_label(
+    kind: LabelType,
+    reference: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<bool, String> {
+    super::labels::update_active(&state, |labels| labels.remove(kind, &reference))
+}
+
+/// The active wallet's labels as a BIP-329 JSONL document (Sparrow,
+/// accounting scripts). The frontend saves it to a file.
+#[tauri::command]
+pub fn btcx_wallet_export_labels(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    Ok(super::labels::read_active(&state)?.to_jsonl())
+}
+
+/// Merge a BIP-329 JSONL document into the active wallet's labels:
+/// imported labels overwrite ours, invalid lines are skipped and counted.
+#[tauri::command]
+pub fn btcx_wallet_import_labels(
+    jsonl: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<LabelImportSummary, String> {
+    super::labels::update_active(&state, |labels| Ok(labels.import_jsonl(&jsonl)))
+}
+
+// ============================================================================
+// Coin Control
+// ============================================================================
+
+/// The active wallet's frozen outpoints ("txid:vout"), persisted across
+/// restarts — the remote-mode `listlockunspent`.
+#[tauri::command]
+pub fn btcx_wallet_frozen_utxos(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<String>, String> {
+    Ok(super::labels::read_active(&state)?
+        .frozen()
+        .iter()
+        .map(|o| o.to_string())
+        .collect())
+}
+
+/// Freeze (`frozen: true`) or thaw outpoints — a persisted `lockunspent`.
+/// Frozen coins are skipped by send, PSBT compose and assignments until
+/// thawed. Returns the resulting frozen set.
+#[tauri::command]
+pub fn btcx_wallet_set_frozen(
+    outpoints: Vec<String>,
+    frozen: bool,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<String>, String> {
+    super::labels::update_active(&state, |labels| {
+        for outpoint in &outpoints {
+            labels.set_frozen(outpoint, frozen)?;
+        }
+        Ok(labels.frozen().iter().map(|o| o.to_string()).collect())
+    })
+}
+
+// ============================================================================
+// Payment URIs & Requests (BIP-21)
+// ============================================================================
+
+/// Parse a BIP-21 payment URI (`btcx:`, `pocx:` or `bitcoin:`; a bare
+/// address also passes) against the active network.
+#[tauri::command]
+pub fn btcx_wallet_parse_payment_uri(
+    uri: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<PaymentUri, String> {
+    super::payreq::parse_payment_uri(state.get_config().network, &uri)
+}
+
+/// Build the canonical `btcx:` URI for an address of the active network.
+#[tauri::command]
+pub fn btcx_wallet_build_payment_uri(
+    request: PaymentUri,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    super::payreq::build_payment_uri(state.get_config().network, &request)
+}
+
+/// Create a payment request (invoice) on the current receive address:
+/// expected amount (`None` = any), optional label/message, optional expiry
+/// in seconds from now.
+#[tauri::command]
+pub async fn btcx_wallet_create_payment_request(
+    amount_sat: Option<u64>,
+    label: Option<String>,
+    message: Option<String>,
+    expires_in_secs: Option<u64>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<PaymentRequestStatus, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        super::payreq::create_request(&state, amount_sat, label, message, expires_in_secs)
+    })
+    .await
+}
+
+/// Every payment request of the active wallet, newest first, with its
+/// status (pending / partially paid / paid / expired) from the synced graph.
+#[tauri::command]
+pub fn btcx_wallet_payment_requests(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<Vec<PaymentRequestStatus>, String> {
+    super::payreq::list_requests(&state)
+}
+
+/// Delete a payment request by its address. Returns whether there was one.
+#[tauri::command]
+pub fn btcx_wallet_delete_payment_request(
+    address: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<bool, String> {
+    super::payreq::delete_request(&state, &address)
+}
+
+// ============================================================================
+// Message Signing
+// ============================================================================
+
+/// Sign `message` with the open wallet's key for `address` — legacy
+/// (`signmessage`, P2PKH/P2WPKH) or BIP-322 simple/full (P2WPKH/P2TR).
+/// Returns the base64 signature.
+#[tauri::command]
+pub async fn btcx_wallet_sign_message(
+    address: String,
+    message: String,
+    format: MessageFormat,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_single_sig_wallet(config.policy())?;
+        super::message::sign_message(&state, &address, &message, format)
+    })
+    .await
+}
+
+/// Verify a legacy or BIP-322 message signature. Chain-only: needs NO open
+/// wallet. `network` picks the address encoding (default: the wallet
+/// config's active network).
+#[tauri::command]
+pub fn btcx_verify_message(
+    address: String,
+    message: String,
+    signature: String,
+    network: Option<WalletNetwork>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<MessageVerification, String> {
+    let network = network.unwrap_or_else(|| state.get_config().network);
+    super::message::verify_message(network, &address, &message, &signature)
+}
+
+// ============================================================================
+// Backup Bundles
+// ============================================================================
+
+/// Export one wallet group (`group`) — or every wallet of the active
+/// network — as a single `backup_passphrase`-sealed bundle (see
+/// `backup`). `wallet_passphrase` unlocks passphrase-encrypted members
+/// other than the open wallet.
+#[tauri::command]
+pub async fn btcx_wallet_export_backup(
+    group: Option<String>,
+    backup_passphrase: String,
+    wallet_passphrase: Option<String>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        super::backup::export_backup(
+            &state,
+            group.as_deref(),
+            &backup_passphrase,
+            wallet_passphrase.as_deref(),
+        )
+    })
+    .await
+}
+
+/// Restore a sealed bundle: registry entries, group membership, key and
+/// side files, then open its active wallet — no restore probe. Refuses
+/// (before writing anything) a bundle whose names or groups are taken.
+/// `wallet_passphrase` encrypts the restored key material at rest.
+#[tauri::command]
+pub async fn btcx_wallet_import_backup(
+    bundle: String,
+    backup_passphrase: String,
+    wallet_passphrase: Option<String>,
+    app: AppHandle,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<BackupRestoreResult, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        super::backup::import_backup(
+            &state,
+            Some(app),
+            &bundle,
+            &backup_passphrase,
+            wallet_passphrase.as_deref(),
+        )
+    })
+    .await
+}
+
 // ============================================================================
 // Configuration & Sync
 // ============================================================================
@@ -2354,6 +3572,11 @@ pub fn ensure_segwit_wallet(policy: DescriptorPolicy) -> Result<(), String> {
              (pre-segwit)"
                 .into(),
         ),
+        DescriptorKindCfg::Multisig => Err(
+            "Forging assignments require a single-key segwit-v0 wallet — the open wallet is \
+             multisig"
+                .into(),
+        ),
     }
 }
 
@@ -2371,7 +3594,9 @@ pub async fn btcx_wallet_create_assignment(
 ) -> Result<super::assignments::CreateAssignmentDto, String> {
     let state = state.inner().clone();
     blocking(move || {
-        ensure_segwit_wallet(state.get_config().policy())?;
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_segwit_wallet(config.policy())?;
         super::assignments::create_assignment(
             &state,
             &plot_address,
@@ -2392,22 +3617,68 @@ pub async fn btcx_wallet_revoke_assignment(
 ) -> Result<super::assignments::RevokeAssignmentDto, String> {
     let state = state.inner().clone();
     blocking(move || {
-        ensure_segwit_wallet(state.get_config().policy())?;
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_segwit_wallet(config.policy())?;
         super::assignments::revoke_assignment(&state, &plot_address, fee_rate_sat_vb)
     })
     .await
 }
 
-/// Assignment status of `plot_address`, derived from its Electrum script
-/// history — the remote-mode replacement for the node's `get_assignment`.
-/// Chain-only: needs no seed and no open wallet.
+/// Preview a forging assignment: the signed, unbroadcast transaction
+/// [`btcx_wallet_create_assignment`] would send, committed with
+/// [`btcx_wallet_commit_preview`].
 #[tauri::command]
-pub async fn btcx_wallet_get_assignment(
+pub async fn btcx_wallet_preview_create_assignment(
     plot_address: String,
+    forging_address: String,
+    fee_rate_sat_vb: Option<f64>,
     state: State<'_, SharedBtcxWalletState>,
-) -> Result<super::assignments::AssignmentStatusDto, String> {
+) -> Result<TxPreview, String> {
     let state = state.inner().clone();
-    blocking(move || super::assignments::get_assignment(&state, &plot_address)).await
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_segwit_wallet(config.policy())?;
+        let tx = super::assignments::build_assignment(
+            &state,
+            &plot_address,
+            &forging_address,
+            fee_rate_sat_vb,
+        )?;
+        super::preview::stage(&state, PreviewKind::Assignment, tx)
+    })
+    .await
+}
+
+/// Preview a revocation (see [`btcx_wallet_preview_create_assignment`]).
+#[tauri::command]
+pub async fn btcx_wallet_preview_revoke_assignment(
+    plot_address: String,
+    fee_rate_sat_vb: Option<f64>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<TxPreview, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let config = state.get_config();
+        ensure_signing_wallet(state.active_source(&config))?;
+        ensure_segwit_wallet(config.policy())?;
+        let tx = super::assignments::build_revocation(&state, &plot_address, fee_rate_sat_vb)?;
+        super::preview::stage(&state, PreviewKind::Revocation, tx)
+    })
+    .await
+}
+
+/// Assignment status of `plot_address`, derived from its Electrum script
+/// history — the remote-mode replacement for the node's `get_assignment`.
+/// Chain-only: needs no seed and no open wallet.
+#[tauri::command]
+pub async fn btcx_wallet_get_assignment(
+    plot_address: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::assignments::AssignmentStatusDto, String> {
+    let state = state.inner().clone();
+    blocking(move || super::assignments::get_assignment(&state, &plot_address)).await
 }
 
 // ============================================================================
@@ -2430,14 +3701,18 @@ pub fn btcx_psbt_analyze(psbt_base64: String) -> Result<super::psbt::PsbtAnalyze
 }
 
 /// Sign a PSBT with the open wallet — client-side `walletprocesspsbt`.
-/// Foreign inputs pass through untouched.
+/// Foreign inputs pass through untouched. Refused on a watch-only wallet.
 #[tauri::command]
 pub async fn btcx_psbt_wallet_process(
     psbt_base64: String,
     state: State<'_, SharedBtcxWalletState>,
 ) -> Result<super::psbt::PsbtProcessDto, String> {
     let state = state.inner().clone();
-    blocking(move || super::psbt::wallet_process(&state, &psbt_base64)).await
+    blocking(move || {
+        ensure_signing_wallet(state.active_source(&state.get_config()))?;
+        super::psbt::wallet_process(&state, &psbt_base64)
+    })
+    .await
 }
 
 /// Finalize a PSBT's wallet-owned inputs — client-side `finalizepsbt`.
@@ -2458,8 +3733,39 @@ pub fn btcx_psbt_combine(psbts: Vec<String>) -> Result<String, String> {
     super::psbt::combine(&psbts)
 }
 
+/// Encode a PSBT as animated `ur:crypto-psbt` QR frames for an air-gapped
+/// signer. Pure; needs no wallet — but runs off the main thread, a large
+/// PSBT takes a while to fragment.
+#[tauri::command]
+pub async fn btcx_ur_encode_psbt(
+    psbt_base64: String,
+    max_fragment_len: Option<usize>,
+    frame_count: Option<usize>,
+) -> Result<super::ur::UrFrames, String> {
+    blocking(move || super::ur::encode_psbt(&psbt_base64, max_fragment_len, frame_count)).await
+}
+
+/// Feed one scanned `ur:crypto-psbt` frame to the scan in progress; the
+/// PSBT comes back once enough frames are in (any order, repeats fine).
+#[tauri::command]
+pub async fn btcx_ur_scan_frame(
+    frame: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::ur::UrScanProgress, String> {
+    let state = state.inner().clone();
+    blocking(move || state.scan_ur_frame(&frame)).await
+}
+
+/// Abandon the animated-QR scan in progress.
+#[tauri::command]
+pub fn btcx_ur_scan_reset(state: State<'_, SharedBtcxWalletState>) {
+    state.reset_ur_scan();
+}
+
 /// Compose a funded, UNSIGNED PSBT from the open wallet — client-side
-/// `walletcreatefundedpsbt` (the Transaction Builder's compose tab).
+/// `walletcreatefundedpsbt` (the Transaction Builder's compose tab). The
+/// spend path of a watch-only wallet: the PSBT carries the key origins an
+/// offline signer needs.
 #[tauri::command]
 pub async fn btcx_wallet_create_funded_psbt(
     outputs: Vec<super::psbt::PsbtRecipient>,
@@ -2472,6 +3778,66 @@ pub async fn btcx_wallet_create_funded_psbt(
         .await
 }
 
+/// Add the spent outputs of a foreign PSBT's inputs, fetched over Electrum —
+/// client-side `utxoupdatepsbt`. Chain-only: `network` picks the server
+/// list (default: the active network).
+#[tauri::command]
+pub async fn btcx_psbt_utxo_update(
+    psbt_base64: String,
+    network: Option<WalletNetwork>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || super::rawtx::utxo_update(&state, &psbt_base64, network)).await
+}
+
+/// The raw transaction of a PSBT, complete or not (final inputs carry
+/// their signatures). Pure; needs no wallet.
+#[tauri::command]
+pub fn btcx_psbt_to_rawtx(psbt_base64: String) -> Result<super::rawtx::RawTxDto, String> {
+    super::rawtx::from_psbt(&psbt_base64)
+}
+
+// ============================================================================
+// Raw Transactions (remote node mode)
+// ============================================================================
+
+/// Decode a raw transaction (hex) for display — client-side
+/// `decoderawtransaction`.
+#[tauri::command]
+pub fn btcx_rawtx_decode(
+    tx_hex: String,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<super::rawtx::RawTxDecodeDto, String> {
+    super::rawtx::decode(state.get_config().network, &tx_hex)
+}
+
+/// Create an unsigned raw transaction from explicit inputs and outputs —
+/// client-side `createrawtransaction`. Needs no wallet.
+#[tauri::command]
+pub fn btcx_rawtx_create(
+    inputs: Vec<super::rawtx::RawTxInput>,
+    outputs: Vec<super::rawtx::RawTxOutput>,
+    locktime: Option<u32>,
+    replaceable: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<String, String> {
+    super::rawtx::create(
+        state.get_config().network,
+        &inputs,
+        &outputs,
+        locktime,
+        replaceable,
+    )
+}
+
+/// Convert a raw transaction into an empty PSBT — client-side
+/// `converttopsbt`. Signature data is refused unless `permit_sig_data`.
+#[tauri::command]
+pub fn btcx_rawtx_to_psbt(tx_hex: String, permit_sig_data: Option<bool>) -> Result<String, String> {
+    super::rawtx::to_psbt(&tx_hex, permit_sig_data.unwrap_or(false))
+}
+
 /// The open wallet's unspent outputs (cache read) — the remote-mode
 /// `listunspent`.
 #[tauri::command]
@@ -2485,27 +3851,119 @@ pub fn btcx_wallet_utxos(
 // Electrum Health & Chain Info (remote node mode)
 // ============================================================================
 
+/// One server in `btcx_electrum_health`: its passive health snapshot plus
+/// its line of the last header consensus check, if one ran on this network.
+#[derive(Debug, Clone, Serialize)]
+pub struct ElectrumServerHealth {
+    #[serde(flatten)]
+    pub snapshot: electrum_btcx::HealthSnapshot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consensus: Option<ServerConsensus>,
+    /// Found by peer discovery rather than configured (see `discovery`).
+    pub discovered: bool,
+}
+
 /// Per-server health snapshots of the ACTIVE network's configured Electrum
-/// servers, with roles stamped from the open runtime (`wallet` = home,
-/// `view` = broadcast fallback, `standby` = configured but unused). Cheap:
-/// reads the passive health cells, no network I/O.
+/// servers followed by its discovered ones, with roles stamped from the
+/// open runtime (`wallet` = home, `view` = broadcast fallback, `standby` =
+/// listed but unused) and the cached header consensus verdicts. Cheap:
+/// reads the passive health cells and the cached report, no network I/O.
 #[tauri::command]
 pub fn btcx_electrum_health(
     state: State<'_, SharedBtcxWalletState>,
-) -> Result<Vec<electrum_btcx::HealthSnapshot>, String> {
+) -> Result<Vec<ElectrumServerHealth>, String> {
     let config = state.get_config();
-    let servers = config.servers();
-    let urls: Vec<&str> = servers.iter().map(String::as_str).collect();
+    let configured = config.servers();
+    let servers = config.listed_servers(config.network);
+    // Health cells are keyed by the URL actually dialed — a proxy or TLS
+    // forwarder's; report the configured URLs.
+    let dialed: Vec<String> = servers
+        .iter()
+        .map(|url| super::tlspin::dialed_url(url))
+        .collect();
+    let urls: Vec<&str> = dialed.iter().map(String::as_str).collect();
     let mut snapshots = electrum_btcx::server_health::coin_snapshots(super::config::COIN_ID, &urls);
     let runtime_urls = state.runtime_urls();
     for snapshot in &mut snapshots {
+        if let Some(i) = dialed.iter().position(|d| *d == snapshot.url) {
+            snapshot.url = servers[i].clone();
+        }
         snapshot.role = Some(match &runtime_urls {
             Some((home, _)) if *home == snapshot.url => "wallet".to_string(),
             Some((_, views)) if views.contains(&snapshot.url) => "view".to_string(),
             _ => "standby".to_string(),
         });
     }
-    Ok(snapshots)
+    let report = state
+        .cached_consensus()
+        .filter(|r| r.network == config.network.as_str());
+    Ok(snapshots
+        .into_iter()
+        .map(|snapshot| ElectrumServerHealth {
+            consensus: report
+                .as_ref()
+                .and_then(|r| r.server(&snapshot.url))
+                .cloned(),
+            discovered: !configured.contains(&snapshot.url),
+            snapshot,
+        })
+        .collect())
+}
+
+/// Run a header consensus check over the active network's servers now
+/// (fresh connections, never the pool) and cache it for
+/// `btcx_electrum_health`.
+#[tauri::command]
+pub async fn btcx_electrum_check_consensus(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<ConsensusReport, String> {
+    let state = state.inner().clone();
+    blocking(move || super::consensus::refresh(&state)).await
+}
+
+/// Set the header consensus mode (`off` / `warn` / `enforce`).
+#[tauri::command]
+pub fn btcx_wallet_set_header_consensus(
+    mode: HeaderConsensusMode,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<(), String> {
+    state.update_config(|c| c.header_consensus = mode)?;
+    Ok(())
+}
+
+/// Run a peer discovery pass for the active network now: ask the servers
+/// (configured, discovered and built-in bootstrap ones) for their peers,
+/// probe the candidates and keep the best healthy ones (appending them to
+/// the server list in `auto` mode). Needs no open wallet. Errors when
+/// discovery is off or there is no server to ask.
+#[tauri::command]
+pub async fn btcx_electrum_discover(
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<DiscoveryReport, String> {
+    let state = state.inner().clone();
+    blocking(move || super::discovery::refresh(&state)).await
+}
+
+/// Set the peer discovery mode (`off` / `views` / `auto`) and, when given,
+/// whether discovered servers may carry wallet traffic (home, failover
+/// views, broadcasts — takes effect at the next wallet open). Turning
+/// discovery off also forgets the discovered servers.
+#[tauri::command]
+pub fn btcx_wallet_set_server_discovery(
+    mode: ServerDiscoveryMode,
+    failover: Option<bool>,
+    state: State<'_, SharedBtcxWalletState>,
+) -> Result<(), String> {
+    state.update_config(|c| {
+        c.server_discovery = Some(mode);
+        if let Some(failover) = failover {
+            c.discovered_failover = failover;
+        }
+        if mode == ServerDiscoveryMode::Off {
+            c.discovered_servers.clear();
+        }
+    })?;
+    Ok(())
 }
 
 /// Result of a live Electrum server probe (`btcx_electrum_probe`).
@@ -2516,12 +3974,23 @@ pub struct ElectrumProbeResult {
     pub height: u64,
     /// Round-trip time of the tip fetch, milliseconds.
     pub latency_ms: f64,
+    /// SHA-256 fingerprint of the certificate an `ssl://` server presented.
+    pub cert_fingerprint: Option<String>,
+    /// Whether that certificate chains to a trusted CA for the host name
+    /// (such a certificate is never pinned).
+    pub cert_ca_valid: bool,
+    /// Whether that certificate is the pinned one (`false` without CA
+    /// validity: not pinned yet — the first wallet connection pins it).
+    pub cert_pinned: bool,
 }
 
 /// Probe one Electrum server with a FRESH connection (never the pool): dial,
 /// verify it serves the expected chain (genesis check — catches a server of
 /// the wrong network), and time a tip fetch. The settings page's "Test
-/// connection" button.
+/// connection" button. An `ssl://` server reports the certificate it
+/// presents and whether a CA vouches for it; a probe never pins it, but a
+/// pinned server presenting another certificate fails with "certificate
+/// changed".
 #[tauri::command]
 pub async fn btcx_electrum_probe(
     url: String,
@@ -2532,23 +4001,53 @@ pub async fn btcx_electrum_probe(
     blocking(move || {
         let network = network.unwrap_or_else(|| state.get_config().network);
         let params = network.params();
-        let backend = electrum_btcx::ElectrumBackend::new(params, url.trim())
-            .map_err(|e| format!("{e:#}"))?;
-        let started = std::time::Instant::now();
-        let (height, _) = backend
-            .tip()
-            .map_err(|e| format!("Server unreachable: {e:#}"))?;
-        backend
-            .verify_chain()
-            .map_err(|e| format!("Wrong chain or unusable server: {e:#}"))?;
+        let url = url.trim();
+        let presented = url
+            .starts_with("ssl://")
+            .then(|| super::tlspin::presented(url))
+            .transpose()?;
+        let (height, latency_ms) = super::discovery::probe(params, url)?;
+        let cert_pinned = match (&presented, super::tlspin::pin_of(url)) {
+            (Some(presented), Some(pin)) => {
+                super::tlspin::same_fingerprint(&presented.fingerprint, &pin)
+            }
+            _ => false,
+        };
         Ok(ElectrumProbeResult {
             height,
-            latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+            latency_ms,
+            cert_ca_valid: presented.as_ref().is_some_and(|p| p.ca_valid),
+            cert_fingerprint: presented.map(|p| p.fingerprint),
+            cert_pinned,
         })
     })
     .await
 }
 
+/// Re-pin an `ssl://` server to the certificate it presents now — the way
+/// out of a "certificate changed" refusal after the operator rotated it.
+/// `fingerprint`, when given, must match the presented certificate. An
+/// open wallet is reopened so its connections re-dial under the new pin.
+/// Returns the new pin.
+#[tauri::command]
+pub async fn btcx_electrum_repin_certificate(
+    url: String,
+    fingerprint: Option<String>,
+    state: State<'_, SharedBtcxWalletState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    let state = state.inner().clone();
+    blocking(move || {
+        let pin = super::tlspin::repin(&state, &url, fingerprint.as_deref())?;
+        if state.is_open() {
+            state.close_runtime();
+            state.open_runtime(Some(app))?;
+        }
+        Ok(pin)
+    })
+    .await
+}
+
 /// Chain tip snapshot from the active network's first configured Electrum
 /// server (`btcx_chain_info`) — the remote-mode replacement for the desktop
 /// header's getblockchaininfo/getblock poll. Chain-only: needs no seed and
@@ -2713,6 +4212,131 @@ mod tests {
         .unwrap_err();
         assert!(err.contains("segwit-v0"), "{err}");
         assert!(err.contains("legacy"), "{err}");
+
+        // Multisig (P2WSH) wallets can't own a plot either — and never
+        // one-shot send: their spends go through the cosigner PSBT flow.
+        let multisig = DescriptorPolicy {
+            kind: DescriptorKindCfg::Multisig,
+            coin_type: 1,
+        };
+        let err = ensure_segwit_wallet(multisig).unwrap_err();
+        assert!(err.contains("multisig"), "{err}");
+        let err = ensure_single_sig_wallet(multisig).unwrap_err();
+        assert!(err.contains("PSBT"), "{err}");
+        assert!(ensure_single_sig_wallet(DescriptorPolicy::default()).is_ok());
+    }
+
+    /// The create-multisig flow, OFFLINE: our BIP-48 key derives from the
+    /// active seed wallet, the cosigners' xpubs join it in a 2-of-3
+    /// `wsh(sortedmulti)` pair that runs the descriptor-import flow —
+    /// registry entry kind=multisig with its shape, runtime open on P2WSH
+    /// addresses, and a public backup listing every cosigner.
+    #[test]
+    fn create_multisig_flow_works_offline() {
+        let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+        std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+        let state = super::super::state::create_btcx_wallet_state();
+        state
+            .update_config(|c| {
+                c.network = WalletNetwork::Regtest;
+                c.set_servers(
+                    WalletNetwork::Regtest,
+                    vec!["tcp://127.0.0.1:1".to_string()],
+                );
+            })
+            .unwrap();
+        import_into_named_wallet(
+            &state,
+            WalletNetwork::Regtest,
+            "seed",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+            None,
+            "",
+            DescriptorPolicy::default(),
+        )
+        .unwrap();
+
+        let (_, own) = own_multisig_keys(&state, &state.get_config()).unwrap();
+        assert!(own.contains("/48'/1'/0'/2']tpub"), "{own}");
+
+        // Two deterministic cosigners (NOT real wallets).
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let cosigners: Vec<String> = [5u8, 6]
+            .iter()
+            .map(|&b| {
+                let master =
+                    bitcoin::bip32::Xpriv::new_master(bitcoin::NetworkKind::Test, &[b; 32])
+                        .unwrap();
+                let path: Vec<bitcoin::bip32::ChildNumber> = [48u32, 1, 0, 2]
+                    .iter()
+                    .map(|&i| bitcoin::bip32::ChildNumber::from_hardened_idx(i).unwrap())
+                    .collect();
+                let account = master.derive_priv(&secp, &path).unwrap();
+                let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+                format!("[{}/48'/1'/0'/2']{xpub}", master.fingerprint(&secp))
+            })
+            .collect();
+
+        // Our own key in the cosigner list is refused.
+        let err = create_multisig_wallet_impl(
+            &state,
+            None,
+            2,
+            &[own.clone(), cosigners[0].clone()],
+            None,
+            Some("dup".into()),
+        )
+        .unwrap_err();
+        assert!(err.contains("own key"), "{err}");
+
+        let result =
+            create_multisig_wallet_impl(&state, None, 2, &cosigners, None, Some("vault".into()))
+                .expect("create multisig");
+        assert_eq!(result.policy.kind, DescriptorKindCfg::Multisig);
+        assert_eq!(
+            result.multisig,
+            Some(MultisigShape {
+                threshold: 2,
+                cosigners: 3
+            })
+        );
+        assert!(result.status.wallet_active);
+        assert!(!result.status.watch_only, "holds our private key");
+        let meta = state
+            .get_config()
+            .wallet_meta(WalletNetwork::Regtest, "vault")
+            .unwrap();
+        assert_eq!(meta.source, WalletSourceCfg::Descriptor);
+        assert_eq!(meta.multisig, result.multisig);
+
+        // P2WSH receive addresses (bech32 v0, 32-byte program).
+        let address = state
+            .with_entry(|entry| current_address_of(entry, WalletNetwork::Regtest))
+            .unwrap();
+        assert!(address.starts_with("rpocx1q"), "{address}");
+        assert!(address.len() > 50, "P2WSH, not P2WPKH: {address}");
+
+        // The backup is public and lists all three cosigners, ours included.
+        let config = state.get_config();
+        let payload = active_descriptor_payload(&state, &config).unwrap();
+        let backup = descriptors::multisig_backup(
+            "vault",
+            &payload.external,
+            payload.internal.as_deref().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(backup.cosigners.len(), 3);
+        let own_xpub = own.rsplit_once(']').unwrap().1;
+        assert!(backup.cosigners.iter().any(|c| c.xpub == own_xpub));
+        assert!(!backup.text.contains("tprv"));
+
+        state.close_runtime();
+        std::env::remove_var("PHOENIX_DATA_DIR");
+        std::env::remove_var("PACT_DISABLE_KEYRING");
     }
 
     /// The full descriptor-import flow, OFFLINE (unreachable Electrum
@@ -2720,7 +4344,8 @@ mod tests {
     /// already say which scripts the wallet owns. Exercises the exact code
     /// `btcx_wallet_import_descriptor` runs: registry entry with
     /// source=descriptor + classified kind, descriptor store on disk (no
-    /// seed file), runtime open, and the watch-only/bare-key rejections.
+    /// seed file), runtime open, the public-paste watch-only wallet, and
+    /// the bare-key rejection.
     #[test]
     fn import_descriptor_flow_works_offline() {
         let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
@@ -2777,17 +4402,7 @@ mod tests {
             .unwrap();
         assert!(address.starts_with("rpocx1q"), "{address}");
 
-        // Watch-only and bare-key pastes never write anything.
-        let tpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
-        let err = import_descriptor_wallet_impl(
-            &state,
-            None,
-            &format!("wpkh({tpub}/0/*)"),
-            None,
-            Some("watchonly".into()),
-        )
-        .unwrap_err();
-        assert!(err.contains("Watch-only"), "{err}");
+        // A bare-key paste never writes anything.
         let err = import_descriptor_wallet_impl(
             &state,
             None,
@@ -2799,9 +4414,39 @@ mod tests {
         assert!(err.contains("full descriptor"), "{err}");
         assert!(state
             .get_config()
-            .wallet_meta(WalletNetwork::Regtest, "watchonly")
+            .wallet_meta(WalletNetwork::Regtest, "barekey")
             .is_none());
 
+        // The PUBLIC side of the same account imports as a watch-only
+        // wallet: no secret on disk, same addresses, signing refused.
+        let tpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+        let public = format!("wpkh([{fp}/84'/1'/0']{tpub}/0/*)");
+        let err =
+            import_descriptor_wallet_impl(&state, None, &public, Some("pw"), Some("wo".into()))
+                .unwrap_err();
+        assert!(err.contains("nothing to encrypt"), "{err}");
+        let result =
+            import_descriptor_wallet_impl(&state, None, &public, None, Some("watchonly".into()))
+                .expect("watch-only import");
+        assert!(result.status.watch_only);
+        assert!(!result.status.seed_encrypted);
+        assert!(result.status.wallet_active, "watch-only runtime opens");
+        let meta = state
+            .get_config()
+            .wallet_meta(WalletNetwork::Regtest, "watchonly")
+            .unwrap();
+        assert_eq!(meta.source, WalletSourceCfg::WatchOnly);
+        let root = BtcxWalletConfig::wallet_root(WalletNetwork::Regtest, "watchonly");
+        assert!(root.join(descstore::PUBLIC_DESCRIPTOR_FILE).exists());
+        assert!(!root.join(descstore::DESCRIPTOR_FILE).exists());
+        assert!(!root.join(seedstore::SEED_FILE).exists());
+        let watched = state
+            .with_entry(|entry| current_address_of(entry, WalletNetwork::Regtest))
+            .unwrap();
+        assert_eq!(watched, address, "same account, same first address");
+        let err = ensure_signing_wallet(state.active_source(&state.get_config())).unwrap_err();
+        assert!(err.contains("watch-only"), "{err}");
+
         // Importing over a taken name is refused before anything parses.
         let err =
             import_descriptor_wallet_impl(&state, None, &external, None, Some("imported".into()))
@@ -2909,6 +4554,8 @@ mod tests {
             send_all: false,
             fee_target,
             fee_rate_sat_vb,
+            fee_mode: FeeEstimateMode::Auto,
+            inputs: None,
         }
     }
 
@@ -2937,6 +4584,14 @@ mod tests {
         assert_eq!(req.amount_sat, Some(12345));
         assert!(!req.send_all);
         assert_eq!(req.fee(), SendFee::RatePerKvb(2500));
+        assert_eq!(req.fee_mode, FeeEstimateMode::Auto);
+
+        let mempool: BtcxSendRequest = serde_json::from_str(
+            r#"{"address":"rpocx1qxyz","amountSat":1,"feeTarget":3,"feeMode":"mempool"}"#,
+        )
+        .unwrap();
+        assert_eq!(mempool.fee_mode, FeeEstimateMode::Mempool);
+        assert_eq!(mempool.fee(), SendFee::Target(3));
 
         let sweep: BtcxSendRequest =
             serde_json::from_str(r#"{"address":"rpocx1qxyz","sendAll":true}"#).unwrap();
@@ -3067,6 +4722,274 @@ mod tests {
         std::env::remove_var("PACT_DISABLE_KEYRING");
     }
 
+    /// A tandem-deleted group comes back from the trash as one batch: the
+    /// trash keeps each member's registry entry, and a restore whose group
+    /// id was taken meanwhile moves the whole group to a free name.
+    #[test]
+    fn deleted_group_restores_from_trash() {
+        let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+        std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+        let state = offline_regtest_state();
+        let net = WalletNetwork::Regtest;
+        let create_family = || {
+            create_wallet_impl(
+                &state,
+                None,
+                MNEMONIC_24,
+                None,
+                "",
+                Some("family".into()),
+                None,
+            )
+            .unwrap();
+            state.close_runtime();
+        };
+        create_family();
+        delete_group_impl(&state, "family", "family").unwrap();
+        let trash = BtcxWalletConfig::wallet_dir()
+            .join(net.as_str())
+            .join(TRASH_SUBDIR);
+
+        // The trash lists both members with their registry entries...
+        let trashed = super::super::trash::list_in(&trash).unwrap();
+        assert_eq!(trashed.len(), 2);
+        assert!(trashed
+            .iter()
+            .all(|t| t.group.as_deref() == Some("family") && t.policy.is_some()));
+        // ...and a group restore brings the batch back. A new `family`
+        // wallet took the group id meanwhile, so the group moves over.
+        create_family();
+        let mut restored = super::super::trash::restore_group(&state, &trashed[0].id).unwrap();
+        restored.sort();
+        assert_eq!(restored, vec!["family-2", "family-2-taproot"]);
+        let config = state.get_config();
+        assert_eq!(config.group_of(net, "family-2-taproot"), "family-2");
+        assert_eq!(
+            config
+                .wallet_meta(net, "family-2-taproot")
+                .unwrap()
+                .policy
+                .kind,
+            DescriptorKindCfg::Bip86
+        );
+        assert!(BtcxWalletConfig::wallet_root(net, "family-2")
+            .join(seedstore::SEED_FILE)
+            .exists());
+        assert!(super::super::trash::list_in(&trash).unwrap().is_empty());
+
+        std::env::remove_var("PHOENIX_DATA_DIR");
+        std::env::remove_var("PACT_DISABLE_KEYRING");
+    }
+
+    /// At-rest passphrase changes, OFFLINE: add, change (wrong current
+    /// refused, nothing touched), and confirmed removal — always on BOTH
+    /// compartments of the group.
+    #[test]
+    fn change_passphrase_rewraps_the_whole_group() {
+        use super::super::rewrap::change_passphrase;
+
+        let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+        std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+        let state = offline_regtest_state();
+        let net = WalletNetwork::Regtest;
+        create_wallet_impl(
+            &state,
+            None,
+            MNEMONIC_24,
+            None,
+            "",
+            Some("lockme".into()),
+            None,
+        )
+        .unwrap();
+        let encrypted = |name: &str| {
+            super::super::state::seed_needs_passphrase(
+                &std::fs::read_to_string(
+                    BtcxWalletConfig::wallet_root(net, name).join(seedstore::SEED_FILE),
+                )
+                .unwrap(),
+            )
+        };
+        let read_with = |name: &str, pass: &str| {
+            let mut store =
+                SeedStore::open(&BtcxWalletConfig::wallet_root(net, name), None).unwrap();
+            store.unlock(pass).map_err(|e| format!("{e:#}"))?;
+            store.mnemonic().map_err(|e| format!("{e:#}"))
+        };
+
+        // Nothing to remove yet.
+        assert!(change_passphrase(&state, None, None, true).is_err());
+
+        // Add: both compartments re-wrapped, the held store stays usable.
+        let change = change_passphrase(&state, None, Some("first"), false).unwrap();
+        assert_eq!(change.rewrapped, vec!["lockme", "lockme-taproot"]);
+        assert!(change.encrypted);
+        assert!(encrypted("lockme") && encrypted("lockme-taproot"));
+        assert_eq!(
+            read_with("lockme-taproot", "first").unwrap().trim(),
+            MNEMONIC_24
+        );
+        assert!(state
+            .with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}")))
+            .is_ok());
+
+        // Change: a wrong current passphrase is refused before any write.
+        assert!(change_passphrase(&state, Some("nope"), Some("second"), false).is_err());
+        assert!(read_with("lockme", "first").is_ok());
+        change_passphrase(&state, Some("first"), Some("second"), false).unwrap();
+        assert!(read_with("lockme", "first").is_err());
+        assert_eq!(read_with("lockme", "second").unwrap().trim(), MNEMONIC_24);
+
+        // Remove: needs the explicit confirmation.
+        let err = change_passphrase(&state, Some("second"), None, false).unwrap_err();
+        assert!(err.contains("confirm"), "{err}");
+        let change = change_passphrase(&state, Some("second"), None, true).unwrap();
+        assert!(!change.encrypted);
+        assert!(!encrypted("lockme") && !encrypted("lockme-taproot"));
+        let root = BtcxWalletConfig::wallet_root(net, "lockme");
+        assert!(!root.join(format!("{}.bak", seedstore::SEED_FILE)).exists());
+
+        state.close_runtime();
+        std::env::remove_var("PHOENIX_DATA_DIR");
+        std::env::remove_var("PACT_DISABLE_KEYRING");
+    }
+
+    /// Backup reveal/verify: the reveal is passphrase-gated (or rides a live
+    /// token), a check is single-use and reports only pass/fail, and
+    /// success stamps every seed compartment of the group.
+    #[test]
+    fn reveal_and_verify_the_seed_backup() {
+        use super::super::seedcheck::{challenge, reveal, verify};
+
+        let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+        std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+        let state = offline_regtest_state();
+        let net = WalletNetwork::Regtest;
+        create_wallet_impl(
+            &state,
+            None,
+            MNEMONIC_24,
+            Some("pw"),
+            "extra",
+            Some("paper".into()),
+            None,
+        )
+        .unwrap();
+
+        assert!(reveal(&state, None, None, true).is_err());
+        assert!(reveal(&state, Some("wrong"), None, true).is_err());
+        let shown = reveal(&state, Some("pw"), None, false).unwrap();
+        assert_eq!(shown.mnemonic.trim(), MNEMONIC_24);
+        assert!(shown.has_bip39_passphrase);
+        state
+            .with_seed(|s| s.unlock("pw").map_err(|e| format!("{e:#}")))
+            .unwrap();
+        let again = reveal(&state, None, Some(&shown.token), false).unwrap();
+        assert_eq!(again.token, shown.token);
+        assert!(reveal(&state, None, Some("not-the-token"), false).is_err());
+
+        let words: Vec<&str> = MNEMONIC_24.split_whitespace().collect();
+        let answers = |positions: &[usize]| -> Vec<String> {
+            positions
+                .iter()
+                .map(|&p| words[p - 1].to_uppercase())
+                .collect()
+        };
+
+        // A wrong word fails and burns the challenge.
+        let check = challenge(&state, None).unwrap();
+        assert_eq!(check.word_count, 24);
+        let mut wrong = answers(&check.positions);
+        wrong[0] = "zzz".into();
+        let outcome = verify(&state, &wrong).unwrap();
+        assert!(!outcome.valid);
+        assert!(verify(&state, &answers(&check.positions)).is_err());
+
+        let check = challenge(&state, Some(6)).unwrap();
+        assert_eq!(check.positions.len(), 6);
+        let outcome = verify(&state, &answers(&check.positions)).unwrap();
+        assert!(outcome.valid);
+        let config = state.get_config();
+        for name in ["paper", "paper-taproot"] {
+            assert_eq!(
+                config.wallet_meta(net, name).unwrap().backup_verified_at,
+                outcome.verified_at
+            );
+        }
+
+        // Without a passphrase there is nothing to check: an explicit
+        // confirmation is required instead.
+        state.close_runtime();
+        create_wallet_impl(
+            &state,
+            None,
+            MNEMONIC_24,
+            None,
+            "",
+            Some("plain".into()),
+            None,
+        )
+        .unwrap();
+        let err = reveal(&state, None, None, false).unwrap_err();
+        assert!(err.contains("confirm"), "{err}");
+        let shown = reveal(&state, None, None, true).unwrap();
+        assert_eq!(shown.mnemonic.trim(), MNEMONIC_24);
+
+        state.close_runtime();
+        std::env::remove_var("PHOENIX_DATA_DIR");
+        std::env::remove_var("PACT_DISABLE_KEYRING");
+    }
+
+    /// A codex32 split of a created wallet recombines to its mnemonic from
+    /// any threshold subset; an encrypted seed needs its passphrase.
+    #[test]
+    fn split_seed_shares_recombine_to_the_mnemonic() {
+        let _guard = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+        std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+        let state = offline_regtest_state();
+        create_wallet_impl(
+            &state,
+            None,
+            MNEMONIC_24,
+            Some("pw"),
+            "",
+            Some("treasury".into()),
+            None,
+        )
+        .unwrap();
+
+        assert!(split_seed_impl(&state, None, true, 2, 3).is_err());
+        let split = split_seed_impl(&state, Some("pw"), false, 2, 3).unwrap();
+        assert_eq!(split.shares.len(), 3);
+        assert!(!split.has_bip39_passphrase);
+        assert!(split
+            .shares
+            .iter()
+            .all(|s| s.starts_with(&format!("ms12{}", split.identifier))));
+        let entropy = codex32::combine(&split.shares[1..]).unwrap();
+        assert_eq!(
+            bip39::Mnemonic::from_entropy(&entropy).unwrap().to_string(),
+            MNEMONIC_24
+        );
+        assert!(codex32::combine(&split.shares[..1]).is_err());
+
+        state.close_runtime();
+        std::env::remove_var("PHOENIX_DATA_DIR");
+        std::env::remove_var("PACT_DISABLE_KEYRING");
+    }
+
     /// Phase 2 materialization: a pre-redesign single-compartment group
     /// gains its missing Taproot sibling on the next open/select — same
     /// seed, same group, migration flags set so the legacy upgrade pass
@@ -3170,6 +5093,8 @@ mod tests {
                         v30_migrated: false,
                         group: "default-v30".to_string(),
                         balance_snapshot: None,
+                        multisig: None,
+                        backup_verified_at: None,
                     },
                 );
             })
@@ -22,8 +22,9 @@ pub const WALLET_SUBDIR: &str = "btcx-wallet";
 /// name the mobile UI's name-less create/restore lands on.
 pub const DEFAULT_WALLET: &str = "default";
 
-/// Trash subdirectory of a network dir — deleted wallets are MOVED here,
-/// never removed (`btcx_wallet_delete`).
+/// Trash subdirectory of a network dir — deleted wallets are MOVED here
+/// (`btcx_wallet_delete`); only an explicit purge or the retention policy
+/// removes them (see `trash`).
 pub const TRASH_SUBDIR: &str = ".trash";
 
 /// Baked-in default mainnet Electrum server (`ssl://host:port`, the same
@@ -32,6 +33,22 @@ pub const TRASH_SUBDIR: &str = ".trash";
 /// [`BtcxWalletConfig::seed_default_servers`].
 pub const DEFAULT_MAINNET_ELECTRUM: &str = "ssl://electrs.bitcoin-po.cx:50002";
 
+/// Built-in mainnet peer discovery bootstrap servers, each run by a
+/// different operator — one operator must not be able to steer every
+/// fresh install's discovery. Extend this list, not the default server.
+pub const BOOTSTRAP_MAINNET_ELECTRUM: &[&str] = &[DEFAULT_MAINNET_ELECTRUM];
+
+/// Built-in peer discovery bootstrap servers per network: asked for peers
+/// only when no configured or previously discovered server answers, so an
+/// install whose configured list is dead still finds servers (see
+/// `discovery`).
+pub fn bootstrap_servers(network: WalletNetwork) -> &'static [&'static str] {
+    match network {
+        WalletNetwork::Mainnet => BOOTSTRAP_MAINNET_ELECTRUM,
+        WalletNetwork::Testnet | WalletNetwork::Regtest => &[],
+    }
+}
+
 /// Network the nodeless wallet runs on. Maps 1:1 onto the static
 /// `params_btcx` BTCX chain parameters.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -86,7 +103,7 @@ impl WalletNetwork {
 
 /// Which standard single-key descriptor family a wallet derives —
 /// serde-friendly mirror of `keys_btcx::DescriptorKind`, plus the `Legacy`
-/// script class only descriptor-IMPORTED wallets can carry.
+/// and `Multisig` script classes only descriptor-IMPORTED wallets can carry.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum DescriptorKindCfg {
@@ -99,16 +116,21 @@ pub enum DescriptorKindCfg {
     /// like taproot (a plot account_id is a segwit-v0 witness program).
     /// Never derived from a seed: `kind()` has no mapping for it.
     Legacy,
+    /// `wsh(sortedmulti(k, …))` — segwit-v0 P2WSH k-of-n. Spends go through
+    /// the cosigner PSBT flow (compose → each cosigner signs → combine →
+    /// finalize); gated from mining/assignments (a plot is P2WPKH).
+    Multisig,
 }
 
 impl DescriptorKindCfg {
-    /// The seed-derivation family, `None` for [`Self::Legacy`] — legacy
-    /// wallets only exist as imported descriptors, never as a seed branch.
+    /// The seed-derivation family, `None` for [`Self::Legacy`] and
+    /// [`Self::Multisig`] — those wallets only exist as imported
+    /// descriptors, never as a seed branch.
     pub fn kind(self) -> Option<keys_btcx::DescriptorKind> {
         match self {
             DescriptorKindCfg::Bip84 => Some(keys_btcx::DescriptorKind::Bip84),
             DescriptorKindCfg::Bip86 => Some(keys_btcx::DescriptorKind::Bip86),
-            DescriptorKindCfg::Legacy => None,
+            DescriptorKindCfg::Legacy | DescriptorKindCfg::Multisig => None,
         }
     }
 }
@@ -123,6 +145,10 @@ pub enum WalletSourceCfg {
     /// Imported private descriptor pair in `descriptor.secret`
     /// (descstore) — the wallet has NO mnemonic.
     Descriptor,
+    /// Imported PUBLIC descriptor pair in `descriptor.public` (descstore)
+    /// — no secrets at all: the wallet syncs and hands out addresses but
+    /// can never sign; spends go out as unsigned PSBTs.
+    WatchOnly,
 }
 
 /// The descriptor branch a wallet was opened with: purpose family + BIP32
@@ -146,6 +172,62 @@ impl Default for DescriptorPolicy {
     }
 }
 
+/// Shape of a `wsh(sortedmulti(k, …))` wallet: `threshold` signatures out
+/// of `cosigners` keys. Registry display only — the descriptors stay the
+/// source of truth.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigShape {
+    pub threshold: u32,
+    pub cosigners: u32,
+}
+
+/// Cross-server header consensus checking of the active network's
+/// Electrum servers (see `consensus`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderConsensusMode {
+    /// No cross-server checks: the home server is trusted as before.
+    Off,
+    /// Check periodically while a wallet is open and report disagreeing or
+    /// lagging servers in `btcx_electrum_health`.
+    #[default]
+    Warn,
+    /// As `Warn`, and refuse broadcasts and balance reads while the home
+    /// server sits on a minority tip or is disputed (no majority chain).
+    Enforce,
+}
+
+/// Electrum peer discovery (`server.peers.subscribe`, see `discovery`).
+/// Unless the user picks one, see [`BtcxWalletConfig::discovery_mode`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerDiscoveryMode {
+    /// Never contact servers the user did not configure.
+    Off,
+    /// Discover peers periodically and list the best healthy ones — the
+    /// configured list is left alone. They only carry wallet traffic (home,
+    /// failover views, broadcasts) with `discovered_failover` set.
+    Views,
+    /// As `Views`, and append the best ones to the network's configured
+    /// list (the user's home server stays first).
+    Auto,
+}
+
+/// A discovered Electrum server that passed the probe (genesis check +
+/// tip fetch), ranked by latency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredServer {
+    pub url: String,
+    /// Chain tip height at the probe.
+    pub height: u64,
+    /// Round-trip time of the probe's tip fetch, milliseconds.
+    pub latency_ms: f64,
+    /// Unix seconds of the probe.
+    pub checked_at: u64,
+}
+
 /// Point-in-time balance snapshot of one wallet — written by the sync
 /// emitter (live wallet), the runtime close, and `btcx_wallet_group_sync`.
 /// DISPLAY ONLY: it paints the wallet selector's compartment strip; spends
@@ -173,8 +255,8 @@ pub struct WalletMeta {
     /// Unix seconds at create/restore/migration time (display only).
     #[serde(default)]
     pub created_at: Option<u64>,
-    /// Key-material source: seed (default, pre-existing configs) or
-    /// imported descriptors.
+    /// Key-material source: seed (default, pre-existing configs),
+    /// imported descriptors, or watch-only public descriptors.
     #[serde(default)]
     pub source: WalletSourceCfg,
     /// Single-address (`wpkh(WIF)`) wallet: one keychain, change returns
@@ -198,6 +280,14 @@ pub struct WalletMeta {
     /// Last known balance, selector display only (see [`BalanceSnapshot`]).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub balance_snapshot: Option<BalanceSnapshot>,
+    /// k-of-n shape of a [`DescriptorKindCfg::Multisig`] wallet; `None` for
+    /// every single-key wallet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigShape>,
+    /// Unix seconds the user last proved their mnemonic backup (see
+    /// `seedcheck`); `None` = never verified since create/restore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_verified_at: Option<u64>,
 }
 
 /// The `electrum_servers` map of a FRESH config: mainnet starts with the
@@ -249,6 +339,38 @@ pub struct BtcxWalletConfig {
     /// The selected wallet per network name (missing = `DEFAULT_WALLET`).
     #[serde(default)]
     pub active_wallet: BTreeMap<String, String>,
+
+    /// Trash retention in days: trashed wallets older than this are purged
+    /// on startup (see `trash`). `None` keeps the trash forever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash_retention_days: Option<u32>,
+
+    /// Cross-server header consensus mode (default: warn only).
+    #[serde(default)]
+    pub header_consensus: HeaderConsensusMode,
+
+    /// Pinned TLS certificates of `ssl://` Electrum servers whose
+    /// certificate no CA vouches for: server URL → SHA-256 fingerprint of
+    /// the leaf certificate, recorded on first connect (see `tlspin`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cert_pins: BTreeMap<String, String>,
+
+    /// Electrum peer discovery mode as the user set it; unset follows
+    /// [`Self::discovery_mode`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_discovery: Option<ServerDiscoveryMode>,
+
+    /// Opt-in: discovered servers may be elected home, serve as failover
+    /// views and carry broadcasts. Off by default — a peer announced by
+    /// some server is not one the user chose to trust.
+    #[serde(default)]
+    pub discovered_failover: bool,
+
+    /// Healthy discovered servers per network name, best first (see
+    /// `discovery`). Kept across restarts so a dead configured server does
+    /// not strand the wallet.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub discovered_servers: BTreeMap<String, Vec<DiscoveredServer>>,
 }
 
 impl Default for BtcxWalletConfig {
@@ -262,6 +384,12 @@ impl Default for BtcxWalletConfig {
             descriptors: BTreeMap::new(),
             wallets: BTreeMap::new(),
             active_wallet: BTreeMap::new(),
+            trash_retention_days: None,
+            header_consensus: HeaderConsensusMode::default(),
+            cert_pins: BTreeMap::new(),
+            server_discovery: None,
+            discovered_failover: false,
+            discovered_servers: BTreeMap::new(),
         }
     }
 }
@@ -352,8 +480,65 @@ impl BtcxWalletConfig {
 
     /// The Electrum servers configured for `network` (not necessarily the
     /// active one — e.g. chain-only broadcasts from the desktop wallet).
+    /// In the proxy's Tor mode `.onion` servers come first.
     pub fn servers_for(&self, network: WalletNetwork) -> Vec<String> {
-        self.electrum_servers
+        crate::proxy::order_servers(
+            self.electrum_servers
+                .get(network.as_str())
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// The peer discovery mode of `network`: the one the user set, else
+    /// `views` while its configured servers are all built-in and `off` once
+    /// the user configured one of their own — discovery then contacts
+    /// servers they did not choose, which needs their opt-in.
+    pub fn discovery_mode(&self, network: WalletNetwork) -> ServerDiscoveryMode {
+        self.server_discovery.unwrap_or_else(|| {
+            let builtin = bootstrap_servers(network);
+            let own = self
+                .electrum_servers
+                .get(network.as_str())
+                .is_some_and(|list| list.iter().any(|s| !builtin.contains(&s.as_str())));
+            if own {
+                ServerDiscoveryMode::Off
+            } else {
+                ServerDiscoveryMode::Views
+            }
+        })
+    }
+
+    /// The configured servers of `network` followed by its discovered ones
+    /// (unless discovery is off) — what the health view lists.
+    pub fn listed_servers(&self, network: WalletNetwork) -> Vec<String> {
+        let mut servers = self.servers_for(network);
+        if self.discovery_mode(network) != ServerDiscoveryMode::Off {
+            for found in self.discovered_for(network) {
+                if !servers.contains(&found.url) {
+                    servers.push(found.url);
+                }
+            }
+        }
+        servers
+    }
+
+    /// The servers a wallet elects its home from, keeps as failover views
+    /// and broadcasts to: the configured ones, plus the discovered ones
+    /// behind them only when `discovered_failover` is set. Configured
+    /// servers always come first, so the user's home server wins whenever
+    /// it verifies.
+    pub fn failover_servers(&self, network: WalletNetwork) -> Vec<String> {
+        if self.discovered_failover {
+            self.listed_servers(network)
+        } else {
+            self.servers_for(network)
+        }
+    }
+
+    /// The discovered servers of `network`, best first.
+    pub fn discovered_for(&self, network: WalletNetwork) -> Vec<DiscoveredServer> {
+        self.discovered_servers
             .get(network.as_str())
             .cloned()
             .unwrap_or_default()
@@ -488,6 +673,8 @@ impl BtcxWalletConfig {
                 .filter(|g| !g.is_empty())
                 .unwrap_or_else(|| name.clone()),
             balance_snapshot: existing.as_ref().and_then(|m| m.balance_snapshot),
+            multisig: existing.as_ref().and_then(|m| m.multisig),
+            backup_verified_at: existing.as_ref().and_then(|m| m.backup_verified_at),
         };
         self.set_wallet_meta(network, &name, meta);
     }
@@ -534,6 +721,15 @@ impl BtcxWalletConfig {
         Self::wallet_root(self.network, &self.active_wallet_name())
     }
 
+    /// Data dir of the ACTIVE wallet, or `None` when the selection names no
+    /// registered wallet — per-wallet side files (labels, replacement links)
+    /// resolve through this so a write can never conjure a wallet dir.
+    pub fn registered_active_root(&self) -> Option<PathBuf> {
+        let name = self.active_wallet_name();
+        self.wallet_meta(self.network, &name)?;
+        Some(Self::wallet_root(self.network, &name))
+    }
+
     /// bdk sqlite store path of the ACTIVE wallet.
     pub fn wallet_db_path(&self) -> PathBuf {
         Self::wallet_db_path_for(self.network, &self.active_wallet_name())
@@ -627,6 +823,8 @@ pub fn migrate_legacy_layout_at(
                     v30_migrated: false,
                     group: DEFAULT_WALLET.to_string(),
                     balance_snapshot: None,
+                    multisig: None,
+                    backup_verified_at: None,
                 },
             );
         }
@@ -903,6 +1101,50 @@ mod tests {
         assert!(!parsed.descriptors.contains_key("mainnet"));
     }
 
+    #[test]
+    fn discovered_servers_follow_the_configured_ones() {
+        let mut config = BtcxWalletConfig {
+            network: WalletNetwork::Testnet,
+            ..Default::default()
+        };
+        let home = "tcp://home.example.org:50001".to_string();
+        config.set_servers(WalletNetwork::Testnet, vec![home.clone()]);
+        // A server of the user's own: discovery waits for their opt-in.
+        assert_eq!(
+            config.discovery_mode(WalletNetwork::Testnet),
+            ServerDiscoveryMode::Off
+        );
+        assert_eq!(
+            config.discovery_mode(WalletNetwork::Mainnet),
+            ServerDiscoveryMode::Views,
+            "only the built-in server"
+        );
+        config.server_discovery = Some(ServerDiscoveryMode::Views);
+        let found = |url: &str| DiscoveredServer {
+            url: url.to_string(),
+            height: 100,
+            latency_ms: 20.0,
+            checked_at: 0,
+        };
+        config.discovered_servers.insert(
+            "testnet".into(),
+            vec![found("ssl://peer.example.org:50002"), found(&home)],
+        );
+        let both = vec![home.clone(), "ssl://peer.example.org:50002".to_string()];
+        assert_eq!(config.listed_servers(WalletNetwork::Testnet), both);
+        // Listed, but no wallet traffic until the user opts in.
+        assert_eq!(
+            config.failover_servers(WalletNetwork::Testnet),
+            vec![home.clone()]
+        );
+        config.discovered_failover = true;
+        assert_eq!(config.failover_servers(WalletNetwork::Testnet), both);
+        assert_eq!(config.servers(), vec![home.clone()]);
+
+        config.server_discovery = Some(ServerDiscoveryMode::Off);
+        assert_eq!(config.failover_servers(WalletNetwork::Testnet), vec![home]);
+    }
+
     #[test]
     fn config_tolerates_older_json_shapes() {
         // Fields added later must default instead of failing the parse.
@@ -987,6 +1229,8 @@ mod tests {
             v30_migrated: false,
             group: "savings".to_string(),
             balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
         };
         config.set_wallet_meta(WalletNetwork::Mainnet, "savings", meta.clone());
         config.set_active_wallet(WalletNetwork::Mainnet, "savings");
@@ -1038,6 +1282,8 @@ mod tests {
             v30_migrated: false,
             group: "imported".to_string(),
             balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
         };
         let json = serde_json::to_string(&meta).unwrap();
         assert!(json.contains(r#""source":"descriptor""#), "{json}");
@@ -1045,6 +1291,37 @@ mod tests {
         let parsed: WalletMeta = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, meta);
 
+        // A watch-only wallet survives it too.
+        let watch = WalletMeta {
+            source: WalletSourceCfg::WatchOnly,
+            ..meta.clone()
+        };
+        let json = serde_json::to_string(&watch).unwrap();
+        assert!(json.contains(r#""source":"watchonly""#), "{json}");
+        assert_eq!(serde_json::from_str::<WalletMeta>(&json).unwrap(), watch);
+
+        // A multisig wallet records its k-of-n shape; single-key entries
+        // never serialize the field.
+        assert!(!json.contains("multisig"), "{json}");
+        let multi = WalletMeta {
+            policy: DescriptorPolicy {
+                kind: DescriptorKindCfg::Multisig,
+                coin_type: 1,
+            },
+            multisig: Some(MultisigShape {
+                threshold: 2,
+                cosigners: 3,
+            }),
+            ..meta.clone()
+        };
+        let json = serde_json::to_string(&multi).unwrap();
+        assert!(json.contains(r#""kind":"multisig""#), "{json}");
+        assert!(
+            json.contains(r#""multisig":{"threshold":2,"cosigners":3}"#),
+            "{json}"
+        );
+        assert_eq!(serde_json::from_str::<WalletMeta>(&json).unwrap(), multi);
+
         // Older configs have no `singleAddress` field — defaults false.
         assert!(!old.single_address);
 
@@ -1062,6 +1339,8 @@ mod tests {
                 height: 7,
                 at: 1700000000,
             }),
+            multisig: None,
+            backup_verified_at: None,
         };
         let json = serde_json::to_string(&single).unwrap();
         assert!(json.contains(r#""singleAddress":true"#), "{json}");
@@ -1114,6 +1393,8 @@ mod tests {
             v30_migrated: false,
             group: String::new(),
             balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
         };
         let v31 = WalletMeta {
             policy: DescriptorPolicy::default(),
@@ -1226,6 +1507,8 @@ mod tests {
             v30_migrated: false,
             group: String::new(),
             balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
         }
     }
 
@@ -0,0 +1,596 @@
+//! Cross-server header consensus: is the home Electrum server on the chain
+//! the other configured servers see?
+//!
+//! The pool trusts the home server for tip and history, and the open-time
+//! election only checks genesis. A check here asks EVERY configured server
+//! of the active network, each over a fresh connection, for
+//! - its tip ([`CONSENSUS_WINDOW`] headers below it, verified as a linked
+//!   PoCX chain: prev hash → hash of the header below, the height embedded
+//!   in each 286-byte header matching the height it was served for), and
+//! - its header at every OTHER server's tip height at or below its own,
+//!   so two servers are compared exactly where the lower one stands.
+//!
+//! Two servers agree when every height both served hashes the same. The
+//! largest agreeing set, if it is a strict majority, is the consensus
+//! chain; servers outside it are `minority`, and servers inside it more
+//! than [`MAX_LAG`] blocks below (above) its median tip are `lagging`
+//! (`ahead` — a tip no peer confirms yet). Work is not checked: with only
+//! community-run electrs instances this is about not trusting ONE server.
+//!
+//! The last report is cached on the state. In `enforce` mode
+//! ([`HeaderConsensusMode`]) broadcasts and balance reads are refused while
+//! the home server is on a minority chain, served a broken one, or is
+//! disputed — no chain has a majority, so it cannot be vouched for.
+
+use std::collections::BTreeMap;
+
+use electrum_client::{Client, ConfigBuilder, ElectrumApi};
+use params_btcx::params::ChainParams;
+use serde::Serialize;
+
+use super::config::HeaderConsensusMode;
+use super::state::BtcxWalletState;
+
+/// Linked headers verified below each server's tip.
+pub const CONSENSUS_WINDOW: u64 = 6;
+/// Blocks a server may trail (or lead) the consensus tip before it is
+/// flagged — block propagation between servers takes a moment.
+pub const MAX_LAG: u64 = 2;
+/// A cached report older than this is refreshed before an enforced
+/// broadcast.
+pub const REPORT_MAX_AGE_SECS: u64 = 600;
+/// Per-request timeout of a consensus connection, seconds.
+const TIMEOUT_SECS: u8 = 10;
+/// Bitcoin-PoCX header: version 4 + prev 32 + merkle 32 + time 4 +
+/// height 4 + … (see `btcx_chain_info`).
+const POCX_HEADER_LEN: usize = 286;
+const HEIGHT_OFFSET: usize = 72;
+
+/// One server's standing in a consensus check.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerVerdict {
+    /// On the consensus chain, within [`MAX_LAG`] of its tip.
+    Agrees,
+    /// On the consensus chain but behind it.
+    Lagging,
+    /// On the consensus chain, but claiming a tip no peer confirms yet.
+    Ahead,
+    /// On a different chain than the majority.
+    Minority,
+    /// Disagrees with some server and no chain has a strict majority.
+    Disputed,
+    /// Served headers that do not link up (or do not hash).
+    Invalid,
+    /// Could not be reached.
+    Unreachable,
+}
+
+/// One server's line of a [`ConsensusReport`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConsensus {
+    pub url: String,
+    pub verdict: ServerVerdict,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tip_hash: Option<String>,
+    /// Why a server is not `agrees`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Outcome of a consensus check over the active network's servers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusReport {
+    pub network: String,
+    /// Unix seconds.
+    pub checked_at: u64,
+    /// Median tip height of the consensus chain (none without a majority).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    /// Servers on the consensus chain, out of those that verified.
+    pub agreeing: usize,
+    pub verified: usize,
+    /// The wallet's home server (open runtime, else the first configured).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_url: Option<String>,
+    /// The home server is on a minority chain or served a broken one.
+    pub home_on_minority: bool,
+    pub servers: Vec<ServerConsensus>,
+}
+
+impl ConsensusReport {
+    /// The line of `url`, if it was checked.
+    pub fn server(&self, url: &str) -> Option<&ServerConsensus> {
+        self.servers.iter().find(|s| s.url == url)
+    }
+
+    /// Why `enforce` mode refuses under this report, if it does.
+    fn enforce_refusal(&self) -> Option<String> {
+        let home = self.home_url.as_deref().unwrap_or_default();
+        if self.home_on_minority {
+            return Some(format!(
+                "Refusing: the home Electrum server {home} is not on the chain the other servers \
+                 agree on — check the server list (header consensus is set to enforce)"
+            ));
+        }
+        match self.server(home).map(|s| s.verdict) {
+            Some(ServerVerdict::Disputed) => Some(format!(
+                "Refusing: the Electrum servers disagree and no chain has a majority, so the \
+                 home server {home} cannot be vouched for — check the server list (header \
+                 consensus is set to enforce)"
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// One server's verified view: height → display hash.
+struct ServerView {
+    tip: u64,
+    hashes: BTreeMap<u64, String>,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub(crate) fn connect(url: &str) -> Result<Client, String> {
+    // `ssl://` servers are dialed through their pinned TLS forwarder (see
+    // `tlspin`), so the client itself only ever speaks plain TCP.
+    let config = ConfigBuilder::new().timeout(Some(TIMEOUT_SECS)).build();
+    let url = super::tlspin::dial_url(url)?;
+    Client::from_config(&url, config).map_err(|e| format!("{e}"))
+}
+
+/// The previous-block hash a raw header commits to, in display order.
+fn prev_hash(raw: &[u8]) -> Option<String> {
+    let mut prev = raw.get(4..36)?.to_vec();
+    prev.reverse();
+    Some(hex::encode(prev))
+}
+
+/// The height a PoCX header carries (`None` for 80-byte headers).
+pub(crate) fn embedded_height(raw: &[u8]) -> Option<u64> {
+    (raw.len() == POCX_HEADER_LEN).then(|| {
+        u64::from(u32::from_le_bytes(
+            raw[HEIGHT_OFFSET..HEIGHT_OFFSET + 4]
+                .try_into()
+                .expect("length checked"),
+        ))
+    })
+}
+
+/// Check the headers `raw` served for ascending `heights`: each carries its
+/// own height, and consecutive heights link by prev hash. Returns the
+/// display hashes by height.
+fn verify_headers(
+    params: &ChainParams,
+    heights: &[u64],
+    raw: &[Vec<u8>],
+) -> Result<BTreeMap<u64, String>, String> {
+    if raw.len() != heights.len() {
+        return Err("server returned the wrong number of headers".into());
+    }
+    let mut hashes = BTreeMap::new();
+    let mut below: Option<(u64, String)> = None;
+    for (&height, header) in heights.iter().zip(raw) {
+        if let Some(embedded) = embedded_height(header) {
+            if embedded != height {
+                return Err(format!(
+                    "header served for height {height} says height {embedded}"
+                ));
+            }
+        }
+        let hash = params
+            .header_hash(header)
+            .map_err(|e| format!("header {height}: {e:#}"))?;
+        if let Some((below_height, below_hash)) = &below {
+            if *below_height + 1 == height && prev_hash(header).as_ref() != Some(below_hash) {
+                return Err(format!(
+                    "header {height} does not link to header {below_height}"
+                ));
+            }
+        }
+        below = Some((height, hash.clone()));
+        hashes.insert(height, hash);
+    }
+    Ok(hashes)
+}
+
+/// Phase 2 for one server: the window below its tip plus `peer_tips`, as
+/// one batch, verified.
+fn fetch_view(
+    params: &ChainParams,
+    client: &Client,
+    tip: u64,
+    tip_raw: &[u8],
+    peer_tips: &[u64],
+) -> Result<ServerView, String> {
+    let mut heights: Vec<u64> = (tip.saturating_sub(CONSENSUS_WINDOW - 1)..=tip)
+        .chain(peer_tips.iter().copied().filter(|&h| h <= tip))
+        .collect();
+    heights.sort_unstable();
+    heights.dedup();
+    let raw = client
+        .batch_block_header_raw(heights.iter().map(|&h| h as u32))
+        .map_err(|e| format!("fetching headers: {e}"))?;
+    let hashes = verify_headers(params, &heights, &raw)?;
+    let tip_hash = params
+        .header_hash(tip_raw)
+        .map_err(|e| format!("tip header: {e:#}"))?;
+    if hashes.get(&tip) != Some(&tip_hash) {
+        return Err("the announced tip is not the header served at its height".into());
+    }
+    Ok(ServerView { tip, hashes })
+}
+
+/// Whether two views hash alike at every height both served.
+fn agree(a: &ServerView, b: &ServerView) -> bool {
+    a.hashes
+        .iter()
+        .all(|(h, hash)| !matches!(b.hashes.get(h), Some(other) if other != hash))
+}
+
+/// A server's verdict and why it is not `agrees`.
+type Verdict = (ServerVerdict, Option<String>);
+
+/// Classify verified views. `views[i]` is `None` for servers that failed
+/// (their verdict is already decided). Returns per-server verdicts and
+/// details, the consensus median tip, and the agreeing count.
+fn classify(views: &[Option<ServerView>]) -> (Vec<Option<Verdict>>, Option<u64>, usize) {
+    let valid: Vec<usize> = (0..views.len()).filter(|&i| views[i].is_some()).collect();
+    let view = |i: usize| views[i].as_ref().expect("valid index");
+    let supporters = |i: usize| valid.iter().filter(|&&j| agree(view(i), view(j))).count();
+    // Agreement is not transitive — a lagging server agrees with every fork
+    // above it — so only a server no agreeing peer is ahead of may lead a
+    // chain: its view spans the heights its supporters served.
+    let heads_its_chain = |i: usize| {
+        valid
+            .iter()
+            .all(|&j| view(j).tip <= view(i).tip || !agree(view(i), view(j)))
+    };
+    let Some(&leader) = valid
+        .iter()
+        .filter(|&&i| heads_its_chain(i))
+        .max_by_key(|&&i| supporters(i))
+    else {
+        return (vec![None; views.len()], None, 0);
+    };
+    let mut out = vec![None; views.len()];
+    let agreeing = supporters(leader);
+    if agreeing * 2 <= valid.len() {
+        // No strict majority: only servers that disagree with nobody stand.
+        for &i in &valid {
+            out[i] = Some(if supporters(i) == valid.len() {
+                (ServerVerdict::Agrees, None)
+            } else {
+                (
+                    ServerVerdict::Disputed,
+                    Some("disagrees with another server and no chain has a majority".into()),
+                )
+            });
+        }
+        return (out, None, 0);
+    }
+    let mut tips: Vec<u64> = valid
+        .iter()
+        .filter(|&&j| agree(view(leader), view(j)))
+        .map(|&j| view(j).tip)
+        .collect();
+    tips.sort_unstable();
+    let median = tips[tips.len() / 2];
+    for &i in &valid {
+        let tip = view(i).tip;
+        out[i] = Some(if !agree(view(leader), view(i)) {
+            (
+                ServerVerdict::Minority,
+                Some(format!(
+                    "on a different chain than {agreeing} of {} servers",
+                    valid.len()
+                )),
+            )
+        } else if tip + MAX_LAG < median {
+            (
+                ServerVerdict::Lagging,
+                Some(format!("{} blocks behind", median - tip)),
+            )
+        } else if tip > median + MAX_LAG {
+            (
+                ServerVerdict::Ahead,
+                Some(format!("{} blocks ahead of its peers", tip - median)),
+            )
+        } else {
+            (ServerVerdict::Agrees, None)
+        });
+    }
+    (out, Some(median), agreeing)
+}
+
+/// Run a consensus check over `servers` (no state involved).
+pub fn check_servers(
+    params: &'static ChainParams,
+    network: &str,
+    servers: &[String],
+    home_url: Option<&str>,
+) -> ConsensusReport {
+    // Phase 1: every server's announced tip, in parallel.
+    let tips: Vec<Result<(Client, u64, Vec<u8>), String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = servers
+            .iter()
+            .map(|url| {
+                scope.spawn(move || {
+                    let client = connect(url)?;
+                    let tip = client
+                        .block_headers_subscribe_raw()
+                        .map_err(|e| format!("{e}"))?;
+                    Ok((client, tip.height as u64, tip.header))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err("check thread panicked".into()))
+            })
+            .collect()
+    });
+    let peer_tips: Vec<u64> = tips
+        .iter()
+        .filter_map(|t| t.as_ref().ok().map(|(_, h, _)| *h))
+        .collect();
+
+    // Phase 2: each reachable server's window + its header at every peer's
+    // tip, verified.
+    let fetched: Vec<Result<ServerView, (ServerVerdict, String)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = tips
+            .iter()
+            .map(|tip| {
+                let peer_tips = &peer_tips;
+                scope.spawn(move || match tip {
+                    Ok((client, height, raw)) => {
+                        fetch_view(params, client, *height, raw, peer_tips)
+                            .map_err(|e| (ServerVerdict::Invalid, e))
+                    }
+                    Err(e) => Err((ServerVerdict::Unreachable, e.clone())),
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join().unwrap_or_else(|_| {
+                    Err((ServerVerdict::Unreachable, "check thread panicked".into()))
+                })
+            })
+            .collect()
+    });
+
+    let (views, failures): (
+        Vec<Option<ServerView>>,
+        Vec<Option<(ServerVerdict, String)>>,
+    ) = fetched
+        .into_iter()
+        .map(|f| match f {
+            Ok(view) => (Some(view), None),
+            Err(failure) => (None, Some(failure)),
+        })
+        .unzip();
+    let (verdicts, height, agreeing) = classify(&views);
+    let verified = views.iter().filter(|v| v.is_some()).count();
+    let server_lines: Vec<ServerConsensus> = servers
+        .iter()
+        .zip(views.iter().zip(failures))
+        .zip(verdicts)
+        .map(|((url, outcome), verdict)| match (outcome, verdict) {
+            ((Some(view), _), Some((verdict, detail))) => ServerConsensus {
+                url: url.clone(),
+                verdict,
+                height: Some(view.tip),
+                tip_hash: view.hashes.get(&view.tip).cloned(),
+                detail,
+            },
+            ((_, failure), _) => {
+                let (verdict, detail) =
+                    failure.unwrap_or((ServerVerdict::Unreachable, "no verdict".to_string()));
+                ServerConsensus {
+                    url: url.clone(),
+                    verdict,
+                    height: None,
+                    tip_hash: None,
+                    detail: Some(detail),
+                }
+            }
+        })
+        .collect();
+    let home_on_minority = home_url
+        .and_then(|home| server_lines.iter().find(|s| s.url == home))
+        .is_some_and(|s| matches!(s.verdict, ServerVerdict::Minority | ServerVerdict::Invalid));
+    ConsensusReport {
+        network: network.to_string(),
+        checked_at: now_secs(),
+        height,
+        agreeing,
+        verified,
+        home_url: home_url.map(str::to_string),
+        home_on_minority,
+        servers: server_lines,
+    }
+}
+
+/// Check the active network's servers now and cache the report.
+pub fn refresh(state: &BtcxWalletState) -> Result<ConsensusReport, String> {
+    let config = state.get_config();
+    let servers = config.servers();
+    if servers.is_empty() {
+        return Err(format!(
+            "No Electrum server configured for {} — add one first",
+            config.network.as_str()
+        ));
+    }
+    let home = state
+        .runtime_urls()
+        .map(|(home, _)| home)
+        .or_else(|| servers.first().cloned());
+    let report = check_servers(
+        config.network.params(),
+        config.network.as_str(),
+        &servers,
+        home.as_deref(),
+    );
+    let previous = state.cached_consensus();
+    for line in &report.servers {
+        let was = previous
+            .as_ref()
+            .and_then(|p| p.server(&line.url))
+            .map(|p| p.verdict);
+        if line.verdict != ServerVerdict::Agrees && was != Some(line.verdict) {
+            log::warn!(
+                "btcx wallet: header consensus: {} is {:?}{}",
+                line.url,
+                line.verdict,
+                line.detail
+                    .as_deref()
+                    .map(|d| format!(" ({d})"))
+                    .unwrap_or_default()
+            );
+        }
+    }
+    state.store_consensus(report.clone());
+    Ok(report)
+}
+
+/// The `enforce`-mode gate in front of broadcasts (`refresh_stale`: a
+/// stale or missing report is re-checked first) and balance reads (cached
+/// report only — those must never block on the network).
+pub fn gate(state: &BtcxWalletState, refresh_stale: bool) -> Result<(), String> {
+    let config = state.get_config();
+    if config.header_consensus != HeaderConsensusMode::Enforce {
+        return Ok(());
+    }
+    let cached = state.cached_consensus().filter(|r| {
+        r.network == config.network.as_str() && r.checked_at + REPORT_MAX_AGE_SECS > now_secs()
+    });
+    let report = match cached {
+        Some(report) => Some(report),
+        None if refresh_stale => Some(refresh(state)?),
+        None => None,
+    };
+    match report.and_then(|r| r.enforce_refusal()) {
+        Some(refusal) => Err(refusal),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(tip: u64, chain: &[(u64, &str)]) -> Option<ServerView> {
+        Some(ServerView {
+            tip,
+            hashes: chain.iter().map(|&(h, s)| (h, s.to_string())).collect(),
+        })
+    }
+
+    #[test]
+    fn majority_flags_the_forked_and_lagging_servers() {
+        let views = vec![
+            view(102, &[(100, "a"), (101, "b"), (102, "c")]),
+            view(102, &[(100, "a"), (101, "b"), (102, "c")]),
+            // Forked at 101.
+            view(102, &[(100, "a"), (101, "x"), (102, "y")]),
+            // Lagging, same chain where it stands.
+            view(96, &[(96, "old")]),
+            None,
+        ];
+        let mut views = views;
+        // The lagging server's height is served by the others too.
+        for v in views.iter_mut().take(3).flatten() {
+            v.hashes.insert(96, "old".into());
+        }
+        let (verdicts, height, agreeing) = classify(&views);
+        assert_eq!(height, Some(102));
+        assert_eq!(agreeing, 3);
+        let verdict = |i: usize| verdicts[i].as_ref().unwrap().0;
+        assert_eq!(verdict(0), ServerVerdict::Agrees);
+        assert_eq!(verdict(1), ServerVerdict::Agrees);
+        assert_eq!(verdict(2), ServerVerdict::Minority);
+        assert_eq!(verdict(3), ServerVerdict::Lagging);
+        assert!(verdicts[4].is_none());
+    }
+
+    #[test]
+    fn no_majority_is_disputed_and_a_lone_leader_is_ahead() {
+        let (verdicts, height, _) = classify(&[view(10, &[(10, "a")]), view(10, &[(10, "b")])]);
+        assert_eq!(height, None);
+        assert!(verdicts
+            .iter()
+            .all(|v| v.as_ref().unwrap().0 == ServerVerdict::Disputed));
+
+        // One server claims a far higher tip over the same history.
+        let (verdicts, height, _) = classify(&[
+            view(10, &[(10, "a")]),
+            view(10, &[(10, "a")]),
+            view(500, &[(10, "a"), (500, "z")]),
+        ]);
+        assert_eq!(height, Some(10));
+        assert_eq!(verdicts[2].as_ref().unwrap().0, ServerVerdict::Ahead);
+    }
+
+    #[test]
+    fn enforce_refuses_a_disputed_home_server() {
+        let line = |url: &str, verdict| ServerConsensus {
+            url: url.into(),
+            verdict,
+            height: Some(10),
+            tip_hash: None,
+            detail: None,
+        };
+        let mut report = ConsensusReport {
+            network: "regtest".into(),
+            checked_at: 0,
+            height: None,
+            agreeing: 1,
+            verified: 2,
+            home_url: Some("tcp://a:1".into()),
+            home_on_minority: false,
+            servers: vec![
+                line("tcp://a:1", ServerVerdict::Disputed),
+                line("tcp://b:1", ServerVerdict::Disputed),
+            ],
+        };
+        assert!(report
+            .enforce_refusal()
+            .unwrap()
+            .contains("no chain has a majority"));
+
+        report.servers[0].verdict = ServerVerdict::Agrees;
+        assert!(report.enforce_refusal().is_none());
+        report.home_on_minority = true;
+        assert!(report
+            .enforce_refusal()
+            .unwrap()
+            .contains("not on the chain"));
+    }
+
+    #[test]
+    fn embedded_height_reads_pocx_headers_only() {
+        let mut raw = vec![0u8; POCX_HEADER_LEN];
+        raw[HEIGHT_OFFSET..HEIGHT_OFFSET + 4].copy_from_slice(&1234u32.to_le_bytes());
+        assert_eq!(embedded_height(&raw), Some(1234));
+        assert_eq!(embedded_height(&[0u8; 80]), None);
+
+        raw[4] = 0xab;
+        raw[35] = 0xcd;
+        let prev = prev_hash(&raw).unwrap();
+        assert!(prev.starts_with("cd") && prev.ends_with("ab"));
+    }
+}
@@ -1,12 +1,23 @@
 //! Descriptor-import parsing, validation and classification.
 //!
-//! One paste box, one or two PRIVATE descriptors (whitespace/newline
-//! separated). The rules, in the order they are applied:
+//! One paste box, one or two descriptors (whitespace/newline separated) —
+//! PRIVATE for a spendable wallet, PUBLIC for a watch-only one. The rules,
+//! in the order they are applied:
 //!
-//! - **Private only (v1).** Public-only descriptors (xpub key material) and
-//!   bare xpubs are rejected with the watch-only-not-yet message; bare
-//!   xprv/tprv keys are rejected asking for a full descriptor. This same
-//!   input is where watch-only (v2) will slot in later.
+//! - **Private or public, never mixed.** A descriptor whose every key
+//!   carries its secret imports as a spendable wallet; one whose keys are
+//!   ALL public (xpub/tpub) imports as WATCH-ONLY (balance, history and
+//!   receive addresses; spending goes through an unsigned PSBT signed
+//!   elsewhere). A single-key descriptor mixing secret and public keys is
+//!   rejected — the one exception is multisig, below.
+//!   A SLIP-132 `zpub` (`vpub` on the test networks) with its key origin,
+//!   `[fingerprint/84'/coin'/account']zpub…`, is accepted as the account
+//!   key of a BIP-84 watch-only wallet (`wpkh([origin]key/0/*)` +
+//!   `wpkh([origin]key/1/*)`) — the origin is what lets an offline signer
+//!   recognize its inputs in the PSBTs the wallet composes, so a zpub
+//!   without one is refused. Every other bare extended key is rejected — a
+//!   bare xpub does not say which script type it covers, a bare xprv gets
+//!   the full-descriptor demand.
 //! - **Checksums** are optional; when a `#checksum` is present it is
 //!   verified (BIP-380) and then stripped — the stored form is the body.
 //! - **Pairing.** Two descriptors: same key + same script type, one `/0/*`
@@ -26,30 +37,42 @@
 //!   / `tr(WIF)` / `sh(wpkh(WIF))` are rejected, and a BARE WIF paste gets
 //!   the wrap-it hint instead of a generic parse error. The WIF's network
 //!   byte is validated against the active network like xprv/tprv.
+//!   Watch-only imports are ranged only (a lone public key is rejected as
+//!   not ranged).
 //! - **Classification** from the script type: `wpkh` → BIP-84 (segwit v0,
 //!   mining + assignments allowed), `tr` → BIP-86 (taproot), `pkh` /
 //!   `sh(wpkh)` → legacy (funds visible + spendable, mining/assignments
-//!   gated — a plot account_id is a segwit-v0 witness program). Everything
-//!   else is unsupported in v1.
-//! - **Network.** The key prefix (xprv vs tprv) must match the active
-//!   network: mainnet keys are `xprv`, testnet/regtest keys are `tprv`.
-//!   The chain binding itself stays bdk's genesis-hash check at open.
+//!   gated — a plot account_id is a segwit-v0 witness program),
+//!   `wsh(sortedmulti(k, …))` → multisig (gated the same way). Everything
+//!   else is unsupported.
+//! - **Multisig.** Every cosigner key is a ranged extended key on the same
+//!   `/0/*` (or `/1/*`, `<0;1>`) branch — the sibling is inferred by
+//!   swapping all of them. Our own key may carry its secret next to the
+//!   cosigners' xpubs (a signing cosigner: the wallet adds its partial
+//!   signature to PSBTs); all-public is a watch-only multisig.
+//! - **Network.** The key prefix must match the active network: mainnet
+//!   keys are `xprv`/`xpub`, testnet/regtest keys are `tprv`/`tpub`. The
+//!   chain binding itself stays bdk's genesis-hash check at open.
 //! - **Coin type** is parsed from the derivation path when present —
 //!   informational only (display), never used for gating.
 //!
 //! The final gate is bdk itself: the pair is built into a throwaway
 //! in-memory wallet (`create_wallet_no_persist`), the exact validation the
 //! real store creation runs later.
+//!
+//! The way back out is [`descriptor_export`]: a wallet's pair as public
+//! (optionally private) checksummed descriptors, its account key, and a
+//! Bitcoin Core `importdescriptors` request.
 
-use bdk_wallet::miniscript::descriptor::{DescriptorSecretKey, DescriptorType, KeyMap};
+use bdk_wallet::miniscript::descriptor::{DescriptorSecretKey, DescriptorType, KeyMap, WshInner};
 use bdk_wallet::miniscript::{Descriptor, DescriptorPublicKey, ForEachKey};
 use bdk_wallet::Wallet;
-use bitcoin::bip32::ChildNumber;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, Xpub};
 use bitcoin::secp256k1::Secp256k1;
 use bitcoin::NetworkKind;
 use serde::Serialize;
 
-use super::config::{DescriptorKindCfg, WalletNetwork};
+use super::config::{DescriptorKindCfg, MultisigShape, WalletNetwork};
 
 /// A structured import error: `code` keys the translated UI message,
 /// `message` carries the English detail.
@@ -78,9 +101,10 @@ impl std::fmt::Display for ImportError {
 /// A validated descriptor import, ready to store and open.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedImport {
-    /// External (receive) private descriptor, checksum-stripped.
+    /// External (receive) descriptor, checksum-stripped — private, or
+    /// public for a watch-only import.
     pub external: String,
-    /// Internal (change) private descriptor, checksum-stripped. `None` for
+    /// Internal (change) descriptor, checksum-stripped. `None` for
     /// a single-address `wpkh(WIF)` wallet: ONE keychain, change returns
     /// to the same address (bdk `create_single`).
     pub internal: Option<String>,
@@ -93,6 +117,10 @@ pub struct ParsedImport {
     pub inferred_internal: bool,
     /// Both branches came from one multipath `<0;1>` descriptor.
     pub from_multipath: bool,
+    /// Public key material only — the wallet watches, it cannot sign.
+    pub watch_only: bool,
+    /// k-of-n shape of a `wsh(sortedmulti)` import.
+    pub multisig: Option<MultisigShape>,
 }
 
 impl ParsedImport {
@@ -121,6 +149,11 @@ pub struct ImportValidation {
     /// Single-address `wpkh(WIF)` wallet — change returns to the same
     /// address (the import form's verdict line).
     pub single_address: bool,
+    /// Public-only import — the form's watch-only verdict line.
+    pub watch_only: bool,
+    /// k-of-n shape of a multisig paste — the form's "2-of-3" verdict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigShape>,
 }
 
 impl ImportValidation {
@@ -135,6 +168,8 @@ impl ImportValidation {
                 inferred_internal: parsed.inferred_internal,
                 from_multipath: parsed.from_multipath,
                 single_address: parsed.single_address(),
+                watch_only: parsed.watch_only,
+                multisig: parsed.multisig,
             },
             Err(e) => Self {
                 valid: false,
@@ -145,6 +180,8 @@ impl ImportValidation {
                 inferred_internal: false,
                 from_multipath: false,
                 single_address: false,
+                watch_only: false,
+                multisig: None,
             },
         }
     }
@@ -161,15 +198,21 @@ pub fn bdk_network(network: WalletNetwork) -> bitcoin::Network {
     }
 }
 
-fn expected_network_kind(network: WalletNetwork) -> NetworkKind {
+/// The BIP32 key serialization (`xprv`/`xpub` vs `tprv`/`tpub`) of
+/// `network`'s descriptors.
+pub fn expected_network_kind(network: WalletNetwork) -> NetworkKind {
     match network {
         WalletNetwork::Mainnet => NetworkKind::Main,
         WalletNetwork::Testnet | WalletNetwork::Regtest => NetworkKind::Test,
     }
 }
 
-const WATCH_ONLY_MESSAGE: &str = "Watch-only wallets are not supported yet — paste the PRIVATE \
-     descriptor (it contains an xprv/tprv key)";
+/// SLIP-132 version bytes of a BIP-84 account key (`zpub` mainnet, `vpub`
+/// testnet) and the BIP32 versions they stand in for (`xpub` / `tpub`).
+const SLIP132_ZPUB: [u8; 4] = [0x04, 0xb2, 0x47, 0x46];
+const SLIP132_VPUB: [u8; 4] = [0x04, 0x5f, 0x1c, 0xf6];
+const BIP32_XPUB: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const BIP32_TPUB: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
 
 /// Base58 body after a recognized extended-key prefix.
 fn is_base58_tail(s: &str) -> bool {
@@ -186,6 +229,90 @@ fn bare_key_family(token: &str, families: [&str; 5]) -> bool {
         .any(|p| token.strip_prefix(p).is_some_and(is_base58_tail))
 }
 
+/// Re-label a BIP32 xpub/tpub with its SLIP-132 BIP-84 version bytes.
+fn slip132(xpub: &Xpub, version: [u8; 4]) -> String {
+    let mut data = xpub.encode().to_vec();
+    data[..4].copy_from_slice(&version);
+    bitcoin::base58::encode_check(&data)
+}
+
+/// Split a `[fingerprint/path]key` token into its origin text and key.
+fn split_origin(token: &str) -> (Option<&str>, &str) {
+    token
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .map_or((None, token), |(origin, key)| (Some(origin), key))
+}
+
+/// Whether `token` is a SLIP-132 BIP-84 account key (`zpub`/`vpub`),
+/// with or without a key origin.
+fn is_zpub(token: &str) -> bool {
+    let (_, key) = split_origin(token);
+    ["zpub", "vpub"]
+        .iter()
+        .any(|p| key.strip_prefix(p).is_some_and(is_base58_tail))
+}
+
+/// The BIP32 account key and normalized key origin (`fingerprint/path`)
+/// of a `[fingerprint/path]zpub…` token. The origin is required and must
+/// reach the key's depth.
+fn zpub_account(token: &str) -> Result<(Xpub, String), ImportError> {
+    let (origin, key) = split_origin(token);
+    let xpub = zpub_to_xpub(key)?;
+    let origin = origin.ok_or_else(|| {
+        ImportError::new(
+            "bare_xpub",
+            "A zpub/vpub needs its key origin so a signer can recognize it — paste it as \
+             [fingerprint/84'/coin'/account']zpub… (the master key fingerprint your signer \
+             shows)",
+        )
+    })?;
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+    let fingerprint: Fingerprint = fingerprint.parse().map_err(|_| {
+        ImportError::new(
+            "parse",
+            format!("Key origin fingerprint '{fingerprint}' is not 8 hex digits"),
+        )
+    })?;
+    let path: DerivationPath = format!("m/{path}")
+        .parse()
+        .map_err(|e| ImportError::new("parse", format!("Key origin path: {e}")))?;
+    let steps: &[ChildNumber] = path.as_ref();
+    if steps.len() != usize::from(xpub.depth) {
+        return Err(ImportError::new(
+            "parse",
+            format!(
+                "The key origin has {} derivation steps but the key sits at depth {}",
+                steps.len(),
+                xpub.depth
+            ),
+        ));
+    }
+    let steps: String = steps.iter().map(|c| format!("/{c}")).collect();
+    Ok((xpub, format!("{fingerprint}{steps}")))
+}
+
+/// Decode a bare SLIP-132 `zpub`/`vpub` into the BIP32 `xpub`/`tpub` it
+/// re-labels (same key data, standard version bytes) — the form miniscript
+/// parses inside `wpkh(...)`.
+fn zpub_to_xpub(token: &str) -> Result<Xpub, ImportError> {
+    let mut data = bitcoin::base58::decode_check(token)
+        .map_err(|e| ImportError::new("parse", format!("Not a valid extended key: {e}")))?;
+    if data.len() != 78 {
+        return Err(ImportError::new("parse", "Not a valid extended key"));
+    }
+    let standard = if data[..4] == SLIP132_ZPUB {
+        BIP32_XPUB
+    } else if data[..4] == SLIP132_VPUB {
+        BIP32_TPUB
+    } else {
+        return Err(ImportError::new("parse", "Not a zpub/vpub account key"));
+    };
+    data[..4].copy_from_slice(&standard);
+    Xpub::decode(&data)
+        .map_err(|e| ImportError::new("parse", format!("Not a valid extended key: {e}")))
+}
+
 /// Verify (when present) and strip the `#checksum` suffix.
 fn strip_checksum(token: &str) -> Result<String, ImportError> {
     let Some((body, given)) = token.rsplit_once('#') else {
@@ -204,10 +331,20 @@ fn strip_checksum(token: &str) -> Result<String, ImportError> {
 
 /// Classify one whitespace-separated token of the paste box.
 fn check_token(token: &str) -> Result<(), ImportError> {
+    if is_zpub(token) {
+        return Err(ImportError::new(
+            "bare_xpub",
+            "A zpub/vpub imports on its own — paste it without a second descriptor",
+        ));
+    }
     if bare_key_family(token, ["xpub", "ypub", "zpub", "Ypub", "Zpub"])
         || bare_key_family(token, ["tpub", "upub", "vpub", "Upub", "Vpub"])
     {
-        return Err(ImportError::new("watch_only", WATCH_ONLY_MESSAGE));
+        return Err(ImportError::new(
+            "bare_xpub",
+            "A bare extended public key does not say which addresses it covers — paste a \
+             descriptor (wpkh(xpub…/0/*) or tr(xpub…/0/*)), or a [fingerprint/path]zpub/vpub",
+        ));
     }
     if bare_key_family(token, ["xprv", "yprv", "zprv", "Yprv", "Zprv"])
         || bare_key_family(token, ["tprv", "uprv", "vprv", "Uprv", "Vprv"])
@@ -237,13 +374,15 @@ fn check_token(token: &str) -> Result<(), ImportError> {
 }
 
 /// The `/0/*` / `/1/*` branch tail of one single-path descriptor body, when
-/// it has exactly one such tail: `Some((branch, sibling_body))`.
+/// EVERY wildcard ends in the same such tail (the one key of a single-key
+/// descriptor, all cosigners of a multisig): `Some((branch, sibling_body))`.
 fn branch_and_sibling(body: &str) -> Option<(u32, String)> {
+    let wildcards = body.matches("/*").count();
     let zeros = body.matches("/0/*").count();
     let ones = body.matches("/1/*").count();
     match (zeros, ones) {
-        (1, 0) => Some((0, body.replacen("/0/*", "/1/*", 1))),
-        (0, 1) => Some((1, body.replacen("/1/*", "/0/*", 1))),
+        (n, 0) if n > 0 && n == wildcards => Some((0, body.replace("/0/*", "/1/*"))),
+        (0, n) if n > 0 && n == wildcards => Some((1, body.replace("/1/*", "/0/*"))),
         _ => None,
     }
 }
@@ -261,6 +400,10 @@ struct ParsedSide {
     /// Non-ranged `wpkh` over a single WIF key — imports as a
     /// single-address wallet (one keychain, change to self).
     single_address: bool,
+    /// Every key is public — a watch-only side.
+    watch_only: bool,
+    /// k-of-n shape of a `wsh(sortedmulti)` side.
+    multisig: Option<MultisigShape>,
 }
 
 fn classify_type(desc: &Descriptor<DescriptorPublicKey>) -> Result<DescriptorKindCfg, ImportError> {
@@ -268,57 +411,97 @@ fn classify_type(desc: &Descriptor<DescriptorPublicKey>) -> Result<DescriptorKin
         DescriptorType::Wpkh => Ok(DescriptorKindCfg::Bip84),
         DescriptorType::Tr => Ok(DescriptorKindCfg::Bip86),
         DescriptorType::Pkh | DescriptorType::ShWpkh => Ok(DescriptorKindCfg::Legacy),
+        DescriptorType::WshSortedMulti => Ok(DescriptorKindCfg::Multisig),
         other => Err(ImportError::new(
             "unsupported_type",
             format!(
                 "Unsupported descriptor type {other:?} — supported: wpkh (segwit), tr (taproot), \
-                 pkh / sh(wpkh) (legacy)"
+                 pkh / sh(wpkh) (legacy), wsh(sortedmulti) (multisig)"
             ),
         )),
     }
 }
 
-/// Coin type from a secret key's paths: prefer the key origin
-/// (`[fp/84'/coin'/0']`), else the in-descriptor derivation path of a
-/// master key (`tprv.../84'/coin'/0'/0/*`). Hardened second element only.
-fn coin_type_of(secret: &DescriptorSecretKey) -> Option<u32> {
-    let DescriptorSecretKey::XPrv(xkey) = secret else {
+/// k-of-n of a `wsh(sortedmulti(k, …))` descriptor, `None` for any other.
+fn multisig_shape(desc: &Descriptor<DescriptorPublicKey>) -> Option<MultisigShape> {
+    let Descriptor::Wsh(wsh) = desc else {
         return None;
     };
+    match wsh.as_inner() {
+        WshInner::SortedMulti(multi) => Some(MultisigShape {
+            threshold: multi.k() as u32,
+            cosigners: multi.pks().len() as u32,
+        }),
+        WshInner::Ms(_) => None,
+    }
+}
+
+/// Coin type from a key's paths: prefer the key origin
+/// (`[fp/84'/coin'/0']`), else the in-descriptor derivation path of a
+/// master key (`tprv.../84'/coin'/0'/0/*`). Hardened second element only.
+fn coin_type_from_paths(
+    origin: Option<&DerivationPath>,
+    derivation_path: &DerivationPath,
+) -> Option<u32> {
     let from_path = |path: &[ChildNumber]| match path.get(1) {
         Some(ChildNumber::Hardened { index }) => Some(*index),
         _ => None,
     };
-    if let Some((_, origin_path)) = &xkey.origin {
+    if let Some(origin_path) = origin {
         if origin_path.len() >= 2 {
             return from_path(origin_path.as_ref());
         }
     }
-    let path: &[ChildNumber] = xkey.derivation_path.as_ref();
+    let path: &[ChildNumber] = derivation_path.as_ref();
     if path.len() >= 3 {
         return from_path(path);
     }
     None
 }
 
+/// Coin type of a secret key (see [`coin_type_from_paths`]).
+fn coin_type_of(secret: &DescriptorSecretKey) -> Option<u32> {
+    let DescriptorSecretKey::XPrv(xkey) = secret else {
+        return None;
+    };
+    coin_type_from_paths(xkey.origin.as_ref().map(|(_, p)| p), &xkey.derivation_path)
+}
+
+/// Coin type of a public key (see [`coin_type_from_paths`]).
+fn coin_type_of_public(key: &DescriptorPublicKey) -> Option<u32> {
+    let DescriptorPublicKey::XPub(xkey) = key else {
+        return None;
+    };
+    coin_type_from_paths(xkey.origin.as_ref().map(|(_, p)| p), &xkey.derivation_path)
+}
+
 fn parse_side(body: &str, network: WalletNetwork) -> Result<ParsedSide, ImportError> {
     let secp = Secp256k1::new();
     let (desc, keymap): (Descriptor<DescriptorPublicKey>, KeyMap) =
         Descriptor::parse_descriptor(&secp, body)
             .map_err(|e| ImportError::new("parse", format!("Not a valid descriptor: {e}")))?;
 
-    // Private-only (v1): every key must carry its secret. A public-only
-    // descriptor has an empty keymap; a mixed one comes up short.
-    let mut key_count = 0usize;
-    desc.for_each_key(|_| {
-        key_count += 1;
+    let mut public_keys: Vec<DescriptorPublicKey> = Vec::new();
+    desc.for_each_key(|key| {
+        public_keys.push(key.clone());
         true
     });
-    if keymap.len() < key_count {
-        return Err(ImportError::new("watch_only", WATCH_ONLY_MESSAGE));
-    }
 
     let kind = classify_type(&desc)?;
+    if kind == DescriptorKindCfg::Multisig {
+        return parse_multisig_side(body, &desc, &keymap, &public_keys, network);
+    }
+
+    // Private or public, never mixed: a public-only descriptor has an empty
+    // keymap (watch-only); a mixed one comes up short.
+    let watch_only = keymap.is_empty() && !public_keys.is_empty();
+    if !watch_only && keymap.len() < public_keys.len() {
+        return Err(ImportError::new(
+            "mixed_keys",
+            "The descriptor mixes private and public keys — paste either the PRIVATE \
+             descriptor (spendable) or the PUBLIC one (watch-only)",
+        ));
+    }
 
     // Single WIF keys (no derivation, no wildcard) are the single-address
     // wallet form — segwit only. Everything else must be ranged.
@@ -344,8 +527,44 @@ fn parse_side(body: &str, network: WalletNetwork) -> Result<ParsedSide, ImportEr
         ));
     };
 
-    // Key network (xprv vs tprv, or the WIF network byte) against the
-    // active app network.
+    if watch_only {
+        return parse_public_side(body, &public_keys, kind, network);
+    }
+
+    let network_kind = check_secret_network(&keymap, network)?;
+
+    // The same-key pair check compares origin + xprv, branch-independent
+    // (the branch lives in `derivation_path`, deliberately excluded).
+    let mut keys: Vec<String> = keymap
+        .values()
+        .map(|secret| match secret {
+            DescriptorSecretKey::XPrv(x) => format!("{:?}:{}", x.origin, x.xkey),
+            DescriptorSecretKey::MultiXPrv(x) => format!("{:?}:{}", x.origin, x.xkey),
+            DescriptorSecretKey::Single(s) => s.key.to_wif(),
+        })
+        .collect();
+    keys.sort();
+
+    let coin_type = keymap.values().find_map(coin_type_of);
+
+    Ok(ParsedSide {
+        body: body.to_string(),
+        kind,
+        coin_type,
+        keys,
+        network: network_kind,
+        single_address,
+        watch_only: false,
+        multisig: None,
+    })
+}
+
+/// Key network of every secret (xprv vs tprv, or the WIF network byte)
+/// against the active app network.
+fn check_secret_network(
+    keymap: &KeyMap,
+    network: WalletNetwork,
+) -> Result<Option<NetworkKind>, ImportError> {
     let mut network_kind = None;
     for secret in keymap.values() {
         let (this, is_wif) = match secret {
@@ -371,33 +590,121 @@ fn parse_side(body: &str, network: WalletNetwork) -> Result<ParsedSide, ImportEr
             ));
         }
     }
+    Ok(network_kind)
+}
 
-    // The same-key pair check compares origin + xprv, branch-independent
-    // (the branch lives in `derivation_path`, deliberately excluded).
-    let mut keys: Vec<String> = keymap
-        .values()
-        .map(|secret| match secret {
-            DescriptorSecretKey::XPrv(x) => format!("{:?}:{}", x.origin, x.xkey),
-            DescriptorSecretKey::MultiXPrv(x) => format!("{:?}:{}", x.origin, x.xkey),
-            DescriptorSecretKey::Single(s) => s.key.to_wif(),
+/// Key network of every extended PUBLIC key (xpub vs tpub) against the
+/// active app network. Raw public keys carry no network byte.
+fn check_public_network(
+    public_keys: &[DescriptorPublicKey],
+    network: WalletNetwork,
+) -> Result<Option<NetworkKind>, ImportError> {
+    let mut network_kind = None;
+    for key in public_keys {
+        let this = match key {
+            DescriptorPublicKey::XPub(x) => x.xkey.network,
+            DescriptorPublicKey::MultiXPub(x) => x.xkey.network,
+            DescriptorPublicKey::Single(_) => continue,
+        };
+        network_kind = Some(this);
+        if this != expected_network_kind(network) {
+            let (have, want) = match this {
+                NetworkKind::Main => ("a mainnet key (xpub)", "tpub"),
+                NetworkKind::Test => ("a testnet key (tpub)", "xpub"),
+            };
+            return Err(ImportError::new(
+                "wrong_network",
+                format!(
+                    "The descriptor contains {have} but the active network is {} — {want} keys \
+                     are required here",
+                    network.as_str()
+                ),
+            ));
+        }
+    }
+    Ok(network_kind)
+}
+
+/// Origin + public key of every key, sorted — the branch-independent
+/// same-key comparison of the public (and multisig) pair checks.
+fn public_key_ids(public_keys: &[DescriptorPublicKey]) -> Vec<String> {
+    let mut keys: Vec<String> = public_keys
+        .iter()
+        .map(|key| match key {
+            DescriptorPublicKey::XPub(x) => format!("{:?}:{}", x.origin, x.xkey),
+            DescriptorPublicKey::MultiXPub(x) => format!("{:?}:{}", x.origin, x.xkey),
+            DescriptorPublicKey::Single(s) => format!("{:?}:{:?}", s.origin, s.key),
         })
         .collect();
     keys.sort();
+    keys
+}
 
-    let coin_type = keymap.values().find_map(coin_type_of);
-
+/// The watch-only tail of [`parse_side`]: network and same-key checks over
+/// the PUBLIC keys (xpub vs tpub). Only reached for ranged descriptors — a
+/// lone public key has no single-address form (the WIF rule needs secrets).
+fn parse_public_side(
+    body: &str,
+    public_keys: &[DescriptorPublicKey],
+    kind: DescriptorKindCfg,
+    network: WalletNetwork,
+) -> Result<ParsedSide, ImportError> {
+    let network_kind = check_public_network(public_keys, network)?;
     Ok(ParsedSide {
         body: body.to_string(),
         kind,
-        coin_type,
-        keys,
+        coin_type: public_keys.iter().find_map(coin_type_of_public),
+        keys: public_key_ids(public_keys),
         network: network_kind,
-        single_address,
+        single_address: false,
+        watch_only: true,
+        multisig: None,
+    })
+}
+
+/// The multisig tail of [`parse_side`]: `wsh(sortedmulti(k, …))` over
+/// ranged extended keys. Mixed keys are the NORMAL form here — our own
+/// secret next to the cosigners' xpubs; all-public is watch-only. The
+/// pair check compares the public form of every cosigner.
+fn parse_multisig_side(
+    body: &str,
+    desc: &Descriptor<DescriptorPublicKey>,
+    keymap: &KeyMap,
+    public_keys: &[DescriptorPublicKey],
+    network: WalletNetwork,
+) -> Result<ParsedSide, ImportError> {
+    let shape = multisig_shape(desc).ok_or_else(|| {
+        ImportError::new(
+            "unsupported_type",
+            "Only wsh(sortedmulti(k, …)) multisig is supported",
+        )
+    })?;
+    if public_keys
+        .iter()
+        .any(|key| matches!(key, DescriptorPublicKey::Single(_)) || !key.has_wildcard())
+    {
+        return Err(ImportError::new(
+            "not_ranged",
+            "Every multisig key must be a ranged extended key (its derivation must end in /*)",
+        ));
+    }
+    let secret_network = check_secret_network(keymap, network)?;
+    let public_network = check_public_network(public_keys, network)?;
+    Ok(ParsedSide {
+        body: body.to_string(),
+        kind: DescriptorKindCfg::Multisig,
+        coin_type: public_keys.iter().find_map(coin_type_of_public),
+        keys: public_key_ids(public_keys),
+        network: secret_network.or(public_network),
+        single_address: false,
+        watch_only: keymap.is_empty(),
+        multisig: Some(shape),
     })
 }
 
-/// Parse + validate the import paste box: one or two private descriptors
-/// for `network`. See the module docs for the full rule set.
+/// Parse + validate the import paste box: one or two private (or public,
+/// watch-only) descriptors, or a zpub/vpub with its key origin, for
+/// `network`. See the module docs for the full rule set.
 pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport, ImportError> {
     let tokens: Vec<&str> = input.split_whitespace().collect();
     let (external_body, internal_body, inferred_internal, from_multipath) = match tokens.as_slice()
@@ -405,14 +712,27 @@ pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport,
         [] => {
             return Err(ImportError::new("empty", "Paste a descriptor to import"));
         }
+        // A SLIP-132 BIP-84 account key with its origin: the standard
+        // watch-only wpkh pair over it (its network is checked like any
+        // xpub's).
+        [single] if is_zpub(single) => {
+            let (xpub, origin) = zpub_account(single)?;
+            (
+                format!("wpkh([{origin}]{xpub}/0/*)"),
+                format!("wpkh([{origin}]{xpub}/1/*)"),
+                true,
+                false,
+            )
+        }
         [single] => {
             check_token(single)?;
             let body = strip_checksum(single)?;
             if body.contains('<') {
-                // Multipath: only the standard <0;1> receive/change pair.
-                // Split textually — a PRIVATE multipath key cannot be
-                // parsed to its public form by miniscript 12 / bdk 2.
-                if body.matches('<').count() != 1 || !body.contains("<0;1>") {
+                // Multipath: only the standard <0;1> receive/change pair
+                // (on every cosigner key of a multisig). Split textually —
+                // a PRIVATE multipath key cannot be parsed to its public
+                // form by miniscript 12 / bdk 2.
+                if body.matches('<').count() != body.matches("<0;1>").count() {
                     return Err(ImportError::new(
                         "multipath_nonstandard",
                         "Only the standard <0;1> receive/change multipath is supported — paste \
@@ -420,8 +740,8 @@ pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport,
                     ));
                 }
                 (
-                    body.replacen("<0;1>", "0", 1),
-                    body.replacen("<0;1>", "1", 1),
+                    body.replace("<0;1>", "0"),
+                    body.replace("<0;1>", "1"),
                     false,
                     true,
                 )
@@ -448,6 +768,8 @@ pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport,
                         coin_type: side.coin_type,
                         inferred_internal: false,
                         from_multipath: false,
+                        watch_only: false,
+                        multisig: None,
                     });
                 }
                 match branch_and_sibling(&body) {
@@ -511,13 +833,16 @@ pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport,
     let external = parse_side(&external_body, network)?;
     let internal = parse_side(&internal_body, network)?;
 
-    if external.kind != internal.kind {
+    if external.kind != internal.kind || external.multisig != internal.multisig {
         return Err(ImportError::new(
             "pair_mismatch",
             "The two descriptors have different script types",
         ));
     }
-    if external.keys != internal.keys || external.network != internal.network {
+    if external.keys != internal.keys
+        || external.network != internal.network
+        || external.watch_only != internal.watch_only
+    {
         return Err(ImportError::new(
             "pair_mismatch",
             "The two descriptors use different keys — external and internal must derive from \
@@ -545,6 +870,351 @@ pub fn parse_import(input: &str, network: WalletNetwork) -> Result<ParsedImport,
         coin_type: external.coin_type.or(internal.coin_type),
         inferred_internal,
         from_multipath,
+        watch_only: external.watch_only,
+        multisig: external.multisig,
+    })
+}
+
+/// Most cosigners a multisig may have — `OP_CHECKMULTISIG` takes at most
+/// 20 keys.
+pub const MAX_MULTISIG_COSIGNERS: usize = 20;
+
+/// Assemble the `wsh(sortedmulti(k, …))` receive/change pair from
+/// ACCOUNT-level key expressions (`[fp/48'/coin'/0'/2']xpub…`, our own one
+/// possibly an `xprv`) — the create-multisig path. Only the shape is
+/// checked here; the result goes through [`parse_import`] like a paste.
+pub fn multisig_descriptors(
+    threshold: u32,
+    keys: &[String],
+) -> Result<(String, String), ImportError> {
+    let keys: Vec<&str> = keys.iter().map(|k| k.trim()).collect();
+    if keys.len() < 2 || keys.len() > MAX_MULTISIG_COSIGNERS {
+        return Err(ImportError::new(
+            "multisig_shape",
+            format!("A multisig needs 2 to {MAX_MULTISIG_COSIGNERS} cosigner keys"),
+        ));
+    }
+    if threshold == 0 || threshold as usize > keys.len() {
+        return Err(ImportError::new(
+            "multisig_shape",
+            format!(
+                "The threshold must be between 1 and the number of cosigners ({})",
+                keys.len()
+            ),
+        ));
+    }
+    for (i, key) in keys.iter().enumerate() {
+        // Account level: no derivation tail of its own — the branch is
+        // appended here for every cosigner alike.
+        let tail = key.rsplit_once(']').map_or(*key, |(_, k)| k);
+        if key.is_empty() || tail.contains('/') || tail.contains('<') || tail.contains('*') {
+            return Err(ImportError::new(
+                "multisig_shape",
+                format!(
+                    "Cosigner {} must be an account-level key ([fingerprint/path]xpub…) without \
+                     a derivation suffix",
+                    i + 1
+                ),
+            ));
+        }
+        if keys[..i].contains(key) {
+            return Err(ImportError::new(
+                "multisig_shape",
+                format!("Cosigner {} is listed twice", i + 1),
+            ));
+        }
+    }
+    let branch = |b: u32| {
+        let list: Vec<String> = keys.iter().map(|k| format!("{k}/{b}/*")).collect();
+        format!("wsh(sortedmulti({threshold},{}))", list.join(","))
+    };
+    Ok((branch(0), branch(1)))
+}
+
+/// One cosigner of a [`MultisigBackup`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigCosigner {
+    /// Master key fingerprint (hex) — the key origin, or the key's own
+    /// fingerprint when it carries none.
+    pub fingerprint: String,
+    /// Origin derivation path (`m/48'/1'/0'/2'`; `m` without an origin).
+    pub derivation_path: String,
+    /// Account xpub (tpub off mainnet).
+    pub xpub: String,
+}
+
+/// The multisig "wallet backup": everything needed to rebuild the wallet's
+/// watch side on another machine or cosigner device. PUBLIC only — our own
+/// key appears as its xpub.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigBackup {
+    pub threshold: u32,
+    pub cosigners: Vec<MultisigCosigner>,
+    /// Public receive descriptor, with checksum.
+    pub external: String,
+    /// Public change descriptor, with checksum.
+    pub internal: String,
+    /// The same, as a Coldcard/Sparrow-style multisig setup file.
+    pub text: String,
+}
+
+/// Build the [`MultisigBackup`] of a stored multisig pair (private or
+/// public descriptors — secrets are dropped by the public parse).
+pub fn multisig_backup(
+    name: &str,
+    external: &str,
+    internal: &str,
+) -> Result<MultisigBackup, String> {
+    let secp = Secp256k1::new();
+    let parse = |body: &str| {
+        Descriptor::<DescriptorPublicKey>::parse_descriptor(&secp, body)
+            .map(|(desc, _)| desc)
+            .map_err(|e| format!("stored descriptor: {e}"))
+    };
+    let (external, internal) = (parse(external)?, parse(internal)?);
+    let shape = multisig_shape(&external).ok_or("The open wallet is not a multisig wallet")?;
+
+    let mut cosigners = Vec::new();
+    external.for_each_key(|key| {
+        if let DescriptorPublicKey::XPub(x) = key {
+            let (fingerprint, path) = match &x.origin {
+                Some((fp, path)) => (*fp, path.clone()),
+                None => (x.xkey.fingerprint(), DerivationPath::master()),
+            };
+            let path: &[ChildNumber] = path.as_ref();
+            let derivation_path = path
+                .iter()
+                .fold("m".to_string(), |acc, child| format!("{acc}/{child}"));
+            cosigners.push(MultisigCosigner {
+                fingerprint: fingerprint.to_string(),
+                derivation_path,
+                xpub: x.xkey.to_string(),
+            });
+        }
+        true
+    });
+
+    let mut text = format!(
+        "# Phoenix multisig setup file\nName: {name}\nPolicy: {} of {}\nFormat: P2WSH\n",
+        shape.threshold, shape.cosigners
+    );
+    for cosigner in &cosigners {
+        text.push_str(&format!(
+            "\nDerivation: {}\n{}: {}\n",
+            cosigner.derivation_path,
+            cosigner.fingerprint.to_uppercase(),
+            cosigner.xpub
+        ));
+    }
+
+    Ok(MultisigBackup {
+        threshold: shape.threshold,
+        cosigners,
+        external: external.to_string(),
+        internal: internal.to_string(),
+        text,
+    })
+}
+
+/// Highest child index of the ranged descriptors in the Core
+/// `importdescriptors` export — Core's default keypool; an active
+/// descriptor tops itself up past it as addresses get used.
+pub const CORE_IMPORT_RANGE_END: u32 = 999;
+
+/// The account key of a single-key wallet, as another tool asks for it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountKeyExport {
+    /// Master key fingerprint (hex).
+    pub fingerprint: String,
+    /// Account derivation path (`m/84'/0'/0'`; `m` without an origin).
+    pub derivation_path: String,
+    /// `[fingerprint/84'/coin'/0']` — the key origin as descriptors write it.
+    pub key_origin: String,
+    /// Account xpub (tpub off mainnet).
+    pub xpub: String,
+    /// The same key with SLIP-132 version bytes (`zpub` / `vpub`) — BIP-84
+    /// (`wpkh`) wallets only.
+    pub slip132: Option<String>,
+}
+
+/// A wallet's descriptors exported for another tool. Keys are serialized
+/// for the wallet's network (`tpub`/`tprv` off mainnet), every descriptor
+/// carries its BIP-380 checksum.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DescriptorExport {
+    /// Public receive descriptor.
+    pub external: String,
+    /// Public change descriptor; `None` for a single-address wallet.
+    pub internal: Option<String>,
+    /// Private receive descriptor — only when asked for.
+    pub external_private: Option<String>,
+    /// Private change descriptor — only when asked for (and paired).
+    pub internal_private: Option<String>,
+    /// The account key — `None` for multisig and single-address wallets.
+    pub account: Option<AccountKeyExport>,
+    /// Bitcoin Core `importdescriptors` request JSON for the same wallet:
+    /// the private descriptors when exported, the public ones otherwise
+    /// (for a `disable_private_keys` node wallet).
+    pub core_import: String,
+}
+
+/// `body#checksum`, after dropping any checksum `body` already carries.
+fn with_checksum(body: &str) -> Result<String, String> {
+    let body = body.rsplit_once('#').map_or(body, |(b, _)| b);
+    let checksum = bdk_wallet::miniscript::descriptor::checksum::desc_checksum(body)
+        .map_err(|e| format!("descriptor checksum: {e}"))?;
+    Ok(format!("{body}#{checksum}"))
+}
+
+/// The public and (when `private`) private form of one stored descriptor,
+/// extended keys re-serialized for `kind` — seed wallets derive with
+/// `xprv` on every network, Core on a test network only parses `tprv`.
+fn export_pair(
+    descriptor: &str,
+    kind: NetworkKind,
+    private: bool,
+) -> Result<(Descriptor<DescriptorPublicKey>, String, Option<String>), String> {
+    let secp = Secp256k1::new();
+    let (desc, keymap) = Descriptor::<DescriptorPublicKey>::parse_descriptor(&secp, descriptor)
+        .map_err(|e| format!("stored descriptor: {e}"))?;
+
+    let mut public = desc.to_string();
+    let mut xpubs = Vec::new();
+    desc.for_each_key(|key| {
+        if let DescriptorPublicKey::XPub(x) = key {
+            xpubs.push(x.xkey);
+        }
+        true
+    });
+    for xkey in xpubs {
+        let relabeled = Xpub {
+            network: kind,
+            ..xkey
+        };
+        public = public.replace(&xkey.to_string(), &relabeled.to_string());
+    }
+
+    let secret = private
+        .then(|| {
+            let mut text = descriptor.to_string();
+            for key in keymap.values() {
+                if let DescriptorSecretKey::XPrv(x) = key {
+                    let relabeled = bitcoin::bip32::Xpriv {
+                        network: kind,
+                        ..x.xkey
+                    };
+                    text = text.replace(&x.xkey.to_string(), &relabeled.to_string());
+                }
+            }
+            with_checksum(&text)
+        })
+        .transpose()?;
+    Ok((desc, with_checksum(&public)?, secret))
+}
+
+/// The account key of a single-xpub descriptor, or `None` (multisig, WIF).
+fn account_key(
+    desc: &Descriptor<DescriptorPublicKey>,
+    kind: NetworkKind,
+) -> Option<AccountKeyExport> {
+    let mut keys = Vec::new();
+    desc.for_each_key(|key| {
+        keys.push(key.clone());
+        true
+    });
+    let [DescriptorPublicKey::XPub(x)] = keys.as_slice() else {
+        return None;
+    };
+    let (fingerprint, path) = match &x.origin {
+        Some((fp, path)) => (*fp, path.clone()),
+        None => (x.xkey.fingerprint(), DerivationPath::master()),
+    };
+    let path: &[ChildNumber] = path.as_ref();
+    let derivation_path = path
+        .iter()
+        .fold("m".to_string(), |acc, child| format!("{acc}/{child}"));
+    let xpub = Xpub {
+        network: kind,
+        ..x.xkey
+    };
+    let slip132 = (desc.desc_type() == DescriptorType::Wpkh).then(|| {
+        let version = match kind {
+            NetworkKind::Main => SLIP132_ZPUB,
+            NetworkKind::Test => SLIP132_VPUB,
+        };
+        slip132(&xpub, version)
+    });
+    Some(AccountKeyExport {
+        fingerprint: fingerprint.to_string(),
+        key_origin: format!("[{fingerprint}{}]", &derivation_path[1..]),
+        derivation_path,
+        xpub: xpub.to_string(),
+        slip132,
+    })
+}
+
+/// One `importdescriptors` request entry. Ranged descriptors go in active
+/// (Core derives new addresses from them); a single-address descriptor is
+/// imported as-is — Core only activates ranged ones. `timestamp` 0 rescans
+/// from genesis: the wallet's `created_at` is its restore time, not the
+/// birth of its keys.
+fn core_import_entry(desc: &str, internal: Option<bool>) -> serde_json::Value {
+    match internal {
+        Some(internal) => serde_json::json!({
+            "desc": desc,
+            "timestamp": 0,
+            "active": true,
+            "internal": internal,
+            "range": [0, CORE_IMPORT_RANGE_END],
+        }),
+        None => serde_json::json!({ "desc": desc, "timestamp": 0 }),
+    }
+}
+
+/// Build the [`DescriptorExport`] of a wallet's stored (or seed-derived)
+/// descriptors. `external`/`internal` may be private or public; the
+/// private forms are only emitted with `private` (and need the secrets).
+pub fn descriptor_export(
+    network: WalletNetwork,
+    external: &str,
+    internal: Option<&str>,
+    private: bool,
+) -> Result<DescriptorExport, String> {
+    let kind = expected_network_kind(network);
+    let (desc, external, external_private) = export_pair(external, kind, private)?;
+    let internal = internal
+        .map(|d| export_pair(d, kind, private))
+        .transpose()?
+        .map(|(_, public, secret)| (public, secret));
+    let (internal, internal_private) = match internal {
+        Some((public, secret)) => (Some(public), secret),
+        None => (None, None),
+    };
+
+    let ranged = internal.is_some();
+    let core = |ext: &str, int: Option<&str>| {
+        let mut entries = vec![core_import_entry(ext, ranged.then_some(false))];
+        entries.extend(int.map(|d| core_import_entry(d, Some(true))));
+        entries
+    };
+    let entries = match external_private.as_deref() {
+        Some(ext) => core(ext, internal_private.as_deref()),
+        None => core(external.as_str(), internal.as_deref()),
+    };
+    let core_import = serde_json::to_string_pretty(&entries)
+        .map_err(|e| format!("serializing the Core import: {e}"))?;
+
+    Ok(DescriptorExport {
+        account: account_key(&desc, kind),
+        external,
+        internal,
+        external_private,
+        internal_private,
+        core_import,
     })
 }
 
@@ -683,23 +1353,307 @@ mod tests {
     }
 
     #[test]
-    fn bare_keys_and_watch_only_are_rejected() {
+    fn bare_keys_are_rejected() {
         // Bare tprv: full-descriptor demand.
         let key = master(NetworkKind::Test).to_string();
         let err = parse_import(&key, WalletNetwork::Regtest).unwrap_err();
         assert_eq!(err.code, "bare_key");
         assert!(err.message.contains("full descriptor"), "{err}");
 
-        // Bare xpub / tpub: watch-only-not-yet.
+        // Bare xpub / tpub: the script type is unknown — ask for a
+        // descriptor (or a zpub).
         let secp = Secp256k1::new();
         let tpub = bitcoin::bip32::Xpub::from_priv(&secp, &master(NetworkKind::Test));
         let err = parse_import(&tpub.to_string(), WalletNetwork::Regtest).unwrap_err();
-        assert_eq!(err.code, "watch_only");
+        assert_eq!(err.code, "bare_xpub");
+        assert!(err.message.contains("zpub"), "{err}");
+    }
+
+    /// Account-level PUBLIC descriptor: the xpub of [`account_desc`]'s key.
+    fn account_pub_desc(network: NetworkKind, func: &str, branch: u32) -> String {
+        let secp = Secp256k1::new();
+        let master = master(network);
+        let path: Vec<ChildNumber> = [84u32, 1, 0]
+            .iter()
+            .map(|&i| ChildNumber::from_hardened_idx(i).unwrap())
+            .collect();
+        let account = master.derive_priv(&secp, &path).unwrap();
+        let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+        let fp = master.fingerprint(&secp);
+        format!("{func}([{fp}/84'/1'/0']{xpub}/{branch}/*)")
+    }
+
+    #[test]
+    fn public_descriptors_import_watch_only() {
+        for (func, kind) in [
+            ("wpkh", DescriptorKindCfg::Bip84),
+            ("tr", DescriptorKindCfg::Bip86),
+        ] {
+            let external = account_pub_desc(NetworkKind::Test, func, 0);
+            let parsed = parse_import(&external, WalletNetwork::Regtest).unwrap();
+            assert!(parsed.watch_only, "{func}");
+            assert_eq!(parsed.kind, kind);
+            assert_eq!(parsed.coin_type, Some(1));
+            assert_eq!(
+                parsed.internal.as_deref(),
+                Some(account_pub_desc(NetworkKind::Test, func, 1).as_str())
+            );
+        }
+
+        // Private imports stay spendable.
+        assert!(
+            !parse_import(&tprv_wpkh(0), WalletNetwork::Regtest)
+                .unwrap()
+                .watch_only
+        );
+
+        // tpub on mainnet is the wrong network, like tprv.
+        let err = parse_import(
+            &account_pub_desc(NetworkKind::Test, "wpkh", 0),
+            WalletNetwork::Mainnet,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, "wrong_network");
+        assert!(err.message.contains("tpub"), "{err}");
+
+        // A public side next to a private side never pairs.
+        let err = parse_import(
+            &format!(
+                "{} {}",
+                tprv_wpkh(0),
+                account_pub_desc(NetworkKind::Test, "wpkh", 1)
+            ),
+            WalletNetwork::Regtest,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, "pair_mismatch");
+
+        // An unranged public key has no watch-only form.
+        let fixed = account_pub_desc(NetworkKind::Test, "wpkh", 0).replacen("/0/*", "/0/5", 1);
+        let err = parse_import(&fixed, WalletNetwork::Regtest).unwrap_err();
+        assert_eq!(err.code, "not_ranged");
+    }
+
+    #[test]
+    fn bare_zpub_imports_as_bip84_watch_only_pair() {
+        let secp = Secp256k1::new();
+        let account = master(NetworkKind::Test)
+            .derive_priv(
+                &secp,
+                &[84u32, 1, 0]
+                    .iter()
+                    .map(|&i| ChildNumber::from_hardened_idx(i).unwrap())
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        let tpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+        let vpub = slip132(&tpub, SLIP132_VPUB);
+        assert!(vpub.starts_with("vpub"), "{vpub}");
+        let fp = master(NetworkKind::Test).fingerprint(&secp);
+        let with_origin = format!("[{fp}/84h/1h/0h]{vpub}");
+
+        let parsed = parse_import(&with_origin, WalletNetwork::Regtest).unwrap();
+        assert!(parsed.watch_only);
+        assert!(parsed.inferred_internal);
+        assert_eq!(parsed.kind, DescriptorKindCfg::Bip84);
+        // The same pair as the account's full public descriptors.
+        assert_eq!(
+            parsed.external,
+            account_pub_desc(NetworkKind::Test, "wpkh", 0)
+        );
+        assert_eq!(
+            parsed.internal,
+            Some(account_pub_desc(NetworkKind::Test, "wpkh", 1))
+        );
+
+        // Without its origin a signer could not find its key: refused.
+        let err = parse_import(&vpub, WalletNetwork::Regtest).unwrap_err();
+        assert_eq!(err.code, "bare_xpub");
+        assert!(err.message.contains("fingerprint"), "{err}");
+        // An origin that does not reach the key's depth is refused.
+        let err =
+            parse_import(&format!("[{fp}/84'/1']{vpub}"), WalletNetwork::Regtest).unwrap_err();
+        assert_eq!(err.code, "parse");
+
+        // A vpub is a test-network key: refused on mainnet.
+        let err = parse_import(&with_origin, WalletNetwork::Mainnet).unwrap_err();
+        assert_eq!(err.code, "wrong_network");
+
+        // A zpub next to a second descriptor is refused.
+        let err = parse_import(
+            &format!("{with_origin} {}", tprv_wpkh(1)),
+            WalletNetwork::Regtest,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, "bare_xpub");
+    }
+
+    /// BIP-48 P2WSH account key `[fp/48'/1'/0'/2']` of test master
+    /// `seed_byte` — private (`tprv`) or public (`tpub`).
+    fn cosigner_key(seed_byte: u8, private: bool) -> String {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(NetworkKind::Test, &[seed_byte; 32]).unwrap();
+        let path: Vec<ChildNumber> = [48u32, 1, 0, 2]
+            .iter()
+            .map(|&i| ChildNumber::from_hardened_idx(i).unwrap())
+            .collect();
+        let account = master.derive_priv(&secp, &path).unwrap();
+        let fp = master.fingerprint(&secp);
+        if private {
+            format!("[{fp}/48'/1'/0'/2']{account}")
+        } else {
+            let xpub = bitcoin::bip32::Xpub::from_priv(&secp, &account);
+            format!("[{fp}/48'/1'/0'/2']{xpub}")
+        }
+    }
+
+    #[test]
+    fn sortedmulti_imports_as_multisig_cosigner_or_watch_only() {
+        // Our secret key next to two cosigner xpubs: a signing cosigner.
+        let keys = vec![
+            cosigner_key(1, true),
+            cosigner_key(2, false),
+            cosigner_key(3, false),
+        ];
+        let (external, internal) = multisig_descriptors(2, &keys).unwrap();
+        let parsed = parse_import(&external, WalletNetwork::Regtest).unwrap();
+        assert_eq!(parsed.kind, DescriptorKindCfg::Multisig);
+        assert_eq!(
+            parsed.multisig,
+            Some(MultisigShape {
+                threshold: 2,
+                cosigners: 3
+            })
+        );
+        assert!(!parsed.watch_only);
+        assert!(parsed.inferred_internal, "every cosigner branch swapped");
+        assert_eq!(parsed.internal.as_deref(), Some(internal.as_str()));
+        assert_eq!(parsed.coin_type, Some(1));
+
+        // The <0;1> multipath form carries both branches on every key.
+        let multipath = external.replace("/0/*", "/<0;1>/*");
+        let parsed = parse_import(&multipath, WalletNetwork::Regtest).unwrap();
+        assert!(parsed.from_multipath);
+        assert_eq!(parsed.internal.as_deref(), Some(internal.as_str()));
+
+        // All-public: a watch-only multisig.
+        let public: Vec<String> = (1..=3).map(|b| cosigner_key(b, false)).collect();
+        let (external, _) = multisig_descriptors(2, &public).unwrap();
+        let parsed = parse_import(&external, WalletNetwork::Regtest).unwrap();
+        assert!(parsed.watch_only);
+        assert_eq!(parsed.kind, DescriptorKindCfg::Multisig);
+
+        // tpub cosigners on mainnet are the wrong network.
+        let err = parse_import(&external, WalletNetwork::Mainnet).unwrap_err();
+        assert_eq!(err.code, "wrong_network");
+
+        // Mixed branches cannot infer a sibling.
+        let mixed = external.replacen("/0/*", "/1/*", 1);
+        let err = parse_import(&mixed, WalletNetwork::Regtest).unwrap_err();
+        assert_eq!(err.code, "needs_internal");
+    }
+
+    #[test]
+    fn multisig_shape_is_validated_before_parsing() {
+        let keys: Vec<String> = (1..=3).map(|b| cosigner_key(b, false)).collect();
+        for (threshold, keys) in [
+            (0, keys.clone()),
+            (4, keys.clone()),
+            (1, keys[..1].to_vec()),
+            (2, vec![keys[0].clone(), keys[1].clone(), keys[0].clone()]),
+            (2, vec![format!("{}/0/*", keys[0]), keys[1].clone()]),
+        ] {
+            let err = multisig_descriptors(threshold, &keys).unwrap_err();
+            assert_eq!(err.code, "multisig_shape", "{err}");
+        }
+    }
+
+    #[test]
+    fn multisig_backup_lists_every_cosigner_publicly() {
+        let keys = vec![
+            cosigner_key(1, true),
+            cosigner_key(2, false),
+            cosigner_key(3, false),
+        ];
+        let (external, internal) = multisig_descriptors(2, &keys).unwrap();
+        let backup = multisig_backup("treasury", &external, &internal).unwrap();
+        assert_eq!(backup.threshold, 2);
+        assert_eq!(backup.cosigners.len(), 3);
+        assert!(backup
+            .cosigners
+            .iter()
+            .all(|c| c.derivation_path == "m/48'/1'/0'/2'" && c.xpub.starts_with("tpub")));
+        assert!(!backup.external.contains("tprv"), "public only");
+        assert!(backup.external.contains('#'), "checksummed");
+        assert!(backup.text.contains("Policy: 2 of 3"), "{}", backup.text);
+        assert!(backup.text.contains("Format: P2WSH"));
+
+        // A single-key wallet has no multisig backup.
+        assert!(multisig_backup("x", &tprv_wpkh(0), &tprv_wpkh(1)).is_err());
+    }
+
+    #[test]
+    fn descriptor_export_reserializes_keys_for_the_network() {
+        // A seed wallet's pair: xprv-serialized whatever the network.
+        let external = account_desc(NetworkKind::Main, "wpkh", 84, 1, 0);
+        let internal = account_desc(NetworkKind::Main, "wpkh", 84, 1, 1);
+        let export =
+            descriptor_export(WalletNetwork::Regtest, &external, Some(&internal), false).unwrap();
+        assert!(export.external.starts_with("wpkh(["), "{}", export.external);
+        assert!(export.external.contains("tpub") && !export.external.contains("xpub"));
+        let (body, checksum) = export.external.rsplit_once('#').unwrap();
+        assert_eq!(
+            bdk_wallet::miniscript::descriptor::checksum::desc_checksum(body).unwrap(),
+            checksum
+        );
+        assert!(export.internal.as_deref().unwrap().contains("/1/*)#"));
+        assert!(export.external_private.is_none() && export.internal_private.is_none());
+
+        let account = export.account.unwrap();
+        let fp = master(NetworkKind::Main).fingerprint(&Secp256k1::new());
+        assert_eq!(account.derivation_path, "m/84'/1'/0'");
+        assert_eq!(account.key_origin, format!("[{fp}/84'/1'/0']"));
+        assert!(account.xpub.starts_with("tpub"));
+        let vpub = account.slip132.unwrap();
+        assert!(vpub.starts_with("vpub"), "{vpub}");
+        assert_eq!(zpub_to_xpub(&vpub).unwrap().to_string(), account.xpub);
+
+        let core: serde_json::Value = serde_json::from_str(&export.core_import).unwrap();
+        assert_eq!(core[0]["desc"], export.external.as_str());
+        assert_eq!(core[0]["internal"], false);
+        assert_eq!(core[1]["internal"], true);
+        assert_eq!(core[1]["active"], true);
+        assert_eq!(core[1]["range"][1], CORE_IMPORT_RANGE_END);
+    }
 
-        // Public-only descriptor: watch-only-not-yet.
-        let err = parse_import(&format!("wpkh({tpub}/0/*)"), WalletNetwork::Regtest).unwrap_err();
-        assert_eq!(err.code, "watch_only");
-        assert!(err.message.contains("Watch-only"), "{err}");
+    #[test]
+    fn descriptor_export_private_forms_feed_the_core_import() {
+        let export = descriptor_export(
+            WalletNetwork::Mainnet,
+            &account_desc(NetworkKind::Main, "tr", 86, 0, 0),
+            Some(&account_desc(NetworkKind::Main, "tr", 86, 0, 1)),
+            true,
+        )
+        .unwrap();
+        let private = export.external_private.as_deref().unwrap();
+        assert!(
+            private.contains("xprv") && private.contains('#'),
+            "{private}"
+        );
+        assert!(
+            export.account.unwrap().slip132.is_none(),
+            "SLIP-132 is BIP-84 only"
+        );
+        let core: serde_json::Value = serde_json::from_str(&export.core_import).unwrap();
+        assert_eq!(core[0]["desc"], private);
+
+        // Multisig: no single account key.
+        let keys = vec![cosigner_key(1, true), cosigner_key(2, false)];
+        let (external, internal) = multisig_descriptors(2, &keys).unwrap();
+        let export =
+            descriptor_export(WalletNetwork::Regtest, &external, Some(&internal), false).unwrap();
+        assert!(export.account.is_none());
+        assert!(!export.external.contains("tprv"), "public only");
     }
 
     #[test]
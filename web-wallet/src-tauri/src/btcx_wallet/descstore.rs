@@ -21,6 +21,14 @@
 //!   obfuscation wrap is the same bar every Linux seed already has.)
 //!
 //! The plaintext payload is the JSON [`DescriptorPayload`].
+//!
+//! ## Watch-only wallets (`descriptor.public`, one line)
+//!
+//! A watch-only wallet has NO key material: its PUBLIC descriptor pair is
+//! stored in `descriptor.public` instead, as `PHXWATCHv1-obfs:<nonce>:<ct>`
+//! — the same obfuscation wrap (never plaintext ASCII, since the xpub still
+//! links every address of the wallet) and never a passphrase: there is
+//! nothing to lock.
 
 use chacha20poly1305::aead::Aead;
 use chacha20poly1305::{ChaCha20Poly1305, KeyInit};
@@ -38,6 +46,10 @@ pub const DESCRIPTOR_FILE: &str = "descriptor.secret";
 /// seed's group must apply (see [`read_bip39_passphrase`]).
 pub const PASSPHRASE_FILE: &str = "seed.passphrase";
 
+/// File name of a WATCH-ONLY wallet's public descriptor pair — in place of
+/// `descriptor.secret`; the two never coexist in one wallet dir.
+pub const PUBLIC_DESCRIPTOR_FILE: &str = "descriptor.public";
+
 const DESC_MAGIC: &str = "PHXDESCv1";
 const DESC_MAGIC_OBFS: &str = "PHXDESCv1-obfs";
 /// Magic of the obfuscation-wrapped BIP39 passphrase sibling file. The wrap
@@ -46,6 +58,9 @@ const DESC_MAGIC_OBFS: &str = "PHXDESCv1-obfs";
 /// must reproduce the exact same keys, including one-shot compartment syncs
 /// that never hold a user passphrase.
 const PASS_MAGIC_OBFS: &str = "PHXPASSv1-obfs";
+/// Magic of the obfuscation-wrapped public descriptor file of a watch-only
+/// wallet.
+const WATCH_MAGIC_OBFS: &str = "PHXWATCHv1-obfs";
 /// scrypt cost, identical to seedstore: N=2^15, r=8, p=1 — interactive.
 const SCRYPT_LOG_N: u8 = 15;
 /// The no-passphrase obfuscation key. NOT a secret — it ships in the
@@ -189,6 +204,24 @@ impl DescStore {
         Ok(())
     }
 
+    /// Re-wrap the stored payload — passphrase-encrypted under a non-empty
+    /// `passphrase`, obfuscation-wrapped otherwise — in ONE atomic write:
+    /// the file is the old wrap or the new one, never neither. The store
+    /// must be readable (unlocked) first; the new passphrase is held.
+    pub fn rewrap(&mut self, passphrase: Option<&str>) -> Result<(), String> {
+        let payload = self.payload()?;
+        let json =
+            serde_json::to_string(&payload).map_err(|e| format!("serializing descriptors: {e}"))?;
+        let pass = passphrase.filter(|p| !p.is_empty());
+        let contents = match pass {
+            Some(pass) => encrypt_passphrase(&json, pass)?,
+            None => encrypt_obfs(&json)?,
+        };
+        write_atomic(&self.file_path(), &contents)?;
+        self.passphrase = pass.map(str::to_string);
+        Ok(())
+    }
+
     /// Read and unwrap the stored descriptor payload.
     pub fn payload(&self) -> Result<DescriptorPayload, String> {
         let path = self.file_path();
@@ -230,42 +263,67 @@ fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
 }
 
 fn encrypt_passphrase(plaintext: &str, passphrase: &str) -> Result<String, String> {
+    seal_with_passphrase(DESC_MAGIC, plaintext, passphrase)
+}
+
+fn decrypt_passphrase(line: &str, passphrase: &str) -> Result<String, String> {
+    open_with_passphrase(DESC_MAGIC, line, passphrase)
+}
+
+/// Passphrase-seal `plaintext` as one `<magic>:<salt>:<nonce>:<ct>` line —
+/// the `PHXDESCv1` scheme (scrypt N=2^15, r=8, p=1 + ChaCha20-Poly1305)
+/// under a caller-chosen magic, shared with the backup bundle.
+pub(crate) fn seal_with_passphrase(
+    magic: &str,
+    plaintext: &str,
+    passphrase: &str,
+) -> Result<String, String> {
     let salt = random_bytes::<16>();
     let nonce = random_bytes::<12>();
     let key = derive_key(passphrase, &salt)?;
     let cipher = ChaCha20Poly1305::new((&key).into());
     let ct = cipher
         .encrypt((&nonce).into(), plaintext.as_bytes())
-        .map_err(|_| "descriptor encryption failed".to_string())?;
+        .map_err(|_| "encryption failed".to_string())?;
     Ok(format!(
-        "{DESC_MAGIC}:{}:{}:{}\n",
+        "{magic}:{}:{}:{}\n",
         hex::encode(salt),
         hex::encode(nonce),
         hex::encode(ct)
     ))
 }
 
-fn decrypt_passphrase(line: &str, passphrase: &str) -> Result<String, String> {
-    let mut parts = line.split(':');
-    let magic = parts.next().unwrap_or_default();
-    if magic != DESC_MAGIC {
-        return Err(format!("unknown descriptor store format {magic:?}"));
+/// Open a line sealed by [`seal_with_passphrase`] under `magic`.
+pub(crate) fn open_with_passphrase(
+    magic: &str,
+    line: &str,
+    passphrase: &str,
+) -> Result<String, String> {
+    let mut parts = line.trim().split(':');
+    let found = parts.next().unwrap_or_default();
+    if found != magic {
+        return Err(format!("unknown file format {found:?} (expected {magic})"));
     }
-    let salt = parts.next().ok_or("malformed descriptor store")?;
-    let nonce = parts.next().ok_or("malformed descriptor store")?;
-    let ct = parts.next().ok_or("malformed descriptor store")?;
+    let salt = parts.next().ok_or("malformed sealed file")?;
+    let nonce = parts.next().ok_or("malformed sealed file")?;
+    let ct = parts.next().ok_or("malformed sealed file")?;
     let key = derive_key(passphrase, &hex::decode(salt).map_err(|e| format!("{e}"))?)?;
     decrypt_raw(nonce, ct, &key).map_err(|_| "wrong passphrase?".to_string())
 }
 
 fn encrypt_obfs(plaintext: &str) -> Result<String, String> {
+    wrap_obfs(DESC_MAGIC_OBFS, plaintext)
+}
+
+/// Obfuscation-wrap `plaintext` under `magic` (see [`OBFUSCATION_KEY`]).
+fn wrap_obfs(magic: &str, plaintext: &str) -> Result<String, String> {
     let nonce = random_bytes::<12>();
     let cipher = ChaCha20Poly1305::new((&OBFUSCATION_KEY).into());
     let ct = cipher
         .encrypt((&nonce).into(), plaintext.as_bytes())
         .map_err(|_| "descriptor wrap failed".to_string())?;
     Ok(format!(
-        "{DESC_MAGIC_OBFS}:{}:{}\n",
+        "{magic}:{}:{}\n",
         hex::encode(nonce),
         hex::encode(ct)
     ))
@@ -287,8 +345,12 @@ fn decrypt_raw(nonce_hex: &str, ct_hex: &str, key: &[u8; 32]) -> Result<String,
 
 /// Atomic write (temp file + fsync + rename), the seedstore pattern: the
 /// file is only ever observed fully written or not at all.
-fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
-    let tmp = path.with_extension("secret.tmp");
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let tmp = path.with_extension(format!("{ext}.tmp"));
     {
         use std::io::Write;
         let mut f =
@@ -301,6 +363,48 @@ fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
     std::fs::rename(&tmp, path).map_err(|e| format!("installing {}: {e}", path.display()))
 }
 
+// ============================================================================
+// Watch-only public descriptor store
+// ============================================================================
+
+/// Persist a watch-only wallet's PUBLIC descriptor payload into `dir`
+/// (obfuscation-wrapped, no passphrase). Refuses to overwrite an existing
+/// file, like [`DescStore::import`].
+pub fn write_public_descriptors(dir: &Path, payload: &DescriptorPayload) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("creating {}: {e}", dir.display()))?;
+    let path = dir.join(PUBLIC_DESCRIPTOR_FILE);
+    if path.exists() {
+        return Err(format!(
+            "{} already exists — refusing to overwrite a descriptor store",
+            path.display()
+        ));
+    }
+    let json =
+        serde_json::to_string(payload).map_err(|e| format!("serializing descriptors: {e}"))?;
+    write_atomic(&path, &wrap_obfs(WATCH_MAGIC_OBFS, &json)?)
+}
+
+/// Read + unwrap a watch-only wallet's public descriptor payload — no
+/// unlock ever needed.
+pub fn read_public_descriptors(dir: &Path) -> Result<DescriptorPayload, String> {
+    let path = dir.join(PUBLIC_DESCRIPTOR_FILE);
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|_| format!("no watch-only descriptors at {}", path.display()))?;
+    let rest = contents
+        .trim()
+        .strip_prefix(&format!("{WATCH_MAGIC_OBFS}:"))
+        .ok_or("unknown watch-only descriptor format")?;
+    let mut parts = rest.split(':');
+    let nonce = parts
+        .next()
+        .ok_or("malformed watch-only descriptor store")?;
+    let ct = parts
+        .next()
+        .ok_or("malformed watch-only descriptor store")?;
+    let json = decrypt_v1(nonce, ct, &OBFUSCATION_KEY)?;
+    serde_json::from_str(&json).map_err(|e| format!("parsing descriptor store: {e}"))
+}
+
 // ============================================================================
 // BIP39 passphrase (the "25th word") sibling store
 // ============================================================================
@@ -464,6 +568,36 @@ mod tests {
         assert_eq!(reopened.payload().unwrap(), payload());
     }
 
+    #[test]
+    fn rewrap_changes_adds_and_removes_the_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DescStore::open(dir.path()).unwrap();
+        store.import(&payload(), None).unwrap();
+
+        // Obfuscation → passphrase.
+        store.rewrap(Some("first")).unwrap();
+        let mut reopened = DescStore::open(dir.path()).unwrap();
+        assert!(reopened.status().locked);
+        reopened.unlock("first").unwrap();
+
+        // Passphrase → another passphrase; the old one stops working.
+        reopened.rewrap(Some("second")).unwrap();
+        let mut again = DescStore::open(dir.path()).unwrap();
+        assert!(again.unlock("first").is_err());
+        again.unlock("second").unwrap();
+        assert_eq!(again.payload().unwrap(), payload());
+
+        // Passphrase → obfuscation: readable with no unlock again.
+        again.rewrap(None).unwrap();
+        let plain = DescStore::open(dir.path()).unwrap();
+        assert!(!plain.status().encrypted);
+        assert_eq!(plain.payload().unwrap(), payload());
+
+        // A locked store cannot be re-wrapped.
+        store.rewrap(Some("x")).unwrap();
+        assert!(DescStore::open(dir.path()).unwrap().rewrap(None).is_err());
+    }
+
     #[test]
     fn empty_passphrase_is_treated_as_none() {
         let dir = tempfile::tempdir().unwrap();
@@ -474,6 +608,26 @@ mod tests {
         assert!(!store.status().encrypted);
     }
 
+    #[test]
+    fn public_descriptors_roundtrip_without_unlock() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_public_descriptors(dir.path()).is_err());
+
+        let public = DescriptorPayload::new(
+            "wpkh(tpubEXAMPLE/0/*)".into(),
+            Some("wpkh(tpubEXAMPLE/1/*)".into()),
+        );
+        write_public_descriptors(dir.path(), &public).unwrap();
+        let on_disk = std::fs::read_to_string(dir.path().join(PUBLIC_DESCRIPTOR_FILE)).unwrap();
+        assert!(on_disk.starts_with(WATCH_MAGIC_OBFS), "{on_disk}");
+        assert!(!on_disk.contains("tpubEXAMPLE"), "never plaintext ASCII");
+        assert!(!dir.path().join(DESCRIPTOR_FILE).exists());
+        assert_eq!(read_public_descriptors(dir.path()).unwrap(), public);
+
+        // Never overwrite.
+        assert!(write_public_descriptors(dir.path(), &public).is_err());
+    }
+
     #[test]
     fn bip39_passphrase_roundtrips_and_is_never_plaintext() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,477 @@
+//! Electrum peer discovery and automatic server list maintenance.
+//!
+//! Electrum servers announce the peers they know through
+//! `server.peers.subscribe`. A discovery pass asks every configured and
+//! previously discovered server for its peers — the built-in bootstrap
+//! servers ([`super::config::bootstrap_servers`]) only when none of those
+//! answers — probes each candidate the way `btcx_electrum_probe` does —
+//! fresh connection, genesis check, timed tip fetch — and keeps the best
+//! healthy ones in `BtcxWalletConfig::discovered_servers`. Passes run in
+//! the background from startup ([`spawn_background`]), wallet open or not,
+//! so a fresh install whose configured server is dead still learns of live
+//! ones.
+//!
+//! Discovery dials servers the user never chose, so it needs their opt-in
+//! once they configured servers of their own: it is then off unless turned
+//! on (see `BtcxWalletConfig::discovery_mode`).
+//!
+//! Discovered servers are LISTED, not used: a peer some server announced
+//! is not one the user chose. They carry wallet traffic — home election,
+//! failover views, broadcasts — only once the user opts in
+//! (`discovered_failover`, see `BtcxWalletConfig::failover_servers`), and
+//! even then rank BEHIND the configured list. In `auto` mode the user has
+//! opted in by other means: the best are appended to the configured list.
+//! Peers on loopback, private or link-local addresses are never taken (a
+//! remote server must not point the wallet into the local network), and
+//! `ssl://` ones are CA-validated or pinned on first use (see `tlspin`).
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use electrum_client::{ElectrumApi, Param};
+use params_btcx::params::ChainParams;
+use serde::Serialize;
+
+use super::config::{bootstrap_servers, DiscoveredServer, ServerDiscoveryMode};
+use super::consensus::MAX_LAG;
+use super::state::{BtcxWalletState, SharedBtcxWalletState};
+
+/// Discovered servers kept (and offered as failover views) per network.
+pub const DISCOVERY_VIEWS: usize = 3;
+/// In `auto` mode, discovered servers are added until the configured list
+/// holds this many entries — user entries are never removed.
+pub const AUTO_LIST_MAX: usize = 6;
+/// Candidates probed per pass.
+const MAX_CANDIDATES: usize = 16;
+/// Electrum's default ports when a peer announces `t` / `s` without one.
+const DEFAULT_TCP_PORT: u16 = 50001;
+const DEFAULT_SSL_PORT: u16 = 50002;
+/// Background cadence: first pass a minute after startup, then hourly.
+const FIRST_PASS_AFTER: Duration = Duration::from_secs(60);
+const PASS_EVERY: Duration = Duration::from_secs(3600);
+
+/// One probed candidate of a discovery pass.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryCandidate {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// Kept as a discovered server.
+    pub healthy: bool,
+    /// Why the candidate was not kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Outcome of a discovery pass (`btcx_electrum_discover`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryReport {
+    pub network: String,
+    /// Unix seconds.
+    pub checked_at: u64,
+    /// Servers asked for peers that answered.
+    pub sources: usize,
+    pub candidates: Vec<DiscoveryCandidate>,
+    /// The kept discovered servers, best first.
+    pub kept: Vec<DiscoveredServer>,
+    /// Servers appended to the configured list (`auto` mode).
+    pub added: Vec<String>,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Probe one server with a FRESH connection (never the pool): dial, verify
+/// it serves the expected chain (genesis check — catches a server of the
+/// wrong network), and time a tip fetch. Returns `(height, latency_ms)`.
+/// Never pins an `ssl://` certificate.
+pub fn probe(params: &'static ChainParams, url: &str) -> Result<(u64, f64), String> {
+    let dial = super::tlspin::probe_dial_url(url)?;
+    let backend =
+        electrum_btcx::ElectrumBackend::new(params, &dial).map_err(|e| format!("{e:#}"))?;
+    let started = Instant::now();
+    let (height, _) = backend
+        .tip()
+        .map_err(|e| format!("Server unreachable: {e:#}"))?;
+    backend
+        .verify_chain()
+        .map_err(|e| format!("Wrong chain or unusable server: {e:#}"))?;
+    Ok((height, started.elapsed().as_secs_f64() * 1000.0))
+}
+
+/// Whether an announced peer address may be dialed: host names other than
+/// `localhost` are, IP literals only when globally routable — loopback,
+/// private, link-local, unique-local and unspecified addresses are not.
+fn is_public_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.eq_ignore_ascii_case("localhost") || host.to_ascii_lowercase().ends_with(".localhost") {
+        return false;
+    }
+    let ip = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(v6)) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        Ok(ip) => ip,
+        Err(_) => return true,
+    };
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast())
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || first & 0xfe00 == 0xfc00 // unique local fc00::/7
+                || first & 0xffc0 == 0xfe80) // link local fe80::/10
+        }
+    }
+}
+
+/// The server URLs of a `server.peers.subscribe` answer:
+/// `[[ip, hostname, ["v1.4", "s50002", "t50001", …]], …]`. TLS is preferred
+/// when a peer offers both; `.onion` peers only count with `onion` set
+/// (they need the proxy); peers on non-public addresses never do.
+pub fn parse_peers(answer: &serde_json::Value, onion: bool) -> Vec<String> {
+    let Some(peers) = answer.as_array() else {
+        return Vec::new();
+    };
+    let mut urls = Vec::new();
+    for peer in peers {
+        let ip = peer.get(0).and_then(|v| v.as_str()).unwrap_or("");
+        let host = peer
+            .get(1)
+            .and_then(|v| v.as_str())
+            .filter(|h| !h.is_empty())
+            .unwrap_or(ip);
+        if host.is_empty() || (host.ends_with(".onion") && !onion) {
+            continue;
+        }
+        if !is_public_host(host) || (!ip.is_empty() && !is_public_host(ip)) {
+            continue;
+        }
+        let features: Vec<&str> = peer
+            .get(2)
+            .and_then(|v| v.as_array())
+            .map(|f| f.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        let port = |prefix: char, default: u16| {
+            features.iter().find_map(|f| {
+                let rest = f.strip_prefix(prefix)?;
+                if rest.is_empty() {
+                    Some(default)
+                } else {
+                    rest.parse::<u16>().ok()
+                }
+            })
+        };
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        let url = match (port('s', DEFAULT_SSL_PORT), port('t', DEFAULT_TCP_PORT)) {
+            (Some(ssl), _) => format!("ssl://{host}:{ssl}"),
+            (None, Some(tcp)) => format!("tcp://{host}:{tcp}"),
+            (None, None) => continue,
+        };
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// The peers `url` announces.
+fn fetch_peers(url: &str, onion: bool) -> Result<Vec<String>, String> {
+    let client = super::consensus::connect(url)?;
+    let answer = client
+        .raw_call("server.peers.subscribe", Vec::<Param>::new())
+        .map_err(|e| format!("{e}"))?;
+    Ok(parse_peers(&answer, onion))
+}
+
+/// Run a discovery pass: ask `sources` for peers, probe every candidate not
+/// in `known` and rank the healthy ones. Returns the report with `kept`
+/// filled (no state involved).
+pub fn discover(
+    params: &'static ChainParams,
+    network: &str,
+    sources: &[String],
+    known: &[String],
+) -> DiscoveryReport {
+    let onion = matches!(crate::proxy::active(), Ok(Some(_)));
+    let answers: Vec<Result<Vec<String>, String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = sources
+            .iter()
+            .map(|url| scope.spawn(move || fetch_peers(url, onion)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err("discovery thread panicked".into()))
+            })
+            .collect()
+    });
+    let mut answered = 0;
+    let mut seen = BTreeSet::new();
+    let mut candidates: Vec<String> = Vec::new();
+    for (source, answer) in sources.iter().zip(answers) {
+        match answer {
+            Ok(peers) => {
+                answered += 1;
+                for url in peers {
+                    if !known.contains(&url) && seen.insert(url.clone()) {
+                        candidates.push(url);
+                    }
+                }
+            }
+            Err(e) => log::debug!("btcx wallet: discovery: {source} gave no peers: {e}"),
+        }
+    }
+    candidates.truncate(MAX_CANDIDATES);
+
+    let probes: Vec<Result<(u64, f64), String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .iter()
+            .map(|url| scope.spawn(move || probe(params, url)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err("probe thread panicked".into()))
+            })
+            .collect()
+    });
+    let best_height = probes
+        .iter()
+        .filter_map(|p| p.as_ref().ok().map(|(h, _)| *h))
+        .max()
+        .unwrap_or(0);
+    let checked_at = now_secs();
+    let mut lines: Vec<DiscoveryCandidate> = candidates
+        .into_iter()
+        .zip(probes)
+        .map(|(url, probe)| match probe {
+            Ok((height, latency_ms)) => {
+                let lagging = height + MAX_LAG < best_height;
+                DiscoveryCandidate {
+                    url,
+                    height: Some(height),
+                    latency_ms: Some(latency_ms),
+                    healthy: !lagging,
+                    detail: lagging.then(|| format!("{} blocks behind", best_height - height)),
+                }
+            }
+            Err(e) => DiscoveryCandidate {
+                url,
+                height: None,
+                latency_ms: None,
+                healthy: false,
+                detail: Some(e),
+            },
+        })
+        .collect();
+    lines.sort_by(|a, b| {
+        b.healthy.cmp(&a.healthy).then(
+            a.latency_ms
+                .unwrap_or(f64::MAX)
+                .total_cmp(&b.latency_ms.unwrap_or(f64::MAX)),
+        )
+    });
+    let kept = lines
+        .iter()
+        .filter(|c| c.healthy)
+        .take(DISCOVERY_VIEWS)
+        .map(|c| DiscoveredServer {
+            url: c.url.clone(),
+            height: c.height.unwrap_or_default(),
+            latency_ms: c.latency_ms.unwrap_or_default(),
+            checked_at,
+        })
+        .collect();
+    DiscoveryReport {
+        network: network.to_string(),
+        checked_at,
+        sources: answered,
+        candidates: lines,
+        kept,
+        added: Vec::new(),
+    }
+}
+
+/// Run a discovery pass for the active network and store the result: the
+/// kept servers replace the network's discovered list, and in `auto` mode
+/// the best are appended to the configured list up to [`AUTO_LIST_MAX`].
+/// Sources are the configured servers and the previously discovered ones;
+/// the built-in bootstrap list only when none of them answers (so
+/// discovery keeps working when every configured server is dead).
+pub fn refresh(state: &BtcxWalletState) -> Result<DiscoveryReport, String> {
+    let config = state.get_config();
+    let network = config.network;
+    let mode = config.discovery_mode(network);
+    if mode == ServerDiscoveryMode::Off {
+        return Err("Server discovery is off".into());
+    }
+    let configured = config.servers();
+    let sources = config.listed_servers(network);
+    let bootstrap: Vec<String> = bootstrap_servers(network)
+        .iter()
+        .map(|url| url.to_string())
+        .filter(|url| !sources.contains(url))
+        .collect();
+    if sources.is_empty() && bootstrap.is_empty() {
+        return Err(format!(
+            "No Electrum server configured for {} — add one first",
+            network.as_str()
+        ));
+    }
+    let mut report = discover(network.params(), network.as_str(), &sources, &configured);
+    if report.sources == 0 && !bootstrap.is_empty() {
+        // Every server of the list is unreachable: ask the bootstrap ones.
+        report = discover(network.params(), network.as_str(), &bootstrap, &configured);
+    }
+    if report.sources == 0 {
+        // Nobody answered: keep the previous list rather than wipe it.
+        return Ok(report);
+    }
+    // Previously discovered servers still count when they were not
+    // re-announced this pass: re-probe them and merge.
+    let mut kept = report.kept.clone();
+    for previous in config.discovered_for(network) {
+        if kept.len() >= DISCOVERY_VIEWS {
+            break;
+        }
+        if kept.iter().any(|k| k.url == previous.url) || configured.contains(&previous.url) {
+            continue;
+        }
+        if report.candidates.iter().any(|c| c.url == previous.url) {
+            continue; // probed this pass and not kept
+        }
+        if let Ok((height, latency_ms)) = probe(network.params(), &previous.url) {
+            kept.push(DiscoveredServer {
+                url: previous.url,
+                height,
+                latency_ms,
+                checked_at: report.checked_at,
+            });
+        }
+    }
+    report.kept = kept;
+
+    let kept = report.kept.clone();
+    let mut added = Vec::new();
+    state.update_config(|c| {
+        c.discovered_servers
+            .insert(network.as_str().to_string(), kept.clone());
+        if mode == ServerDiscoveryMode::Auto {
+            let list = c
+                .electrum_servers
+                .entry(network.as_str().to_string())
+                .or_default();
+            for found in &kept {
+                if list.len() >= AUTO_LIST_MAX {
+                    break;
+                }
+                if !list.contains(&found.url) {
+                    list.push(found.url.clone());
+                    added.push(found.url.clone());
+                }
+            }
+        }
+    })?;
+    if !added.is_empty() {
+        log::info!(
+            "btcx wallet: discovery added {} to the {} server list",
+            added.join(", "),
+            network.as_str()
+        );
+    }
+    report.added = added;
+    Ok(report)
+}
+
+/// Run discovery passes in the background for the life of the app — a
+/// minute after startup, then hourly — whether a wallet is open or not.
+/// Passes are skipped while discovery is off; failures only log.
+pub fn spawn_background(state: SharedBtcxWalletState) {
+    let spawned = std::thread::Builder::new()
+        .name("btcx-discovery".into())
+        .spawn(move || {
+            std::thread::sleep(FIRST_PASS_AFTER);
+            loop {
+                let config = state.get_config();
+                if config.discovery_mode(config.network) != ServerDiscoveryMode::Off {
+                    match refresh(&state) {
+                        Ok(report) => log::debug!(
+                            "btcx wallet: discovery kept {} of {} candidates",
+                            report.kept.len(),
+                            report.candidates.len()
+                        ),
+                        Err(e) => log::debug!("btcx wallet: discovery skipped: {e}"),
+                    }
+                }
+                std::thread::sleep(PASS_EVERY);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("btcx wallet: could not start peer discovery: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_prefer_tls_and_skip_onions_and_private_addresses() {
+        let answer = serde_json::json!([
+            [
+                "1.2.3.4",
+                "electrum.example.org",
+                ["v1.4", "s50002", "t50001"]
+            ],
+            ["5.6.7.8", "plain.example.org", ["v1.4", "t"]],
+            ["", "abcdefg.onion", ["v1.4", "t50001"]],
+            ["9.9.9.9", "", ["v1.4", "s"]],
+            ["::1", "", ["s51002"]],
+            ["192.168.1.20", "", ["s50002"]],
+            ["10.0.0.5", "internal.example.org", ["t50001"]],
+            ["", "localhost", ["t50001"]],
+            ["169.254.1.1", "", ["t50001"]],
+            ["fe80::1", "", ["s50002"]],
+            ["fd00::7", "", ["s50002"]],
+            ["::ffff:127.0.0.1", "", ["s50002"]],
+            ["2001:db8::1", "", ["s50002"]],
+            ["1.1.1.1", "nothing.example.org", ["v1.4", "p10000"]],
+            "garbage"
+        ]);
+        assert_eq!(
+            parse_peers(&answer, false),
+            vec![
+                "ssl://electrum.example.org:50002",
+                "tcp://plain.example.org:50001",
+                "ssl://9.9.9.9:50002",
+                "ssl://[2001:db8::1]:50002",
+            ]
+        );
+        assert!(parse_peers(&answer, true).contains(&"tcp://abcdefg.onion:50001".to_string()));
+        assert!(parse_peers(&serde_json::json!(null), false).is_empty());
+    }
+}
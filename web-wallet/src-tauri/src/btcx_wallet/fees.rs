@@ -0,0 +1,261 @@
+//! Mempool fee histogram estimation.
+//!
+//! The server's `blockchain.estimatefee` answers from the node's estimator,
+//! which needs a history of confirmed transactions — on a young chain it
+//! often has none, and the send form fell back to the coin's floor. This
+//! second estimator reads the live mempool instead: the home server's
+//! `mempool.get_fee_histogram` lists `[feerate, vsize]` buckets, highest
+//! feerate first, and a transaction confirms within `n` blocks when the
+//! mempool paying more than it fits into `n` blocks. The projection walks
+//! the buckets until the target's block space (block weight limit / 4 per
+//! block) is used up; the bucket it stops in is the feerate to match. A
+//! mempool shallower than the target's block space needs only the floor.
+//! The PoCX block spacing turns each target into an expected wait.
+//!
+//! The projection is a snapshot: it ignores transactions arriving while
+//! waiting, so it is optimistic for long targets on a busy chain — which is
+//! why `auto` mode asks the server's estimator first.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use electrum_btcx::SendFee;
+use electrum_client::{ElectrumApi, Param};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wallet_btcx::BdkWalletBackend;
+
+use super::state::BtcxWalletState;
+
+/// Consensus block weight limit (Bitcoin-PoCX keeps Bitcoin's).
+pub const MAX_BLOCK_WEIGHT: u64 = 4_000_000;
+/// Weight left for the coinbase and the block header.
+const COINBASE_RESERVE_WEIGHT: u64 = 4_000;
+/// PoCX target block spacing.
+pub const BLOCK_TIME_SECS: u64 = 120;
+/// Confirmation targets the estimates report per source.
+pub const TARGETS: [u16; 4] = [1, 3, 6, 12];
+/// A fetched histogram is reused this long (the estimates and the send
+/// that follows them see the same mempool).
+const HISTOGRAM_TTL: Duration = Duration::from_secs(30);
+
+/// `[feerate sat/vB, vsize]` buckets, highest feerate first.
+pub type Histogram = Vec<(f64, u64)>;
+
+/// The last histogram per home server: (url, fetched at, buckets).
+static CACHE: Mutex<Option<(String, Instant, Histogram)>> = Mutex::new(None);
+
+/// Which estimator answers a confirmation target.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeEstimateMode {
+    /// The server's estimator; the mempool projection where it has no data.
+    #[default]
+    Auto,
+    /// The server's estimator only (the pre-histogram behavior).
+    Server,
+    /// The mempool projection only.
+    Mempool,
+}
+
+/// Where an estimate came from.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeSource {
+    /// The server's `blockchain.estimatefee`.
+    Server,
+    /// Projected from the mempool histogram.
+    Mempool,
+    /// The mempool leaves room at the coin's floor feerate.
+    Floor,
+}
+
+/// Block space one block offers mempool transactions, in vbytes.
+fn block_vsize() -> u64 {
+    (MAX_BLOCK_WEIGHT - COINBASE_RESERVE_WEIGHT) / 4
+}
+
+/// Expected wait for a `blocks` confirmation target.
+pub fn eta_secs(blocks: u16) -> u64 {
+    u64::from(blocks) * BLOCK_TIME_SECS
+}
+
+/// Parse a `mempool.get_fee_histogram` answer. Malformed buckets are
+/// skipped; the result is sorted highest feerate first whatever the
+/// server's order.
+pub fn parse_histogram(answer: &Value) -> Histogram {
+    let mut buckets: Histogram = answer
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|bucket| {
+            let pair = bucket.as_array()?;
+            let feerate = pair.first()?.as_f64()?;
+            let vsize = pair.get(1)?.as_f64()?;
+            (feerate.is_finite() && feerate >= 0.0 && vsize >= 0.0)
+                .then_some((feerate, vsize as u64))
+        })
+        .collect();
+    buckets.sort_by(|a, b| b.0.total_cmp(&a.0));
+    buckets
+}
+
+/// The feerate (sat/kvB) that confirms within `blocks` blocks against
+/// `histogram`, never below `floor_kvb`.
+pub fn project(histogram: &[(f64, u64)], blocks: u16, floor_kvb: u64) -> (u64, FeeSource) {
+    let capacity = u64::from(blocks.max(1)) * block_vsize();
+    let mut ahead = 0u64;
+    for &(feerate, vsize) in histogram {
+        ahead = ahead.saturating_add(vsize);
+        if ahead >= capacity {
+            let kvb = (feerate * 1000.0).ceil() as u64;
+            return if kvb > floor_kvb {
+                (kvb, FeeSource::Mempool)
+            } else {
+                (floor_kvb, FeeSource::Floor)
+            };
+        }
+    }
+    (floor_kvb, FeeSource::Floor)
+}
+
+/// The home server's fee histogram, cached for [`HISTOGRAM_TTL`].
+pub fn histogram(state: &BtcxWalletState) -> Result<Histogram, String> {
+    let (home, _) = state
+        .runtime_urls()
+        .ok_or("The nodeless wallet is not open")?;
+    if let Ok(cache) = CACHE.lock() {
+        if let Some((url, at, buckets)) = cache.as_ref() {
+            if *url == home && at.elapsed() < HISTOGRAM_TTL {
+                return Ok(buckets.clone());
+            }
+        }
+    }
+    let client = super::consensus::connect(&home)?;
+    let answer = client
+        .raw_call("mempool.get_fee_histogram", Vec::<Param>::new())
+        .map_err(|e| format!("Fee histogram from {home}: {e}"))?;
+    let buckets = parse_histogram(&answer);
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = Some((home, Instant::now(), buckets.clone()));
+    }
+    Ok(buckets)
+}
+
+/// Answers confirmation targets in one mode, fetching the histogram at most
+/// once (a failed fetch is not retried per target).
+pub struct FeeEstimator<'a> {
+    state: &'a BtcxWalletState,
+    backend: &'a BdkWalletBackend,
+    mode: FeeEstimateMode,
+    histogram: Option<Result<Histogram, String>>,
+}
+
+impl<'a> FeeEstimator<'a> {
+    pub fn new(
+        state: &'a BtcxWalletState,
+        backend: &'a BdkWalletBackend,
+        mode: FeeEstimateMode,
+    ) -> Self {
+        Self {
+            state,
+            backend,
+            mode,
+            histogram: None,
+        }
+    }
+
+    /// The coin's floor feerate, sat/kvB.
+    pub fn floor_kvb(&self) -> u64 {
+        self.backend.params().min_feerate_sat_kvb
+    }
+
+    /// The feerate (sat/kvB) and its source for a `blocks` target; `None`
+    /// when the mode has no answer. In `auto` mode an unreachable histogram
+    /// is `None` too; in `mempool` mode it is the error.
+    pub fn estimate(&mut self, blocks: u16) -> Result<Option<(u64, FeeSource)>, String> {
+        if self.mode != FeeEstimateMode::Mempool {
+            let server = self
+                .backend
+                .chain()
+                .fee_estimate_kvb(blocks)
+                .map_err(|e| format!("{e:#}"))?;
+            if let Some(kvb) = server {
+                return Ok(Some((kvb, FeeSource::Server)));
+            }
+            if self.mode == FeeEstimateMode::Server {
+                return Ok(None);
+            }
+        }
+        let floor_kvb = self.floor_kvb();
+        let state = self.state;
+        match self.histogram.get_or_insert_with(|| histogram(state)) {
+            Ok(buckets) => Ok(Some(project(buckets, blocks, floor_kvb))),
+            Err(e) if self.mode == FeeEstimateMode::Mempool => Err(e.clone()),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Resolve a send's fee under `mode`: explicit rates, `server` mode and
+/// targets the server's estimator answers pass through unchanged (the chain
+/// resolves them as before); any other target becomes the projected rate.
+pub fn resolve_send_fee(
+    state: &BtcxWalletState,
+    fee: SendFee,
+    mode: FeeEstimateMode,
+) -> Result<SendFee, String> {
+    let SendFee::Target(blocks) = fee else {
+        return Ok(fee);
+    };
+    if mode == FeeEstimateMode::Server {
+        return Ok(fee);
+    }
+    let backend = state.backend()?;
+    let mut estimator = FeeEstimator::new(state, &backend, mode);
+    Ok(match estimator.estimate(blocks)? {
+        Some((kvb, FeeSource::Mempool | FeeSource::Floor)) => SendFee::RatePerKvb(kvb),
+        Some((_, FeeSource::Server)) | None => fee,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_projection_walks_block_space() {
+        let answer = serde_json::json!([
+            [2.0, 900_000],
+            [50.0, 500_000],
+            [10.0, 700_000],
+            [1.5, 2_000_000],
+            ["bad", 1],
+            [3.0]
+        ]);
+        let buckets = parse_histogram(&answer);
+        assert_eq!(
+            buckets,
+            vec![
+                (50.0, 500_000),
+                (10.0, 700_000),
+                (2.0, 900_000),
+                (1.5, 2_000_000)
+            ]
+        );
+
+        // One block (999k vB) fills inside the 10 sat/vB bucket.
+        assert_eq!(project(&buckets, 1, 1_000), (10_000, FeeSource::Mempool));
+        // Two blocks reach the 2 sat/vB bucket; three the 1.5 one.
+        assert_eq!(project(&buckets, 2, 1_000), (2_000, FeeSource::Mempool));
+        assert_eq!(project(&buckets, 3, 1_000), (1_500, FeeSource::Mempool));
+        // Deeper targets than the mempool need only the floor.
+        assert_eq!(project(&buckets, 12, 1_000), (1_000, FeeSource::Floor));
+        // A bucket below the floor is the floor.
+        assert_eq!(project(&buckets, 3, 1_800), (1_800, FeeSource::Floor));
+        // An empty mempool: floor for every target.
+        assert_eq!(project(&[], 1, 1_000), (1_000, FeeSource::Floor));
+
+        assert_eq!(eta_secs(6), 720);
+    }
+}
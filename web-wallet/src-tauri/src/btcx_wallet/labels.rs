@@ -0,0 +1,473 @@
+//! BIP-329 wallet labels.
+//!
+//! Each named wallet keeps its user notes in `wallet/labels.jsonl`, next to
+//! its bdk store, so a rename/delete/trash move carries them along with the
+//! wallet dir. The file IS the BIP-329 interchange format — one JSON record
+//! per line, `{"type","ref","label"[,"origin"][,"spendable"]}` — so export
+//! is a plain read and files from Sparrow, Core-adjacent tools or
+//! accounting scripts import without translation.
+//!
+//! ## Reference forms
+//!
+//! - `tx` — a txid.
+//! - `addr` — an address, in the chain's own encoding (pocx/tpocx/rpocx).
+//! - `output` / `input` — an outpoint, `txid:vout`.
+//! - `xpub` — an account xpub or a (public) descriptor of the wallet.
+//! - `pubkey` — a hex public key (imported and exported as-is).
+//!
+//! Txids and outpoints are normalized on the way in (lower-case hex), so a
+//! label set through the UI and one imported from another wallet land on
+//! the same record. Unknown record types and malformed lines in an IMPORTED
+//! file are skipped and counted — our own file is only ever written by this
+//! module, so a malformed line there is an error rather than silent loss.
+//!
+//! ## Frozen coins
+//!
+//! BIP-329's `"spendable": false` on an `output` record is the wallet's
+//! persisted coin freeze (Core's `lockunspent`, but surviving restarts and
+//! travelling with a label export). Every builder that coin-selects — send,
+//! PSBT compose, assignments — excludes [`LabelStore::frozen`]; thawing
+//! drops the flag (absent = spendable, the BIP-329 default).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::BtcxWalletConfig;
+use super::state::SharedBtcxWalletState;
+
+/// File name of the label store inside a wallet's `wallet/` dir (next to
+/// `btcx.sqlite`).
+pub const LABELS_FILE: &str = "labels.jsonl";
+
+/// Serializes read-modify-write cycles on label files: the frontend fires
+/// label edits from several views, and each is a whole-file rewrite.
+static LABELS_LOCK: Mutex<()> = Mutex::new(());
+
+/// BIP-329 record type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelType {
+    Tx,
+    Addr,
+    Pubkey,
+    Input,
+    Output,
+    Xpub,
+}
+
+/// One BIP-329 record, field names as the spec spells them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelRecord {
+    #[serde(rename = "type")]
+    pub kind: LabelType,
+    #[serde(rename = "ref")]
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Key origin of `addr`/`output` records (`wpkh([fp/84'/..])`), kept
+    /// as imported so a round trip through Phoenix loses nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// `output` records only: `false` marks a coin the user excluded from
+    /// spending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spendable: Option<bool>,
+}
+
+impl LabelRecord {
+    /// Nothing left worth a line in the file.
+    fn is_empty(&self) -> bool {
+        self.label.is_none() && self.origin.is_none() && self.spendable.is_none()
+    }
+}
+
+/// Outcome of a BIP-329 import.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelImportSummary {
+    /// Records merged into the store (new or updated).
+    pub imported: usize,
+    /// Lines that were not valid BIP-329 records (or of an unknown type).
+    pub skipped: usize,
+}
+
+/// Canonical form of a record reference; rejects references that cannot
+/// name anything of their type.
+pub fn normalize_ref(kind: LabelType, reference: &str) -> Result<String, String> {
+    let reference = reference.trim();
+    if reference.is_empty() {
+        return Err("Label reference is empty".into());
+    }
+    match kind {
+        LabelType::Tx => bitcoin::Txid::from_str(reference)
+            .map(|t| t.to_string())
+            .map_err(|e| format!("invalid txid: {e}")),
+        LabelType::Input | LabelType::Output => bitcoin::OutPoint::from_str(reference)
+            .map(|o| o.to_string())
+            .map_err(|e| format!("invalid outpoint (expected txid:vout): {e}")),
+        LabelType::Pubkey => bitcoin::PublicKey::from_str(reference)
+            .map(|k| k.to_string())
+            .map_err(|e| format!("invalid public key: {e}")),
+        LabelType::Addr | LabelType::Xpub => Ok(reference.to_string()),
+    }
+}
+
+/// One wallet's label file, loaded whole (label sets are small — a record
+/// per annotated tx/address/coin).
+#[derive(Debug, Clone, Default)]
+pub struct LabelStore {
+    path: PathBuf,
+    records: BTreeMap<(LabelType, String), LabelRecord>,
+}
+
+impl LabelStore {
+    /// Label file of the wallet whose data dir is `root`.
+    pub fn path_in(root: &Path) -> PathBuf {
+        root.join("wallet").join(LABELS_FILE)
+    }
+
+    /// Read a label file; a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut store = Self {
+            path: path.to_path_buf(),
+            records: BTreeMap::new(),
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(store),
+            Err(e) => return Err(format!("reading {}: {e}", path.display())),
+        };
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: LabelRecord = serde_json::from_str(line)
+                .map_err(|e| format!("{} line {}: {e}", path.display(), n + 1))?;
+            store
+                .records
+                .insert((record.kind, record.reference.clone()), record);
+        }
+        Ok(store)
+    }
+
+    /// Persist the store (atomic whole-file rewrite).
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("creating {}: {e}", parent.display()))?;
+        }
+        super::descstore::write_atomic(&self.path, &self.to_jsonl())
+    }
+
+    /// The label text of one record, if any. `reference` must already be
+    /// normalized (see [`normalize_ref`]).
+    pub fn label(&self, kind: LabelType, reference: &str) -> Option<&str> {
+        self.records
+            .get(&(kind, reference.to_string()))
+            .and_then(|r| r.label.as_deref())
+    }
+
+    /// One record, if any.
+    pub fn get(&self, kind: LabelType, reference: &str) -> Option<&LabelRecord> {
+        self.records.get(&(kind, reference.to_string()))
+    }
+
+    /// Every record, in (type, ref) order.
+    pub fn records(&self) -> impl Iterator<Item = &LabelRecord> {
+        self.records.values()
+    }
+
+    /// Set (or replace) a record's label; other fields are kept.
+    pub fn set(
+        &mut self,
+        kind: LabelType,
+        reference: &str,
+        label: &str,
+    ) -> Result<LabelRecord, String> {
+        let reference = normalize_ref(kind, reference)?;
+        let label = label.trim();
+        if label.is_empty() {
+            return Err("Label is empty — delete it instead".into());
+        }
+        let record = self
+            .records
+            .entry((kind, reference.clone()))
+            .or_insert_with(|| LabelRecord {
+                kind,
+                reference,
+                label: None,
+                origin: None,
+                spendable: None,
+            });
+        record.label = Some(label.to_string());
+        Ok(record.clone())
+    }
+
+    /// Drop a record's label. The record itself goes once nothing else
+    /// (origin, spendable flag) is left on it. Returns whether a label was
+    /// removed.
+    pub fn remove(&mut self, kind: LabelType, reference: &str) -> Result<bool, String> {
+        let key = (kind, normalize_ref(kind, reference)?);
+        let Some(record) = self.records.get_mut(&key) else {
+            return Ok(false);
+        };
+        let removed = record.label.take().is_some();
+        if record.is_empty() {
+            self.records.remove(&key);
+        }
+        Ok(removed)
+    }
+
+    /// Freeze (`spendable: false`) or thaw one outpoint.
+    pub fn set_frozen(&mut self, outpoint: &str, frozen: bool) -> Result<(), String> {
+        let key = (
+            LabelType::Output,
+            normalize_ref(LabelType::Output, outpoint)?,
+        );
+        if frozen {
+            let reference = key.1.clone();
+            self.records
+                .entry(key)
+                .or_insert_with(|| LabelRecord {
+                    kind: LabelType::Output,
+                    reference,
+                    label: None,
+                    origin: None,
+                    spendable: None,
+                })
+                .spendable = Some(false);
+        } else if let Some(record) = self.records.get_mut(&key) {
+            record.spendable = None;
+            if record.is_empty() {
+                self.records.remove(&key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Every frozen outpoint.
+    pub fn frozen(&self) -> BTreeSet<bitcoin::OutPoint> {
+        self.records
+            .values()
+            .filter(|r| r.kind == LabelType::Output && r.spendable == Some(false))
+            .filter_map(|r| bitcoin::OutPoint::from_str(&r.reference).ok())
+            .collect()
+    }
+
+    /// The store as BIP-329 JSONL (one record per line, trailing newline).
+    pub fn to_jsonl(&self) -> String {
+        self.records
+            .values()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Merge a BIP-329 JSONL document: imported fields overwrite ours,
+    /// fields it leaves out are kept. Bad lines are skipped and counted.
+    pub fn import_jsonl(&mut self, text: &str) -> LabelImportSummary {
+        let mut summary = LabelImportSummary::default();
+        for line in text.lines() {
+            // BIP-329 files may carry a UTF-8 BOM on their first line.
+            let line = line.trim().trim_start_matches('\u{feff}');
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = serde_json::from_str::<LabelRecord>(line)
+                .ok()
+                .and_then(|mut r| {
+                    r.reference = normalize_ref(r.kind, &r.reference).ok()?;
+                    Some(r)
+                });
+            let Some(mut incoming) = parsed else {
+                summary.skipped += 1;
+                continue;
+            };
+            incoming.label = incoming
+                .label
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty());
+            if incoming.is_empty() {
+                summary.skipped += 1;
+                continue;
+            }
+            let key = (incoming.kind, incoming.reference.clone());
+            match self.records.get_mut(&key) {
+                Some(existing) => {
+                    existing.label = incoming.label.or(existing.label.take());
+                    existing.origin = incoming.origin.or(existing.origin.take());
+                    existing.spendable = incoming.spendable.or(existing.spendable);
+                }
+                None => {
+                    self.records.insert(key, incoming);
+                }
+            }
+            summary.imported += 1;
+        }
+        summary
+    }
+}
+
+/// Label file of the ACTIVE wallet. Errors when no wallet is selected, so
+/// a label edit can never conjure a wallet dir for an unregistered name.
+fn active_labels_path(state: &SharedBtcxWalletState) -> Result<PathBuf, String> {
+    let config = state.get_config();
+    let name = config.active_wallet_name();
+    config
+        .wallet_meta(config.network, &name)
+        .ok_or_else(|| "No active wallet".to_string())?;
+    Ok(LabelStore::path_in(&BtcxWalletConfig::wallet_root(
+        config.network,
+        &name,
+    )))
+}
+
+/// Read the active wallet's labels.
+pub fn read_active(state: &SharedBtcxWalletState) -> Result<LabelStore, String> {
+    let path = active_labels_path(state)?;
+    let _guard = LABELS_LOCK.lock().map_err(|_| "label lock poisoned")?;
+    LabelStore::load(&path)
+}
+
+/// The active wallet's labels for DISPLAY decoration (tx list, addresses,
+/// coins): a missing or unreadable file yields an empty store — labels are
+/// garnish there, never a reason to fail the list.
+pub fn display_labels(state: &SharedBtcxWalletState) -> LabelStore {
+    read_active(state).unwrap_or_else(|e| {
+        log::warn!("btcx wallet: reading labels failed: {e}");
+        LabelStore::default()
+    })
+}
+
+/// Load, mutate and persist the active wallet's labels under the label
+/// lock. Nothing is written when `f` fails.
+pub fn update_active<T>(
+    state: &SharedBtcxWalletState,
+    f: impl FnOnce(&mut LabelStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = active_labels_path(state)?;
+    let _guard = LABELS_LOCK.lock().map_err(|_| "label lock poisoned")?;
+    let mut store = LabelStore::load(&path)?;
+    let out = f(&mut store)?;
+    store.save()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+
+    #[test]
+    fn labels_persist_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = LabelStore::path_in(dir.path());
+        let mut store = LabelStore::load(&path).unwrap();
+        assert_eq!(store.records().count(), 0, "missing file = empty store");
+
+        store.set(LabelType::Tx, TXID, "  rent  ").unwrap();
+        store
+            .set(
+                LabelType::Output,
+                &format!("{}:1", TXID.to_uppercase()),
+                "pool payout",
+            )
+            .unwrap();
+        store.save().unwrap();
+
+        let mut reread = LabelStore::load(&path).unwrap();
+        assert_eq!(reread.label(LabelType::Tx, TXID), Some("rent"));
+        assert_eq!(
+            reread.label(LabelType::Output, &format!("{TXID}:1")),
+            Some("pool payout"),
+            "outpoints are normalized to lower-case hex"
+        );
+
+        assert!(reread.remove(LabelType::Tx, TXID).unwrap());
+        assert!(!reread.remove(LabelType::Tx, TXID).unwrap());
+        assert_eq!(reread.label(LabelType::Tx, TXID), None);
+        assert!(reread.set(LabelType::Tx, "not-a-txid", "x").is_err());
+        assert!(reread.set(LabelType::Tx, TXID, "   ").is_err());
+    }
+
+    #[test]
+    fn freezing_keeps_the_label_and_thaw_drops_the_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = LabelStore::path_in(dir.path());
+        let outpoint = format!("{TXID}:2");
+        let mut store = LabelStore::load(&path).unwrap();
+        store
+            .set(LabelType::Output, &outpoint, "plot ownership coin")
+            .unwrap();
+        store.set_frozen(&outpoint, true).unwrap();
+        store.set_frozen(&format!("{TXID}:3"), true).unwrap();
+        store.save().unwrap();
+
+        let mut store = LabelStore::load(&path).unwrap();
+        let frozen: Vec<String> = store.frozen().iter().map(|o| o.to_string()).collect();
+        assert_eq!(frozen, vec![outpoint.clone(), format!("{TXID}:3")]);
+
+        store.set_frozen(&outpoint, false).unwrap();
+        store.set_frozen(&format!("{TXID}:3"), false).unwrap();
+        assert!(store.frozen().is_empty());
+        assert_eq!(
+            store.label(LabelType::Output, &outpoint),
+            Some("plot ownership coin"),
+            "thawing keeps the label"
+        );
+        assert_eq!(
+            store.records().count(),
+            1,
+            "a bare thawed record is dropped"
+        );
+        assert!(store.set_frozen("nope", true).is_err());
+    }
+
+    #[test]
+    fn bip329_import_merges_and_skips_junk() {
+        let mut store = LabelStore::default();
+        store.set(LabelType::Addr, "rpocx1qexample", "old").unwrap();
+        let doc = format!(
+            "\u{feff}{{\"type\":\"tx\",\"ref\":\"{TXID}\",\"label\":\"Transaction\",\"origin\":\"wpkh([d34db33f/84'/0'/0'])\"}}\n\
+             {{\"type\":\"addr\",\"ref\":\"rpocx1qexample\",\"label\":\"Address\"}}\n\
+             {{\"type\":\"output\",\"ref\":\"{TXID}:0\",\"spendable\":false}}\n\
+             {{\"type\":\"bogus\",\"ref\":\"x\",\"label\":\"y\"}}\n\
+             {{\"type\":\"tx\",\"ref\":\"zz\",\"label\":\"bad txid\"}}\n\
+             not json\n\n"
+        );
+        let summary = store.import_jsonl(&doc);
+        assert_eq!(
+            summary,
+            LabelImportSummary {
+                imported: 3,
+                skipped: 3
+            }
+        );
+        assert_eq!(
+            store.label(LabelType::Addr, "rpocx1qexample"),
+            Some("Address")
+        );
+        let output = store.get(LabelType::Output, &format!("{TXID}:0")).unwrap();
+        assert_eq!(output.spendable, Some(false));
+        assert_eq!(output.label, None);
+        assert_eq!(
+            store.frozen().len(),
+            1,
+            "imported spendable:false freezes the coin"
+        );
+
+        // Export is one valid record per line and re-imports losslessly.
+        let exported = store.to_jsonl();
+        assert_eq!(exported.lines().count(), 3);
+        assert!(exported.contains("\"type\":\"tx\""), "{exported}");
+        assert!(exported.contains("\"ref\":"), "{exported}");
+        let mut copy = LabelStore::default();
+        assert_eq!(copy.import_jsonl(&exported).imported, 3);
+        assert_eq!(copy.to_jsonl(), exported);
+    }
+}
@@ -42,7 +42,7 @@
 use anyhow::{anyhow, Context, Result};
 use bdk_wallet::rusqlite::Connection;
 use bdk_wallet::{KeychainKind, Wallet};
-use bitcoin::bip32::ChildNumber;
+use bitcoin::bip32::{ChildNumber, Xpriv, Xpub};
 use bitcoin::{BlockHash, ScriptBuf};
 use serde::Serialize;
 use std::path::Path;
@@ -259,6 +259,29 @@ pub fn candidate_spks(
     Ok(spks)
 }
 
+/// Our BIP-48 P2WSH multisig account key of `seed`
+/// (`m/48'/coin_type'/0'/2'`) as `(private, public)` key expressions with
+/// full origin — `[fp/48'/coin'/0'/2']xprv…` / `…xpub…`, serialized for
+/// `network` (tprv/tpub off mainnet). The public one is what we hand to
+/// cosigners; the private one goes into our own `wsh(sortedmulti)` wallet.
+pub fn multisig_account_keys(
+    seed: &WalletSeed,
+    coin_type: u32,
+    network: bitcoin::NetworkKind,
+) -> Result<(String, String)> {
+    let secp = seed.secp();
+    let master = seed.master_xpriv();
+    let path: Vec<ChildNumber> = [48, coin_type, 0, 2]
+        .iter()
+        .map(|&i| ChildNumber::from_hardened_idx(i))
+        .collect::<Result<_, _>>()?;
+    let account = master.derive_priv(secp, &path)?;
+    let account = Xpriv { network, ..account };
+    let xpub = Xpub::from_priv(secp, &account);
+    let origin = format!("[{}/48'/{coin_type}'/0'/2']", master.fingerprint(secp));
+    Ok((format!("{origin}{account}"), format!("{origin}{xpub}")))
+}
+
 /// One probed branch that HAS transaction history, with the deepest used
 /// index per keychain — what [`ensure_probe_reach`] needs to make the
 /// wallet's sync see everything the probe saw. Serialized into the restore
@@ -429,6 +452,21 @@ mod tests {
         DescriptorPolicy { kind, coin_type }
     }
 
+    #[test]
+    fn multisig_account_keys_pair_up() {
+        let (private, public) =
+            multisig_account_keys(&seed(), 1, bitcoin::NetworkKind::Test).unwrap();
+        let (origin, xprv) = private.split_once(']').unwrap();
+        let (pub_origin, xpub) = public.split_once(']').unwrap();
+        assert_eq!(origin, pub_origin);
+        assert!(origin.ends_with("/48'/1'/0'/2'"), "{origin}");
+        assert!(xprv.starts_with("tprv") && xpub.starts_with("tpub"));
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let derived = Xpub::from_priv(&secp, &Xpriv::from_str(xprv).unwrap());
+        assert_eq!(derived.to_string(), xpub);
+    }
+
     #[test]
     fn probe_candidate_ordering() {
         // Mainnet: new-standard (BTCX coin type) branches first, legacy
@@ -0,0 +1,466 @@
+//! Message signing and verification — proving control of an address
+//! (plot ownership, payout addresses) without moving coins.
+//!
+//! Two signature families, over the chain's own address encodings:
+//!
+//! - **Legacy** (`signmessage` / BIP-137): a 65-byte recoverable ECDSA
+//!   signature over Core's "Bitcoin Signed Message" hash, which
+//!   Bitcoin-PoCX inherits unchanged. P2PKH signs with header 31–34 like
+//!   Core; P2WPKH uses BIP-137's 39–42. Verification accepts every BIP-137
+//!   header (27–42) and checks the recovered key against the address.
+//! - **BIP-322** simple (base64 witness) and full (base64 `to_sign` tx) for
+//!   P2WPKH and P2TR (BIP-86 key path). Full signatures with extra
+//!   proof-of-funds inputs are refused.
+//!
+//! Verification needs no wallet at all — it is a pure function of
+//! (network, address, message, signature), so a pool backend linking this
+//! crate runs the exact code the app does.
+
+use bitcoin::base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::{CompressedPublicKey, Keypair, TapTweak};
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{All, Message, Secp256k1, SecretKey, Verification, XOnlyPublicKey};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::{
+    absolute::LockTime, opcodes, script::Builder, transaction::Version, Amount, OutPoint,
+    ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use serde::{Deserialize, Serialize};
+
+use electrum_btcx::WalletEntry;
+
+use super::config::WalletNetwork;
+use super::state::SharedBtcxWalletState;
+
+/// Signature format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageFormat {
+    Legacy,
+    Bip322Simple,
+    Bip322Full,
+}
+
+/// Outcome of a verification. A well-formed signature by another key is
+/// `valid: false`; an undecodable one is an error.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageVerification {
+    pub valid: bool,
+    /// The format the signature was recognised as.
+    pub format: MessageFormat,
+}
+
+fn parse_spk(network: WalletNetwork, address: &str) -> Result<ScriptBuf, String> {
+    network
+        .params()
+        .parse_address(address.trim())
+        .map_err(|e| format!("{address}: {e:#}"))
+}
+
+// ============================================================================
+// Legacy (BIP-137)
+// ============================================================================
+
+/// Core's `MESSAGE_MAGIC`-prefixed double-SHA256 of `message`.
+fn legacy_digest(message: &str) -> Message {
+    Message::from_digest(bitcoin::sign_message::signed_msg_hash(message).to_byte_array())
+}
+
+fn sign_legacy(secp: &Secp256k1<All>, sk: &SecretKey, spk: &ScriptBuf, message: &str) -> String {
+    let signature = secp.sign_ecdsa_recoverable(&legacy_digest(message), sk);
+    let (recid, compact) = signature.serialize_compact();
+    // BIP-137 headers: compressed P2PKH 31–34, P2WPKH 39–42.
+    let base = if spk.is_p2wpkh() { 39 } else { 31 };
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(base + recid.to_i32() as u8);
+    bytes.extend_from_slice(&compact);
+    BASE64.encode(bytes)
+}
+
+fn verify_legacy<C: Verification>(
+    secp: &Secp256k1<C>,
+    spk: &ScriptBuf,
+    message: &str,
+    raw: &[u8],
+) -> Result<bool, String> {
+    let header = raw[0];
+    let recid = RecoveryId::from_i32(i32::from((header - 27) & 3))
+        .map_err(|e| format!("signature header: {e}"))?;
+    let Ok(signature) = RecoverableSignature::from_compact(&raw[1..], recid) else {
+        return Ok(false);
+    };
+    let Ok(key) = secp.recover_ecdsa(&legacy_digest(message), &signature) else {
+        return Ok(false);
+    };
+    let compressed = header >= 31;
+    let key = bitcoin::PublicKey {
+        compressed,
+        inner: key,
+    };
+    let wpkh = || ScriptBuf::new_p2wpkh(&CompressedPublicKey(key.inner).wpubkey_hash());
+    Ok(if spk.is_p2pkh() {
+        ScriptBuf::new_p2pkh(&key.pubkey_hash()) == *spk
+    } else if spk.is_p2wpkh() {
+        compressed && wpkh() == *spk
+    } else if spk.is_p2sh() {
+        // BIP-137 P2SH-P2WPKH (35–38).
+        compressed && ScriptBuf::new_p2sh(&wpkh().script_hash()) == *spk
+    } else {
+        return Err("Legacy signatures cover P2PKH and P2WPKH addresses only".to_string());
+    })
+}
+
+// ============================================================================
+// BIP-322
+// ============================================================================
+
+/// `tagged_hash("BIP0322-signed-message", message)`.
+pub fn bip322_message_hash(message: &[u8]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(b"BIP0322-signed-message");
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(message);
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// The virtual `to_spend` transaction committing to `message` and `spk`.
+fn to_spend(spk: &ScriptBuf, message: &str) -> Transaction {
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::all_zeros(),
+                vout: 0xFFFF_FFFF,
+            },
+            script_sig: Builder::new()
+                .push_opcode(opcodes::OP_0)
+                .push_slice(bip322_message_hash(message.as_bytes()))
+                .into_script(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: spk.clone(),
+        }],
+    }
+}
+
+/// The simple-form `to_sign` transaction spending `to_spend`.
+fn to_sign(to_spend: &Transaction, witness: Witness) -> Transaction {
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(to_spend.compute_txid(), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness,
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::all::OP_RETURN)
+                .into_script(),
+        }],
+    }
+}
+
+/// Witness for input 0 of `to_sign`, spending the zero-value `spk` output.
+fn bip322_witness(
+    secp: &Secp256k1<All>,
+    sk: &SecretKey,
+    spk: &ScriptBuf,
+    to_sign: &Transaction,
+) -> Result<Witness, String> {
+    let mut cache = SighashCache::new(to_sign);
+    if spk.is_p2wpkh() {
+        let sighash = cache
+            .p2wpkh_signature_hash(0, spk, Amount::ZERO, EcdsaSighashType::All)
+            .map_err(|e| format!("sighash: {e}"))?;
+        let signature = secp.sign_ecdsa(&Message::from_digest(sighash.to_byte_array()), sk);
+        Ok(Witness::p2wpkh(
+            &bitcoin::ecdsa::Signature::sighash_all(signature),
+            &sk.public_key(secp),
+        ))
+    } else if spk.is_p2tr() {
+        let prevout = TxOut {
+            value: Amount::ZERO,
+            script_pubkey: spk.clone(),
+        };
+        let sighash = cache
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&[prevout]),
+                TapSighashType::Default,
+            )
+            .map_err(|e| format!("sighash: {e}"))?;
+        let keypair = Keypair::from_secret_key(secp, sk)
+            .tap_tweak(secp, None)
+            .to_inner();
+        let signature =
+            secp.sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), &keypair);
+        Ok(Witness::p2tr_key_spend(&bitcoin::taproot::Signature {
+            signature,
+            sighash_type: TapSighashType::Default,
+        }))
+    } else {
+        Err("BIP-322 signing covers P2WPKH and P2TR addresses only".to_string())
+    }
+}
+
+/// Check input 0's witness of `to_sign` against `spk`.
+fn verify_witness<C: Verification>(
+    secp: &Secp256k1<C>,
+    spk: &ScriptBuf,
+    to_sign: &Transaction,
+) -> Result<bool, String> {
+    let witness = &to_sign.input[0].witness;
+    let mut cache = SighashCache::new(to_sign);
+    if spk.is_p2wpkh() {
+        if witness.len() != 2 {
+            return Ok(false);
+        }
+        let (Ok(signature), Ok(key)) = (
+            bitcoin::ecdsa::Signature::from_slice(&witness[0]),
+            CompressedPublicKey::from_slice(&witness[1]),
+        ) else {
+            return Ok(false);
+        };
+        if ScriptBuf::new_p2wpkh(&key.wpubkey_hash()) != *spk {
+            return Ok(false);
+        }
+        let sighash = cache
+            .p2wpkh_signature_hash(0, spk, Amount::ZERO, signature.sighash_type)
+            .map_err(|e| format!("sighash: {e}"))?;
+        let digest = Message::from_digest(sighash.to_byte_array());
+        Ok(secp
+            .verify_ecdsa(&digest, &signature.signature, &key.0)
+            .is_ok())
+    } else if spk.is_p2tr() {
+        if witness.len() != 1 {
+            return Ok(false);
+        }
+        let Ok(signature) = bitcoin::taproot::Signature::from_slice(&witness[0]) else {
+            return Ok(false);
+        };
+        let output_key = XOnlyPublicKey::from_slice(&spk.as_bytes()[2..34])
+            .map_err(|e| format!("taproot output key: {e}"))?;
+        let prevout = TxOut {
+            value: Amount::ZERO,
+            script_pubkey: spk.clone(),
+        };
+        let sighash = cache
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(&[prevout]), signature.sighash_type)
+            .map_err(|e| format!("sighash: {e}"))?;
+        let digest = Message::from_digest(sighash.to_byte_array());
+        Ok(secp
+            .verify_schnorr(&signature.signature, &digest, &output_key)
+            .is_ok())
+    } else {
+        Err("BIP-322 verification covers P2WPKH and P2TR addresses only".to_string())
+    }
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+/// Sign `message` for `spk` with the key `sk` (which must own `spk`).
+pub fn sign_with_key(
+    sk: &SecretKey,
+    spk: &ScriptBuf,
+    message: &str,
+    format: MessageFormat,
+) -> Result<String, String> {
+    let secp = Secp256k1::new();
+    match format {
+        MessageFormat::Legacy => {
+            if !(spk.is_p2pkh() || spk.is_p2wpkh()) {
+                return Err("Legacy signatures cover P2PKH and P2WPKH addresses only".to_string());
+            }
+            Ok(sign_legacy(&secp, sk, spk, message))
+        }
+        MessageFormat::Bip322Simple | MessageFormat::Bip322Full => {
+            let mut tx = to_sign(&to_spend(spk, message), Witness::new());
+            tx.input[0].witness = bip322_witness(&secp, sk, spk, &tx)?;
+            Ok(BASE64.encode(if format == MessageFormat::Bip322Simple {
+                bitcoin::consensus::serialize(&tx.input[0].witness)
+            } else {
+                bitcoin::consensus::serialize(&tx)
+            }))
+        }
+    }
+}
+
+/// Verify a signature of `message` by `spk`'s owner; the format is
+/// recognised from the encoding (65 bytes with a BIP-137 header → legacy,
+/// a witness stack → BIP-322 simple, a transaction → BIP-322 full).
+pub fn verify_spk(
+    spk: &ScriptBuf,
+    message: &str,
+    signature: &str,
+) -> Result<MessageVerification, String> {
+    let raw = BASE64
+        .decode(signature.trim())
+        .map_err(|_| "The signature is not base64".to_string())?;
+    let secp = Secp256k1::verification_only();
+    if raw.len() == 65 && (27..=42).contains(&raw[0]) {
+        return Ok(MessageVerification {
+            valid: verify_legacy(&secp, spk, message, &raw)?,
+            format: MessageFormat::Legacy,
+        });
+    }
+    let to_spend = to_spend(spk, message);
+    if let Ok(witness) = bitcoin::consensus::deserialize::<Witness>(&raw) {
+        return Ok(MessageVerification {
+            valid: verify_witness(&secp, spk, &to_sign(&to_spend, witness))?,
+            format: MessageFormat::Bip322Simple,
+        });
+    }
+    let tx: Transaction = bitcoin::consensus::deserialize(&raw)
+        .map_err(|_| "Unrecognised signature encoding".to_string())?;
+    if tx.input.len() > 1 {
+        return Err("BIP-322 proof-of-funds signatures are not supported".to_string());
+    }
+    let shaped = tx.input.len() == 1
+        && tx.input[0].previous_output == OutPoint::new(to_spend.compute_txid(), 0)
+        && tx.output.len() == 1
+        && tx.output[0].value == Amount::ZERO
+        && tx.output[0].script_pubkey.is_op_return();
+    Ok(MessageVerification {
+        valid: shaped && verify_witness(&secp, spk, &tx)?,
+        format: MessageFormat::Bip322Full,
+    })
+}
+
+/// [`verify_spk`] for an address of `network`. Needs no wallet.
+pub fn verify_message(
+    network: WalletNetwork,
+    address: &str,
+    message: &str,
+    signature: &str,
+) -> Result<MessageVerification, String> {
+    verify_spk(&parse_spk(network, address)?, message, signature)
+}
+
+/// The open wallet's private key for `spk`, from its signers' key map.
+fn secret_key_for(entry: &WalletEntry, spk: &ScriptBuf) -> Result<SecretKey, String> {
+    use bdk_wallet::miniscript::descriptor::{DescriptorSecretKey, Wildcard};
+    use bitcoin::bip32::ChildNumber;
+
+    let (keychain, index) = entry
+        .wallet
+        .derivation_of_spk(spk.clone())
+        .ok_or_else(|| "The address does not belong to this wallet".to_string())?;
+    let secp = Secp256k1::new();
+    let owns = |sk: &SecretKey| {
+        let key = sk.public_key(&secp);
+        let wpkh = ScriptBuf::new_p2wpkh(&CompressedPublicKey(key).wpubkey_hash());
+        let pkh = ScriptBuf::new_p2pkh(&bitcoin::PublicKey::new(key).pubkey_hash());
+        let tr = ScriptBuf::new_p2tr(&secp, key.x_only_public_key().0, None);
+        [wpkh, pkh, tr].contains(spk)
+    };
+    for secret in entry
+        .wallet
+        .get_signers(keychain)
+        .as_key_map(&secp)
+        .values()
+    {
+        let sk = match secret {
+            DescriptorSecretKey::Single(single) => single.key.inner,
+            DescriptorSecretKey::XPrv(xkey) => {
+                let path = match xkey.wildcard {
+                    Wildcard::None => xkey.derivation_path.clone(),
+                    Wildcard::Unhardened => xkey
+                        .derivation_path
+                        .child(ChildNumber::from_normal_idx(index).map_err(|e| e.to_string())?),
+                    Wildcard::Hardened => xkey
+                        .derivation_path
+                        .child(ChildNumber::from_hardened_idx(index).map_err(|e| e.to_string())?),
+                };
+                xkey.xkey
+                    .derive_priv(&secp, &path)
+                    .map_err(|e| format!("key derivation: {e}"))?
+                    .private_key
+            }
+            DescriptorSecretKey::MultiXPrv(_) => continue,
+        };
+        if owns(&sk) {
+            return Ok(sk);
+        }
+    }
+    Err("The wallet holds no private key for this address".to_string())
+}
+
+/// Sign `message` with the open wallet's key for `address`.
+pub fn sign_message(
+    state: &SharedBtcxWalletState,
+    address: &str,
+    message: &str,
+    format: MessageFormat,
+) -> Result<String, String> {
+    let spk = parse_spk(state.get_config().network, address)?;
+    let sk = state.with_entry(|entry| secret_key_for(entry, &spk))?;
+    sign_with_key(&sk, &spk, message, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip322_message_hash_matches_the_spec_vectors() {
+        assert_eq!(
+            hex::encode(bip322_message_hash(b"")),
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1"
+        );
+        assert_eq!(
+            hex::encode(bip322_message_hash(b"Hello World")),
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a"
+        );
+    }
+
+    #[test]
+    fn every_format_round_trips_and_rejects_other_keys() {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[7; 32]).unwrap();
+        let other = SecretKey::from_slice(&[8; 32]).unwrap();
+        let key = sk.public_key(&secp);
+        let wpkh = ScriptBuf::new_p2wpkh(&CompressedPublicKey(key).wpubkey_hash());
+        let pkh = ScriptBuf::new_p2pkh(&bitcoin::PublicKey::new(key).pubkey_hash());
+        let tr = ScriptBuf::new_p2tr(&secp, key.x_only_public_key().0, None);
+        let msg = "plot 0xdeadbeef belongs to me";
+
+        let cases = [
+            (&wpkh, MessageFormat::Legacy),
+            (&pkh, MessageFormat::Legacy),
+            (&wpkh, MessageFormat::Bip322Simple),
+            (&wpkh, MessageFormat::Bip322Full),
+            (&tr, MessageFormat::Bip322Simple),
+            (&tr, MessageFormat::Bip322Full),
+        ];
+        for (spk, format) in cases {
+            let signature = sign_with_key(&sk, spk, msg, format).unwrap();
+            let ok = verify_spk(spk, msg, &signature).unwrap();
+            assert!(ok.valid, "{format:?} over {spk:?}");
+            assert_eq!(ok.format, format);
+            assert!(
+                !verify_spk(spk, "something else", &signature).unwrap().valid,
+                "{format:?}: another message must not verify"
+            );
+            let forged = sign_with_key(&other, spk, msg, format).unwrap();
+            assert!(
+                !verify_spk(spk, msg, &forged).unwrap().valid,
+                "{format:?}: another key must not verify"
+            );
+        }
+        assert!(sign_with_key(&sk, &pkh, msg, MessageFormat::Bip322Simple).is_err());
+        assert!(sign_with_key(&sk, &tr, msg, MessageFormat::Legacy).is_err());
+        assert!(verify_spk(&wpkh, msg, "not base64!").is_err());
+    }
+}
@@ -27,15 +27,28 @@
 //!
 //! - `btcx_wallet_config.json` — network, per-network Electrum servers,
 //!   the named-wallet registry (per-wallet descriptor policy), the active
-//!   wallet per network, active flag.
+//!   wallet per network, active flag, the pinned TLS certificates of
+//!   `ssl://` servers (see `tlspin`), the discovered servers per network
+//!   (see `discovery`).
 //! - `btcx-wallet/<network>/<name>/seed.mnemonic` — one seed PER named
 //!   wallet, never plaintext (see `seedstore`). Descriptor-IMPORTED wallets
 //!   have `descriptor.secret` (the private descriptor pair, see
-//!   `descstore`) here instead — they carry no mnemonic.
+//!   `descstore`) here instead — they carry no mnemonic. WATCH-ONLY wallets
+//!   have only `descriptor.public` (the public pair) — no secret at all.
 //! - `btcx-wallet/<network>/<name>/wallet/btcx.sqlite` — that wallet's bdk
-//!   store.
+//!   store; `wallet/labels.jsonl` next to it holds the wallet's BIP-329
+//!   labels (see `labels`), `wallet/replacements.json` its RBF
+//!   replacement links (see `replacements`), `wallet/payment_requests.json`
+//!   its BIP-21 payment requests (see `payreq`), `wallet/spv.json` the
+//!   PoCX headers and merkle proofs of its verified confirmations (see
+//!   `spv`).
+//! - A backup bundle (see `backup`) seals a group's or a network's
+//!   registry entries, key files and side files into one file.
+//! - A codex32 split (see `codex32`) turns a seed into k-of-n `ms1…`
+//!   shares kept off-device; nothing of it is stored here.
 //! - `btcx-wallet/<network>/.trash/<name>-<ts>/` — deleted wallets are
-//!   moved here, never removed.
+//!   moved here with their registry entry (`trash.json`); only an explicit
+//!   purge or the opt-in retention removes them (see `trash`).
 //!
 //! The pre-multi-wallet layout (ONE root seed at `btcx-wallet/seed.mnemonic`
 //! shared by per-network stores at `btcx-wallet/<network>/wallet/`) is
@@ -43,13 +56,32 @@
 //! (see `config::migrate_legacy_layout_at`).
 
 pub mod assignments;
+pub mod backup;
+pub mod codex32;
 pub mod commands;
 pub mod config;
+pub mod consensus;
 pub mod descriptors;
 pub mod descstore;
+pub mod discovery;
+pub mod fees;
+pub mod labels;
 pub mod manager;
+pub mod message;
+pub mod payreq;
+pub mod policy;
+pub mod preview;
 pub mod psbt;
+pub mod rawtx;
+pub mod replacements;
+pub mod rewrap;
+pub mod seedcheck;
+pub mod spend;
+pub mod spv;
 pub mod state;
+pub mod tlspin;
+pub mod trash;
+pub mod ur;
 
 #[cfg(test)]
 mod roundtrip_25th_word_test;
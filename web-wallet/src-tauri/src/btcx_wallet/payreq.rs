@@ -0,0 +1,562 @@
+//! BIP-21 payment URIs and payment requests.
+//!
+//! ## URIs
+//!
+//! We emit the canonical `btcx:` scheme (the frontend's `payment-uri.ts`
+//! builds the same); inbound parsing also accepts `pocx:` and `bitcoin:`
+//! (the node's Qt wallet still emits the latter) plus a bare address. The
+//! address must decode under the ACTIVE network's own encoding (HRP /
+//! base58 prefixes), so a Bitcoin URI is refused rather than paid to the
+//! wrong chain. Per BIP-21, `amount` is a decimal coin value, `label` and
+//! `message` are percent-decoded, other unknown parameters are ignored and
+//! an unknown `req-` parameter makes the whole URI invalid.
+//!
+//! ## Payment requests
+//!
+//! An invoice is a receive address plus an expected amount and an optional
+//! expiry, persisted per wallet in `wallet/payment_requests.json` and keyed
+//! by the address — one request per address, so a payment can never be
+//! credited to two invoices. Status is never stored: it is derived on every
+//! read from what the synced tx graph says the address received.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bitcoin::{Amount, Denomination, ScriptBuf};
+use serde::{Deserialize, Serialize};
+
+use electrum_btcx::WalletEntry;
+
+use super::config::WalletNetwork;
+use super::state::SharedBtcxWalletState;
+
+/// Scheme we emit. Keep in sync with `CANONICAL_URI_SCHEME` in the
+/// frontend's `payment-uri.ts`.
+pub const CANONICAL_URI_SCHEME: &str = "btcx";
+
+/// Schemes accepted on parse (case-insensitive).
+pub const ACCEPTED_URI_SCHEMES: [&str; 3] = ["btcx", "pocx", "bitcoin"];
+
+/// File name of the request store inside a wallet's `wallet/` dir.
+pub const PAYMENT_REQUESTS_FILE: &str = "payment_requests.json";
+
+/// Serializes read-modify-write cycles on request files.
+static REQUESTS_LOCK: Mutex<()> = Mutex::new(());
+
+// ============================================================================
+// URIs
+// ============================================================================
+
+/// A parsed (or to-be-built) payment URI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentUri {
+    /// Address in the network's canonical encoding.
+    pub address: String,
+    #[serde(default)]
+    pub amount_sat: Option<u64>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Percent-decode a query component (`+` is a literal plus in BIP-21, not
+/// a space — it is an RFC 3986 URI, not a form post).
+fn percent_decode(s: &str) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| format!("bad percent-escape in {s:?}"))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| format!("{s:?} is not UTF-8"))
+}
+
+/// Percent-encode a query value (RFC 3986 unreserved characters kept).
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// Validate `address` against `network` and return its canonical encoding
+/// (lower-case bech32 — QR codes often carry the upper-case form).
+fn canonical_address(network: WalletNetwork, address: &str) -> Result<String, String> {
+    let spk: ScriptBuf = network
+        .params()
+        .parse_address(address)
+        .map_err(|e| format!("{address}: {e:#}"))?;
+    super::psbt::spk_to_address(network, &spk)
+        .ok_or_else(|| format!("{address}: unsupported address script"))
+}
+
+/// Parse a BIP-21 URI (or a bare address) for `network`.
+pub fn parse_payment_uri(network: WalletNetwork, uri: &str) -> Result<PaymentUri, String> {
+    let raw = uri.trim();
+    if raw.is_empty() {
+        return Err("Empty payment URI".to_string());
+    }
+    let rest = match raw.split_once(':') {
+        Some((scheme, rest)) => {
+            if !ACCEPTED_URI_SCHEMES
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(scheme))
+            {
+                return Err(format!("Unsupported URI scheme \"{scheme}:\""));
+            }
+            rest.strip_prefix("//").unwrap_or(rest)
+        }
+        None => raw,
+    };
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut out = PaymentUri {
+        address: canonical_address(network, &percent_decode(address)?)?,
+        amount_sat: None,
+        label: None,
+        message: None,
+    };
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(key)?;
+        let value = percent_decode(value)?;
+        let slot = match key.as_str() {
+            "amount" => {
+                if out.amount_sat.is_some() {
+                    return Err("amount is given twice".to_string());
+                }
+                let amount = Amount::from_str_in(&value, Denomination::Bitcoin)
+                    .map_err(|e| format!("amount {value}: {e}"))?;
+                out.amount_sat = Some(amount.to_sat());
+                continue;
+            }
+            "label" => &mut out.label,
+            "message" => &mut out.message,
+            k if k.starts_with("req-") => {
+                return Err(format!("Unsupported required parameter \"{k}\""));
+            }
+            _ => continue,
+        };
+        if slot.is_some() {
+            return Err(format!("{key} is given twice"));
+        }
+        *slot = Some(value);
+    }
+    Ok(out)
+}
+
+/// Build our canonical `btcx:` URI. The address is validated against
+/// `network` first; empty label/message are left out.
+pub fn build_payment_uri(network: WalletNetwork, request: &PaymentUri) -> Result<String, String> {
+    let mut uri = format!(
+        "{CANONICAL_URI_SCHEME}:{}",
+        canonical_address(network, request.address.trim())?
+    );
+    let mut params = Vec::new();
+    if let Some(sat) = request.amount_sat.filter(|&s| s > 0) {
+        params.push(format!("amount={}", format_coins(sat)));
+    }
+    for (key, value) in [("label", &request.label), ("message", &request.message)] {
+        if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            params.push(format!("{key}={}", percent_encode(value)));
+        }
+    }
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    Ok(uri)
+}
+
+/// `sat` as a decimal coin amount without trailing zeros (`0.5`, `12`).
+fn format_coins(sat: u64) -> String {
+    let whole = sat / 100_000_000;
+    let frac = sat % 100_000_000;
+    if frac == 0 {
+        return whole.to_string();
+    }
+    let frac = format!("{frac:08}");
+    format!("{whole}.{}", frac.trim_end_matches('0'))
+}
+
+// ============================================================================
+// Payment requests
+// ============================================================================
+
+/// A stored payment request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequest {
+    pub address: String,
+    /// Expected amount; `None` = any amount settles it.
+    #[serde(default)]
+    pub amount_sat: Option<u64>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// Unix seconds; `None` = never expires.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+/// Derived state of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    PartiallyPaid,
+    Paid,
+    Expired,
+}
+
+/// A request with its status as of the last sync.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequestStatus {
+    #[serde(flatten)]
+    pub request: PaymentRequest,
+    pub status: PaymentStatus,
+    /// Everything the address received, mempool included.
+    pub received_sat: u64,
+    /// The confirmed part of `received_sat`.
+    pub confirmed_sat: u64,
+    /// The request as a `btcx:` URI (QR / share).
+    pub uri: String,
+}
+
+/// Status of `request` given what its address received (`received_sat`,
+/// mempool included) at `now`. A paid request stays paid past its expiry;
+/// an unpaid or partially paid one expires.
+pub fn derive_status(request: &PaymentRequest, received_sat: u64, now: u64) -> PaymentStatus {
+    let paid = match request.amount_sat {
+        Some(expected) => received_sat >= expected,
+        None => received_sat > 0,
+    };
+    if paid {
+        PaymentStatus::Paid
+    } else if request.expires_at.is_some_and(|t| now >= t) {
+        PaymentStatus::Expired
+    } else if received_sat > 0 {
+        PaymentStatus::PartiallyPaid
+    } else {
+        PaymentStatus::Pending
+    }
+}
+
+/// One wallet's payment requests, keyed by address.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequestStore {
+    #[serde(default)]
+    requests: BTreeMap<String, PaymentRequest>,
+}
+
+impl PaymentRequestStore {
+    /// Request file inside a wallet dir.
+    pub fn path_in(root: &Path) -> PathBuf {
+        root.join("wallet").join(PAYMENT_REQUESTS_FILE)
+    }
+
+    /// Read a request file; a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("reading {}: {e}", path.display())),
+        }
+    }
+
+    /// Atomically rewrite the request file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("creating {}: {e}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        super::descstore::write_atomic(path, &json)
+    }
+
+    pub fn get(&self, address: &str) -> Option<&PaymentRequest> {
+        self.requests.get(address)
+    }
+
+    /// All requests, newest first.
+    pub fn requests(&self) -> Vec<&PaymentRequest> {
+        let mut all: Vec<&PaymentRequest> = self.requests.values().collect();
+        all.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        all
+    }
+
+    pub fn insert(&mut self, request: PaymentRequest) {
+        self.requests.insert(request.address.clone(), request);
+    }
+
+    /// Remove a request. Returns whether there was one.
+    pub fn remove(&mut self, address: &str) -> bool {
+        self.requests.remove(address).is_some()
+    }
+}
+
+fn active_path(state: &SharedBtcxWalletState) -> Result<PathBuf, String> {
+    state
+        .get_config()
+        .registered_active_root()
+        .map(|root| PaymentRequestStore::path_in(&root))
+        .ok_or_else(|| "No active wallet".to_string())
+}
+
+fn update_active<T>(
+    state: &SharedBtcxWalletState,
+    f: impl FnOnce(&mut PaymentRequestStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = active_path(state)?;
+    let _guard = REQUESTS_LOCK.lock().map_err(|_| "request lock poisoned")?;
+    let mut store = PaymentRequestStore::load(&path)?;
+    let out = f(&mut store)?;
+    store.save(&path)?;
+    Ok(out)
+}
+
+fn read_active(state: &SharedBtcxWalletState) -> Result<PaymentRequestStore, String> {
+    let path = active_path(state)?;
+    let _guard = REQUESTS_LOCK.lock().map_err(|_| "request lock poisoned")?;
+    PaymentRequestStore::load(&path)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// What `spk` received across the canonical history: (total, confirmed).
+fn received_by(entry: &WalletEntry, spk: &ScriptBuf) -> (u64, u64) {
+    use bdk_wallet::chain::ChainPosition;
+    let mut total = 0;
+    let mut confirmed = 0;
+    for wtx in entry.wallet.transactions() {
+        let sat: u64 = wtx
+            .tx_node
+            .tx
+            .output
+            .iter()
+            .filter(|o| o.script_pubkey == *spk)
+            .map(|o| o.value.to_sat())
+            .sum();
+        total += sat;
+        if matches!(wtx.chain_position, ChainPosition::Confirmed { .. }) {
+            confirmed += sat;
+        }
+    }
+    (total, confirmed)
+}
+
+/// Decorate stored requests with their status from the synced graph.
+fn with_status(
+    state: &SharedBtcxWalletState,
+    requests: Vec<PaymentRequest>,
+) -> Result<Vec<PaymentRequestStatus>, String> {
+    let network = state.get_config().network;
+    let params = network.params();
+    let now = now_secs();
+    state.with_entry(|entry| {
+        requests
+            .into_iter()
+            .map(|request| {
+                let spk = params
+                    .parse_address(&request.address)
+                    .map_err(|e| format!("{}: {e:#}", request.address))?;
+                let (received_sat, confirmed_sat) = received_by(entry, &spk);
+                let uri = build_payment_uri(
+                    network,
+                    &PaymentUri {
+                        address: request.address.clone(),
+                        amount_sat: request.amount_sat,
+                        label: request.label.clone(),
+                        message: request.message.clone(),
+                    },
+                )?;
+                Ok(PaymentRequestStatus {
+                    status: derive_status(&request, received_sat, now),
+                    request,
+                    received_sat,
+                    confirmed_sat,
+                    uri,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Create a payment request on the CURRENT receive address (see
+/// `current_address_impl`). When that address already backs a request, a
+/// fresh one is handed out instead (capped like any new address), so each
+/// invoice has its own address. A label is also stored as the address's
+/// BIP-329 label.
+pub fn create_request(
+    state: &SharedBtcxWalletState,
+    amount_sat: Option<u64>,
+    label: Option<String>,
+    message: Option<String>,
+    expires_in_secs: Option<u64>,
+) -> Result<PaymentRequestStatus, String> {
+    if amount_sat == Some(0) {
+        return Err("The amount must be greater than zero".to_string());
+    }
+    let tidy = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let (label, message) = (tidy(label), tidy(message));
+    let mut address = super::commands::current_address_impl(state)?;
+    if read_active(state)?.get(&address).is_some() {
+        address = state
+            .backend()?
+            .wallet_new_address()
+            .map_err(|e| format!("{e:#}"))?;
+    }
+    let now = now_secs();
+    let request = PaymentRequest {
+        address: address.clone(),
+        amount_sat,
+        label: label.clone(),
+        message,
+        created_at: now,
+        expires_at: expires_in_secs.map(|s| now.saturating_add(s)),
+    };
+    update_active(state, |store| {
+        if store.get(&address).is_some() {
+            return Err(format!("{address} already backs a payment request"));
+        }
+        store.insert(request.clone());
+        Ok(())
+    })?;
+    if let Some(label) = label {
+        let written = super::labels::update_active(state, |labels| {
+            labels.set(super::labels::LabelType::Addr, &address, &label)
+        });
+        if let Err(e) = written {
+            log::warn!("btcx wallet: labelling {address} failed: {e}");
+        }
+    }
+    with_status(state, vec![request])?
+        .pop()
+        .ok_or_else(|| "request vanished".to_string())
+}
+
+/// Every payment request of the active wallet with its derived status,
+/// newest first.
+pub fn list_requests(state: &SharedBtcxWalletState) -> Result<Vec<PaymentRequestStatus>, String> {
+    let requests = read_active(state)?
+        .requests()
+        .into_iter()
+        .cloned()
+        .collect();
+    with_status(state, requests)
+}
+
+/// Delete a payment request (the address and any funds stay). Returns
+/// whether there was one.
+pub fn delete_request(state: &SharedBtcxWalletState, address: &str) -> Result<bool, String> {
+    update_active(state, |store| Ok(store.remove(address.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    fn regtest_address(b: u8) -> String {
+        let spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([b; 20]));
+        super::super::psbt::spk_to_address(WalletNetwork::Regtest, &spk).unwrap()
+    }
+
+    #[test]
+    fn uris_round_trip_and_reject_unknown_required_params() {
+        let net = WalletNetwork::Regtest;
+        let addr = regtest_address(1);
+        let request = PaymentUri {
+            address: addr.clone(),
+            amount_sat: Some(150_000_000),
+            label: Some("Pool payout #7".into()),
+            message: Some("week 12 & 13".into()),
+        };
+        let uri = build_payment_uri(net, &request).unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "btcx:{addr}?amount=1.5&label=Pool%20payout%20%237&message=week%2012%20%26%2013"
+            )
+        );
+        assert_eq!(parse_payment_uri(net, &uri).unwrap(), request);
+
+        // Other schemes, upper-case QR form, unknown optional params.
+        let parsed = parse_payment_uri(
+            net,
+            &format!("BITCOIN:{}?foo=bar&amount=0.00000001", addr.to_uppercase()),
+        )
+        .unwrap();
+        assert_eq!(parsed.address, addr);
+        assert_eq!(parsed.amount_sat, Some(1));
+        assert_eq!(parse_payment_uri(net, &addr).unwrap().amount_sat, None);
+
+        let err = parse_payment_uri(net, &format!("btcx:{addr}?req-somethingnew=1")).unwrap_err();
+        assert!(err.contains("req-somethingnew"), "{err}");
+        assert!(parse_payment_uri(net, &format!("btcx:{addr}?amount=1e3")).is_err());
+        assert!(parse_payment_uri(net, &format!("btcx:{addr}?amount=1&amount=2")).is_err());
+        assert!(parse_payment_uri(net, &format!("litecoin:{addr}")).is_err());
+        let mainnet = super::super::psbt::spk_to_address(
+            WalletNetwork::Mainnet,
+            &ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([1; 20])),
+        )
+        .unwrap();
+        assert!(parse_payment_uri(net, &format!("btcx:{mainnet}")).is_err());
+    }
+
+    #[test]
+    fn request_status_follows_what_the_address_received() {
+        let request = PaymentRequest {
+            address: regtest_address(2),
+            amount_sat: Some(1_000),
+            label: None,
+            message: None,
+            created_at: 100,
+            expires_at: Some(200),
+        };
+        assert_eq!(derive_status(&request, 0, 150), PaymentStatus::Pending);
+        assert_eq!(
+            derive_status(&request, 400, 150),
+            PaymentStatus::PartiallyPaid
+        );
+        assert_eq!(derive_status(&request, 1_000, 150), PaymentStatus::Paid);
+        assert_eq!(derive_status(&request, 400, 200), PaymentStatus::Expired);
+        assert_eq!(derive_status(&request, 1_200, 900), PaymentStatus::Paid);
+
+        let open = PaymentRequest {
+            amount_sat: None,
+            expires_at: None,
+            ..request
+        };
+        assert_eq!(derive_status(&open, 0, u64::MAX), PaymentStatus::Pending);
+        assert_eq!(derive_status(&open, 1, 150), PaymentStatus::Paid);
+    }
+}
@@ -0,0 +1,913 @@
+//! Local standardness and policy checks before broadcast.
+//!
+//! A client-side `testmempoolaccept`: the checks a default node runs on a
+//! transaction before relaying it, answered locally so a rejection comes
+//! back as structured reasons instead of a server's opaque string.
+//!
+//! - **dust**: every spendable output must be worth at least what spending
+//!   it costs at the dust relay feerate (Core's `GetDustThreshold`);
+//! - **min relay fee**: the fee must meet the coin's floor feerate;
+//! - **OP_RETURN**: at most one, at most 83 script bytes — the 46-byte
+//!   `POCX` assignment marker fits;
+//! - **weight**: at most the standard 400 000 WU;
+//! - **signatures**: every input's signature is verified against its
+//!   prevout, fetched over Electrum — P2WPKH, P2SH-P2WPKH, P2PKH, P2TR key
+//!   path and P2WSH multisig. Other scripts (taproot script paths, custom
+//!   witness scripts) are listed as unchecked and left to the server;
+//! - **conflicts**: an input already spent by one of our own unconfirmed
+//!   transactions is a double-spend unless it validly replaces it (the
+//!   original signals RBF and the new fee pays for both), one spent by a
+//!   confirmed transaction is missing.
+//!
+//! `spend::broadcast_and_record` runs it on every wallet send, batch,
+//! assignment and fee bump; a chain-only broadcast the server rejects gets
+//! the local reasons appended.
+
+use std::collections::{BTreeSet, HashMap};
+
+use bitcoin::hashes::Hash;
+use bitcoin::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{Message, Secp256k1, VerifyOnly, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache};
+use bitcoin::{
+    Amount, CompressedPublicKey, OutPoint, PublicKey, Script, ScriptBuf, Transaction, TxOut,
+    Witness,
+};
+use electrum_btcx::ElectrumBackend;
+use params_btcx::params::ChainParams;
+use serde::Serialize;
+
+use super::config::WalletNetwork;
+use super::state::BtcxWalletState;
+
+/// Standard transaction weight limit (Core's `MAX_STANDARD_TX_WEIGHT`).
+pub const MAX_STANDARD_TX_WEIGHT: u64 = 400_000;
+/// Largest relayed OP_RETURN script (Core's `-datacarriersize` default).
+pub const MAX_OP_RETURN_RELAY: usize = 83;
+/// Dust relay feerate, sat/kvB (Core's `-dustrelayfee` default).
+pub const DUST_RELAY_FEE_SAT_KVB: u64 = 3_000;
+
+/// Why a transaction would not be relayed.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RejectReason {
+    /// No inputs or no outputs.
+    Empty,
+    Dust,
+    MinRelayFee,
+    OpReturnSize,
+    MultipleOpReturn,
+    TxWeight,
+    /// A prevout that does not exist, or was spent by a confirmed tx.
+    MissingInputs,
+    OutputsExceedInputs,
+    MissingSignature,
+    InvalidSignature,
+    /// Conflicts with an unconfirmed wallet tx it may not replace.
+    DoubleSpend,
+}
+
+/// One failed check.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRejection {
+    pub reason: RejectReason,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<usize>,
+}
+
+/// Verdict of the local checks (`btcx_test_mempool_accept`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyCheck {
+    pub txid: String,
+    /// No check failed.
+    pub allowed: bool,
+    pub vsize: u64,
+    pub weight: u64,
+    /// Absent when a prevout is missing.
+    pub fee_sat: Option<u64>,
+    pub fee_rate_sat_vb: Option<f64>,
+    pub rejections: Vec<PolicyRejection>,
+    /// Inputs whose script is not verified locally (the server judges them).
+    pub unchecked_inputs: Vec<usize>,
+    /// Unconfirmed wallet transactions this one validly replaces.
+    pub replaces: Vec<String>,
+}
+
+impl PolicyCheck {
+    /// The rejections as one line, for error strings.
+    pub fn summary(&self) -> String {
+        self.rejections
+            .iter()
+            .map(|r| r.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// A wallet transaction spending one of the checked transaction's inputs.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub outpoint: OutPoint,
+    pub txid: bitcoin::Txid,
+    pub confirmed: bool,
+    /// Signals BIP-125 replaceability.
+    pub replaceable: bool,
+    pub fee_sat: Option<u64>,
+    pub vsize: u64,
+}
+
+/// Outcome of one input's script check.
+enum Verdict {
+    Valid,
+    Missing,
+    Invalid(String),
+    Unchecked,
+}
+
+/// The value below which `out` is dust: the cost of creating and later
+/// spending it at the dust relay feerate. OP_RETURN outputs are never dust.
+pub fn dust_threshold(out: &TxOut) -> u64 {
+    if out.script_pubkey.is_op_return() {
+        return 0;
+    }
+    // The spending input: outpoint, sequence, script length, and a
+    // 107-byte signature+key (discounted to a quarter for witness ones).
+    let spend = if out.script_pubkey.is_witness_program() {
+        32 + 4 + 1 + 107 / 4 + 4
+    } else {
+        32 + 4 + 1 + 107 + 4
+    };
+    let size = bitcoin::consensus::encode::serialize(out).len() as u64 + spend;
+    size * DUST_RELAY_FEE_SAT_KVB / 1000
+}
+
+/// Run every check on `tx`. `prevouts` holds the outputs its inputs spend
+/// (missing ones are rejected), `conflicts` our wallet's transactions that
+/// spend the same coins.
+pub fn check(
+    params: &ChainParams,
+    tx: &Transaction,
+    prevouts: &HashMap<OutPoint, TxOut>,
+    conflicts: &[Conflict],
+) -> PolicyCheck {
+    let txid = tx.compute_txid();
+    let vsize = tx.vsize() as u64;
+    let weight = tx.weight().to_wu();
+    let mut rejections = Vec::new();
+    let mut reject = |reason, message: String, input, output| {
+        rejections.push(PolicyRejection {
+            reason,
+            message,
+            input,
+            output,
+        })
+    };
+
+    if tx.input.is_empty() || tx.output.is_empty() {
+        reject(
+            RejectReason::Empty,
+            "The transaction has no inputs or no outputs".into(),
+            None,
+            None,
+        );
+    }
+    if weight > MAX_STANDARD_TX_WEIGHT {
+        reject(
+            RejectReason::TxWeight,
+            format!("Weight {weight} WU exceeds the standard limit of {MAX_STANDARD_TX_WEIGHT}"),
+            None,
+            None,
+        );
+    }
+
+    // Outputs: dust and OP_RETURN.
+    let mut op_returns = 0;
+    for (n, out) in tx.output.iter().enumerate() {
+        if out.script_pubkey.is_op_return() {
+            op_returns += 1;
+            let len = out.script_pubkey.len();
+            if len > MAX_OP_RETURN_RELAY {
+                reject(
+                    RejectReason::OpReturnSize,
+                    format!(
+                        "Output {n}: OP_RETURN of {len} bytes exceeds the {MAX_OP_RETURN_RELAY}-byte \
+                         relay limit"
+                    ),
+                    None,
+                    Some(n),
+                );
+            }
+            continue;
+        }
+        let threshold = dust_threshold(out);
+        if out.value.to_sat() < threshold {
+            reject(
+                RejectReason::Dust,
+                format!(
+                    "Output {n} pays {} sat, below the {threshold}-sat dust threshold",
+                    out.value.to_sat()
+                ),
+                None,
+                Some(n),
+            );
+        }
+    }
+    if op_returns > 1 {
+        reject(
+            RejectReason::MultipleOpReturn,
+            format!("{op_returns} OP_RETURN outputs — at most one is relayed"),
+            None,
+            None,
+        );
+    }
+
+    // Inputs: prevouts, then fee.
+    let spent: Vec<Option<&TxOut>> = tx
+        .input
+        .iter()
+        .map(|i| prevouts.get(&i.previous_output))
+        .collect();
+    for (index, (input, prevout)) in tx.input.iter().zip(&spent).enumerate() {
+        if prevout.is_none() {
+            reject(
+                RejectReason::MissingInputs,
+                format!(
+                    "Input {index} spends {}, which does not exist",
+                    input.previous_output
+                ),
+                Some(index),
+                None,
+            );
+        }
+    }
+    let output_sat: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+    let input_sat: Option<u64> = spent.iter().map(|p| p.map(|o| o.value.to_sat())).sum();
+    let fee_sat = match input_sat {
+        Some(input_sat) if input_sat < output_sat => {
+            reject(
+                RejectReason::OutputsExceedInputs,
+                format!("Outputs ({output_sat} sat) exceed inputs ({input_sat} sat)"),
+                None,
+                None,
+            );
+            None
+        }
+        Some(input_sat) => Some(input_sat - output_sat),
+        None => None,
+    };
+    if let Some(fee) = fee_sat {
+        let min_fee = (vsize * params.min_feerate_sat_kvb).div_ceil(1000);
+        if fee < min_fee {
+            reject(
+                RejectReason::MinRelayFee,
+                format!(
+                    "Fee {fee} sat is below the minimum relay fee of {min_fee} sat for {vsize} vB"
+                ),
+                None,
+                None,
+            );
+        }
+    }
+
+    // Signatures.
+    let mut unchecked_inputs = Vec::new();
+    let all: Option<Vec<TxOut>> = spent.iter().map(|p| p.cloned()).collect();
+    let secp = Secp256k1::verification_only();
+    let mut cache = SighashCache::new(tx);
+    for (index, prevout) in spent.iter().enumerate() {
+        let Some(prevout) = prevout else { continue };
+        match verify_input(&secp, &mut cache, tx, index, prevout, all.as_deref()) {
+            Verdict::Valid => {}
+            Verdict::Unchecked => unchecked_inputs.push(index),
+            Verdict::Missing => reject(
+                RejectReason::MissingSignature,
+                format!("Input {index} is not signed"),
+                Some(index),
+                None,
+            ),
+            Verdict::Invalid(why) => reject(
+                RejectReason::InvalidSignature,
+                format!("Input {index}: {why}"),
+                Some(index),
+                None,
+            ),
+        }
+    }
+
+    // Conflicts with our own graph.
+    let mut replaces = Vec::new();
+    let mut conflicting: Vec<&Conflict> = Vec::new();
+    for conflict in conflicts.iter().filter(|c| c.txid != txid) {
+        if conflict.confirmed {
+            reject(
+                RejectReason::MissingInputs,
+                format!(
+                    "{} was already spent by the confirmed transaction {}",
+                    conflict.outpoint, conflict.txid
+                ),
+                tx.input
+                    .iter()
+                    .position(|i| i.previous_output == conflict.outpoint),
+                None,
+            );
+        } else if !conflicting.iter().any(|c| c.txid == conflict.txid) {
+            conflicting.push(conflict);
+        }
+    }
+    if !conflicting.is_empty() {
+        match replacement_refusal(&conflicting, fee_sat, vsize, params.min_feerate_sat_kvb) {
+            None => replaces.extend(conflicting.iter().map(|c| c.txid.to_string())),
+            Some(why) => reject(RejectReason::DoubleSpend, why, None, None),
+        }
+    }
+
+    PolicyCheck {
+        txid: txid.to_string(),
+        allowed: rejections.is_empty(),
+        vsize,
+        weight,
+        fee_sat,
+        fee_rate_sat_vb: fee_sat.map(|fee| fee as f64 / vsize.max(1) as f64),
+        rejections,
+        unchecked_inputs,
+        replaces,
+    }
+}
+
+/// Why replacing the unconfirmed `conflicts` is refused (BIP-125: every
+/// original signals, the new fee covers the originals' plus its own relay,
+/// and the feerate rises); `None` when it is a valid replacement.
+fn replacement_refusal(
+    conflicts: &[&Conflict],
+    fee_sat: Option<u64>,
+    vsize: u64,
+    min_feerate_sat_kvb: u64,
+) -> Option<String> {
+    let ids = conflicts
+        .iter()
+        .map(|c| c.txid.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Some(c) = conflicts.iter().find(|c| !c.replaceable) {
+        return Some(format!(
+            "Double-spend: conflicts with the unconfirmed transaction {}, which is not replaceable",
+            c.txid
+        ));
+    }
+    let (Some(fee), Some(old)) = (
+        fee_sat,
+        conflicts.iter().map(|c| c.fee_sat).sum::<Option<u64>>(),
+    ) else {
+        return Some(format!(
+            "Double-spend: conflicts with {ids} and the fees cannot be compared"
+        ));
+    };
+    let needed = old + (vsize * min_feerate_sat_kvb).div_ceil(1000);
+    if fee < needed {
+        return Some(format!(
+            "Double-spend: replacing {ids} needs a fee of at least {needed} sat, this pays {fee}"
+        ));
+    }
+    let rate = fee as f64 / vsize.max(1) as f64;
+    if conflicts.iter().any(|c| {
+        c.fee_sat
+            .is_some_and(|old| old as f64 / c.vsize.max(1) as f64 >= rate)
+    }) {
+        return Some(format!(
+            "Double-spend: replacing {ids} needs a higher feerate than {rate:.2} sat/vB"
+        ));
+    }
+    None
+}
+
+/// Verify input `index`'s signature(s) against its `prevout`. `all` is
+/// every input's prevout (taproot signs over all of them).
+fn verify_input(
+    secp: &Secp256k1<VerifyOnly>,
+    cache: &mut SighashCache<&Transaction>,
+    tx: &Transaction,
+    index: usize,
+    prevout: &TxOut,
+    all: Option<&[TxOut]>,
+) -> Verdict {
+    let input = &tx.input[index];
+    let spk = &prevout.script_pubkey;
+    if input.witness.is_empty() && input.script_sig.is_empty() {
+        return Verdict::Missing;
+    }
+    if spk.is_p2wpkh() {
+        verify_p2wpkh(secp, cache, index, spk, prevout.value, &input.witness)
+    } else if spk.is_p2tr() {
+        verify_p2tr_key(secp, cache, index, spk, &input.witness, all)
+    } else if spk.is_p2wsh() {
+        verify_p2wsh_multisig(secp, cache, index, spk, prevout.value, &input.witness)
+    } else if spk.is_p2sh() {
+        let Some(redeem) = pushes(&input.script_sig).and_then(|p| p.last().copied()) else {
+            return Verdict::Invalid("unparsable scriptSig".into());
+        };
+        let redeem = Script::from_bytes(redeem);
+        if ScriptBuf::new_p2sh(&redeem.script_hash()) != *spk {
+            return Verdict::Invalid("redeem script does not match the output".into());
+        }
+        if redeem.is_p2wpkh() {
+            verify_p2wpkh(secp, cache, index, redeem, prevout.value, &input.witness)
+        } else {
+            Verdict::Unchecked
+        }
+    } else if spk.is_p2pkh() {
+        verify_p2pkh(secp, cache, index, spk, &input.script_sig)
+    } else {
+        Verdict::Unchecked
+    }
+}
+
+/// The data pushes of a scriptSig (`None` if it holds anything else).
+fn pushes(script: &Script) -> Option<Vec<&[u8]>> {
+    script
+        .instructions()
+        .map(|i| match i {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn verify_ecdsa(
+    secp: &Secp256k1<VerifyOnly>,
+    digest: [u8; 32],
+    sig: &bitcoin::ecdsa::Signature,
+    key: &bitcoin::secp256k1::PublicKey,
+) -> bool {
+    secp.verify_ecdsa(&Message::from_digest(digest), &sig.signature, key)
+        .is_ok()
+}
+
+/// P2WPKH (or the P2WPKH redeem script of P2SH-P2WPKH): `[sig, pubkey]`.
+fn verify_p2wpkh(
+    secp: &Secp256k1<VerifyOnly>,
+    cache: &mut SighashCache<&Transaction>,
+    index: usize,
+    program: &Script,
+    value: Amount,
+    witness: &Witness,
+) -> Verdict {
+    if witness.is_empty() {
+        return Verdict::Missing;
+    }
+    let (Some(sig), Some(key), 2) = (witness.nth(0), witness.nth(1), witness.len()) else {
+        return Verdict::Invalid("expected a signature and a public key".into());
+    };
+    let Ok(sig) = bitcoin::ecdsa::Signature::from_slice(sig) else {
+        return Verdict::Invalid("malformed signature".into());
+    };
+    let Ok(key) = CompressedPublicKey::from_slice(key) else {
+        return Verdict::Invalid("malformed public key".into());
+    };
+    if ScriptBuf::new_p2wpkh(&key.wpubkey_hash()) != *program {
+        return Verdict::Invalid("public key does not match the output".into());
+    }
+    match cache.p2wpkh_signature_hash(index, program, value, sig.sighash_type) {
+        Ok(sighash) if verify_ecdsa(secp, sighash.to_byte_array(), &sig, &key.0) => Verdict::Valid,
+        Ok(_) => Verdict::Invalid("signature does not verify".into()),
+        Err(e) => Verdict::Invalid(format!("sighash: {e}")),
+    }
+}
+
+/// P2PKH: scriptSig `<sig> <pubkey>`.
+fn verify_p2pkh(
+    secp: &Secp256k1<VerifyOnly>,
+    cache: &mut SighashCache<&Transaction>,
+    index: usize,
+    spk: &Script,
+    script_sig: &Script,
+) -> Verdict {
+    let Some(&[sig, key]) = pushes(script_sig).as_deref() else {
+        return Verdict::Invalid("expected a signature and a public key".into());
+    };
+    let Ok(sig) = bitcoin::ecdsa::Signature::from_slice(sig) else {
+        return Verdict::Invalid("malformed signature".into());
+    };
+    let Ok(key) = PublicKey::from_slice(key) else {
+        return Verdict::Invalid("malformed public key".into());
+    };
+    if ScriptBuf::new_p2pkh(&key.pubkey_hash()) != *spk {
+        return Verdict::Invalid("public key does not match the output".into());
+    }
+    match cache.legacy_signature_hash(index, spk, sig.sighash_type.to_u32()) {
+        Ok(sighash) if verify_ecdsa(secp, sighash.to_byte_array(), &sig, &key.inner) => {
+            Verdict::Valid
+        }
+        Ok(_) => Verdict::Invalid("signature does not verify".into()),
+        Err(e) => Verdict::Invalid(format!("sighash: {e}")),
+    }
+}
+
+/// P2TR key path: a single Schnorr signature (script paths are unchecked).
+fn verify_p2tr_key(
+    secp: &Secp256k1<VerifyOnly>,
+    cache: &mut SighashCache<&Transaction>,
+    index: usize,
+    spk: &Script,
+    witness: &Witness,
+    all: Option<&[TxOut]>,
+) -> Verdict {
+    let (Some(all), 1) = (all, witness.len()) else {
+        return Verdict::Unchecked;
+    };
+    let Some(Ok(sig)) = witness.nth(0).map(bitcoin::taproot::Signature::from_slice) else {
+        return Verdict::Invalid("malformed signature".into());
+    };
+    let Ok(key) = XOnlyPublicKey::from_slice(&spk.as_bytes()[2..]) else {
+        return Verdict::Invalid("malformed output key".into());
+    };
+    match cache.taproot_key_spend_signature_hash(index, &Prevouts::All(all), sig.sighash_type) {
+        Ok(sighash) => {
+            let msg = Message::from_digest(sighash.to_byte_array());
+            match secp.verify_schnorr(&sig.signature, &msg, &key) {
+                Ok(()) => Verdict::Valid,
+                Err(_) => Verdict::Invalid("signature does not verify".into()),
+            }
+        }
+        Err(e) => Verdict::Invalid(format!("sighash: {e}")),
+    }
+}
+
+/// `OP_m <keys> OP_n OP_CHECKMULTISIG`: (m, keys in order).
+fn multisig_keys(script: &Script) -> Option<(usize, Vec<PublicKey>)> {
+    let ins = script.instructions().collect::<Result<Vec<_>, _>>().ok()?;
+    let small = |i: &Instruction| match i {
+        Instruction::Op(op) => {
+            let v = op.to_u8();
+            (0x51..=0x60).contains(&v).then(|| usize::from(v - 0x50))
+        }
+        _ => None,
+    };
+    let (first, rest) = ins.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (n_op, keys) = rest.split_last()?;
+    if *last != Instruction::Op(OP_CHECKMULTISIG) {
+        return None;
+    }
+    let (m, n) = (small(first)?, small(n_op)?);
+    let keys = keys
+        .iter()
+        .map(|i| match i {
+            Instruction::PushBytes(bytes) => PublicKey::from_slice(bytes.as_bytes()).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (keys.len() == n && (1..=n).contains(&m)).then_some((m, keys))
+}
+
+/// P2WSH multisig: `[<empty>, sig.., witness script]`, signatures in key
+/// order (CHECKMULTISIG). Other witness scripts are unchecked.
+fn verify_p2wsh_multisig(
+    secp: &Secp256k1<VerifyOnly>,
+    cache: &mut SighashCache<&Transaction>,
+    index: usize,
+    spk: &Script,
+    value: Amount,
+    witness: &Witness,
+) -> Verdict {
+    let items: Vec<&[u8]> = witness.iter().collect();
+    let Some((script, rest)) = items.split_last() else {
+        return Verdict::Missing;
+    };
+    let script = Script::from_bytes(script);
+    if ScriptBuf::new_p2wsh(&script.wscript_hash()) != *spk {
+        return Verdict::Invalid("witness script does not match the output".into());
+    }
+    let Some((m, keys)) = multisig_keys(script) else {
+        return Verdict::Unchecked;
+    };
+    let Some((dummy, sigs)) = rest.split_first() else {
+        return Verdict::Missing;
+    };
+    if !dummy.is_empty() {
+        return Verdict::Invalid("CHECKMULTISIG dummy element must be empty".into());
+    }
+    let sigs: Vec<&[u8]> = sigs.iter().copied().filter(|s| !s.is_empty()).collect();
+    if sigs.len() < m {
+        return Verdict::Missing;
+    }
+    let mut keys = keys.iter();
+    for raw in sigs {
+        let Ok(sig) = bitcoin::ecdsa::Signature::from_slice(raw) else {
+            return Verdict::Invalid("malformed signature".into());
+        };
+        let digest = match cache.p2wsh_signature_hash(index, script, value, sig.sighash_type) {
+            Ok(sighash) => sighash.to_byte_array(),
+            Err(e) => return Verdict::Invalid(format!("sighash: {e}")),
+        };
+        if !keys.any(|key| verify_ecdsa(secp, digest, &sig, &key.inner)) {
+            return Verdict::Invalid("signatures do not match the keys in order".into());
+        }
+    }
+    Verdict::Valid
+}
+
+/// The transactions `tx`'s inputs spend, fetched over Electrum and keyed
+/// by txid. One the server does not know is left out; a server that
+/// answers nothing at all is an error.
+pub fn fetch_prev_txs(
+    chain: &ElectrumBackend,
+    tx: &Transaction,
+) -> Result<HashMap<bitcoin::Txid, Transaction>, String> {
+    let txids: Vec<String> = tx
+        .input
+        .iter()
+        .map(|i| i.previous_output.txid.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let prevs = match chain.get_raw_txs(&txids) {
+        Ok(prevs) => prevs,
+        // The batch fails as a whole on one unknown txid: ask one by one.
+        Err(batch) => {
+            let found: Vec<Transaction> = txids
+                .iter()
+                .filter_map(|txid| chain.get_raw_tx(txid).ok())
+                .collect();
+            if found.is_empty() && !txids.is_empty() {
+                return Err(format!("fetching the prevouts: {batch:#}"));
+            }
+            found
+        }
+    };
+    Ok(prevs.into_iter().map(|p| (p.compute_txid(), p)).collect())
+}
+
+/// The outputs `tx`'s inputs spend, fetched over Electrum. A prevout the
+/// server does not know is left out (and rejected by [`check`]).
+pub fn fetch_prevouts(
+    chain: &ElectrumBackend,
+    tx: &Transaction,
+) -> Result<HashMap<OutPoint, TxOut>, String> {
+    let by_txid = fetch_prev_txs(chain, tx)?;
+    Ok(tx
+        .input
+        .iter()
+        .filter_map(|i| {
+            let out = by_txid
+                .get(&i.previous_output.txid)?
+                .output
+                .get(i.previous_output.vout as usize)?;
+            Some((i.previous_output, out.clone()))
+        })
+        .collect())
+}
+
+/// Every canonical transaction of the open wallet that spends one of
+/// `tx`'s inputs (none when no wallet is open).
+pub fn wallet_conflicts(state: &BtcxWalletState, tx: &Transaction) -> Vec<Conflict> {
+    let spent: BTreeSet<OutPoint> = tx.input.iter().map(|i| i.previous_output).collect();
+    state
+        .with_entry(|entry| {
+            let mut conflicts = Vec::new();
+            for wtx in entry.wallet.transactions() {
+                let other = &wtx.tx_node.tx;
+                for input in other
+                    .input
+                    .iter()
+                    .filter(|i| spent.contains(&i.previous_output))
+                {
+                    conflicts.push(Conflict {
+                        outpoint: input.previous_output,
+                        txid: wtx.tx_node.txid,
+                        confirmed: matches!(
+                            wtx.chain_position,
+                            bdk_wallet::chain::ChainPosition::Confirmed { .. }
+                        ),
+                        replaceable: other.is_explicitly_rbf(),
+                        fee_sat: entry.wallet.calculate_fee(other).ok().map(|f| f.to_sat()),
+                        vsize: other.vsize() as u64,
+                    });
+                }
+            }
+            Ok(conflicts)
+        })
+        .unwrap_or_default()
+}
+
+/// Check a wallet transaction before `spend::broadcast_and_record` sends it.
+pub fn check_wallet_tx(
+    state: &BtcxWalletState,
+    params: &ChainParams,
+    chain: &ElectrumBackend,
+    tx: &Transaction,
+) -> Result<PolicyCheck, String> {
+    let prevouts = fetch_prevouts(chain, tx)?;
+    Ok(check(params, tx, &prevouts, &wallet_conflicts(state, tx)))
+}
+
+/// `btcx_test_mempool_accept`: check a raw transaction for `network`
+/// (default: the active one) — against the open wallet's server and graph
+/// when it is on that network, otherwise chain-only (like
+/// `btcx_broadcast_tx`). Servers are tried in turn until one returns the
+/// prevouts.
+pub fn test_accept(
+    state: &BtcxWalletState,
+    tx_hex: &str,
+    network: Option<WalletNetwork>,
+) -> Result<PolicyCheck, String> {
+    let tx: Transaction = bitcoin::consensus::encode::deserialize_hex(tx_hex.trim())
+        .map_err(|e| format!("Not a valid raw transaction: {e}"))?;
+    let network = network.unwrap_or(state.get_config().network);
+    let conflicts = if state
+        .open_wallet_name()
+        .is_some_and(|(open, _)| open == network)
+    {
+        wallet_conflicts(state, &tx)
+    } else {
+        Vec::new()
+    };
+    let prevouts = state.with_network_chain(network, |chain| fetch_prevouts(chain, &tx))?;
+    Ok(check(network.params(), &tx, &prevouts, &conflicts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::sighash::EcdsaSighashType;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Sequence, TxIn, Txid};
+
+    const PREV_SAT: u64 = 100_000;
+
+    fn params() -> &'static ChainParams {
+        WalletNetwork::Regtest.params()
+    }
+
+    /// A P2WPKH coin of `PREV_SAT` and a signed spend of it paying `outputs`.
+    fn signed_spend(outputs: Vec<TxOut>) -> (Transaction, HashMap<OutPoint, TxOut>) {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let key = CompressedPublicKey(sk.public_key(&secp));
+        let spk = ScriptBuf::new_p2wpkh(&key.wpubkey_hash());
+        let outpoint = OutPoint {
+            txid: Txid::from_byte_array([1; 32]),
+            vout: 0,
+        };
+        let mut tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: outputs,
+        };
+        let sighash = SighashCache::new(&tx)
+            .p2wpkh_signature_hash(0, &spk, Amount::from_sat(PREV_SAT), EcdsaSighashType::All)
+            .unwrap();
+        let sig = secp.sign_ecdsa(&Message::from_digest(sighash.to_byte_array()), &sk);
+        tx.input[0].witness = Witness::p2wpkh(&bitcoin::ecdsa::Signature::sighash_all(sig), &key.0);
+        let prevouts = HashMap::from([(
+            outpoint,
+            TxOut {
+                value: Amount::from_sat(PREV_SAT),
+                script_pubkey: spk,
+            },
+        )]);
+        (tx, prevouts)
+    }
+
+    fn pay(value_sat: u64) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value_sat),
+            script_pubkey: ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([9; 20])),
+        }
+    }
+
+    fn op_return(len: usize) -> ScriptBuf {
+        let data = vec![0u8; len];
+        ScriptBuf::new_op_return(<&bitcoin::script::PushBytes>::try_from(data.as_slice()).unwrap())
+    }
+
+    fn reasons(check: &PolicyCheck) -> Vec<RejectReason> {
+        check.rejections.iter().map(|r| r.reason).collect()
+    }
+
+    #[test]
+    fn signed_standard_spend_is_accepted() {
+        let (tx, prevouts) = signed_spend(vec![pay(90_000)]);
+        let check = check(params(), &tx, &prevouts, &[]);
+        assert!(check.allowed, "{check:?}");
+        assert_eq!(check.fee_sat, Some(10_000));
+        assert!(check.unchecked_inputs.is_empty());
+
+        // A 44-byte POCX assignment marker is a 46-byte OP_RETURN: relayed.
+        let marker = op_return(44);
+        assert_eq!(marker.len(), 46);
+        let (tx, prevouts) = signed_spend(vec![
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: marker,
+            },
+            pay(90_000),
+        ]);
+        assert!(check_ok(&tx, &prevouts));
+    }
+
+    fn check_ok(tx: &Transaction, prevouts: &HashMap<OutPoint, TxOut>) -> bool {
+        check(params(), tx, prevouts, &[]).allowed
+    }
+
+    #[test]
+    fn policy_violations_are_reported() {
+        // P2WPKH dust threshold: (31 + 67) vB at 3 sat/vB.
+        assert_eq!(dust_threshold(&pay(0)), 294);
+        let (tx, prevouts) = signed_spend(vec![pay(293), pay(90_000)]);
+        let verdict = check(params(), &tx, &prevouts, &[]);
+        assert_eq!(reasons(&verdict), vec![RejectReason::Dust]);
+        assert_eq!(verdict.rejections[0].output, Some(0));
+
+        let big = op_return(81);
+        let (tx, prevouts) = signed_spend(vec![
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: big.clone(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: big,
+            },
+            pay(90_000),
+        ]);
+        assert_eq!(
+            reasons(&check(params(), &tx, &prevouts, &[])),
+            vec![
+                RejectReason::OpReturnSize,
+                RejectReason::OpReturnSize,
+                RejectReason::MultipleOpReturn
+            ]
+        );
+
+        // Outputs above the input; then an unknown prevout.
+        let (tx, prevouts) = signed_spend(vec![pay(PREV_SAT + 1)]);
+        assert_eq!(
+            reasons(&check(params(), &tx, &prevouts, &[])),
+            vec![RejectReason::OutputsExceedInputs]
+        );
+        let (tx, _) = signed_spend(vec![pay(90_000)]);
+        let verdict = check(params(), &tx, &HashMap::new(), &[]);
+        assert_eq!(reasons(&verdict), vec![RejectReason::MissingInputs]);
+        assert_eq!(verdict.fee_sat, None);
+    }
+
+    #[test]
+    fn signatures_are_verified_against_the_prevout() {
+        let (mut tx, prevouts) = signed_spend(vec![pay(90_000)]);
+        // Changing an output after signing breaks the SIGHASH_ALL signature.
+        tx.output[0].value = Amount::from_sat(89_000);
+        assert_eq!(
+            reasons(&check(params(), &tx, &prevouts, &[])),
+            vec![RejectReason::InvalidSignature]
+        );
+        tx.input[0].witness = Witness::new();
+        assert_eq!(
+            reasons(&check(params(), &tx, &prevouts, &[])),
+            vec![RejectReason::MissingSignature]
+        );
+    }
+
+    #[test]
+    fn conflicts_need_a_valid_replacement() {
+        let (tx, prevouts) = signed_spend(vec![pay(90_000)]);
+        let conflict = |replaceable, fee_sat| Conflict {
+            outpoint: tx.input[0].previous_output,
+            txid: Txid::from_byte_array([2; 32]),
+            confirmed: false,
+            replaceable,
+            fee_sat: Some(fee_sat),
+            vsize: 110,
+        };
+        let verdict = check(params(), &tx, &prevouts, &[conflict(false, 1_000)]);
+        assert_eq!(reasons(&verdict), vec![RejectReason::DoubleSpend]);
+        let verdict = check(params(), &tx, &prevouts, &[conflict(true, 20_000)]);
+        assert_eq!(reasons(&verdict), vec![RejectReason::DoubleSpend]);
+        // A fee bump of a replaceable original is fine.
+        let verdict = check(params(), &tx, &prevouts, &[conflict(true, 1_000)]);
+        assert!(verdict.allowed, "{verdict:?}");
+        assert_eq!(verdict.replaces.len(), 1);
+
+        let spent = Conflict {
+            confirmed: true,
+            ..conflict(true, 1_000)
+        };
+        assert_eq!(
+            reasons(&check(params(), &tx, &prevouts, &[spent])),
+            vec![RejectReason::MissingInputs]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Two-step spends: preview, then commit.
+//!
+//! A preview builds and SIGNS the exact transaction a send, sweep,
+//! assignment or revocation would broadcast, parks it under a random
+//! short-lived handle and returns its summary — txid, fee, vsize, every
+//! input and output, change. The commit broadcasts exactly that parked
+//! transaction: it never touches a tx builder, so coin selection cannot
+//! silently differ from what the user confirmed. If the wallet moved on in
+//! between (a parked input got spent, frozen, or the wallet was switched),
+//! the commit refuses and the user previews again.
+//!
+//! While parked, a preview keeps its change address revealed so a second
+//! preview never shares it, and its inputs are reserved: no other build's
+//! coin selection picks them ([`reserved`], `spend::CoinControl`), so two
+//! live previews never race for one coin unless the user named it
+//! explicitly. Discarding or expiring a preview hands the address back
+//! (`cancel_tx`), the way a batch dry run does, and frees its coins.
+
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use bitcoin::{OutPoint, Transaction};
+use rand::Rng;
+use serde::Serialize;
+
+use super::config::WalletNetwork;
+use super::spend;
+use super::state::{BtcxWalletState, SharedBtcxWalletState};
+
+/// How long a preview can be committed.
+pub const PREVIEW_TTL: Duration = Duration::from_secs(300);
+
+/// What a preview spends for.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewKind {
+    Send,
+    Sweep,
+    Assignment,
+    Revocation,
+}
+
+/// A signed, not yet broadcast transaction waiting for its commit.
+#[derive(Debug, Clone)]
+pub struct PendingTx {
+    pub kind: PreviewKind,
+    /// The wallet that signed it — a commit on another wallet is refused.
+    pub wallet: (WalletNetwork, String),
+    pub tx: Transaction,
+    pub created: Instant,
+}
+
+impl PendingTx {
+    pub fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.created) >= PREVIEW_TTL
+    }
+}
+
+/// One coin a preview spends.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewInput {
+    /// `txid:vout`.
+    pub outpoint: String,
+    pub value_sat: u64,
+}
+
+/// One output of a preview, in transaction order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewOutput {
+    pub n: u32,
+    pub address: Option<String>,
+    pub value_sat: u64,
+    /// Pays our own change address.
+    pub change: bool,
+    pub op_return: bool,
+}
+
+/// The summary of a parked transaction (`btcx_wallet_preview_*`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPreview {
+    /// Pass to `btcx_wallet_commit_preview` / `btcx_wallet_discard_preview`.
+    pub handle: String,
+    pub kind: PreviewKind,
+    /// The txid the commit will broadcast.
+    pub txid: String,
+    pub fee_sat: u64,
+    pub vsize: u64,
+    pub fee_rate_sat_vb: f64,
+    /// Sum paid to outputs other than our change.
+    pub amount_sat: u64,
+    pub change_sat: u64,
+    pub inputs: Vec<PreviewInput>,
+    pub outputs: Vec<PreviewOutput>,
+    /// Seconds left to commit.
+    pub expires_in_secs: u64,
+}
+
+/// A fresh random handle.
+fn new_handle() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 16]>())
+}
+
+/// Hand a dropped preview's change address back to the wallet — only if
+/// that wallet is still the open one (a closed wallet reloads from disk).
+fn release(state: &BtcxWalletState, pending: &PendingTx) {
+    if state.open_wallet_name().as_ref() == Some(&pending.wallet) {
+        let _ = state.with_entry(|entry| {
+            entry.wallet.cancel_tx(&pending.tx);
+            Ok(())
+        });
+    }
+}
+
+/// Release every expired preview.
+fn prune(state: &BtcxWalletState) {
+    for pending in state.take_expired_previews(Instant::now()) {
+        release(state, &pending);
+    }
+}
+
+/// The coins the live previews spend (expired ones are released first).
+pub fn reserved(state: &BtcxWalletState) -> BTreeSet<OutPoint> {
+    prune(state);
+    state.preview_inputs(Instant::now())
+}
+
+/// Park a signed wallet transaction and summarize it.
+pub fn stage(
+    state: &BtcxWalletState,
+    kind: PreviewKind,
+    tx: Transaction,
+) -> Result<TxPreview, String> {
+    prune(state);
+    let wallet = state
+        .open_wallet_name()
+        .ok_or("The nodeless wallet is not open")?;
+    let network = wallet.0;
+    let (fee_sat, inputs, changes) = state.with_entry(|entry| {
+        let fee = entry
+            .wallet
+            .calculate_fee(&tx)
+            .map_err(|e| format!("computing the fee: {e}"))?;
+        let inputs = tx
+            .input
+            .iter()
+            .map(|i| PreviewInput {
+                outpoint: i.previous_output.to_string(),
+                value_sat: entry
+                    .wallet
+                    .get_utxo(i.previous_output)
+                    .map(|u| u.txout.value.to_sat())
+                    .unwrap_or(0),
+            })
+            .collect::<Vec<_>>();
+        let changes = tx
+            .output
+            .iter()
+            .map(|o| {
+                matches!(
+                    entry.wallet.derivation_of_spk(o.script_pubkey.clone()),
+                    Some((bdk_wallet::KeychainKind::Internal, _))
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok((fee.to_sat(), inputs, changes))
+    })?;
+
+    let outputs: Vec<PreviewOutput> = tx
+        .output
+        .iter()
+        .zip(&changes)
+        .enumerate()
+        .map(|(n, (o, &change))| PreviewOutput {
+            n: n as u32,
+            address: super::psbt::spk_to_address(network, &o.script_pubkey),
+            value_sat: o.value.to_sat(),
+            change,
+            op_return: o.script_pubkey.is_op_return(),
+        })
+        .collect();
+    let change_sat = outputs
+        .iter()
+        .filter(|o| o.change)
+        .map(|o| o.value_sat)
+        .sum();
+    let amount_sat = outputs
+        .iter()
+        .filter(|o| !o.change)
+        .map(|o| o.value_sat)
+        .sum();
+    let vsize = tx.vsize() as u64;
+    let handle = new_handle();
+    let preview = TxPreview {
+        handle: handle.clone(),
+        kind,
+        txid: tx.compute_txid().to_string(),
+        fee_sat,
+        vsize,
+        fee_rate_sat_vb: fee_sat as f64 / vsize.max(1) as f64,
+        amount_sat,
+        change_sat,
+        inputs,
+        outputs,
+        expires_in_secs: PREVIEW_TTL.as_secs(),
+    };
+    state.store_preview(
+        handle,
+        PendingTx {
+            kind,
+            wallet,
+            tx,
+            created: Instant::now(),
+        },
+    );
+    Ok(preview)
+}
+
+/// Broadcast exactly the parked transaction of `handle`. Refused — and the
+/// preview dropped — when it expired, the wallet changed, or one of its
+/// coins is no longer an unspent, unfrozen coin of this wallet. Any other
+/// failure (labels unreadable, servers down, broadcast rejected) keeps the
+/// preview so the commit can be retried.
+pub fn commit(state: &SharedBtcxWalletState, handle: &str) -> Result<String, String> {
+    prune(state);
+    let pending = state
+        .take_preview(handle)
+        .ok_or("Unknown or expired preview — preview the transaction again")?;
+    match broadcast_pending(state, &pending) {
+        Ok(txid) => Ok(txid),
+        Err((e, true)) => {
+            release(state, &pending);
+            Err(e)
+        }
+        Err((e, false)) => {
+            state.store_preview(handle.to_string(), pending);
+            Err(e)
+        }
+    }
+}
+
+/// The commit's checks and broadcast; errors carry whether the preview is
+/// stale (drop it) or the commit may be retried.
+fn broadcast_pending(
+    state: &SharedBtcxWalletState,
+    pending: &PendingTx,
+) -> Result<String, (String, bool)> {
+    let retry = |e: String| (e, false);
+    if state.open_wallet_name().as_ref() != Some(&pending.wallet) {
+        return Err((
+            "The wallet changed since the preview — preview the transaction again".into(),
+            true,
+        ));
+    }
+    let spent: Vec<OutPoint> = pending.tx.input.iter().map(|i| i.previous_output).collect();
+    let frozen = spend::frozen_outpoints(state).map_err(retry)?;
+    let unspent = state
+        .with_entry(|entry| {
+            Ok(entry
+                .wallet
+                .list_unspent()
+                .map(|u| u.outpoint)
+                .collect::<BTreeSet<_>>())
+        })
+        .map_err(retry)?;
+    if let Some(gone) = spent.iter().find(|o| !unspent.contains(o)) {
+        return Err((
+            format!("Coin {gone} was spent since the preview — preview the transaction again"),
+            true,
+        ));
+    }
+    spend::check_not_frozen(&spent, &frozen).map_err(|e| (e, true))?;
+
+    let backend = state.backend().map_err(retry)?;
+    spend::broadcast_and_record(state, &backend, &pending.tx).map_err(retry)
+}
+
+/// Drop a preview without broadcasting it (unknown handles are fine).
+pub fn discard(state: &BtcxWalletState, handle: &str) {
+    if let Some(pending) = state.take_preview(handle) {
+        release(state, &pending);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_expire_after_the_ttl() {
+        let pending = PendingTx {
+            kind: PreviewKind::Send,
+            wallet: (WalletNetwork::Regtest, "main".into()),
+            tx: Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::absolute::LockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            },
+            created: Instant::now(),
+        };
+        assert!(!pending.expired(pending.created));
+        assert!(!pending.expired(pending.created + PREVIEW_TTL - Duration::from_secs(1)));
+        assert!(pending.expired(pending.created + PREVIEW_TTL));
+
+        let (a, b) = (new_handle(), new_handle());
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, b);
+    }
+}
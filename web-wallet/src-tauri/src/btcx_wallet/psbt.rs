@@ -6,11 +6,17 @@
 //! (compose → sign → finalize → broadcast, import → sign, combine
 //! half-signed copies) over `bitcoin::Psbt` + the BDK wallet.
 //!
+//! These flows are also the ONLY spend path of two wallet kinds: a
+//! watch-only wallet composes and hands the unsigned PSBT to the key
+//! holder, and a `wsh(sortedmulti)` multisig wallet composes, adds its own
+//! partial signature (`wallet_process`), and `combine`s the cosigners'
+//! copies — `finalize` completes once the threshold of signatures is in.
+//!
 //! Deliberately NOT reproduced: finalizing FOREIGN non-final inputs (needs
 //! a miniscript satisfier — the wallet finalizes its own inputs and passes
-//! through inputs that are already final), `joinpsbts`, and
-//! `testmempoolaccept` (no Electrum equivalent; the broadcast fan-over is
-//! the mempool test).
+//! through inputs that are already final) and `joinpsbts`. The
+//! raw-transaction RPCs (`decoderawtransaction`, `converttopsbt`,
+//! `utxoupdatepsbt`, ...) live in `rawtx`, `testmempoolaccept` in `policy`.
 //!
 //! Addresses are rendered under the chain's own HRP (pocx/tpocx/rpocx) —
 //! never through `bitcoin::Address`, which only knows bc/tb/bcrt.
@@ -24,10 +30,11 @@ use serde::{Deserialize, Serialize};
 use electrum_btcx::SendFee;
 
 use super::config::WalletNetwork;
+use super::labels::LabelType;
 use super::state::SharedBtcxWalletState;
 
 /// Parse a base64 PSBT string.
-fn parse_psbt(psbt_base64: &str) -> Result<Psbt, String> {
+pub(super) fn parse_psbt(psbt_base64: &str) -> Result<Psbt, String> {
     Psbt::from_str(psbt_base64.trim()).map_err(|e| format!("Not a valid PSBT: {e}"))
 }
 
@@ -122,7 +129,7 @@ pub struct PsbtDecodeDto {
 }
 
 /// Whether one PSBT input is final (finalized witness or scriptSig).
-fn input_is_final(input: &bitcoin::psbt::Input) -> bool {
+pub(super) fn input_is_final(input: &bitcoin::psbt::Input) -> bool {
     input.final_script_witness.is_some() || input.final_script_sig.is_some()
 }
 
@@ -137,6 +144,21 @@ fn input_utxo(psbt: &Psbt, index: usize) -> Option<bitcoin::TxOut> {
     prev.output.get(vout).cloned()
 }
 
+/// A transaction's outputs as the decode views show them.
+pub(super) fn vout_dtos(network: WalletNetwork, tx: &bitcoin::Transaction) -> Vec<PsbtVoutDto> {
+    tx.output
+        .iter()
+        .enumerate()
+        .map(|(n, o)| PsbtVoutDto {
+            n: n as u32,
+            value_sat: o.value.to_sat(),
+            address: spk_to_address(network, &o.script_pubkey),
+            script_hex: o.script_pubkey.to_hex_string(),
+            op_return: o.script_pubkey.is_op_return(),
+        })
+        .collect()
+}
+
 /// Decode a PSBT for display (client-side `decodepsbt`).
 pub fn decode(network: WalletNetwork, psbt_base64: &str) -> Result<PsbtDecodeDto, String> {
     let psbt = parse_psbt(psbt_base64)?;
@@ -151,18 +173,7 @@ pub fn decode(network: WalletNetwork, psbt_base64: &str) -> Result<PsbtDecodeDto
             sequence: i.sequence.0,
         })
         .collect();
-    let vout = tx
-        .output
-        .iter()
-        .enumerate()
-        .map(|(n, o)| PsbtVoutDto {
-            n: n as u32,
-            value_sat: o.value.to_sat(),
-            address: spk_to_address(network, &o.script_pubkey),
-            script_hex: o.script_pubkey.to_hex_string(),
-            op_return: o.script_pubkey.is_op_return(),
-        })
-        .collect();
+    let vout = vout_dtos(network, tx);
     let inputs: Vec<PsbtInputDto> = psbt
         .inputs
         .iter()
@@ -449,8 +460,9 @@ pub struct PsbtComposeOptions {
 }
 
 /// Compose a funded, UNSIGNED PSBT paying `outputs` (client-side
-/// `walletcreatefundedpsbt`): the wallet coin-selects, adds change, and
-/// signals RBF; signing is a separate step (`wallet_process`).
+/// `walletcreatefundedpsbt`): the wallet coin-selects (never a frozen coin),
+/// adds change, and signals RBF; signing is a separate step
+/// (`wallet_process`).
 pub fn create_funded_psbt(
     state: &SharedBtcxWalletState,
     outputs: &[PsbtRecipient],
@@ -468,12 +480,8 @@ pub fn create_funded_psbt(
     }
     let manual_utxos: Option<Vec<bitcoin::OutPoint>> = options
         .utxos
-        .as_ref()
-        .map(|list| {
-            list.iter()
-                .map(|s| bitcoin::OutPoint::from_str(s).map_err(|e| format!("{s}: {e}")))
-                .collect::<Result<Vec<_>, _>>()
-        })
+        .as_deref()
+        .map(super::spend::parse_outpoints)
         .transpose()?;
     let push_data: Option<bitcoin::script::PushBytesBuf> = options
         .data_hex
@@ -500,11 +508,7 @@ pub fn create_funded_psbt(
         Some(rate) => SendFee::RatePerKvb((rate * 1000.0).round().max(0.0) as u64),
         None => SendFee::Target(6),
     };
-    let feerate_kvb = backend
-        .chain()
-        .resolve_send_fee(fee)
-        .map_err(|e| format!("{e:#}"))?;
-    let feerate = bitcoin::FeeRate::from_sat_per_kwu((feerate_kvb + 2) / 4);
+    let feerate = super::spend::resolve_feerate(&backend, fee)?;
 
     #[allow(clippy::type_complexity)]
     let spks: Vec<(ScriptBuf, u64)> = outputs
@@ -517,7 +521,7 @@ pub fn create_funded_psbt(
         })
         .collect::<Result<_, _>>()?;
 
-    state.ensure_first_sync()?;
+    let control = super::spend::coin_control(state)?;
     let psbt = state.with_entry(|entry| {
         // subtract_fee_output carries DRAIN semantics: the builder UI only
         // sets it via MAX ("everything left"), so the target output receives
@@ -538,19 +542,13 @@ pub fn create_funded_psbt(
         if let Some(data) = &push_data {
             builder.add_data(data);
         }
-        match (&manual_utxos, options.subtract_fee_output) {
-            (Some(outpoints), _) => {
-                builder
-                    .add_utxos(outpoints)
-                    .map_err(|e| format!("coin selection: {e}"))?;
-                builder.manually_selected_only();
-            }
-            // Auto coins + MAX: the target drains the WHOLE wallet (minus
-            // the other outputs and fee) — spend everything.
-            (None, Some(_)) => {
-                builder.drain_wallet();
-            }
-            (None, None) => {}
+        // Manual coins are spent exactly; frozen and unverified coins are
+        // never selected (frozen ones refused when named manually).
+        super::spend::apply_coin_control(&mut builder, manual_utxos.as_deref(), &control)?;
+        // Auto coins + MAX: the target drains the WHOLE wallet (minus the
+        // other outputs, the fee and any skipped coins) — spend everything.
+        if manual_utxos.is_none() && options.subtract_fee_output.is_some() {
+            builder.drain_wallet();
         }
         for (i, (spk, amount_sat)) in spks.iter().enumerate() {
             if options.subtract_fee_output == Some(i) {
@@ -600,11 +598,19 @@ pub struct WalletUtxoDto {
     /// P2WPKH the coins are then guarded only by the pubkey, not its hash; the
     /// coins view surfaces this as "Public key known".
     pub exposed: bool,
+    /// The user's BIP-329 label: the coin's own `output` label, else the
+    /// label of the address it sits on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Frozen by the user — excluded from every coin selection.
+    pub frozen: bool,
 }
 
 /// The wallet's unspent outputs, from the background-synced cache.
 pub fn wallet_utxos(state: &SharedBtcxWalletState) -> Result<Vec<WalletUtxoDto>, String> {
     let network = state.get_config().network;
+    let labels = super::labels::display_labels(state);
+    let frozen = labels.frozen();
     state.with_entry(|entry| {
         let tip = entry.wallet.latest_checkpoint().height();
         // Scripts whose pubkey is revealed on-chain: any output the wallet owns
@@ -624,14 +630,25 @@ pub fn wallet_utxos(state: &SharedBtcxWalletState) -> Result<Vec<WalletUtxoDto>,
                     Some(height) if height <= tip => tip - height + 1,
                     _ => 0,
                 };
+                let address = spk_to_address(network, &utxo.txout.script_pubkey);
+                let label = labels
+                    .label(LabelType::Output, &utxo.outpoint.to_string())
+                    .or_else(|| {
+                        address
+                            .as_deref()
+                            .and_then(|a| labels.label(LabelType::Addr, a))
+                    })
+                    .map(str::to_string);
                 WalletUtxoDto {
                     txid: utxo.outpoint.txid.to_string(),
                     vout: utxo.outpoint.vout,
                     amount_sat: utxo.txout.value.to_sat(),
-                    address: spk_to_address(network, &utxo.txout.script_pubkey),
+                    address,
                     confirmations,
                     is_change: utxo.keychain == bdk_wallet::KeychainKind::Internal,
                     exposed: exposed_scripts.contains(&utxo.txout.script_pubkey),
+                    label,
+                    frozen: frozen.contains(&utxo.outpoint),
                 }
             })
             .collect())
@@ -0,0 +1,469 @@
+//! Client-side raw transaction tools for the Transaction Builder
+//!
+//! The raw-transaction counterparts of the `psbt` module: remote
+//! (nodeless) mode has no `decoderawtransaction`/`createrawtransaction`/
+//! `converttopsbt`/`utxoupdatepsbt` node RPCs. Decoding, creation and the
+//! raw tx ⇄ PSBT conversions are pure; the UTXO update fetches the spent
+//! transactions over Electrum (chain-only: no open wallet needed). Signing
+//! goes through the PSBT tools (`btcx_psbt_wallet_process`).
+//!
+//! Addresses are rendered with `psbt::spk_to_address` and parsed with the
+//! chain params — the chain's own HRPs and base58 versions.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use bitcoin::{
+    absolute, transaction, Amount, OutPoint, Psbt, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Txid, Witness,
+};
+use electrum_btcx::ElectrumBackend;
+use serde::{Deserialize, Serialize};
+
+use super::config::WalletNetwork;
+use super::psbt::{input_is_final, parse_psbt, spk_to_address, vout_dtos, PsbtVoutDto};
+use super::state::SharedBtcxWalletState;
+
+/// Largest OP_RETURN payload `create` accepts (the standardness limit).
+const MAX_DATA_BYTES: usize = 80;
+
+/// Parse a raw transaction hex string.
+fn parse_tx(tx_hex: &str) -> Result<Transaction, String> {
+    bitcoin::consensus::encode::deserialize_hex(tx_hex.trim())
+        .map_err(|e| format!("Not a valid raw transaction: {e}"))
+}
+
+/// `tx` with every scriptSig and witness emptied — the unsigned form a PSBT
+/// carries.
+fn stripped(mut tx: Transaction) -> Transaction {
+    for input in &mut tx.input {
+        input.script_sig = ScriptBuf::new();
+        input.witness = Witness::new();
+    }
+    tx
+}
+
+// ============================================================================
+// Decode
+// ============================================================================
+
+/// One input as the raw decode view shows it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawVinDto {
+    pub txid: String,
+    pub vout: u32,
+    pub sequence: u32,
+    pub script_sig_hex: String,
+    /// Witness stack items, hex.
+    pub witness: Vec<String>,
+}
+
+/// `btcx_rawtx_decode` result — the client-side decoderawtransaction.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxDecodeDto {
+    pub txid: String,
+    pub wtxid: String,
+    pub version: i32,
+    pub locktime: u32,
+    pub size: u64,
+    pub vsize: u64,
+    pub weight: u64,
+    /// Some input signals BIP-125 replaceability.
+    pub rbf: bool,
+    pub vin: Vec<RawVinDto>,
+    pub vout: Vec<PsbtVoutDto>,
+}
+
+/// Decode a raw transaction for display (client-side
+/// `decoderawtransaction`).
+pub fn decode(network: WalletNetwork, tx_hex: &str) -> Result<RawTxDecodeDto, String> {
+    let tx = parse_tx(tx_hex)?;
+    let vin = tx
+        .input
+        .iter()
+        .map(|i| RawVinDto {
+            txid: i.previous_output.txid.to_string(),
+            vout: i.previous_output.vout,
+            sequence: i.sequence.0,
+            script_sig_hex: i.script_sig.to_hex_string(),
+            witness: i.witness.iter().map(hex::encode).collect(),
+        })
+        .collect();
+    Ok(RawTxDecodeDto {
+        txid: tx.compute_txid().to_string(),
+        wtxid: tx.compute_wtxid().to_string(),
+        version: tx.version.0,
+        locktime: tx.lock_time.to_consensus_u32(),
+        size: tx.total_size() as u64,
+        vsize: tx.vsize() as u64,
+        weight: tx.weight().to_wu(),
+        rbf: tx.is_explicitly_rbf(),
+        vin,
+        vout: vout_dtos(network, &tx),
+    })
+}
+
+// ============================================================================
+// Create
+// ============================================================================
+
+/// One input of a created transaction.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxInput {
+    pub txid: String,
+    pub vout: u32,
+    /// Explicit nSequence; defaults per `replaceable` and the locktime.
+    #[serde(default)]
+    pub sequence: Option<u32>,
+}
+
+/// One output of a created transaction: `address` + `amountSat`, or an
+/// OP_RETURN `dataHex` (at most one, worth nothing).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxOutput {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub amount_sat: u64,
+    #[serde(default)]
+    pub data_hex: Option<String>,
+}
+
+/// Create an unsigned raw transaction spending exactly `inputs` into
+/// exactly `outputs`, in the given order (client-side
+/// `createrawtransaction`) — no coin selection, no change, no fee logic.
+/// Inputs without an explicit sequence signal RBF unless `replaceable` is
+/// false, in which case they still enable a non-zero `locktime`.
+pub fn create(
+    network: WalletNetwork,
+    inputs: &[RawTxInput],
+    outputs: &[RawTxOutput],
+    locktime: Option<u32>,
+    replaceable: Option<bool>,
+) -> Result<String, String> {
+    if inputs.is_empty() {
+        return Err("Give at least one input".to_string());
+    }
+    if outputs.is_empty() {
+        return Err("Give at least one output".to_string());
+    }
+    let locktime = locktime.unwrap_or(0);
+    let default_sequence = if replaceable.unwrap_or(true) {
+        Sequence::ENABLE_RBF_NO_LOCKTIME
+    } else if locktime != 0 {
+        Sequence::ENABLE_LOCKTIME_NO_RBF
+    } else {
+        Sequence::MAX
+    };
+
+    let mut seen = BTreeSet::new();
+    let input = inputs
+        .iter()
+        .map(|i| {
+            let txid =
+                Txid::from_str(i.txid.trim()).map_err(|e| format!("Input txid {}: {e}", i.txid))?;
+            let outpoint = OutPoint::new(txid, i.vout);
+            if !seen.insert(outpoint) {
+                return Err(format!("Input {outpoint} is listed twice"));
+            }
+            Ok(TxIn {
+                previous_output: outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: i.sequence.map(Sequence).unwrap_or(default_sequence),
+                witness: Witness::new(),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let params = network.params();
+    let mut has_data = false;
+    let output = outputs
+        .iter()
+        .enumerate()
+        .map(|(n, o)| {
+            let data = o.data_hex.as_deref().map(str::trim);
+            let script_pubkey = match (o.address.as_deref().map(str::trim), data) {
+                (Some(address), None) => params
+                    .parse_address(address)
+                    .map_err(|e| format!("{address}: {e:#}"))?,
+                (None, Some(data)) => {
+                    if has_data {
+                        return Err("Only one OP_RETURN data output is allowed".to_string());
+                    }
+                    has_data = true;
+                    let bytes = hex::decode(data).map_err(|e| format!("OP_RETURN data: {e}"))?;
+                    if bytes.len() > MAX_DATA_BYTES {
+                        return Err(format!(
+                            "OP_RETURN data is {} bytes — the standardness limit is {MAX_DATA_BYTES}",
+                            bytes.len()
+                        ));
+                    }
+                    let push = bitcoin::script::PushBytesBuf::try_from(bytes)
+                        .map_err(|e| format!("OP_RETURN data: {e}"))?;
+                    if o.amount_sat != 0 {
+                        return Err(format!(
+                            "Output {n}: an OP_RETURN data output carries no amount"
+                        ));
+                    }
+                    ScriptBuf::new_op_return(push)
+                }
+                _ => {
+                    return Err(format!(
+                        "Output {n}: give either an address and amount or OP_RETURN data"
+                    ))
+                }
+            };
+            Ok(TxOut {
+                value: Amount::from_sat(o.amount_sat),
+                script_pubkey,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::from_consensus(locktime),
+        input,
+        output,
+    };
+    Ok(bitcoin::consensus::encode::serialize_hex(&tx))
+}
+
+// ============================================================================
+// Raw tx ⇄ PSBT
+// ============================================================================
+
+/// Convert a raw transaction into an empty PSBT (client-side
+/// `converttopsbt`). A transaction carrying signatures is refused unless
+/// `permit_sig_data`, which drops them — the PSBT holds the unsigned form.
+pub fn to_psbt(tx_hex: &str, permit_sig_data: bool) -> Result<String, String> {
+    let tx = parse_tx(tx_hex)?;
+    let signed = tx
+        .input
+        .iter()
+        .any(|i| !i.script_sig.is_empty() || !i.witness.is_empty());
+    if signed && !permit_sig_data {
+        return Err(
+            "The transaction carries signatures — allow dropping them to convert it".to_string(),
+        );
+    }
+    Psbt::from_unsigned_tx(stripped(tx))
+        .map(|psbt| psbt.to_string())
+        .map_err(|e| format!("Converting to a PSBT: {e}"))
+}
+
+/// `btcx_psbt_to_rawtx` result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTxDto {
+    /// The transaction with every final input's scriptSig/witness filled in.
+    pub hex: String,
+    /// Every input final — ready to broadcast.
+    pub complete: bool,
+}
+
+/// The transaction of `psbt` with its final inputs' scriptSigs/witnesses
+/// in place; non-final inputs stay bare.
+fn extract(psbt: &Psbt) -> RawTxDto {
+    let mut tx = psbt.unsigned_tx.clone();
+    for (raw, input) in tx.input.iter_mut().zip(&psbt.inputs) {
+        if let Some(script_sig) = &input.final_script_sig {
+            raw.script_sig = script_sig.clone();
+        }
+        if let Some(witness) = &input.final_script_witness {
+            raw.witness = witness.clone();
+        }
+    }
+    RawTxDto {
+        hex: bitcoin::consensus::encode::serialize_hex(&tx),
+        complete: !psbt.inputs.is_empty() && psbt.inputs.iter().all(input_is_final),
+    }
+}
+
+/// The raw transaction of a PSBT, complete or not — the reverse of
+/// [`to_psbt`]. Unlike `finalize` it never signs or finalizes anything.
+pub fn from_psbt(psbt_base64: &str) -> Result<RawTxDto, String> {
+    Ok(extract(&parse_psbt(psbt_base64)?))
+}
+
+// ============================================================================
+// UTXO update
+// ============================================================================
+
+/// Fill in the spent output of every non-final input that carries none,
+/// from the transactions `chain` knows: the full previous transaction
+/// always, the witness UTXO too for segwit spends. Inputs the server does
+/// not know stay as they are. Returns how many inputs were filled.
+fn fill_utxos(chain: &ElectrumBackend, psbt: &mut Psbt) -> Result<usize, String> {
+    let open = |input: &bitcoin::psbt::Input| {
+        !input_is_final(input) && input.witness_utxo.is_none() && input.non_witness_utxo.is_none()
+    };
+    if !psbt.inputs.iter().any(open) {
+        return Ok(0);
+    }
+    let prevs = super::policy::fetch_prev_txs(chain, &psbt.unsigned_tx)?;
+    let mut filled = 0;
+    for (input, txin) in psbt.inputs.iter_mut().zip(&psbt.unsigned_tx.input) {
+        if !open(input) {
+            continue;
+        }
+        let outpoint = txin.previous_output;
+        let Some(prev) = prevs.get(&outpoint.txid) else {
+            continue;
+        };
+        let Some(out) = prev.output.get(outpoint.vout as usize) else {
+            continue;
+        };
+        if out.script_pubkey.is_witness_program() {
+            input.witness_utxo = Some(out.clone());
+        }
+        input.non_witness_utxo = Some(prev.clone());
+        filled += 1;
+    }
+    Ok(filled)
+}
+
+/// Add the spent outputs of a foreign PSBT's inputs, fetched over Electrum
+/// for `network` (client-side `utxoupdatepsbt`). Inputs the server does not
+/// know are left for `analyze` to report as missing their UTXO.
+pub fn utxo_update(
+    state: &SharedBtcxWalletState,
+    psbt_base64: &str,
+    network: Option<WalletNetwork>,
+) -> Result<String, String> {
+    let psbt = parse_psbt(psbt_base64)?;
+    let network = network.unwrap_or(state.get_config().network);
+    let psbt = state.with_network_chain(network, |chain| {
+        let mut updated = psbt.clone();
+        fill_utxos(chain, &mut updated)?;
+        Ok(updated)
+    })?;
+    Ok(psbt.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    fn address(byte: u8) -> String {
+        let spk = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([byte; 20]));
+        spk_to_address(WalletNetwork::Regtest, &spk).unwrap()
+    }
+
+    fn input(vout: u32) -> RawTxInput {
+        RawTxInput {
+            txid: "11".repeat(32),
+            vout,
+            sequence: None,
+        }
+    }
+
+    fn pay(byte: u8, amount_sat: u64) -> RawTxOutput {
+        RawTxOutput {
+            address: Some(address(byte)),
+            amount_sat,
+            data_hex: None,
+        }
+    }
+
+    #[test]
+    fn create_then_decode_round_trips() {
+        let data = RawTxOutput {
+            address: None,
+            amount_sat: 0,
+            data_hex: Some("504f4358".into()),
+        };
+        let hex = create(
+            WalletNetwork::Regtest,
+            &[input(0), input(1)],
+            &[pay(7, 50_000), data],
+            Some(100),
+            None,
+        )
+        .unwrap();
+        let decoded = decode(WalletNetwork::Regtest, &hex).unwrap();
+        assert_eq!(decoded.vin.len(), 2);
+        assert_eq!(decoded.locktime, 100);
+        assert!(decoded.rbf);
+        assert_eq!(
+            decoded.vout[0].address.as_deref(),
+            Some(address(7).as_str())
+        );
+        assert_eq!(decoded.vout[0].value_sat, 50_000);
+        assert!(decoded.vout[1].op_return);
+        assert_eq!(decoded.txid, decoded.wtxid);
+
+        // Non-replaceable with a locktime: the locktime stays enforceable.
+        let hex = create(
+            WalletNetwork::Regtest,
+            &[input(0)],
+            &[pay(7, 1_000)],
+            Some(100),
+            Some(false),
+        )
+        .unwrap();
+        let decoded = decode(WalletNetwork::Regtest, &hex).unwrap();
+        assert_eq!(decoded.vin[0].sequence, 0xffff_fffe);
+        assert!(!decoded.rbf);
+    }
+
+    #[test]
+    fn create_rejects_bad_shapes() {
+        let net = WalletNetwork::Regtest;
+        assert!(create(net, &[], &[pay(7, 1)], None, None).is_err());
+        assert!(create(net, &[input(0)], &[], None, None).is_err());
+        assert!(create(net, &[input(0), input(0)], &[pay(7, 1)], None, None).is_err());
+        // A mainnet address on regtest.
+        let mut foreign = pay(7, 1);
+        foreign.address = Some(
+            spk_to_address(
+                WalletNetwork::Mainnet,
+                &ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([7; 20])),
+            )
+            .unwrap(),
+        );
+        assert!(create(net, &[input(0)], &[foreign], None, None).is_err());
+        let data = |len: usize| RawTxOutput {
+            address: None,
+            amount_sat: 0,
+            data_hex: Some("00".repeat(len)),
+        };
+        assert!(create(net, &[input(0)], &[data(81)], None, None).is_err());
+        assert!(create(net, &[input(0)], &[data(1), data(1)], None, None).is_err());
+        // Value sent to an OP_RETURN is burned: refused, not zeroed.
+        let mut burn = data(1);
+        burn.amount_sat = 1_000;
+        assert!(create(net, &[input(0)], &[burn], None, None).is_err());
+    }
+
+    #[test]
+    fn psbt_conversion_keeps_the_transaction() {
+        let hex = create(
+            WalletNetwork::Regtest,
+            &[input(0)],
+            &[pay(7, 1_000)],
+            None,
+            None,
+        )
+        .unwrap();
+        let psbt = to_psbt(&hex, false).unwrap();
+        let back = from_psbt(&psbt).unwrap();
+        assert_eq!(back.hex, hex);
+        assert!(!back.complete);
+
+        // Signature data is refused unless dropping it is allowed.
+        let mut tx = parse_tx(&hex).unwrap();
+        tx.input[0].witness = Witness::from_slice(&[vec![1u8; 71], vec![2u8; 33]]);
+        let signed = bitcoin::consensus::encode::serialize_hex(&tx);
+        assert!(to_psbt(&signed, false).is_err());
+        assert_eq!(
+            from_psbt(&to_psbt(&signed, true).unwrap()).unwrap().hex,
+            hex
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! RBF replacement links.
+//!
+//! Once a replacement (fee bump or cancel) is accepted, the original drops
+//! out of the canonical history and nothing in the tx graph says which tx
+//! took its place — a conflict is just a conflict. Each named wallet
+//! therefore records `original → replacement` in `wallet/replacements.json`
+//! when it broadcasts one, and the transaction detail reads the links back
+//! in both directions ("replaced by" / "replaces").
+//!
+//! Links are decoration: a failed write is logged, never turned into an
+//! error after the replacement is already out.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use super::state::SharedBtcxWalletState;
+
+/// File name of the link store inside a wallet's `wallet/` dir.
+pub const REPLACEMENTS_FILE: &str = "replacements.json";
+
+/// Serializes read-modify-write cycles on link files.
+static REPLACEMENTS_LOCK: Mutex<()> = Mutex::new(());
+
+/// One wallet's replacement links, keyed by the replaced txid.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacementLog {
+    #[serde(default)]
+    replaced_by: BTreeMap<String, String>,
+}
+
+impl ReplacementLog {
+    /// Link file inside a wallet dir.
+    pub fn path_in(root: &Path) -> PathBuf {
+        root.join("wallet").join(REPLACEMENTS_FILE)
+    }
+
+    /// Read a link file; a missing file is an empty log.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("reading {}: {e}", path.display())),
+        }
+    }
+
+    /// Atomically rewrite the link file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("creating {}: {e}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        super::descstore::write_atomic(path, &json)
+    }
+
+    /// Record that `replacement` replaced `original`. Bumping the same tx
+    /// twice keeps the latest link.
+    pub fn record(&mut self, original: &str, replacement: &str) {
+        self.replaced_by.insert(
+            original.trim().to_ascii_lowercase(),
+            replacement.trim().to_ascii_lowercase(),
+        );
+    }
+
+    /// The tx that replaced `txid`, if any.
+    pub fn replaced_by(&self, txid: &str) -> Option<&str> {
+        self.replaced_by
+            .get(&txid.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// The tx `txid` replaced, if any.
+    pub fn replaces(&self, txid: &str) -> Option<&str> {
+        let txid = txid.to_ascii_lowercase();
+        self.replaced_by
+            .iter()
+            .find(|(_, replacement)| **replacement == txid)
+            .map(|(original, _)| original.as_str())
+    }
+}
+
+fn active_path(state: &SharedBtcxWalletState) -> Result<PathBuf, String> {
+    state
+        .get_config()
+        .registered_active_root()
+        .map(|root| ReplacementLog::path_in(&root))
+        .ok_or_else(|| "No active wallet".to_string())
+}
+
+/// The active wallet's links; a missing or unreadable file yields an empty
+/// log (logged) — the detail page must not fail over a decoration.
+pub fn read_active(state: &SharedBtcxWalletState) -> ReplacementLog {
+    let loaded = active_path(state).and_then(|path| {
+        let _guard = REPLACEMENTS_LOCK
+            .lock()
+            .map_err(|_| "replacement lock poisoned")?;
+        ReplacementLog::load(&path)
+    });
+    loaded.unwrap_or_else(|e| {
+        log::warn!("btcx wallet: reading replacement links failed: {e}");
+        ReplacementLog::default()
+    })
+}
+
+/// Record a broadcast replacement in the active wallet. Best effort.
+pub fn record_active(state: &SharedBtcxWalletState, original: &str, replacement: &str) {
+    let written = active_path(state).and_then(|path| {
+        let _guard = REPLACEMENTS_LOCK
+            .lock()
+            .map_err(|_| "replacement lock poisoned")?;
+        let mut log = ReplacementLog::load(&path)?;
+        log.record(original, replacement);
+        log.save(&path)
+    });
+    if let Err(e) = written {
+        log::warn!("btcx wallet: recording {original} → {replacement} failed: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_resolve_in_both_directions() {
+        let a = "aa".repeat(32);
+        let b = "bb".repeat(32);
+        let c = "cc".repeat(32);
+        let dir = tempfile::tempdir().unwrap();
+        let path = ReplacementLog::path_in(dir.path());
+        let mut log = ReplacementLog::load(&path).unwrap();
+        assert_eq!(log, ReplacementLog::default(), "missing file = empty log");
+
+        // A bumped, then the bump cancelled: a chain of two links.
+        log.record(&a.to_uppercase(), &b);
+        log.record(&b, &c);
+        log.save(&path).unwrap();
+
+        let log = ReplacementLog::load(&path).unwrap();
+        assert_eq!(log.replaced_by(&a), Some(b.as_str()));
+        assert_eq!(log.replaces(&b), Some(a.as_str()));
+        assert_eq!(log.replaced_by(&b), Some(c.as_str()));
+        assert_eq!(log.replaces(&c.to_uppercase()), Some(b.as_str()));
+        assert_eq!(log.replaces(&a), None);
+        assert_eq!(log.replaced_by(&c), None);
+    }
+}
@@ -0,0 +1,433 @@
+//! Changing a wallet's AT-REST passphrase after the fact.
+//!
+//! The at-rest wrap is picked at create/restore/import time: a user
+//! passphrase (lockable), or the transparent keyring/obfuscation wrap.
+//! [`change_passphrase`] re-wraps the active wallet's key file under a new
+//! choice — change, add, or (explicitly confirmed) remove the passphrase —
+//! and does it for EVERY same-source member of its group, since the
+//! compartments of one seed must never disagree on their protection (the
+//! reason `materialize_group_compartments` skips encrypted seeds).
+//!
+//! Descriptor stores re-wrap in one atomic write ([`DescStore::rewrap`]).
+//! The seed file belongs to `seedstore`, which only writes a wrap on
+//! import: the old file is set aside as `seed.mnemonic.bak`, the mnemonic
+//! re-imported and read back. Either way every member's old key file is
+//! kept as `<file>.bak`, dropped only once EVERY member re-wrapped — any
+//! failure puts them all back. Before the first member is touched, a group
+//! journal listing the members goes into the network dir; it is removed
+//! once the last one re-wrapped. A crash in between is settled by
+//! [`recover_interrupted`] at startup and on the next wallet open: with the
+//! journal still there EVERY member is rolled back, so a group never ends
+//! up half old, half new.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use seedstore::SeedStore;
+
+use super::config::{BtcxWalletConfig, WalletNetwork, WalletSourceCfg};
+use super::descstore::{self, DescStore};
+use super::state::{self, SharedBtcxWalletState};
+
+/// Outcome of a passphrase change.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PassphraseChange {
+    /// Every re-wrapped wallet (the active one and its group siblings).
+    pub rewrapped: Vec<String>,
+    /// Whether the key files are now passphrase-encrypted.
+    pub encrypted: bool,
+}
+
+/// The key files a change re-wraps, one per source.
+const KEY_FILES: [&str; 2] = [seedstore::SEED_FILE, descstore::DESCRIPTOR_FILE];
+
+/// The group journal of a change in progress, in the network dir.
+const JOURNAL_FILE: &str = ".passphrase-change.json";
+
+/// Which members a change re-wraps and which key file — written before
+/// the first member is touched, removed once every member re-wrapped.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Journal {
+    file: String,
+    members: Vec<String>,
+}
+
+fn backup_path(root: &Path, file: &str) -> PathBuf {
+    root.join(format!("{file}.bak"))
+}
+
+fn journal_path(network_dir: &Path) -> PathBuf {
+    network_dir.join(JOURNAL_FILE)
+}
+
+/// Record the change about to re-wrap `roots` (all in `network_dir`).
+fn begin_journal(
+    network_dir: &Path,
+    file: &str,
+    roots: &[(String, PathBuf)],
+) -> Result<(), String> {
+    let journal = Journal {
+        file: file.to_string(),
+        members: roots.iter().map(|(n, _)| n.clone()).collect(),
+    };
+    let json = serde_json::to_string(&journal).map_err(|e| e.to_string())?;
+    descstore::write_atomic(&journal_path(network_dir), &json)
+}
+
+/// Drop the journal: past this point the change stands.
+fn end_journal(network_dir: &Path) {
+    let path = journal_path(network_dir);
+    if let Err(e) = std::fs::remove_file(&path) {
+        log::error!("btcx wallet: removing {}: {e}", path.display());
+    }
+}
+
+/// Roll back EVERY member of a change whose journal is still in
+/// `network_dir` — it was interrupted before all of them re-wrapped.
+fn recover_group(network_dir: &Path) -> Result<(), String> {
+    let path = journal_path(network_dir);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("reading {}: {e}", path.display())),
+    };
+    let journal: Journal =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+    for name in &journal.members {
+        rollback(&network_dir.join(name), &journal.file);
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("removing {}: {e}", path.display()))?;
+    log::warn!(
+        "btcx wallet: rolled back an interrupted passphrase change of {:?}",
+        journal.members
+    );
+    Ok(())
+}
+
+/// Settle a wallet dir left behind by an interrupted change. A group
+/// journal next to it rolls the whole group back first (see
+/// [`recover_group`]). Otherwise the change had finished: a key file is
+/// only ever installed whole, so when it exists it is the newer wrap and
+/// the set-aside copy goes; when it is missing the copy comes back.
+pub fn recover_interrupted(root: &Path) -> Result<(), String> {
+    if let Some(network_dir) = root.parent() {
+        recover_group(network_dir)?;
+    }
+    for file in KEY_FILES {
+        let backup = backup_path(root, file);
+        if !backup.exists() {
+            continue;
+        }
+        let key = root.join(file);
+        if key.exists() {
+            std::fs::remove_file(&backup)
+                .map_err(|e| format!("removing {}: {e}", backup.display()))?;
+        } else {
+            std::fs::rename(&backup, &key)
+                .map_err(|e| format!("restoring {}: {e}", key.display()))?;
+            log::warn!(
+                "btcx wallet: restored {} after an interrupted passphrase change",
+                key.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// [`recover_interrupted`] over every registered wallet (startup).
+pub fn recover_all(config: &BtcxWalletConfig) {
+    for network in [
+        WalletNetwork::Mainnet,
+        WalletNetwork::Testnet,
+        WalletNetwork::Regtest,
+    ] {
+        for name in config.wallet_names(network) {
+            let root = BtcxWalletConfig::wallet_root(network, &name);
+            if let Err(e) = recover_interrupted(&root) {
+                log::error!("btcx wallet: settling '{name}' after a passphrase change: {e}");
+            }
+        }
+    }
+}
+
+/// Whether a member's key file carries the user-passphrase wrap.
+fn is_passphrase_wrapped(root: &Path, source: WalletSourceCfg) -> bool {
+    match source {
+        WalletSourceCfg::Seed => state::seed_needs_passphrase(
+            &std::fs::read_to_string(root.join(seedstore::SEED_FILE)).unwrap_or_default(),
+        ),
+        WalletSourceCfg::Descriptor => descstore::is_passphrase_descriptor_file(
+            &std::fs::read_to_string(root.join(descstore::DESCRIPTOR_FILE)).unwrap_or_default(),
+        ),
+        WalletSourceCfg::WatchOnly => false,
+    }
+}
+
+/// Re-import `mnemonic` into `root` under `passphrase`, the old seed file
+/// set aside first, and read it back.
+fn rewrap_seed(root: &Path, mnemonic: &str, passphrase: Option<&str>) -> Result<(), String> {
+    let seed = root.join(seedstore::SEED_FILE);
+    std::fs::rename(&seed, backup_path(root, seedstore::SEED_FILE))
+        .map_err(|e| format!("setting aside {}: {e}", seed.display()))?;
+    let mut store =
+        SeedStore::open(root, None).map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+    store
+        .import_seed(mnemonic, passphrase)
+        .map(|_| ())
+        .map_err(|e| format!("{e:#}"))?;
+    let mut check =
+        SeedStore::open(root, None).map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+    if let Some(pass) = passphrase {
+        check.unlock(pass).map_err(|e| format!("{e:#}"))?;
+    }
+    let read_back = check.mnemonic().map_err(|e| format!("{e:#}"))?;
+    if read_back.trim() != mnemonic.trim() {
+        return Err("the re-wrapped seed does not read back".into());
+    }
+    Ok(())
+}
+
+/// Set `root`'s descriptor store aside (a copy — [`DescStore::rewrap`]
+/// replaces the file in place) and re-wrap it under `passphrase`.
+fn rewrap_desc(root: &Path, store: &mut DescStore, passphrase: Option<&str>) -> Result<(), String> {
+    let file = root.join(descstore::DESCRIPTOR_FILE);
+    std::fs::copy(&file, backup_path(root, descstore::DESCRIPTOR_FILE))
+        .map_err(|e| format!("setting aside {}: {e}", file.display()))?;
+    store.rewrap(passphrase)
+}
+
+/// Undo a re-wrap of `file` on `root`: drop whatever new file exists and
+/// put the set-aside one back.
+fn rollback(root: &Path, file: &str) {
+    let backup = backup_path(root, file);
+    if !backup.exists() {
+        return;
+    }
+    let key = root.join(file);
+    let _ = std::fs::remove_file(&key);
+    if let Err(e) = std::fs::rename(&backup, &key) {
+        log::error!(
+            "btcx wallet: restoring {} after a failed passphrase change: {e}",
+            key.display()
+        );
+    }
+}
+
+/// Drop the set-aside copies once every member re-wrapped.
+fn drop_backups(roots: &[(String, PathBuf)], file: &str) {
+    for (_, root) in roots {
+        let backup = backup_path(root, file);
+        if let Err(e) = std::fs::remove_file(&backup) {
+            log::warn!("btcx wallet: removing {}: {e}", backup.display());
+        }
+    }
+}
+
+/// Change, add, or remove the at-rest passphrase of the ACTIVE wallet and
+/// every same-source member of its group.
+///
+/// - `current` unlocks passphrase-wrapped members (ignored otherwise);
+/// - `new` non-empty → passphrase wrap; `None`/empty → the transparent
+///   wrap, which REMOVES the passphrase and needs `confirm_remove`.
+///
+/// Every member is read (and the current passphrase verified) before any
+/// file is touched. The held store ends up unlocked with `new`.
+pub fn change_passphrase(
+    state: &SharedBtcxWalletState,
+    current: Option<&str>,
+    new: Option<&str>,
+    confirm_remove: bool,
+) -> Result<PassphraseChange, String> {
+    let config = state.get_config();
+    let network = config.network;
+    let active = config.active_wallet_name();
+    let meta = config
+        .wallet_meta(network, &active)
+        .ok_or("No active wallet")?;
+    let source = meta.source;
+    if source == WalletSourceCfg::WatchOnly {
+        return Err("A watch-only wallet holds no keys to encrypt".into());
+    }
+    let current = current.filter(|p| !p.is_empty());
+    let new = new.filter(|p| !p.is_empty());
+    let group = config.group_of(network, &active);
+    let network_dir = BtcxWalletConfig::wallet_dir().join(network.as_str());
+    let members: Vec<String> = config
+        .group_members(network, &group)
+        .into_iter()
+        .filter(|n| config.wallet_meta(network, n).map(|m| m.source) == Some(source))
+        .collect();
+    let roots: Vec<(String, PathBuf)> = members
+        .into_iter()
+        .map(|n| {
+            let root = BtcxWalletConfig::wallet_root(network, &n);
+            (n, root)
+        })
+        .collect();
+
+    if new.is_none() {
+        if !roots.iter().any(|(_, r)| is_passphrase_wrapped(r, source)) {
+            return Err("No passphrase is set — nothing to remove".into());
+        }
+        if !confirm_remove {
+            return Err(
+                "Removing the passphrase leaves the keys readable by anyone with access to \
+                 this computer's files — confirm to proceed"
+                    .into(),
+            );
+        }
+    }
+    let unlock_with = |name: &str| {
+        current.ok_or_else(|| {
+            format!("'{name}' is passphrase-encrypted — enter the current passphrase")
+        })
+    };
+
+    match source {
+        WalletSourceCfg::Seed => {
+            // Phase 1: read every member's mnemonic — nothing written yet.
+            let mut mnemonics = Vec::with_capacity(roots.len());
+            for (name, root) in &roots {
+                recover_interrupted(root)?;
+                let mut store = SeedStore::open(root, None)
+                    .map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+                if is_passphrase_wrapped(root, source) {
+                    store
+                        .unlock(unlock_with(name)?)
+                        .map_err(|e| format!("'{name}': {e:#}"))?;
+                }
+                mnemonics.push(store.mnemonic().map_err(|e| format!("'{name}': {e:#}"))?);
+            }
+            // Phase 2: re-wrap all, or put every one back.
+            begin_journal(&network_dir, seedstore::SEED_FILE, &roots)?;
+            for (i, ((name, root), mnemonic)) in roots.iter().zip(&mnemonics).enumerate() {
+                if let Err(e) = rewrap_seed(root, mnemonic, new) {
+                    for (_, done) in &roots[..=i] {
+                        rollback(done, seedstore::SEED_FILE);
+                    }
+                    end_journal(&network_dir);
+                    return Err(format!(
+                        "Re-wrapping '{name}' failed (nothing changed): {e}"
+                    ));
+                }
+            }
+            // Phase 3: every member verified — commit, then drop the
+            // set-aside files.
+            end_journal(&network_dir);
+            drop_backups(&roots, seedstore::SEED_FILE);
+        }
+        // Descriptor stores (watch-only was refused above).
+        _ => {
+            let mut stores = Vec::with_capacity(roots.len());
+            for (name, root) in &roots {
+                recover_interrupted(root)?;
+                let mut store = DescStore::open(root)?;
+                if is_passphrase_wrapped(root, source) {
+                    store
+                        .unlock(unlock_with(name)?)
+                        .map_err(|e| format!("'{name}': {e}"))?;
+                }
+                store.payload().map_err(|e| format!("'{name}': {e}"))?;
+                stores.push(store);
+            }
+            // Same journal and set-aside-and-restore as the seed branch.
+            begin_journal(&network_dir, descstore::DESCRIPTOR_FILE, &roots)?;
+            for (i, ((name, root), store)) in roots.iter().zip(stores.iter_mut()).enumerate() {
+                if let Err(e) = rewrap_desc(root, store, new) {
+                    for (_, done) in &roots[..=i] {
+                        rollback(done, descstore::DESCRIPTOR_FILE);
+                    }
+                    end_journal(&network_dir);
+                    return Err(format!(
+                        "Re-wrapping '{name}' failed (nothing changed): {e}"
+                    ));
+                }
+            }
+            end_journal(&network_dir);
+            drop_backups(&roots, descstore::DESCRIPTOR_FILE);
+        }
+    }
+
+    // The held store still carries the old passphrase — reopen it under
+    // the new one so the open wallet keeps signing without a re-unlock.
+    state.drop_seed_passphrase();
+    if let Some(pass) = new {
+        match source {
+            WalletSourceCfg::Seed => {
+                state.with_seed(|s| s.unlock(pass).map_err(|e| format!("{e:#}")))?
+            }
+            _ => state.with_desc(|d| d.unlock(pass))?,
+        }
+    }
+    let rewrapped: Vec<String> = roots.into_iter().map(|(n, _)| n).collect();
+    log::info!(
+        "btcx wallet: at-rest passphrase {} on {rewrapped:?}",
+        if new.is_some() { "set" } else { "removed" }
+    );
+    Ok(PassphraseChange {
+        rewrapped,
+        encrypted: new.is_some(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_change_settles_on_the_whole_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let seed = root.join(seedstore::SEED_FILE);
+        let desc = root.join(descstore::DESCRIPTOR_FILE);
+
+        // Crash after the set-aside, before the re-import: the copy
+        // comes back.
+        std::fs::write(backup_path(root, seedstore::SEED_FILE), "old seed").unwrap();
+        // Crash after the atomic descriptor re-wrap: the new file stays.
+        std::fs::write(&desc, "new desc").unwrap();
+        std::fs::write(backup_path(root, descstore::DESCRIPTOR_FILE), "old desc").unwrap();
+
+        recover_interrupted(root).unwrap();
+        assert_eq!(std::fs::read_to_string(&seed).unwrap(), "old seed");
+        assert_eq!(std::fs::read_to_string(&desc).unwrap(), "new desc");
+        for file in KEY_FILES {
+            assert!(!backup_path(root, file).exists());
+        }
+        // Nothing set aside: a no-op.
+        recover_interrupted(root).unwrap();
+        assert_eq!(std::fs::read_to_string(&seed).unwrap(), "old seed");
+    }
+
+    #[test]
+    fn interrupted_group_change_rolls_every_member_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots: Vec<(String, PathBuf)> = ["a", "b", "c"]
+            .iter()
+            .map(|n| (n.to_string(), dir.path().join(n)))
+            .collect();
+        for (_, root) in &roots {
+            std::fs::create_dir_all(root).unwrap();
+        }
+        let seed = |root: &Path| root.join(seedstore::SEED_FILE);
+        begin_journal(dir.path(), seedstore::SEED_FILE, &roots).unwrap();
+        // "a" re-wrapped, "b" set aside, "c" not reached yet.
+        std::fs::write(seed(&roots[0].1), "new a").unwrap();
+        std::fs::write(backup_path(&roots[0].1, seedstore::SEED_FILE), "old a").unwrap();
+        std::fs::write(backup_path(&roots[1].1, seedstore::SEED_FILE), "old b").unwrap();
+        std::fs::write(seed(&roots[2].1), "old c").unwrap();
+
+        // Opening any one member settles the whole group.
+        recover_interrupted(&roots[2].1).unwrap();
+        for (name, root) in &roots {
+            assert_eq!(
+                std::fs::read_to_string(seed(root)).unwrap(),
+                format!("old {name}")
+            );
+            assert!(!backup_path(root, seedstore::SEED_FILE).exists());
+        }
+        assert!(!journal_path(dir.path()).exists());
+    }
+}
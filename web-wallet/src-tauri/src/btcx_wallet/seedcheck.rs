@@ -0,0 +1,427 @@
+//! Mnemonic backup reveal and verification, after onboarding.
+//!
+//! - **Reveal** re-displays the active seed wallet's mnemonic. A
+//!   passphrase-encrypted seed needs its passphrase, checked against a
+//!   fresh store (a merely unlocked session is not enough); the transparent
+//!   wraps have nothing to check, so they need an explicit confirmation
+//!   instead. Each reveal opens a short-lived session
+//!   whose token re-reveals without the passphrase until it expires — the
+//!   "show again" of a backup screen, not a standing bypass.
+//! - **Verification** never returns the seed: the backend picks random word
+//!   positions (at least [`MIN_CHALLENGE_WORDS`]), the user types those
+//!   words from their paper backup, and only pass/fail comes back — not how
+//!   many were wrong. A challenge is single-use, so answers cannot be
+//!   probed one word at a time, and [`MAX_FAILED_CHECKS`] failures in a row
+//!   lock new challenges out for [`LOCKOUT_SECS`]. Success stamps
+//!   `WalletMeta::backup_verified_at` on every seed compartment of the
+//!   group (they share the mnemonic).
+//!
+//! Sessions live in memory only and are bound to the wallet they were
+//! issued for; switching wallets or restarting the app drops them.
+
+use std::sync::Mutex;
+
+use rand::Rng;
+use serde::Serialize;
+
+use seedstore::SeedStore;
+
+use super::config::WalletSourceCfg;
+use super::descstore;
+use super::state::{self, SharedBtcxWalletState};
+
+/// How long a reveal token re-reveals without the passphrase.
+const REVEAL_TTL_SECS: u64 = 120;
+/// How long a verification challenge stays answerable.
+const CHALLENGE_TTL_SECS: u64 = 600;
+/// Words asked per challenge unless the caller picks a count.
+pub const DEFAULT_CHALLENGE_WORDS: usize = 4;
+/// Fewest words a challenge asks — a single word is a 1-in-2048 guess.
+pub const MIN_CHALLENGE_WORDS: usize = 3;
+/// Failed checks in a row before new challenges are refused.
+pub const MAX_FAILED_CHECKS: u32 = 3;
+/// How long new challenges are refused after too many failures.
+pub const LOCKOUT_SECS: u64 = 900;
+
+struct RevealSession {
+    wallet: String,
+    token: String,
+    expires_at: u64,
+}
+
+struct Challenge {
+    wallet: String,
+    positions: Vec<usize>,
+    expires_at: u64,
+}
+
+/// Failed checks in a row for one wallet.
+struct FailedChecks {
+    wallet: String,
+    failures: u32,
+    locked_until: u64,
+}
+
+static REVEAL: Mutex<Option<RevealSession>> = Mutex::new(None);
+static CHALLENGE: Mutex<Option<Challenge>> = Mutex::new(None);
+static FAILED: Mutex<Option<FailedChecks>> = Mutex::new(None);
+
+/// A revealed mnemonic (`btcx_wallet_reveal_mnemonic`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MnemonicReveal {
+    pub mnemonic: String,
+    /// Whether a BIP39 25th word is set — the mnemonic alone then does NOT
+    /// recover the funds.
+    pub has_bip39_passphrase: bool,
+    /// Re-reveals without the passphrase until `expires_at`.
+    pub token: String,
+    /// Unix seconds.
+    pub expires_at: u64,
+}
+
+/// Word positions to answer (`btcx_wallet_backup_challenge`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupChallenge {
+    /// 1-based, ascending.
+    pub positions: Vec<usize>,
+    /// Length of the mnemonic (12 or 24).
+    pub word_count: usize,
+    /// Unix seconds.
+    pub expires_at: u64,
+}
+
+/// Outcome of `btcx_wallet_verify_backup`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupVerification {
+    pub valid: bool,
+    /// The recorded timestamp on success.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<u64>,
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `network/name` of the active wallet, refusing non-seed wallets.
+fn active_seed_wallet(state: &SharedBtcxWalletState) -> Result<String, String> {
+    let config = state.get_config();
+    let name = config.active_wallet_name();
+    let meta = config
+        .wallet_meta(config.network, &name)
+        .ok_or("No active wallet")?;
+    if meta.source != WalletSourceCfg::Seed {
+        return Err("This wallet has no mnemonic — its keys are imported descriptors".into());
+    }
+    Ok(format!("{}/{name}", config.network.as_str()))
+}
+
+/// `k` distinct 1-based positions out of `n`, ascending.
+fn pick_positions(rng: &mut impl Rng, n: usize, k: usize) -> Result<Vec<usize>, String> {
+    if n == 0 {
+        return Err("The stored seed has no words".into());
+    }
+    let k = k.clamp(MIN_CHALLENGE_WORDS.min(n), n);
+    let mut positions: Vec<usize> = rand::seq::index::sample(rng, n, k)
+        .into_iter()
+        .map(|i| i + 1)
+        .collect();
+    positions.sort_unstable();
+    Ok(positions)
+}
+
+/// Wrong answers among `answers` (challenge order) for `positions`.
+/// Case and surrounding whitespace are ignored.
+fn count_mistakes(words: &[&str], positions: &[usize], answers: &[String]) -> usize {
+    positions
+        .iter()
+        .zip(answers)
+        .filter(|(&pos, answer)| {
+            !words
+                .get(pos - 1)
+                .is_some_and(|w| w.eq_ignore_ascii_case(answer.trim()))
+        })
+        .count()
+}
+
+/// Refuse while `wallet` is locked out after too many failed checks.
+fn check_lockout(failed: &Option<FailedChecks>, wallet: &str, now: u64) -> Result<(), String> {
+    match failed {
+        Some(f) if f.wallet == wallet && f.locked_until > now => Err(format!(
+            "Too many failed backup checks — try again in {} minutes",
+            (f.locked_until - now).div_ceil(60)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Count one more failed check of `wallet`; the [`MAX_FAILED_CHECKS`]th in
+/// a row starts a lockout. A lapsed lockout or another wallet starts over.
+fn record_failure(failed: &mut Option<FailedChecks>, wallet: &str, now: u64) {
+    let entry = failed
+        .take()
+        .filter(|f| f.wallet == wallet && (f.locked_until == 0 || f.locked_until > now))
+        .unwrap_or(FailedChecks {
+            wallet: wallet.to_string(),
+            failures: 0,
+            locked_until: 0,
+        });
+    let failures = entry.failures + 1;
+    let locked_until = if failures >= MAX_FAILED_CHECKS {
+        now + LOCKOUT_SECS
+    } else {
+        0
+    };
+    *failed = Some(FailedChecks {
+        wallet: entry.wallet,
+        failures,
+        locked_until,
+    });
+}
+
+/// The active seed wallet's mnemonic and whether a BIP39 25th word is set,
+/// gated on the at-rest `passphrase` when the seed is passphrase-encrypted
+/// — checked against a fresh store, so an unlocked session alone does not
+/// pass. A transparently wrapped seed has no passphrase to check and
+/// needs `confirm` instead. Shared with the codex32 split
+/// (`btcx_wallet_split_seed`).
+pub(crate) fn gated_mnemonic(
+    state: &SharedBtcxWalletState,
+    passphrase: Option<&str>,
+    confirm: bool,
+) -> Result<(String, bool), String> {
+    active_seed_wallet(state)?;
+    let root = state.get_config().active_wallet_root();
+    let mut store =
+        SeedStore::open(&root, None).map_err(|e| format!("Failed to open seed store: {e:#}"))?;
+    if state::seed_needs_passphrase(
+        &std::fs::read_to_string(root.join(seedstore::SEED_FILE)).unwrap_or_default(),
+    ) {
+        let pass = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or("Enter the wallet passphrase to show the recovery phrase")?;
+        store.unlock(pass).map_err(|e| format!("{e:#}"))?;
+    } else if !confirm {
+        return Err(
+            "This wallet has no passphrase — anyone at this computer can read the recovery \
+             phrase; confirm to show it"
+                .into(),
+        );
+    }
+    let mnemonic = store.mnemonic().map_err(|e| format!("{e:#}"))?;
+    let has_bip39_passphrase = !descstore::read_bip39_passphrase(&root)?.is_empty();
+    Ok((mnemonic, has_bip39_passphrase))
+}
+
+/// Reveal the active seed wallet's mnemonic: with its at-rest `passphrase`
+/// (required when the seed is passphrase-encrypted, `confirm` otherwise),
+/// or with the `token` of a still-live reveal of the same wallet.
+pub fn reveal(
+    state: &SharedBtcxWalletState,
+    passphrase: Option<&str>,
+    token: Option<&str>,
+    confirm: bool,
+) -> Result<MnemonicReveal, String> {
+    let wallet = active_seed_wallet(state)?;
+    let now = now_secs();
+    let mut session = REVEAL.lock().map_err(|_| "reveal lock poisoned")?;
+    let live = session
+        .as_ref()
+        .filter(|s| s.wallet == wallet && s.expires_at > now)
+        .filter(|s| token.is_some_and(|t| t == s.token))
+        .map(|s| (s.token.clone(), s.expires_at));
+
+    let (mnemonic, has_bip39_passphrase, token, expires_at) = match live {
+        Some((token, expires_at)) => {
+            // A live token stands in for the passphrase (and the
+            // confirmation): an encrypted seed reads through the session's
+            // held (unlocked) store.
+            let root = state.get_config().active_wallet_root();
+            let mnemonic = if state::seed_needs_passphrase(
+                &std::fs::read_to_string(root.join(seedstore::SEED_FILE)).unwrap_or_default(),
+            ) {
+                state.with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}")))?
+            } else {
+                gated_mnemonic(state, None, true)?.0
+            };
+            let has_bip39 = !descstore::read_bip39_passphrase(&root)?.is_empty();
+            (mnemonic, has_bip39, token, expires_at)
+        }
+        None => {
+            let (mnemonic, has_bip39) = gated_mnemonic(state, passphrase, confirm)?;
+            let token = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+            let expires_at = now + REVEAL_TTL_SECS;
+            *session = Some(RevealSession {
+                wallet,
+                token: token.clone(),
+                expires_at,
+            });
+            (mnemonic, has_bip39, token, expires_at)
+        }
+    };
+    Ok(MnemonicReveal {
+        mnemonic,
+        has_bip39_passphrase,
+        token,
+        expires_at,
+    })
+}
+
+/// Issue a verification challenge: `count` random word positions (default
+/// [`DEFAULT_CHALLENGE_WORDS`], at least [`MIN_CHALLENGE_WORDS`]) of the
+/// active seed. Replaces any pending challenge. Needs the seed readable
+/// (unlocked), and is refused during a lockout.
+pub fn challenge(
+    state: &SharedBtcxWalletState,
+    count: Option<usize>,
+) -> Result<BackupChallenge, String> {
+    let wallet = active_seed_wallet(state)?;
+    check_lockout(
+        &*FAILED.lock().map_err(|_| "backup check lock poisoned")?,
+        &wallet,
+        now_secs(),
+    )?;
+    let mnemonic = state
+        .with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}")))
+        .map_err(|_| "Unlock the wallet first".to_string())?;
+    let word_count = mnemonic.split_whitespace().count();
+    let positions = pick_positions(
+        &mut rand::thread_rng(),
+        word_count,
+        count.unwrap_or(DEFAULT_CHALLENGE_WORDS),
+    )?;
+    let expires_at = now_secs() + CHALLENGE_TTL_SECS;
+    *CHALLENGE.lock().map_err(|_| "challenge lock poisoned")? = Some(Challenge {
+        wallet,
+        positions: positions.clone(),
+        expires_at,
+    });
+    Ok(BackupChallenge {
+        positions,
+        word_count,
+        expires_at,
+    })
+}
+
+/// Check `answers` (challenge order) against the stored seed. Consumes the
+/// pending challenge whatever the outcome; a failure counts toward the
+/// lockout, success clears it and records the timestamp on every seed
+/// compartment of the active wallet's group.
+pub fn verify(
+    state: &SharedBtcxWalletState,
+    answers: &[String],
+) -> Result<BackupVerification, String> {
+    let wallet = active_seed_wallet(state)?;
+    let challenge = CHALLENGE
+        .lock()
+        .map_err(|_| "challenge lock poisoned")?
+        .take()
+        .filter(|c| c.wallet == wallet && c.expires_at > now_secs())
+        .ok_or("No pending backup check — start a new one")?;
+    if answers.len() != challenge.positions.len() {
+        return Err(format!(
+            "Expected {} words, got {}",
+            challenge.positions.len(),
+            answers.len()
+        ));
+    }
+    let mnemonic = state.with_seed(|s| s.mnemonic().map_err(|e| format!("{e:#}")))?;
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    let mut failed = FAILED.lock().map_err(|_| "backup check lock poisoned")?;
+    if count_mistakes(&words, &challenge.positions, answers) > 0 {
+        record_failure(&mut failed, &wallet, now_secs());
+        return Ok(BackupVerification {
+            valid: false,
+            verified_at: None,
+        });
+    }
+    *failed = None;
+    drop(failed);
+
+    let now = now_secs();
+    state.update_config(|c| {
+        let network = c.network;
+        let group = c.group_of(network, &c.active_wallet_name());
+        for name in c.group_members(network, &group) {
+            if let Some(mut meta) = c.wallet_meta(network, &name) {
+                if meta.source == WalletSourceCfg::Seed {
+                    meta.backup_verified_at = Some(now);
+                    c.set_wallet_meta(network, &name, meta);
+                }
+            }
+        }
+    })?;
+    Ok(BackupVerification {
+        valid: true,
+        verified_at: Some(now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_distinct_sorted_and_in_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let p = pick_positions(&mut rng, 24, 4).unwrap();
+            assert_eq!(p.len(), 4);
+            assert!(p.windows(2).all(|w| w[0] < w[1]));
+            assert!(p.iter().all(|&i| (1..=24).contains(&i)));
+        }
+        // Counts clamp to the mnemonic length and the minimum.
+        assert_eq!(pick_positions(&mut rng, 12, 40).unwrap().len(), 12);
+        assert_eq!(
+            pick_positions(&mut rng, 12, 0).unwrap().len(),
+            MIN_CHALLENGE_WORDS
+        );
+        assert_eq!(
+            pick_positions(&mut rng, 12, 1).unwrap().len(),
+            MIN_CHALLENGE_WORDS
+        );
+        // An empty seed is an error, not a panic.
+        assert!(pick_positions(&mut rng, 0, 4).is_err());
+    }
+
+    #[test]
+    fn mistakes_ignore_case_and_whitespace() {
+        let words = ["abandon", "ability", "able", "about"];
+        let answers = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            count_mistakes(&words, &[1, 3], &answers(&[" Abandon", "ABLE "])),
+            0
+        );
+        assert_eq!(
+            count_mistakes(&words, &[2, 4], &answers(&["ability", "above"])),
+            1
+        );
+        assert_eq!(count_mistakes(&words, &[5], &answers(&["zoo"])), 1);
+    }
+
+    #[test]
+    fn repeated_failures_lock_challenges_out() {
+        let mut failed = None;
+        let now = 1_000;
+        for _ in 1..MAX_FAILED_CHECKS {
+            record_failure(&mut failed, "regtest/a", now);
+            assert!(check_lockout(&failed, "regtest/a", now).is_ok());
+        }
+        record_failure(&mut failed, "regtest/a", now);
+        assert!(check_lockout(&failed, "regtest/a", now).is_err());
+        // Only that wallet, and only for the lockout window.
+        assert!(check_lockout(&failed, "regtest/b", now).is_ok());
+        let later = now + LOCKOUT_SECS;
+        assert!(check_lockout(&failed, "regtest/a", later).is_ok());
+        // After it lapses the count starts over.
+        record_failure(&mut failed, "regtest/a", later);
+        assert!(check_lockout(&failed, "regtest/a", later).is_ok());
+        assert_eq!(failed.as_ref().unwrap().failures, 1);
+    }
+}
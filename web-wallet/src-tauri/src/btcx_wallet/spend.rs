@@ -0,0 +1,1073 @@
+//! Client-side spend pipeline: coin control → build → sign → broadcast.
+//!
+//! wallet-btcx's one-shot `wallet_send` coin-selects over EVERY wallet UTXO
+//! and has no hook for an exclusion list, so sends are built here over the
+//! BDK wallet instead — the same way the PSBT compose path and the forging
+//! assignment markers already are. That puts one coin-control rule in front
+//! of every builder:
+//!
+//! - **Frozen coins** (BIP-329 `spendable: false`, see `labels`) are never
+//!   auto-selected, and an explicit input list naming one is refused — the
+//!   user thaws the coin first. A label file that cannot be read fails the
+//!   build rather than silently forgetting the freezes.
+//! - **Unverified receives** (confirmed, no SPV proof yet — see `spv`) are
+//!   never auto-selected either: the balance counts them as pending, so a
+//!   send spends exactly what the balance calls spendable. Naming one as
+//!   an explicit input is the user's call and allowed.
+//! - **Explicit inputs** (`txid:vout`) spend exactly those coins; change
+//!   still goes to the internal keychain.
+//!
+//! Batch payments (one transaction, N recipients — pool payouts) and CPFP
+//! children run through the same builder; payout CSV files are validated
+//! row by row against the active network before anything is built.
+//! Cancelling an unconfirmed send is a BIP-125 replacement that spends the
+//! same inputs back to the wallet at the minimum fee the relay rules accept.
+//!
+//! Broadcast is home-first with fallover to the healthy views (any
+//! acceptance wins), then the tx is folded into the local cache and
+//! persisted — broadcast-before-persist, like wallet-btcx sends: a crash
+//! re-learns the tx from our own spk history on the next sync.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use bdk_wallet::{SignOptions, TxBuilder, TxOrdering};
+use bitcoin::{Amount, Denomination, OutPoint, Psbt, ScriptBuf, Sequence, Transaction};
+use serde::{Deserialize, Serialize};
+
+use electrum_btcx::{SendFee, WalletEntry};
+use wallet_btcx::BdkWalletBackend;
+
+use super::config::WalletNetwork;
+use super::labels::LabelType;
+use super::state::SharedBtcxWalletState;
+
+/// Resolve a send fee (explicit rate or confirmation target) to a bdk
+/// feerate — sat/kvB → sat/kwu, the same rounding wallet-btcx uses.
+pub fn resolve_feerate(
+    backend: &BdkWalletBackend,
+    fee: SendFee,
+) -> Result<bitcoin::FeeRate, String> {
+    let feerate_kvb = backend
+        .chain()
+        .resolve_send_fee(fee)
+        .map_err(|e| format!("{e:#}"))?;
+    Ok(bitcoin::FeeRate::from_sat_per_kwu((feerate_kvb + 2) / 4))
+}
+
+/// The active wallet's frozen outpoints. Unlike the display paths, an
+/// unreadable label file is an ERROR here: building without the freezes
+/// could spend exactly the coins the user set aside.
+pub fn frozen_outpoints(state: &SharedBtcxWalletState) -> Result<BTreeSet<OutPoint>, String> {
+    Ok(super::labels::read_active(state)?.frozen())
+}
+
+/// The coins automatic selection must leave alone.
+#[derive(Debug, Clone, Default)]
+pub struct CoinControl {
+    /// Frozen by the user — never spent, not even when named.
+    pub frozen: BTreeSet<OutPoint>,
+    /// Confirmed receives without an SPV proof — skipped by selection.
+    pub unverified: BTreeSet<OutPoint>,
+    /// Spent by a live preview (see `preview`) — skipped by selection.
+    pub reserved: BTreeSet<OutPoint>,
+}
+
+impl CoinControl {
+    /// Every outpoint selection must skip, minus the `explicit` inputs the
+    /// caller spends on purpose (those were already checked for freezes).
+    pub fn unspendable(&self, explicit: &[OutPoint]) -> Vec<OutPoint> {
+        let skipped: BTreeSet<&OutPoint> = self
+            .frozen
+            .iter()
+            .chain(&self.unverified)
+            .chain(&self.reserved)
+            .collect();
+        skipped
+            .into_iter()
+            .filter(|o| !explicit.contains(o))
+            .copied()
+            .collect()
+    }
+}
+
+/// Coin control for a build on the open wallet: the freezes, the receives
+/// the balance does not count yet and the coins live previews hold. Waits
+/// for the first sync and
+/// runs an SPV pass first (best effort — an unreachable server leaves the
+/// coins unverified, i.e. unselected). A wallet without an independent
+/// second server holds nothing back ([`super::spv::holds_back`]).
+pub fn coin_control(state: &SharedBtcxWalletState) -> Result<CoinControl, String> {
+    let frozen = frozen_outpoints(state)?;
+    state.ensure_first_sync()?;
+    if !super::spv::holds_back(state) {
+        return Ok(CoinControl {
+            frozen,
+            unverified: BTreeSet::new(),
+        });
+    }
+    if let Err(e) = super::spv::verify_active(state) {
+        log::debug!("btcx wallet: SPV pass before coin selection skipped: {e}");
+    }
+    let store = super::spv::read_active(state);
+    let unverified =
+        state.with_entry(|entry| Ok(super::spv::unverified_receives(&entry.wallet, &store)))?;
+    Ok(CoinControl {
+        frozen,
+        unverified,
+        reserved: super::preview::reserved(state),
+    })
+}
+
+/// Parse an explicit input list (`txid:vout` each).
+pub fn parse_outpoints(list: &[String]) -> Result<Vec<OutPoint>, String> {
+    list.iter()
+        .map(|s| OutPoint::from_str(s.trim()).map_err(|e| format!("{s}: {e}")))
+        .collect()
+}
+
+/// Refuse an explicit input list that names a frozen coin.
+pub fn check_not_frozen(inputs: &[OutPoint], frozen: &BTreeSet<OutPoint>) -> Result<(), String> {
+    match inputs.iter().find(|o| frozen.contains(o)) {
+        Some(o) => Err(format!(
+            "Coin {o} is frozen — unfreeze it before spending it"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Apply coin control to a builder: explicit inputs are spent exactly (and
+/// only), frozen, unverified and reserved coins are excluded from selection.
+pub fn apply_coin_control<Cs>(
+    builder: &mut TxBuilder<'_, Cs>,
+    inputs: Option<&[OutPoint]>,
+    control: &CoinControl,
+) -> Result<(), String> {
+    if let Some(inputs) = inputs {
+        check_not_frozen(inputs, &control.frozen)?;
+        builder
+            .add_utxos(inputs)
+            .map_err(|e| format!("coin selection: {e}"))?;
+        builder.manually_selected_only();
+    }
+    builder.unspendable(control.unspendable(inputs.unwrap_or_default()));
+    Ok(())
+}
+
+/// Sign every wallet input and extract the final transaction.
+pub fn sign_and_extract(entry: &mut WalletEntry, mut psbt: Psbt) -> Result<Transaction, String> {
+    let done = entry
+        .wallet
+        .sign(&mut psbt, SignOptions::default())
+        .map_err(|e| format!("signing: {e}"))?;
+    if !done {
+        return Err("wallet could not finalize the transaction".to_string());
+    }
+    psbt.extract_tx()
+        .map_err(|e| format!("extracting the transaction: {e}"))
+}
+
+/// Broadcast through the home server, falling over to the healthy views —
+/// any acceptance wins (the wallet-btcx `broadcast_fan` pattern; that
+/// method is private, so it is reproduced over the backend's public
+/// chain/views accessors).
+pub fn fan_broadcast(backend: &BdkWalletBackend, tx: &Transaction) -> Result<String, String> {
+    let mut last_err: Option<String> = None;
+    let mut tried = false;
+    for server in std::iter::once(backend.chain()).chain(backend.views().iter()) {
+        if !server.health().available() {
+            continue;
+        }
+        tried = true;
+        match server.broadcast(tx) {
+            Ok(txid) => return Ok(txid.to_string()),
+            Err(e) => last_err = Some(format!("{e:#}")),
+        }
+    }
+    if !tried {
+        // Everything is inside a backoff window: dial the home anyway
+        // rather than stranding a signed transaction.
+        return backend
+            .chain()
+            .broadcast(tx)
+            .map(|txid| txid.to_string())
+            .map_err(|e| format!("{e:#}"));
+    }
+    Err(format!(
+        "Transaction rejected — {}",
+        last_err.expect("tried at least one server")
+    ))
+}
+
+/// Broadcast a signed wallet transaction, then fold it into the cache and
+/// persist (which also keeps the change-address reveal). Returns the txid.
+/// The local policy checks run first (see `policy`): a transaction they
+/// reject never leaves the process; checks that cannot run (no prevouts
+/// from the server) leave the verdict to the server.
+pub fn broadcast_and_record(
+    state: &SharedBtcxWalletState,
+    backend: &BdkWalletBackend,
+    tx: &Transaction,
+) -> Result<String, String> {
+    super::consensus::gate(state, true)?;
+    match super::policy::check_wallet_tx(state, backend.params(), backend.chain(), tx) {
+        Ok(check) if !check.allowed => {
+            return Err(format!(
+                "Transaction fails the local policy checks — {}",
+                check.summary()
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("btcx wallet: policy checks skipped: {e}"),
+    }
+    let txid = fan_broadcast(backend, tx)?;
+    state.with_entry(|entry| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        entry.wallet.apply_unconfirmed_txs([(tx.clone(), now)]);
+        entry
+            .wallet
+            .persist(&mut entry.conn)
+            .map_err(|e| format!("persisting wallet: {e}"))?;
+        Ok(())
+    })?;
+    let _ = state.poke();
+    Ok(txid)
+}
+
+/// Build and sign one RBF-signaling wallet spend: `recipients` are paid
+/// exact amounts, `drain_to` (if any) receives everything left over
+/// instead of a change output — with no explicit `inputs` that drains every
+/// selectable coin. Returns the signed tx and its fee.
+fn build_signed(
+    entry: &mut WalletEntry,
+    feerate: bitcoin::FeeRate,
+    inputs: Option<&[OutPoint]>,
+    control: &CoinControl,
+    recipients: &[(ScriptBuf, u64)],
+    drain_to: Option<ScriptBuf>,
+) -> Result<(Transaction, Amount), String> {
+    let mut builder = entry.wallet.build_tx();
+    builder
+        .ordering(TxOrdering::Shuffle)
+        .fee_rate(feerate)
+        .set_exact_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME);
+    apply_coin_control(&mut builder, inputs, control)?;
+    for (spk, amount_sat) in recipients {
+        builder.add_recipient(spk.clone(), Amount::from_sat(*amount_sat));
+    }
+    if let Some(spk) = drain_to {
+        if inputs.is_none() {
+            builder.drain_wallet();
+        }
+        builder.drain_to(spk);
+    }
+    let psbt = builder
+        .finish()
+        .map_err(|e| format!("building the transaction: {e}"))?;
+    let fee = psbt.fee().map_err(|e| format!("computing the fee: {e}"))?;
+    Ok((sign_and_extract(entry, psbt)?, fee))
+}
+
+/// Pay `amount_sat` to `address` — or, with `amount_sat: None`, sweep every
+/// selectable coin (all unfrozen, verified coins, or exactly `inputs`) to it, fee
+/// taken out of the swept amount. RBF-signaling. Returns the txid.
+pub fn send(
+    state: &SharedBtcxWalletState,
+    address: &str,
+    amount_sat: Option<u64>,
+    fee: SendFee,
+    inputs: Option<&[String]>,
+) -> Result<String, String> {
+    let (tx, backend) = build_send(state, address, amount_sat, fee, inputs)?;
+    broadcast_and_record(state, &backend, &tx)
+}
+
+/// Build and sign [`send`]'s transaction without broadcasting it (the
+/// preview step, see `preview`). Returns it with the backend it was built
+/// against.
+pub fn build_send(
+    state: &SharedBtcxWalletState,
+    address: &str,
+    amount_sat: Option<u64>,
+    fee: SendFee,
+    inputs: Option<&[String]>,
+) -> Result<(Transaction, BdkWalletBackend), String> {
+    let inputs = inputs.map(parse_outpoints).transpose()?;
+    let spk: ScriptBuf = state
+        .get_config()
+        .network
+        .params()
+        .parse_address(address.trim())
+        .map_err(|e| format!("{address}: {e:#}"))?;
+    let backend = state.backend()?;
+    let feerate = resolve_feerate(&backend, fee)?;
+
+    let control = coin_control(state)?;
+    let (tx, _) = state.with_entry(|entry| match amount_sat {
+        Some(amount_sat) => build_signed(
+            entry,
+            feerate,
+            inputs.as_deref(),
+            &control,
+            &[(spk, amount_sat)],
+            None,
+        ),
+        None => build_signed(entry, feerate, inputs.as_deref(), &control, &[], Some(spk)),
+    })?;
+    Ok((tx, backend))
+}
+
+// ============================================================================
+// Batch payments
+// ============================================================================
+
+/// One payee of a batch send.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRecipient {
+    pub address: String,
+    pub amount_sat: u64,
+    /// Optional note; stored as the BIP-329 label of the paying output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// One rejected row of a payout CSV.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutCsvError {
+    /// 1-based line number in the file.
+    pub line: usize,
+    pub message: String,
+}
+
+/// A parsed payout CSV. Nothing may be built from it while `errors` is
+/// non-empty — every row is validated up front so a typo in row 37 never
+/// surfaces as a half-paid pool.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutCsv {
+    pub recipients: Vec<BatchRecipient>,
+    pub errors: Vec<PayoutCsvError>,
+    /// Sum of the valid rows' amounts.
+    pub total_sat: u64,
+}
+
+/// Strip one pair of surrounding double quotes (spreadsheet exports).
+fn unquote(field: &str) -> &str {
+    let field = field.trim();
+    field
+        .strip_prefix('"')
+        .and_then(|f| f.strip_suffix('"'))
+        .unwrap_or(field)
+        .trim()
+}
+
+/// Parse a payout CSV — `address,amount[,label]` per row, amount in coins
+/// (up to 8 decimals, e.g. `0.125`). Every address is checked against the
+/// `network`'s own encoding (HRP / base58 prefixes), so a testnet or
+/// Bitcoin address is a row error, not a lost payment. Blank lines, `#`
+/// comments and a leading `address,amount` header are skipped; a label may
+/// itself contain commas.
+pub fn parse_payout_csv(network: WalletNetwork, text: &str) -> PayoutCsv {
+    let params = network.params();
+    let mut out = PayoutCsv {
+        recipients: Vec::new(),
+        errors: Vec::new(),
+        total_sat: 0,
+    };
+    let mut seen_row = false;
+    for (n, raw) in text.lines().enumerate() {
+        let line = raw.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.splitn(3, ',');
+        let address = unquote(fields.next().unwrap_or_default());
+        let amount = fields.next().map(unquote);
+        let label = fields
+            .next()
+            .map(unquote)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string);
+        let first_row = !seen_row;
+        seen_row = true;
+        if first_row && address.eq_ignore_ascii_case("address") {
+            continue;
+        }
+        let mut fail = |message: String| {
+            out.errors.push(PayoutCsvError {
+                line: n + 1,
+                message,
+            })
+        };
+        let Some(amount) = amount.filter(|a| !a.is_empty()) else {
+            fail("missing amount (expected address,amount[,label])".into());
+            continue;
+        };
+        if let Err(e) = params.parse_address(address) {
+            fail(format!("{address}: {e:#}"));
+            continue;
+        }
+        let amount_sat = match Amount::from_str_in(amount, Denomination::Bitcoin) {
+            Ok(a) if a > Amount::ZERO => a.to_sat(),
+            Ok(_) => {
+                fail("amount must be greater than zero".into());
+                continue;
+            }
+            Err(e) => {
+                fail(format!("amount {amount}: {e}"));
+                continue;
+            }
+        };
+        out.total_sat = out.total_sat.saturating_add(amount_sat);
+        out.recipients.push(BatchRecipient {
+            address: address.to_string(),
+            amount_sat,
+            label,
+        });
+    }
+    out
+}
+
+/// Outcome of a batch send — or, on a dry run, of the identical build that
+/// was signed but never broadcast.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSendResult {
+    /// Absent on a dry run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    pub recipients: usize,
+    /// Sum paid to the recipients.
+    pub amount_sat: u64,
+    pub fee_sat: u64,
+    /// Value returned to our own change address (0 when there is none).
+    pub change_sat: u64,
+    pub vsize: u64,
+    /// Effective feerate of the signed transaction.
+    pub fee_rate_sat_vb: f64,
+}
+
+/// Pay every recipient in ONE RBF-signaling transaction (frozen coins
+/// excluded, `inputs` pinning the coins when given). `dry_run` builds and
+/// signs the exact transaction — so fee, change and vsize are final, not
+/// estimates — then releases its change address and stops short of
+/// broadcasting. A real send stores each row's label on its output.
+pub fn batch_send(
+    state: &SharedBtcxWalletState,
+    recipients: &[BatchRecipient],
+    fee: SendFee,
+    inputs: Option<&[String]>,
+    dry_run: bool,
+) -> Result<BatchSendResult, String> {
+    if recipients.is_empty() {
+        return Err("Give at least one recipient".to_string());
+    }
+    let params = state.get_config().network.params();
+    let payees: Vec<(ScriptBuf, u64)> = recipients
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            if r.amount_sat == 0 {
+                return Err(format!(
+                    "recipient {}: amount must be greater than zero",
+                    i + 1
+                ));
+            }
+            params
+                .parse_address(r.address.trim())
+                .map(|spk| (spk, r.amount_sat))
+                .map_err(|e| format!("recipient {} ({}): {e:#}", i + 1, r.address))
+        })
+        .collect::<Result<_, _>>()?;
+    let inputs = inputs.map(parse_outpoints).transpose()?;
+    let backend = state.backend()?;
+    let feerate = resolve_feerate(&backend, fee)?;
+
+    let control = coin_control(state)?;
+    let (tx, fee, change_sat) = state.with_entry(|entry| {
+        let (tx, fee) = build_signed(entry, feerate, inputs.as_deref(), &control, &payees, None)?;
+        let change_sat = tx
+            .output
+            .iter()
+            .filter(|o| {
+                matches!(
+                    entry.wallet.derivation_of_spk(o.script_pubkey.clone()),
+                    Some((bdk_wallet::KeychainKind::Internal, _))
+                )
+            })
+            .map(|o| o.value.to_sat())
+            .sum::<u64>();
+        if dry_run {
+            // Hand the change address back: nothing will ever pay it.
+            entry.wallet.cancel_tx(&tx);
+        }
+        Ok((tx, fee, change_sat))
+    })?;
+
+    let vsize = tx.vsize() as u64;
+    let mut result = BatchSendResult {
+        txid: None,
+        recipients: payees.len(),
+        amount_sat: payees.iter().map(|(_, a)| a).sum(),
+        fee_sat: fee.to_sat(),
+        change_sat,
+        vsize,
+        fee_rate_sat_vb: fee.to_sat() as f64 / vsize.max(1) as f64,
+    };
+    if dry_run {
+        return Ok(result);
+    }
+    let txid = broadcast_and_record(state, &backend, &tx)?;
+    label_batch_outputs(state, &txid, &tx, recipients, &payees);
+    result.txid = Some(txid);
+    Ok(result)
+}
+
+/// Store each labelled row as the BIP-329 label of the output paying it.
+/// Best effort — the payment is already out, a label write must not turn
+/// it into an error.
+fn label_batch_outputs(
+    state: &SharedBtcxWalletState,
+    txid: &str,
+    tx: &Transaction,
+    recipients: &[BatchRecipient],
+    payees: &[(ScriptBuf, u64)],
+) {
+    let mut claimed = vec![false; tx.output.len()];
+    let mut rows = Vec::new();
+    for (recipient, (spk, amount_sat)) in recipients.iter().zip(payees) {
+        // Outputs are shuffled; match on (script, value), each output once.
+        let Some(vout) = tx.output.iter().enumerate().position(|(i, o)| {
+            !claimed[i] && o.script_pubkey == *spk && o.value.to_sat() == *amount_sat
+        }) else {
+            continue;
+        };
+        claimed[vout] = true;
+        if let Some(label) = &recipient.label {
+            rows.push((format!("{txid}:{vout}"), label.clone()));
+        }
+    }
+    if rows.is_empty() {
+        return;
+    }
+    let written = super::labels::update_active(state, |labels| {
+        for (outpoint, label) in &rows {
+            labels.set(LabelType::Output, outpoint, label)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        log::warn!("btcx wallet: labelling batch outputs of {txid} failed: {e}");
+    }
+}
+
+// ============================================================================
+// Child-pays-for-parent
+// ============================================================================
+
+/// Outcome of a CPFP child broadcast.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpfpResult {
+    /// The child's txid.
+    pub txid: String,
+    pub parent_txid: String,
+    pub parent_fee_sat: u64,
+    pub parent_vsize: u64,
+    pub child_fee_sat: u64,
+    pub child_vsize: u64,
+    /// (parent fee + child fee) / (parent vsize + child vsize) — what a
+    /// miner evaluating the package actually earns per vbyte.
+    pub package_fee_rate_sat_vb: f64,
+}
+
+/// The child fee that lifts a (parent, child) package to `rate_sat_vb`,
+/// never below the child's own 1 sat/vB relay floor.
+pub fn cpfp_child_fee(
+    parent_fee: u64,
+    parent_vsize: u64,
+    child_vsize: u64,
+    rate_sat_vb: f64,
+) -> u64 {
+    let package = (rate_sat_vb * (parent_vsize + child_vsize) as f64).ceil() as u64;
+    package.saturating_sub(parent_fee).max(child_vsize)
+}
+
+/// Fee a transaction pays, from its prevouts (`prevs`: the funding txs of
+/// its inputs, any order).
+fn fee_from_prevouts(tx: &Transaction, prevs: &[Transaction]) -> Result<u64, String> {
+    let by_txid: std::collections::HashMap<bitcoin::Txid, &Transaction> =
+        prevs.iter().map(|p| (p.compute_txid(), p)).collect();
+    let mut input_sat = 0u64;
+    for txin in &tx.input {
+        let prevout = txin.previous_output;
+        let value = by_txid
+            .get(&prevout.txid)
+            .and_then(|p| p.output.get(prevout.vout as usize))
+            .ok_or_else(|| format!("prevout {prevout} not found"))?
+            .value
+            .to_sat();
+        input_sat += value;
+    }
+    let output_sat: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+    input_sat
+        .checked_sub(output_sat)
+        .ok_or_else(|| "outputs exceed inputs".to_string())
+}
+
+/// Speed up a stuck UNCONFIRMED incoming transaction by spending our
+/// output(s) of it in a child whose fee lifts the package to
+/// `package_fee_rate_sat_vb`. The parent's fee comes from its prevouts,
+/// fetched over Electrum (a foreign tx's inputs are not in our graph); its
+/// own unconfirmed ancestors are not counted. The child sweeps our parent
+/// outputs to a fresh change address, pulling in another (unfrozen) coin
+/// only when those outputs cannot cover the fee.
+pub fn cpfp(
+    state: &SharedBtcxWalletState,
+    parent_txid: &str,
+    package_fee_rate_sat_vb: f64,
+) -> Result<CpfpResult, String> {
+    if !package_fee_rate_sat_vb.is_finite() || package_fee_rate_sat_vb <= 0.0 {
+        return Err("Give a positive package feerate".to_string());
+    }
+    let parent_txid: bitcoin::Txid = parent_txid
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid txid: {e}"))?;
+    let backend = state.backend()?;
+    let control = coin_control(state)?;
+
+    let (parent, ours, change_spk) = state.with_entry(|entry| {
+        use bdk_wallet::chain::ChainPosition;
+        let wtx = entry
+            .wallet
+            .get_tx(parent_txid)
+            .ok_or_else(|| "transaction not found in wallet history".to_string())?;
+        if matches!(wtx.chain_position, ChainPosition::Confirmed { .. }) {
+            return Err("The transaction is already confirmed".to_string());
+        }
+        let parent = wtx.tx_node.tx.clone();
+        let ours: Vec<OutPoint> = entry
+            .wallet
+            .list_unspent()
+            .filter(|u| u.outpoint.txid == parent_txid)
+            .map(|u| u.outpoint)
+            .collect();
+        let change_spk = entry
+            .wallet
+            .next_unused_address(bdk_wallet::KeychainKind::Internal)
+            .address
+            .script_pubkey();
+        Ok((parent, ours, change_spk))
+    })?;
+    if parent.is_coinbase() {
+        return Err("A coinbase transaction cannot be fee-bumped".to_string());
+    }
+    if ours.is_empty() {
+        return Err(
+            "The transaction pays no unspent output to this wallet — there is nothing to \
+             spend from it"
+                .to_string(),
+        );
+    }
+    check_not_frozen(&ours, &control.frozen)?;
+
+    let mut prev_txids: Vec<String> = parent
+        .input
+        .iter()
+        .map(|i| i.previous_output.txid.to_string())
+        .collect();
+    prev_txids.sort();
+    prev_txids.dedup();
+    let prevs = backend
+        .chain()
+        .get_raw_txs(&prev_txids)
+        .map_err(|e| format!("fetching the parent's inputs: {e:#}"))?;
+    let parent_fee = fee_from_prevouts(&parent, &prevs)?;
+    let parent_vsize = parent.vsize() as u64;
+    let parent_rate = parent_fee as f64 / parent_vsize as f64;
+    if parent_rate >= package_fee_rate_sat_vb {
+        return Err(format!(
+            "The transaction already pays {parent_rate:.2} sat/vB — no child is needed"
+        ));
+    }
+
+    // The child's fee depends on its own size, which depends on whether an
+    // extra coin is needed: build at the target rate to learn the shape,
+    // then rebuild at the exact absolute fee until the package clears.
+    let feerate = bitcoin::FeeRate::from_sat_per_kwu(
+        (package_fee_rate_sat_vb * 250.0).ceil().max(1.0) as u64,
+    );
+    let mut child_fee: Option<u64> = None;
+    for _ in 0..3 {
+        let (child, fee) = state.with_entry(|entry| {
+            let mut builder = entry.wallet.build_tx();
+            builder
+                .ordering(TxOrdering::Shuffle)
+                .set_exact_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME)
+                .drain_to(change_spk.clone());
+            match child_fee {
+                Some(sat) => builder.fee_absolute(Amount::from_sat(sat)),
+                None => builder.fee_rate(feerate),
+            };
+            builder
+                .add_utxos(&ours)
+                .map_err(|e| format!("selecting the parent output: {e}"))?;
+            builder.unspendable(control.unspendable(&ours));
+            let psbt = builder
+                .finish()
+                .map_err(|e| format!("building the child: {e}"))?;
+            let fee = psbt.fee().map_err(|e| format!("computing the fee: {e}"))?;
+            let child = sign_and_extract(entry, psbt)?;
+            Ok((child, fee.to_sat()))
+        })?;
+        let child_vsize = child.vsize() as u64;
+        let needed = cpfp_child_fee(
+            parent_fee,
+            parent_vsize,
+            child_vsize,
+            package_fee_rate_sat_vb,
+        );
+        if fee >= needed {
+            let txid = broadcast_and_record(state, &backend, &child)?;
+            return Ok(CpfpResult {
+                txid,
+                parent_txid: parent_txid.to_string(),
+                parent_fee_sat: parent_fee,
+                parent_vsize,
+                child_fee_sat: fee,
+                child_vsize,
+                package_fee_rate_sat_vb: (parent_fee + fee) as f64
+                    / (parent_vsize + child_vsize) as f64,
+            });
+        }
+        state.with_entry(|entry| {
+            entry.wallet.cancel_tx(&child);
+            Ok(())
+        })?;
+        child_fee = Some(needed);
+    }
+    Err("Could not settle the child's fee — try again".to_string())
+}
+
+// ============================================================================
+// Cancel (RBF double-spend back to the wallet)
+// ============================================================================
+
+/// Incremental relay feerate (Core's `-incrementalrelayfee` default, which
+/// Bitcoin-PoCX keeps): what a replacement must add per vbyte of its own.
+pub const INCREMENTAL_RELAY_SAT_VB: u64 = 1;
+
+/// Outcome of a cancel broadcast.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelResult {
+    /// The replacement's txid.
+    pub txid: String,
+    /// The cancelled transaction.
+    pub replaced_txid: String,
+    /// What came back to the wallet.
+    pub amount_sat: u64,
+    pub fee_sat: u64,
+    pub vsize: u64,
+    pub fee_rate_sat_vb: f64,
+}
+
+/// The smallest fee a replacement of `replacement_vsize` vB may pay when
+/// it evicts an original (`original_fee` over `original_vsize`) whose
+/// evicted set — original plus unconfirmed descendants — paid
+/// `evicted_fee`: at least the evicted fees plus the incremental relay fee
+/// on its own size (BIP-125 rules 3 and 4), and a strictly higher feerate
+/// than the original (Core's rule 6).
+pub fn min_replacement_fee(
+    original_fee: u64,
+    original_vsize: u64,
+    evicted_fee: u64,
+    replacement_vsize: u64,
+) -> u64 {
+    let pays_for_bandwidth = evicted_fee + INCREMENTAL_RELAY_SAT_VB * replacement_vsize;
+    let beats_feerate = original_fee * replacement_vsize / original_vsize.max(1) + 1;
+    pays_for_bandwidth.max(beats_feerate)
+}
+
+/// Cancel an UNCONFIRMED, RBF-signaling wallet send: replace it with a
+/// transaction spending the same inputs to a fresh internal address, at the
+/// minimum fee that replaces it (see [`min_replacement_fee`]). Every input
+/// must be the wallet's own. Unconfirmed descendants the wallet knows are
+/// evicted too, so their fees are counted. The link is recorded for the
+/// transaction detail (see `replacements`).
+pub fn cancel(state: &SharedBtcxWalletState, txid: &str) -> Result<CancelResult, String> {
+    let txid: bitcoin::Txid = txid
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid txid: {e}"))?;
+    let backend = state.backend()?;
+    let control = coin_control(state)?;
+
+    let (original_fee, original_vsize, evicted_fee, change_spk) = state.with_entry(|entry| {
+        use bdk_wallet::chain::ChainPosition;
+        let wtx = entry
+            .wallet
+            .get_tx(txid)
+            .ok_or_else(|| "transaction not found in wallet history".to_string())?;
+        if matches!(wtx.chain_position, ChainPosition::Confirmed { .. }) {
+            return Err("The transaction is already confirmed".to_string());
+        }
+        let original = wtx.tx_node.tx.clone();
+        if !original.is_explicitly_rbf() {
+            return Err("The transaction does not signal replaceability (BIP-125)".to_string());
+        }
+        let graph = entry.wallet.tx_graph();
+        let all_ours = original.input.iter().all(|txin| {
+            graph
+                .get_txout(txin.previous_output)
+                .is_some_and(|o| entry.wallet.is_mine(o.script_pubkey.clone()))
+        });
+        if !all_ours {
+            return Err("Only the wallet's own sends can be cancelled".to_string());
+        }
+        let original_fee = entry
+            .wallet
+            .calculate_fee(&original)
+            .map_err(|e| format!("computing the fee: {e}"))?
+            .to_sat();
+        let descendants: Vec<bitcoin::Txid> =
+            graph.walk_descendants(txid, |_, d| Some(d)).collect();
+        let mut evicted_fee = original_fee;
+        for d in descendants {
+            let Some(child) = entry.wallet.get_tx(d) else {
+                continue; // already evicted itself
+            };
+            let child = child.tx_node.tx.clone();
+            evicted_fee += entry
+                .wallet
+                .calculate_fee(&child)
+                .map_err(|e| format!("computing the fee of descendant {d}: {e}"))?
+                .to_sat();
+        }
+        let change_spk = entry
+            .wallet
+            .next_unused_address(bdk_wallet::KeychainKind::Internal)
+            .address
+            .script_pubkey();
+        Ok((
+            original_fee,
+            original.vsize() as u64,
+            evicted_fee,
+            change_spk,
+        ))
+    })?;
+
+    // The fee depends on the replacement's size: start from the original's
+    // size (a one-output cancel is never larger), then settle on the exact
+    // minimum for the signed size.
+    let mut fee_sat =
+        min_replacement_fee(original_fee, original_vsize, evicted_fee, original_vsize);
+    for attempt in 0..3 {
+        let (tx, amount_sat) = state.with_entry(|entry| {
+            // The original's own inputs are what a cancel spends, frozen or
+            // not — only extra coins are held back.
+            let inputs: Vec<OutPoint> = entry
+                .wallet
+                .get_tx(txid)
+                .map(|wtx| {
+                    wtx.tx_node
+                        .tx
+                        .input
+                        .iter()
+                        .map(|i| i.previous_output)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut builder = entry
+                .wallet
+                .build_fee_bump(txid)
+                .map_err(|e| format!("building the replacement: {e}"))?;
+            builder
+                .set_recipients(Vec::new())
+                .drain_to(change_spk.clone())
+                .fee_absolute(Amount::from_sat(fee_sat))
+                .set_exact_sequence(Sequence::ENABLE_RBF_NO_LOCKTIME)
+                .unspendable(control.unspendable(&inputs));
+            let psbt = builder
+                .finish()
+                .map_err(|e| format!("building the replacement: {e}"))?;
+            let tx = sign_and_extract(entry, psbt)?;
+            let amount_sat = tx.output.iter().map(|o| o.value.to_sat()).sum();
+            Ok((tx, amount_sat))
+        })?;
+        let vsize = tx.vsize() as u64;
+        let needed = min_replacement_fee(original_fee, original_vsize, evicted_fee, vsize);
+        // Signature lengths wobble by a byte between builds: once rebuilt,
+        // overshooting the minimum by that much is accepted.
+        if fee_sat == needed || (fee_sat > needed && attempt > 0) {
+            let replacement = broadcast_and_record(state, &backend, &tx)?;
+            super::replacements::record_active(state, &txid.to_string(), &replacement);
+            return Ok(CancelResult {
+                txid: replacement,
+                replaced_txid: txid.to_string(),
+                amount_sat,
+                fee_sat,
+                vsize,
+                fee_rate_sat_vb: fee_sat as f64 / vsize.max(1) as f64,
+            });
+        }
+        state.with_entry(|entry| {
+            entry.wallet.cancel_tx(&tx);
+            Ok(())
+        })?;
+        fee_sat = needed;
+    }
+    Err("Could not settle the replacement fee — try again".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_skips_unverified_and_reserved_coins_unless_named() {
+        let txid = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+        let coin = |vout| parse_outpoints(&[format!("{txid}:{vout}")]).unwrap()[0];
+        let control = CoinControl {
+            frozen: [coin(0)].into(),
+            unverified: [coin(1), coin(2)].into(),
+            reserved: [coin(2), coin(3)].into(),
+        };
+        assert_eq!(
+            control.unspendable(&[]),
+            vec![coin(0), coin(1), coin(2), coin(3)]
+        );
+        // An explicit input is spent on purpose, an unverified or reserved
+        // one included.
+        assert_eq!(
+            control.unspendable(&[coin(1), coin(3)]),
+            vec![coin(0), coin(2)]
+        );
+    }
+
+    #[test]
+    fn explicit_inputs_must_not_be_frozen() {
+        let txid = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+        let inputs = parse_outpoints(&[format!("{txid}:0"), format!(" {txid}:1 ")]).unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert!(parse_outpoints(&["nope".to_string()]).is_err());
+
+        let mut frozen = BTreeSet::new();
+        assert!(check_not_frozen(&inputs, &frozen).is_ok());
+        frozen.insert(inputs[1]);
+        let err = check_not_frozen(&inputs, &frozen).unwrap_err();
+        assert!(err.contains("frozen") && err.contains(":1"), "{err}");
+    }
+
+    #[test]
+    fn cpfp_child_fee_lifts_the_package() {
+        // 200 vB parent at 1 sat/vB, 110 vB child, target 10 sat/vB:
+        // package needs 3100, parent paid 200 → child pays 2900.
+        assert_eq!(cpfp_child_fee(200, 200, 110, 10.0), 2900);
+        // Fractional targets round the package UP.
+        assert_eq!(cpfp_child_fee(200, 200, 110, 1.5), 265);
+        // A parent already near the target still leaves the child its
+        // own relay floor.
+        assert_eq!(cpfp_child_fee(3000, 200, 110, 10.0), 110);
+    }
+
+    #[test]
+    fn cancel_fee_meets_every_replacement_rule() {
+        // 141 vB original paying 282 sat (2 sat/vB), 110 vB cancel:
+        // rules 3+4 need 282 + 110 = 392, rule 6 needs > 2 sat/vB → 221.
+        assert_eq!(min_replacement_fee(282, 141, 282, 110), 392);
+        // Evicted descendants raise the absolute floor.
+        assert_eq!(min_replacement_fee(282, 141, 1_000, 110), 1_110);
+        // A high-feerate original: the feerate rule dominates.
+        // 100 vB at 50 sat/vB → 110 vB needs > 5500, i.e. 5501.
+        assert_eq!(min_replacement_fee(5_000, 100, 5_000, 110), 5_501);
+    }
+
+    #[test]
+    fn parent_fee_comes_from_the_prevouts() {
+        use bitcoin::{absolute::LockTime, transaction::Version, TxIn, TxOut};
+        let funding = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                TxOut {
+                    value: Amount::from_sat(7_000),
+                    script_pubkey: ScriptBuf::new(),
+                },
+                TxOut {
+                    value: Amount::from_sat(5_000),
+                    script_pubkey: ScriptBuf::new(),
+                },
+            ],
+        };
+        let spend = |vouts: &[u32], out_sat: u64| Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vouts
+                .iter()
+                .map(|&vout| TxIn {
+                    previous_output: OutPoint::new(funding.compute_txid(), vout),
+                    ..Default::default()
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(out_sat),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        let prevs = [funding.clone()];
+        assert_eq!(fee_from_prevouts(&spend(&[0, 1], 11_500), &prevs), Ok(500));
+        assert!(fee_from_prevouts(&spend(&[0], 8_000), &prevs).is_err());
+        assert!(fee_from_prevouts(&spend(&[2], 1), &prevs).is_err());
+    }
+
+    #[test]
+    fn payout_csv_validates_every_row_against_the_network() {
+        use super::super::psbt::spk_to_address;
+        use bitcoin::hashes::Hash;
+        let spk = |b: u8| ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([b; 20]));
+        let regtest = |b| spk_to_address(WalletNetwork::Regtest, &spk(b)).unwrap();
+        let mainnet = spk_to_address(WalletNetwork::Mainnet, &spk(3)).unwrap();
+        let csv = format!(
+            "address,amount,label\n\
+             {a},0.5,miner one\n\
+             \n\
+             # paid weekly\n\
+             \"{b}\",\"1.00000001\",\"rig 7, rack 2\"\n\
+             {mainnet},0.1\n\
+             {a},0\n\
+             {a},0.123456789\n\
+             {b}\n",
+            a = regtest(1),
+            b = regtest(2),
+        );
+        let parsed = parse_payout_csv(WalletNetwork::Regtest, &csv);
+        assert_eq!(
+            parsed.recipients,
+            vec![
+                BatchRecipient {
+                    address: regtest(1),
+                    amount_sat: 50_000_000,
+                    label: Some("miner one".into()),
+                },
+                BatchRecipient {
+                    address: regtest(2),
+                    amount_sat: 100_000_001,
+                    label: Some("rig 7, rack 2".into()),
+                },
+            ]
+        );
+        assert_eq!(parsed.total_sat, 150_000_001);
+        let lines: Vec<usize> = parsed.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![6, 7, 8, 9], "{:?}", parsed.errors);
+        assert!(parsed.errors[0].message.contains(&mainnet));
+        assert!(parsed.errors[3].message.contains("missing amount"));
+    }
+}
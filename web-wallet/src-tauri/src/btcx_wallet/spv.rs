@@ -0,0 +1,594 @@
+//! SPV verification of the wallet's confirmed transactions.
+//!
+//! The sync takes confirmations as the Electrum server reports them. Each
+//! confirmed wallet tx is therefore checked once per block it confirms in:
+//! the block's PoCX header is fetched and must hash (`params-btcx`) to the
+//! block hash the sync anchored the tx to, and the server's
+//! `blockchain.transaction.get_merkle` branch must lead from the txid to
+//! that header's merkle root. Verified headers and proofs are kept in
+//! `wallet/spv.json` next to the bdk store; a reorg re-anchors the tx to
+//! another block hash, which is simply not verified yet.
+//!
+//! Header and branch come from the home server, so the header alone proves
+//! nothing about the chain — work is not checked here (see `consensus`).
+//! Before a header is stored it is therefore CROSS-CHECKED: at least one
+//! other server must serve the same header hash at that height, and more
+//! servers must agree with the home server than contradict it. Only
+//! INDEPENDENT servers count ([`independent`]): two URLs resolving to the
+//! same host are one server.
+//!
+//! Until its tx verifies, a confirmed output counts as PENDING in the
+//! balance (`btcx_wallet_balance`): change as trusted, receives as
+//! untrusted — and automatic coin selection skips those receives (see
+//! `spend::coin_control`), so what a send can use is what the balance
+//! calls spendable. A wallet without an independent second server has
+//! nothing to cross-check against and trusts its home server instead
+//! ([`holds_back`] is false): nothing is held back, as before SPV — the
+//! header consensus report is all it gets. The store is a cache —
+//! deleting it only costs a re-check.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bdk_wallet::chain::ChainPosition;
+use bdk_wallet::{KeychainKind, LocalOutput};
+use bitcoin::hashes::Hash;
+use bitcoin::OutPoint;
+use electrum_btcx::WalletHandle;
+use electrum_client::ElectrumApi;
+use params_btcx::params::ChainParams;
+use serde::{Deserialize, Serialize};
+
+use super::state::SharedBtcxWalletState;
+
+/// File name of the header/proof store inside a wallet's `wallet/` dir.
+pub const SPV_FILE: &str = "spv.json";
+
+/// Confirmations before a coinbase output is spendable (bdk counts it as
+/// immature until then — those are left alone here).
+const COINBASE_MATURITY: u32 = 100;
+
+/// Merkle root inside a raw header (after version + prev hash).
+const MERKLE_ROOT: std::ops::Range<usize> = 36..68;
+
+/// Serializes read-modify-write cycles on SPV stores.
+static SPV_LOCK: Mutex<()> = Mutex::new(());
+
+/// The last [`independent`] answer, keyed by home + others — resolving
+/// hosts is too slow for every balance read.
+static INDEPENDENT: Mutex<Option<(Vec<String>, Vec<String>)>> = Mutex::new(None);
+
+/// One verified transaction: the block it was proven in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpvProof {
+    pub height: u32,
+    pub block_hash: String,
+    /// Unix seconds.
+    pub verified_at: u64,
+}
+
+/// One wallet's verified headers and proofs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpvStore {
+    /// Raw header hex by height — only heights holding a verified tx.
+    #[serde(default)]
+    headers: BTreeMap<u32, String>,
+    /// Proofs keyed by txid.
+    #[serde(default)]
+    proofs: BTreeMap<String, SpvProof>,
+}
+
+impl SpvStore {
+    /// Store file inside a wallet dir.
+    pub fn path_in(root: &Path) -> PathBuf {
+        root.join("wallet").join(SPV_FILE)
+    }
+
+    /// Read a store; a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("reading {}: {e}", path.display())),
+        }
+    }
+
+    /// Atomically rewrite the store.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("creating {}: {e}", parent.display()))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        super::descstore::write_atomic(path, &json)
+    }
+
+    /// Whether `txid` is proven in the block `block_hash`.
+    pub fn is_verified(&self, txid: &str, block_hash: &str) -> bool {
+        self.proofs
+            .get(txid)
+            .is_some_and(|p| p.block_hash == block_hash)
+    }
+
+    /// Record a proof and its header.
+    fn record(&mut self, txid: String, proof: SpvProof, raw_header: &[u8]) {
+        self.headers.insert(proof.height, hex::encode(raw_header));
+        self.proofs.insert(txid, proof);
+    }
+
+    /// The stored header at `height`, if it hashes to `block_hash`.
+    fn header(&self, params: &ChainParams, height: u32, block_hash: &str) -> Option<Vec<u8>> {
+        let raw = hex::decode(self.headers.get(&height)?).ok()?;
+        (params.header_hash(&raw).ok()? == block_hash).then_some(raw)
+    }
+
+    /// Drop proofs of txs no longer confirmed (`keep`) and headers no proof
+    /// points at.
+    fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.proofs.retain(|txid, _| keep(txid));
+        let heights: BTreeSet<u32> = self.proofs.values().map(|p| p.height).collect();
+        self.headers.retain(|h, _| heights.contains(h));
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Addresses the host of `url` resolves to (port ignored) — or the
+/// lowercased host itself when it does not resolve, or while the proxy is
+/// on (a local lookup would leak the name around it).
+fn host_addrs(url: &str) -> BTreeSet<String> {
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => authority,
+    };
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    if !matches!(crate::proxy::active(), Ok(None)) {
+        return BTreeSet::from([host]);
+    }
+    let resolved: BTreeSet<String> = (host.as_str(), 0)
+        .to_socket_addrs()
+        .map(|addrs| addrs.map(|a| a.ip().to_string()).collect())
+        .unwrap_or_default();
+    if resolved.is_empty() {
+        BTreeSet::from([host])
+    } else {
+        resolved
+    }
+}
+
+/// The servers of `others` on a different host than `home` and than each
+/// other (first one wins) — the ones whose agreement means anything.
+pub fn independent(home: &str, others: &[String]) -> Vec<String> {
+    let mut seen = vec![host_addrs(home)];
+    let mut kept = Vec::new();
+    for url in others {
+        let addrs = host_addrs(url);
+        if seen.iter().all(|s| s.is_disjoint(&addrs)) {
+            seen.push(addrs);
+            kept.push(url.clone());
+        }
+    }
+    kept
+}
+
+/// [`independent`], remembered for the last server list asked about.
+fn independent_cached(home: &str, others: &[String]) -> Vec<String> {
+    let key: Vec<String> = std::iter::once(home.to_string())
+        .chain(others.iter().cloned())
+        .collect();
+    if let Ok(cache) = INDEPENDENT.lock() {
+        if let Some((_, kept)) = cache.as_ref().filter(|(k, _)| *k == key) {
+            return kept.clone();
+        }
+    }
+    let kept = independent(home, others);
+    if let Ok(mut cache) = INDEPENDENT.lock() {
+        *cache = Some((key, kept.clone()));
+    }
+    kept
+}
+
+/// Whether unverified confirmations are held back for the open wallet:
+/// only when an independent second server exists to cross-check against.
+/// A single-server wallet trusts its home server (see the module doc).
+pub fn holds_back(state: &SharedBtcxWalletState) -> bool {
+    state
+        .runtime_urls()
+        .is_some_and(|(home, views)| !independent_cached(&home, &views).is_empty())
+}
+
+/// The active wallet's store; a missing or unreadable file is an empty
+/// store (logged) — everything then simply counts as unverified.
+pub fn read_active(state: &SharedBtcxWalletState) -> SpvStore {
+    let loaded = state
+        .get_config()
+        .registered_active_root()
+        .ok_or_else(|| "No active wallet".to_string())
+        .and_then(|root| {
+            let _guard = SPV_LOCK.lock().map_err(|_| "spv lock poisoned")?;
+            SpvStore::load(&SpvStore::path_in(&root))
+        });
+    loaded.unwrap_or_else(|e| {
+        log::warn!("btcx wallet: reading the SPV store failed: {e}");
+        SpvStore::default()
+    })
+}
+
+/// Check one tx against a raw header: the header hashes to `block_hash`,
+/// carries `height` (PoCX headers), and the merkle branch leads from the
+/// txid to its merkle root.
+fn check_proof(
+    params: &ChainParams,
+    txid: &bitcoin::Txid,
+    height: u32,
+    block_hash: &str,
+    raw_header: &[u8],
+    merkle: &electrum_client::GetMerkleRes,
+) -> Result<(), String> {
+    let hash = params
+        .header_hash(raw_header)
+        .map_err(|e| format!("header {height}: {e:#}"))?;
+    if hash != block_hash {
+        return Err(format!(
+            "header {height} hashes to {hash}, the sync anchored {block_hash}"
+        ));
+    }
+    if super::consensus::embedded_height(raw_header).is_some_and(|h| h != u64::from(height)) {
+        return Err(format!(
+            "header served for height {height} carries another height"
+        ));
+    }
+    if merkle.block_height != height as usize {
+        return Err(format!(
+            "merkle branch is for height {}, not {height}",
+            merkle.block_height
+        ));
+    }
+    let root = raw_header
+        .get(MERKLE_ROOT)
+        .and_then(|r| <[u8; 32]>::try_from(r).ok())
+        .ok_or("header too short")?;
+    let root = bitcoin::TxMerkleNode::from_byte_array(root);
+    if !electrum_client::utils::validate_merkle_proof(txid, &root, merkle) {
+        return Err("merkle branch does not lead to the header's merkle root".into());
+    }
+    Ok(())
+}
+
+/// Whether the home server's header hash stands: `others` are the hashes
+/// the other servers served at the same height (`None`: no answer). At
+/// least one must match, and the matches (home included) must outnumber
+/// the contradictions.
+fn corroborated(home_hash: &str, others: &[Option<String>]) -> bool {
+    let agree = others
+        .iter()
+        .filter(|h| h.as_deref() == Some(home_hash))
+        .count();
+    let disagree = others.iter().flatten().count() - agree;
+    agree >= 1 && agree + 1 > disagree
+}
+
+/// The header hashes each of `others` serves at `heights`, one fresh
+/// connection per server; an unreachable server answers `None` throughout.
+fn cross_check(
+    params: &ChainParams,
+    others: &[String],
+    heights: &[u32],
+) -> BTreeMap<u32, Vec<Option<String>>> {
+    let mut served: BTreeMap<u32, Vec<Option<String>>> = BTreeMap::new();
+    for url in others {
+        let raw = super::consensus::connect(url).and_then(|client| {
+            client
+                .batch_block_header_raw(heights.iter().copied())
+                .map_err(|e| format!("{e}"))
+        });
+        let raw = raw.unwrap_or_else(|e| {
+            log::debug!("btcx wallet: SPV cross-check against {url} skipped: {e}");
+            Vec::new()
+        });
+        for (i, &height) in heights.iter().enumerate() {
+            let hash = raw
+                .get(i)
+                .and_then(|header| params.header_hash(header).ok());
+            served.entry(height).or_default().push(hash);
+        }
+    }
+    served
+}
+
+/// The wallet's confirmed history: `(txid, height, block hash)`.
+fn confirmed_history(wallet: &bdk_wallet::Wallet) -> Vec<(bitcoin::Txid, u32, String)> {
+    wallet
+        .transactions()
+        .filter_map(|wtx| match wtx.chain_position {
+            ChainPosition::Confirmed { anchor, .. } => Some((
+                wtx.tx_node.txid,
+                anchor.block_id.height,
+                anchor.block_id.hash.to_string(),
+            )),
+            ChainPosition::Unconfirmed { .. } => None,
+        })
+        .collect()
+}
+
+/// Verify the confirmed txs of the wallet at `root` that have no proof for
+/// their current block yet: header and merkle branch from the home server
+/// `url`, each new header cross-checked against the [`independent`] ones
+/// of `others` (see the module doc). Returns how many verified; failures
+/// are logged and retried on the next pass. Without an independent server
+/// nothing verifies — and nothing is held back either ([`holds_back`]).
+pub fn verify_wallet(
+    params: &ChainParams,
+    root: &Path,
+    url: &str,
+    others: &[String],
+    handle: &WalletHandle,
+) -> Result<usize, String> {
+    // The confirmed history under a brief lock (pure CPU).
+    let confirmed = {
+        let entry = handle.lock().map_err(|_| "wallet entry poisoned")?;
+        confirmed_history(&entry.wallet)
+    };
+    verify_confirmed(params, root, url, others, confirmed)
+}
+
+/// [`verify_wallet`] for the open wallet, on demand — the spend paths run
+/// it before coin selection so a coin that just confirmed is not skipped
+/// for want of an emitter pass.
+pub fn verify_active(state: &SharedBtcxWalletState) -> Result<usize, String> {
+    let config = state.get_config();
+    let root = config
+        .registered_active_root()
+        .ok_or_else(|| "No active wallet".to_string())?;
+    let (home, views) = state
+        .runtime_urls()
+        .ok_or_else(|| "The nodeless wallet is not open".to_string())?;
+    let confirmed = state.with_entry(|entry| Ok(confirmed_history(&entry.wallet)))?;
+    verify_confirmed(config.network.params(), &root, &home, &views, confirmed)
+}
+
+fn verify_confirmed(
+    params: &ChainParams,
+    root: &Path,
+    url: &str,
+    others: &[String],
+    confirmed: Vec<(bitcoin::Txid, u32, String)>,
+) -> Result<usize, String> {
+    let others = independent_cached(url, others);
+    let path = SpvStore::path_in(root);
+    let mut store = {
+        let _guard = SPV_LOCK.lock().map_err(|_| "spv lock poisoned")?;
+        SpvStore::load(&path)?
+    };
+    let before = store.clone();
+    let current: std::collections::HashSet<String> = confirmed
+        .iter()
+        .map(|(txid, ..)| txid.to_string())
+        .collect();
+    store.retain(|txid| current.contains(txid));
+    let pending: Vec<_> = confirmed
+        .into_iter()
+        .filter(|(txid, _, hash)| !store.is_verified(&txid.to_string(), hash))
+        .collect();
+
+    let mut verified = 0;
+    // Nothing to cross-check against: nothing can verify.
+    if !pending.is_empty() && !others.is_empty() {
+        // Pass 1: proofs against the home server. A stored header was
+        // cross-checked when it was recorded.
+        let client = super::consensus::connect(url)?;
+        let mut proven = Vec::new();
+        for (txid, height, block_hash) in pending {
+            let (raw, stored) = match store.header(params, height, &block_hash) {
+                Some(raw) => (raw, true),
+                None => match client.block_header_raw(height as usize) {
+                    Ok(raw) => (raw, false),
+                    Err(e) => {
+                        log::warn!("btcx wallet: SPV header {height}: {e}");
+                        continue;
+                    }
+                },
+            };
+            let checked = client
+                .transaction_get_merkle(&txid, height as usize)
+                .map_err(|e| format!("fetching the merkle branch: {e}"))
+                .and_then(|merkle| check_proof(params, &txid, height, &block_hash, &raw, &merkle));
+            match checked {
+                Ok(()) => proven.push((txid, height, block_hash, raw, stored)),
+                Err(e) => log::warn!("btcx wallet: SPV check of {txid} failed: {e}"),
+            }
+        }
+
+        // Pass 2: every new header against the other servers.
+        let mut fresh: Vec<u32> = proven
+            .iter()
+            .filter(|(.., stored)| !stored)
+            .map(|(_, height, ..)| *height)
+            .collect();
+        fresh.sort_unstable();
+        fresh.dedup();
+        let served = if fresh.is_empty() {
+            BTreeMap::new()
+        } else {
+            cross_check(params, &others, &fresh)
+        };
+        for (txid, height, block_hash, raw, stored) in proven {
+            let others = served.get(&height).map(Vec::as_slice).unwrap_or_default();
+            if !stored && !corroborated(&block_hash, others) {
+                log::warn!(
+                    "btcx wallet: SPV header {height} of {txid} not confirmed by another \
+                     server ({} of {} answered)",
+                    others.iter().flatten().count(),
+                    others.len()
+                );
+                continue;
+            }
+            let proof = SpvProof {
+                height,
+                block_hash,
+                verified_at: now_secs(),
+            };
+            store.record(txid.to_string(), proof, &raw);
+            verified += 1;
+        }
+    }
+    if store != before {
+        let _guard = SPV_LOCK.lock().map_err(|_| "spv lock poisoned")?;
+        store.save(&path)?;
+    }
+    Ok(verified)
+}
+
+/// The wallet's unspent outputs the balance would count as confirmed but
+/// whose tx has no proof for its block. Immature coinbase outputs are
+/// bdk's immature bucket and left out.
+fn unverified_outputs<'a>(
+    wallet: &'a bdk_wallet::Wallet,
+    store: &'a SpvStore,
+) -> impl Iterator<Item = LocalOutput> + 'a {
+    let tip = wallet.latest_checkpoint().height();
+    wallet.list_unspent().filter(move |utxo| {
+        let ChainPosition::Confirmed { anchor, .. } = utxo.chain_position else {
+            return false;
+        };
+        let txid = utxo.outpoint.txid;
+        if store.is_verified(&txid.to_string(), &anchor.block_id.hash.to_string()) {
+            return false;
+        }
+        let coinbase = wallet
+            .get_tx(txid)
+            .is_some_and(|wtx| wtx.tx_node.tx.is_coinbase());
+        !(coinbase && (tip + 1).saturating_sub(anchor.block_id.height) < COINBASE_MATURITY)
+    })
+}
+
+/// Value of the unverified confirmed outputs: `(change, receives)`.
+pub fn unverified_confirmed(wallet: &bdk_wallet::Wallet, store: &SpvStore) -> (u64, u64) {
+    let mut unverified = (0, 0);
+    for utxo in unverified_outputs(wallet, store) {
+        let value = utxo.txout.value.to_sat();
+        match utxo.keychain {
+            KeychainKind::Internal => unverified.0 += value,
+            KeychainKind::External => unverified.1 += value,
+        }
+    }
+    unverified
+}
+
+/// The unverified confirmed RECEIVES — the outputs the balance moves to
+/// untrusted pending, which automatic coin selection must skip.
+pub fn unverified_receives(wallet: &bdk_wallet::Wallet, store: &SpvStore) -> BTreeSet<OutPoint> {
+    unverified_outputs(wallet, store)
+        .filter(|utxo| utxo.keychain == KeychainKind::External)
+        .map(|utxo| utxo.outpoint)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_follow_the_block_and_prune_with_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = SpvStore::path_in(dir.path());
+        let mut store = SpvStore::load(&path).unwrap();
+        assert_eq!(store, SpvStore::default(), "missing file = empty store");
+
+        let proof = |height, hash: &str| SpvProof {
+            height,
+            block_hash: hash.into(),
+            verified_at: 1,
+        };
+        store.record("aa".into(), proof(10, "h10"), &[1, 2]);
+        store.record("bb".into(), proof(11, "h11"), &[3]);
+        store.save(&path).unwrap();
+
+        let mut store = SpvStore::load(&path).unwrap();
+        assert!(store.is_verified("aa", "h10"));
+        // Reorged into another block: not verified until re-proven.
+        assert!(!store.is_verified("aa", "h10-reorg"));
+        assert!(!store.is_verified("cc", "h10"));
+
+        // "bb" left the confirmed history: its proof and header go.
+        store.retain(|txid| txid == "aa");
+        assert!(!store.is_verified("bb", "h11"));
+        assert_eq!(store.headers.keys().copied().collect::<Vec<_>>(), [10]);
+    }
+
+    #[test]
+    fn headers_need_an_independent_majority() {
+        let h = |s: &str| Some(s.to_string());
+        // Nobody else answered, or nobody else exists: home alone proves
+        // nothing.
+        assert!(!corroborated("a", &[]));
+        assert!(!corroborated("a", &[None, None]));
+        // One other server serving the same header is enough...
+        assert!(corroborated("a", &[h("a"), None]));
+        // ...unless more servers contradict the home server than back it.
+        assert!(corroborated("a", &[h("a"), h("b")]));
+        assert!(!corroborated("a", &[h("a"), h("b"), h("b")]));
+        assert!(!corroborated("a", &[h("b")]));
+    }
+
+    #[test]
+    fn one_host_counts_once_whatever_the_port() {
+        let urls = |u: &[&str]| u.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let home = "tcp://127.0.0.1:60401";
+        // The home host on another port is not a second opinion.
+        assert!(independent(home, &urls(&["ssl://127.0.0.1:50002"])).is_empty());
+        // Two others on one host count once.
+        assert_eq!(
+            independent(
+                home,
+                &urls(&[
+                    "tcp://10.0.0.1:50001",
+                    "ssl://10.0.0.1:50002",
+                    "tcp://[::2]:1"
+                ])
+            ),
+            urls(&["tcp://10.0.0.1:50001", "tcp://[::2]:1"])
+        );
+    }
+
+    #[test]
+    fn proofs_check_header_hash_height_and_merkle_root() {
+        let params = &params_btcx::params::BTCX_REGTEST;
+        // A one-tx block: the merkle root IS the txid, the branch is empty.
+        let txid: bitcoin::Txid = "aa".repeat(32).parse().unwrap();
+        let mut raw = vec![0u8; 286];
+        raw[MERKLE_ROOT].copy_from_slice(&txid.to_byte_array());
+        raw[72..76].copy_from_slice(&7u32.to_le_bytes());
+        let hash = params.header_hash(&raw).unwrap();
+        let merkle = |block_height| electrum_client::GetMerkleRes {
+            block_height,
+            pos: 0,
+            merkle: Vec::new(),
+        };
+        check_proof(params, &txid, 7, &hash, &raw, &merkle(7)).expect("valid proof");
+
+        let err = check_proof(params, &txid, 7, &"00".repeat(32), &raw, &merkle(7));
+        assert!(err.unwrap_err().contains("the sync anchored"));
+        let err = check_proof(params, &txid, 8, &hash, &raw, &merkle(8));
+        assert!(err.unwrap_err().contains("carries another height"));
+        let err = check_proof(params, &txid, 7, &hash, &raw, &merkle(6));
+        assert!(err.unwrap_err().contains("merkle branch is for height 6"));
+        let other: bitcoin::Txid = "bb".repeat(32).parse().unwrap();
+        let err = check_proof(params, &other, 7, &hash, &raw, &merkle(7));
+        assert!(err.unwrap_err().contains("merkle root"));
+    }
+}
@@ -11,6 +11,7 @@
 //!   wallet-btcx contract) + the `btcx-wallet:sync` event emitter thread.
 
 use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
@@ -24,8 +25,14 @@ use seedstore::SeedStore;
 use tauri::Emitter;
 use wallet_btcx::BdkWalletBackend;
 
-use super::config::{BtcxWalletConfig, DescriptorPolicy, WalletNetwork, WalletSourceCfg, COIN_ID};
+use super::config::{
+    BtcxWalletConfig, DescriptorPolicy, HeaderConsensusMode, WalletNetwork, WalletSourceCfg,
+    COIN_ID,
+};
+use super::consensus::ConsensusReport;
 use super::descstore::DescStore;
+use super::preview::PendingTx;
+use super::ur::{UrScan, UrScanProgress};
 use super::{descriptors, manager};
 
 /// Seed lifecycle as the frontend sees it.
@@ -70,6 +77,9 @@ pub struct BtcxWalletStatus {
     /// The selected wallet is a single-address (`wpkh(WIF)`) wallet — one
     /// address, change to self; the receive page hides "new address".
     pub single_address: bool,
+    /// The selected wallet is WATCH-ONLY (public descriptors, no keys) —
+    /// it can compose unsigned PSBTs but never sign or send.
+    pub watch_only: bool,
     /// Wallet-cache chain height (bdk checkpoint tip), once open.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub synced_height: Option<u32>,
@@ -124,6 +134,13 @@ pub struct BtcxWalletState {
     /// fails any directory move. Rename/delete wait on these via
     /// [`Self::wait_wallet_released`] instead of blind retries.
     closing: Mutex<Vec<(String, std::sync::Weak<Mutex<WalletEntry>>)>>,
+    /// Last cross-server header consensus report (`consensus::refresh`).
+    consensus: Mutex<Option<ConsensusReport>>,
+    /// Signed, unbroadcast transactions awaiting their commit, by preview
+    /// handle (`preview`).
+    previews: Mutex<BTreeMap<String, PendingTx>>,
+    /// The animated-QR PSBT scan in progress (`ur`).
+    ur_scan: Mutex<UrScan>,
 }
 
 /// Type alias for shared BTCX wallet state.
@@ -152,7 +169,11 @@ pub fn create_btcx_wallet_state() -> SharedBtcxWalletState {
             log::error!("btcx wallet: saving group-migrated config failed: {e}");
         }
     }
-    Arc::new(BtcxWalletState {
+    // Trash retention (opt-in): purge trashed wallets past their keep.
+    super::trash::apply_retention(&config);
+    // Key files a crashed passphrase change left set aside.
+    super::rewrap::recover_all(&config);
+    let state = Arc::new(BtcxWalletState {
         config: Mutex::new(config),
         seed: Mutex::new(None),
         desc: Mutex::new(None),
@@ -160,7 +181,13 @@ pub fn create_btcx_wallet_state() -> SharedBtcxWalletState {
         runtime: Mutex::new(None),
         sync_gate: Mutex::new(()),
         closing: Mutex::new(Vec::new()),
-    })
+        consensus: Mutex::new(None),
+        previews: Mutex::new(BTreeMap::new()),
+        ur_scan: Mutex::new(UrScan::default()),
+    });
+    // Server certificate pins live in this state's config.
+    super::tlspin::attach(&state);
+    state
 }
 
 impl BtcxWalletState {
@@ -185,7 +212,9 @@ impl BtcxWalletState {
         let key = Self::seed_key(&config);
         let mut guard = self.seed.lock().map_err(|_| "seed store lock poisoned")?;
         if guard.as_ref().map(|(k, _)| k.as_str()) != Some(key.as_str()) {
-            let store = SeedStore::open(&config.active_wallet_root(), None)
+            let root = config.active_wallet_root();
+            super::rewrap::recover_interrupted(&root)?;
+            let store = SeedStore::open(&root, None)
                 .map_err(|e| format!("Failed to open seed store: {e:#}"))?;
             *guard = Some((key, store));
         }
@@ -207,7 +236,9 @@ impl BtcxWalletState {
             .lock()
             .map_err(|_| "descriptor store lock poisoned")?;
         if guard.as_ref().map(|(k, _)| k.as_str()) != Some(key.as_str()) {
-            let store = DescStore::open(&config.active_wallet_root())?;
+            let root = config.active_wallet_root();
+            super::rewrap::recover_interrupted(&root)?;
+            let store = DescStore::open(&root)?;
             *guard = Some((key, store));
         }
         f(&mut guard.as_mut().expect("descriptor store just opened").1)
@@ -285,9 +316,13 @@ impl BtcxWalletState {
         // to a healthy one so one bad server never bricks the wallet. A
         // reachable server that DISAGREES with no healthy alternative is a
         // connection error (the UI already handles it) — never a silent sync
-        // against an unverified server.
-        let (home_url, view_urls) =
-            self.elect_verified_home(&servers, params, config.network.as_str())?;
+        // against an unverified server. Discovered servers (see `discovery`)
+        // join behind the configured ones only when the user opted in.
+        let (home_url, view_urls) = self.elect_verified_home(
+            &config.failover_servers(config.network),
+            params,
+            config.network.as_str(),
+        )?;
 
         let handle = match self.active_source(&config) {
             WalletSourceCfg::Seed => {
@@ -320,13 +355,29 @@ impl BtcxWalletState {
                 )
                 .map_err(|e| format!("Failed to open wallet: {e:#}"))?
             }
+            WalletSourceCfg::WatchOnly => {
+                // Nothing to unlock; an absent store is the closed verdict.
+                let Ok(payload) =
+                    super::descstore::read_public_descriptors(&config.active_wallet_root())
+                else {
+                    return Ok(false);
+                };
+                manager::open_wallet_from_descriptors(
+                    &config.wallet_db_path(),
+                    params,
+                    &payload.external,
+                    payload.internal.as_deref(),
+                    descriptors::bdk_network(config.network),
+                )
+                .map_err(|e| format!("Failed to open wallet: {e:#}"))?
+            }
         };
 
         // The worker gets its OWN connection to the home server (never the
         // pooled one) so each socket has exactly one caller domain — see
         // wallet_btcx::WalletManager::ensure_worker.
         let worker_chain = Arc::new(
-            ElectrumBackend::new(params, &home_url)
+            ElectrumBackend::new(params, &super::tlspin::dial_url(&home_url)?)
                 .map_err(|e| format!("Failed to set up Electrum connection: {e:#}"))?,
         );
         let worker = SyncWorker::spawn(COIN_ID, worker_chain, &handle);
@@ -385,6 +436,10 @@ impl BtcxWalletState {
             }
             runtime.emitter_stop.store(true, Ordering::Relaxed);
             runtime.worker.shutdown();
+            // Previews were signed by this wallet; none may outlive it.
+            if let Ok(mut previews) = self.previews.lock() {
+                previews.clear();
+            }
             // The worker/emitter may outlive this close (a blocking connect
             // ignores the shutdown flag) — leave a weak marker so
             // rename/delete can wait for the actual sqlite release.
@@ -472,8 +527,7 @@ impl BtcxWalletState {
         let meta = config
             .wallet_meta(network, name)
             .ok_or_else(|| format!("No wallet named '{name}' on {}", network.as_str()))?;
-        let servers = config.servers();
-        if servers.is_empty() {
+        if config.servers().is_empty() {
             return Err(format!(
                 "No Electrum server configured for {}",
                 network.as_str()
@@ -483,9 +537,11 @@ impl BtcxWalletState {
         // Same chain-verified election as the runtime open: a one-shot that
         // synced against a wrong-chain server would write garbage into the
         // compartment's persistent store.
-        let (home_url, _views) = self.elect_verified_home(&servers, params, network.as_str())?;
+        let (home_url, _views) =
+            self.elect_verified_home(&config.failover_servers(network), params, network.as_str())?;
 
         let root = BtcxWalletConfig::wallet_root(network, name);
+        super::rewrap::recover_interrupted(&root)?;
         let db_path = BtcxWalletConfig::wallet_db_path_for(network, name);
         let handle = match meta.source {
             WalletSourceCfg::Seed => {
@@ -518,10 +574,21 @@ impl BtcxWalletState {
                 )
                 .map_err(|e| format!("Failed to open wallet: {e:#}"))?
             }
+            WalletSourceCfg::WatchOnly => {
+                let payload = super::descstore::read_public_descriptors(&root)?;
+                manager::open_wallet_from_descriptors(
+                    &db_path,
+                    params,
+                    &payload.external,
+                    payload.internal.as_deref(),
+                    descriptors::bdk_network(network),
+                )
+                .map_err(|e| format!("Failed to open wallet: {e:#}"))?
+            }
         };
 
         let worker_chain = Arc::new(
-            ElectrumBackend::new(params, &home_url)
+            ElectrumBackend::new(params, &super::tlspin::dial_url(&home_url)?)
                 .map_err(|e| format!("Failed to set up Electrum connection: {e:#}"))?,
         );
         let worker = SyncWorker::spawn(COIN_ID, worker_chain, &handle);
@@ -629,13 +696,12 @@ impl BtcxWalletState {
             .chain(rt.view_urls.iter().map(String::as_str))
             .collect();
         let chain = self
-            .pool
-            .get(rt.params, COIN_ID, &rt.home_url, &live)
+            .pooled(rt.params, &rt.home_url, &live)
             .map_err(|e| format!("Electrum connection: {e:#}"))?;
         let views = rt
             .view_urls
             .iter()
-            .map(|url| self.pool.get(rt.params, COIN_ID, url, &live))
+            .map(|url| self.pooled(rt.params, url, &live))
             .collect::<anyhow::Result<Vec<_>>>()
             .map_err(|e| format!("Electrum connection: {e:#}"))?;
         Ok(BdkWalletBackend::new(
@@ -646,6 +712,23 @@ impl BtcxWalletState {
         ))
     }
 
+    /// The pooled connection to configured server `url`, with `live` the
+    /// configured servers still in use (the pool prunes the rest). Both
+    /// are dialed through the app-wide proxy when it is on (see
+    /// `crate::proxy`) — never directly — and `ssl://` servers through
+    /// their pinned TLS forwarder (see `tlspin`).
+    fn pooled(
+        &self,
+        params: &'static ChainParams,
+        url: &str,
+        live: &[&str],
+    ) -> anyhow::Result<Arc<ElectrumBackend>> {
+        let live: Vec<String> = live.iter().map(|u| super::tlspin::dialed_url(u)).collect();
+        let live: Vec<&str> = live.iter().map(String::as_str).collect();
+        let url = super::tlspin::dial_url(url).map_err(anyhow::Error::msg)?;
+        self.pool.get(params, COIN_ID, &url, &live)
+    }
+
     /// A pooled Electrum connection to the FIRST configured server of the
     /// active network — for operations that need the chain but not an open
     /// wallet (restore probing).
@@ -659,8 +742,7 @@ impl BtcxWalletState {
             )
         })?;
         let live: Vec<&str> = servers.iter().map(String::as_str).collect();
-        self.pool
-            .get(config.network.params(), COIN_ID, home, &live)
+        self.pooled(config.network.params(), home, &live)
             .map_err(|e| format!("Electrum connection: {e:#}"))
     }
 
@@ -674,18 +756,20 @@ impl BtcxWalletState {
     /// probe blind against an unverifiable server.
     pub fn verified_probe_chain(&self) -> Result<Arc<ElectrumBackend>, String> {
         let config = self.get_config();
-        let servers = config.servers();
-        if servers.is_empty() {
+        if config.servers().is_empty() {
             return Err(format!(
                 "No Electrum server configured for {} — add one first",
                 config.network.as_str()
             ));
         }
+        // Opted-in discovered servers follow the configured ones: a fresh
+        // install whose baked-in server died can still restore.
+        let servers = config.failover_servers(config.network);
         let params = config.network.params();
         let live: Vec<&str> = servers.iter().map(String::as_str).collect();
         let mut last_err: Option<String> = None;
         for url in &servers {
-            match self.pool.get(params, COIN_ID, url, &live) {
+            match self.pooled(params, url, &live) {
                 Ok(backend) => match backend.verify_chain() {
                     Ok(()) => return Ok(backend),
                     Err(e) => {
@@ -702,6 +786,49 @@ impl BtcxWalletState {
         ))
     }
 
+    /// Run chain-only work on `network` (policy checks, PSBT UTXO lookups)
+    /// against one server after another until it succeeds: the open
+    /// wallet's own server first when it is on that network, then every
+    /// failover server — dialed fresh, like [`broadcast_tx_over_electrum`],
+    /// since `network` may not be the one the pool serves. A server that
+    /// is down or cannot answer (say, a pruned prevout) is skipped.
+    pub fn with_network_chain<T>(
+        &self,
+        network: WalletNetwork,
+        mut work: impl FnMut(&ElectrumBackend) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut last_err = None;
+        if self
+            .open_wallet_name()
+            .is_some_and(|(open, _)| open == network)
+        {
+            match self.backend().and_then(|backend| work(backend.chain())) {
+                Ok(done) => return Ok(done),
+                Err(e) => last_err = Some(format!("wallet server: {e}")),
+            }
+        }
+        let servers = self.get_config().failover_servers(network);
+        if servers.is_empty() && last_err.is_none() {
+            return Err(format!(
+                "No Electrum server configured for {} — add one in the wallet settings first",
+                network.as_str()
+            ));
+        }
+        let params = network.params();
+        for url in &servers {
+            let dialed = super::tlspin::dial_url(url)
+                .and_then(|dial| ElectrumBackend::new(params, &dial).map_err(|e| format!("{e:#}")));
+            match dialed.and_then(|chain| work(&chain)) {
+                Ok(done) => return Ok(done),
+                Err(e) => last_err = Some(format!("{url}: {e}")),
+            }
+        }
+        Err(format!(
+            "No Electrum server could answer — {}",
+            last_err.unwrap_or_default()
+        ))
+    }
+
     /// Elect a home server whose chain is VERIFIED (genesis match + not
     /// pruned), returning `(home_url, view_urls)` with the elected server
     /// first and the rest in configured order. The first server that verifies
@@ -730,8 +857,7 @@ impl BtcxWalletState {
         let mut last_err: Option<String> = None;
         for (i, url) in servers.iter().enumerate() {
             let verdict = self
-                .pool
-                .get(params, COIN_ID, url, &live)
+                .pooled(params, url, &live)
                 .and_then(|b| b.verify_chain());
             match verdict {
                 Ok(()) => {
@@ -782,14 +908,16 @@ impl BtcxWalletState {
     ) -> Result<String, String> {
         let config = self.get_config();
         let network = network.unwrap_or(config.network);
-        let servers = config.servers_for(network);
-        if servers.is_empty() {
+        if config.servers_for(network).is_empty() {
             return Err(format!(
                 "No Electrum server configured for {} — add one in the wallet settings first",
                 network.as_str()
             ));
         }
-        broadcast_tx_over_electrum(network.params(), &servers, tx_hex)
+        if network == config.network {
+            super::consensus::gate(self, true)?;
+        }
+        broadcast_tx_over_electrum(network.params(), &config.failover_servers(network), tx_hex)
     }
 
     /// Run `f` on the open wallet entry (bdk wallet + sqlite connection).
@@ -823,6 +951,72 @@ impl BtcxWalletState {
             .map(|rt| (rt.home_url.clone(), rt.view_urls.clone()))
     }
 
+    /// The last header consensus report, if a check ran.
+    pub fn cached_consensus(&self) -> Option<ConsensusReport> {
+        self.consensus.lock().ok()?.clone()
+    }
+
+    /// Cache a header consensus report (`consensus::refresh`).
+    pub fn store_consensus(&self, report: ConsensusReport) {
+        if let Ok(mut slot) = self.consensus.lock() {
+            *slot = Some(report);
+        }
+    }
+
+    /// Park a previewed transaction under `handle`.
+    pub fn store_preview(&self, handle: String, pending: PendingTx) {
+        if let Ok(mut previews) = self.previews.lock() {
+            previews.insert(handle, pending);
+        }
+    }
+
+    /// Remove and return the preview of `handle` — at most one caller ever
+    /// gets it, so a preview is broadcast at most once.
+    pub fn take_preview(&self, handle: &str) -> Option<PendingTx> {
+        self.previews.lock().ok()?.remove(handle)
+    }
+
+    /// The coins spent by the previews still live at `now` — reserved for
+    /// their commits, so no other build selects them.
+    pub fn preview_inputs(&self, now: std::time::Instant) -> BTreeSet<bitcoin::OutPoint> {
+        let Ok(previews) = self.previews.lock() else {
+            return BTreeSet::new();
+        };
+        previews
+            .values()
+            .filter(|p| !p.expired(now))
+            .flat_map(|p| p.tx.input.iter().map(|i| i.previous_output))
+            .collect()
+    }
+
+    /// Remove and return every preview expired at `now`.
+    pub fn take_expired_previews(&self, now: std::time::Instant) -> Vec<PendingTx> {
+        let Ok(mut previews) = self.previews.lock() else {
+            return Vec::new();
+        };
+        let expired: Vec<String> = previews
+            .iter()
+            .filter(|(_, p)| p.expired(now))
+            .map(|(h, _)| h.clone())
+            .collect();
+        expired.iter().filter_map(|h| previews.remove(h)).collect()
+    }
+
+    /// Feed one scanned UR frame to the animated-QR scan in progress.
+    pub fn scan_ur_frame(&self, frame: &str) -> Result<UrScanProgress, String> {
+        self.ur_scan
+            .lock()
+            .map_err(|_| "UR scan state unavailable".to_string())?
+            .receive(frame)
+    }
+
+    /// Drop the animated-QR scan in progress.
+    pub fn reset_ur_scan(&self) {
+        if let Ok(mut scan) = self.ur_scan.lock() {
+            *scan = UrScan::default();
+        }
+    }
+
     /// Bounded wait for the sync worker's first completed pass of this run,
     /// poking it first — operations that BUILD/SPEND call this so they can
     /// never coin-select from a cache that has not seen the chain at all
@@ -849,6 +1043,8 @@ impl BtcxWalletState {
     ///
     /// For descriptor-source wallets the "seed" lifecycle fields describe
     /// the descriptor store instead — same states, same lock/unlock UX.
+    /// A watch-only wallet is never locked: unlocked once its public
+    /// descriptors are on disk.
     pub fn status(&self) -> Result<BtcxWalletStatus, String> {
         let config = self.get_config();
         let seed = match self.active_source(&config) {
@@ -873,6 +1069,17 @@ impl BtcxWalletState {
                     SeedState::Unlocked
                 }
             }
+            WalletSourceCfg::WatchOnly => {
+                if config
+                    .active_wallet_root()
+                    .join(super::descstore::PUBLIC_DESCRIPTOR_FILE)
+                    .exists()
+                {
+                    SeedState::Unlocked
+                } else {
+                    SeedState::None
+                }
+            }
         };
 
         let (wallet_active, synced_height, sync_age_secs) = {
@@ -908,11 +1115,14 @@ impl BtcxWalletState {
                 )
                 .map(|contents| super::descstore::is_passphrase_descriptor_file(&contents))
                 .unwrap_or(false),
+                WalletSourceCfg::WatchOnly => false,
             };
 
-        let single_address = config
-            .wallet_meta(config.network, &config.active_wallet_name())
-            .map(|m| m.single_address)
+        let meta = config.wallet_meta(config.network, &config.active_wallet_name());
+        let single_address = meta.as_ref().map(|m| m.single_address).unwrap_or(false);
+        let watch_only = meta
+            .as_ref()
+            .map(|m| m.source == WalletSourceCfg::WatchOnly)
             .unwrap_or(false);
 
         Ok(BtcxWalletStatus {
@@ -923,6 +1133,7 @@ impl BtcxWalletState {
             network: config.network.as_str().to_string(),
             wallet_name: config.active_wallet_name(),
             single_address,
+            watch_only,
             synced_height,
             sync_age_secs,
         })
@@ -948,7 +1159,11 @@ struct SyncEvent {
 /// `degraded` instead of `down`.
 pub fn overall_health(home_url: &str, view_urls: &[String]) -> &'static str {
     use electrum_btcx::HealthState;
-    let state = |url: &str| electrum_btcx::server_health(COIN_ID, url).state();
+    // Health cells are keyed by the URL actually dialed (see `pooled`).
+    let state = |url: &str| {
+        let dialed = super::tlspin::dialed_url(url);
+        electrum_btcx::server_health(COIN_ID, &dialed).state()
+    };
     match state(home_url) {
         HealthState::Healthy => "healthy",
         HealthState::Untested => "connecting",
@@ -971,7 +1186,8 @@ pub fn overall_health(home_url: &str, view_urls: &[String]) -> &'static str {
 /// and the passive health cells (the SyncWorker surface has no callback
 /// hook; polling its cheap accessors is the sanctioned pattern). Also
 /// persists the live wallet's balance snapshot (selector display) whenever
-/// balance or height moved. Exits with the runtime that spawned it.
+/// balance or height moved, and SPV-checks new confirmations (see `spv`).
+/// Exits with the runtime that spawned it.
 #[allow(clippy::too_many_arguments)]
 fn spawn_sync_emitter(
     app: tauri::AppHandle,
@@ -994,6 +1210,11 @@ fn spawn_sync_emitter(
             // ~2 minutes (40 × 3s iterations).
             const GAP_WATCH_EVERY: u32 = 40;
             let mut gap_tick: u32 = GAP_WATCH_EVERY - 1;
+            // Header consensus cadence (unless `off`): first check ~30s
+            // after open, then every ~5 minutes (100 × 3s iterations).
+            const CONSENSUS_EVERY: u32 = 100;
+            let mut consensus_tick: u32 = CONSENSUS_EVERY - 10;
+            let mut spv_due = true;
             loop {
                 // ~3s cadence, checking the stop flag every 500ms so a
                 // close/network-switch never waits on a sleeping thread.
@@ -1013,12 +1234,44 @@ fn spawn_sync_emitter(
                         Ok(false) => {}
                         Err(e) => log::debug!("btcx wallet: gap watch skipped: {e}"),
                     }
+                    // Retry confirmations whose SPV check failed earlier.
+                    spv_due = true;
+                }
+                consensus_tick += 1;
+                if consensus_tick >= CONSENSUS_EVERY {
+                    consensus_tick = 0;
+                    if state.get_config().header_consensus != HeaderConsensusMode::Off {
+                        match super::consensus::refresh(&state) {
+                            Ok(report) if report.home_on_minority => log::warn!(
+                                "btcx wallet: home server {home_url} is not on the chain \
+                                 {} of {} servers agree on",
+                                report.agreeing,
+                                report.verified
+                            ),
+                            Ok(_) => {}
+                            Err(e) => log::debug!("btcx wallet: header consensus skipped: {e}"),
+                        }
+                    }
                 }
                 let age = worker.fresh_age();
                 let Ok(entry) = handle.lock() else { return };
                 let height = entry.wallet.latest_checkpoint().height();
                 let balance_sat = entry.wallet.balance().total().to_sat();
                 drop(entry);
+                // New blocks or txs: prove the new confirmations (unproven
+                // ones count as pending, see spv) before the event — a pass
+                // that proved anything re-emits so the UI re-reads the
+                // balance.
+                if spv_due || last_snapshot != Some((balance_sat, height)) {
+                    spv_due = false;
+                    let root = BtcxWalletConfig::wallet_root(network, &wallet_name);
+                    let params = network.params();
+                    match super::spv::verify_wallet(params, &root, &home_url, &view_urls, &handle) {
+                        Ok(0) => {}
+                        Ok(_) => last = None,
+                        Err(e) => log::debug!("btcx wallet: SPV pass skipped: {e}"),
+                    }
+                }
                 // Persist the selector snapshot when it moved (a config
                 // write — rare: block cadence or an actual balance change).
                 if last_snapshot != Some((balance_sat, height)) {
@@ -1083,10 +1336,12 @@ pub fn broadcast_tx_over_electrum(
     let mut last_reject: Option<String> = None;
     let mut last_connect: Option<String> = None;
     for url in servers {
-        let backend = match ElectrumBackend::new(params, url) {
+        let dialed = super::tlspin::dial_url(url)
+            .and_then(|dial| ElectrumBackend::new(params, &dial).map_err(|e| format!("{e:#}")));
+        let backend = match dialed {
             Ok(backend) => backend,
             Err(e) => {
-                last_connect = Some(format!("{url}: {e:#}"));
+                last_connect = Some(format!("{url}: {e}"));
                 continue;
             }
         };
@@ -1096,7 +1351,18 @@ pub fn broadcast_tx_over_electrum(
         }
         match backend.broadcast(&tx) {
             Ok(txid) => return Ok(txid.to_string()),
-            Err(e) => last_reject = Some(format!("{url}: {e:#}")),
+            Err(e) => {
+                // Explain the server's verdict with the local checks where
+                // they find something (see `policy`).
+                let local = super::policy::fetch_prevouts(&backend, &tx)
+                    .map(|prevouts| super::policy::check(params, &tx, &prevouts, &[]));
+                last_reject = Some(match local {
+                    Ok(check) if !check.allowed => {
+                        format!("{url}: {e:#} (local checks: {})", check.summary())
+                    }
+                    _ => format!("{url}: {e:#}"),
+                });
+            }
         }
     }
     match (last_reject, last_connect) {
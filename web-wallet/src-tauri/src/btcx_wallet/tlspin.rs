@@ -0,0 +1,380 @@
+//! Certificate trust for `ssl://` Electrum servers: CA validation first,
+//! trust-on-first-use pinning for the servers CA validation rejects.
+//!
+//! A server certificate that chains to the webpki roots for the server's
+//! host name is accepted as any TLS client would accept it. Community
+//! electrs instances mostly serve self-signed certificates, which no chain
+//! validation accepts — for THOSE the wallet trusts the certificate the
+//! server presented the first time it was dialed: its SHA-256 fingerprint
+//! is recorded in `BtcxWalletConfig::cert_pins` during that handshake, and
+//! a later handshake presenting a different certificate is refused with a
+//! "certificate changed" error until the user re-pins the server
+//! (`btcx_electrum_repin_certificate`). The built-in default server is
+//! never pinned: it must pass CA validation.
+//!
+//! The Electrum stack dials from a URL and offers no verifier hook, so an
+//! `ssl://` server is rewritten (see [`dial_url`]) to a plain `tcp://`
+//! loopback forwarder that terminates TLS itself (`crate::proxy::forward`,
+//! through the app-wide proxy when it is on). The pin is read and recorded
+//! inside each handshake, so a dial costs no extra connection and a re-pin
+//! applies to the next handshake. Handshake signatures are always verified
+//! against the presented certificate, so a pin cannot be replayed without
+//! the server's key.
+
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+
+use super::config::DEFAULT_MAINNET_ELECTRUM;
+use super::state::{BtcxWalletState, SharedBtcxWalletState};
+use crate::proxy::forward::{self, Upstream};
+
+/// The wallet state whose config holds the pins (see [`attach`]).
+static STATE: RwLock<Weak<BtcxWalletState>> = RwLock::new(Weak::new());
+
+/// Make `state`'s config the pin store. Dials made without an attached
+/// state (library use, tests) accept CA-valid certificates only.
+pub(crate) fn attach(state: &SharedBtcxWalletState) {
+    if let Ok(mut slot) = STATE.write() {
+        *slot = Arc::downgrade(state);
+    }
+}
+
+fn attached() -> Option<SharedBtcxWalletState> {
+    STATE.read().ok()?.upgrade()
+}
+
+/// SHA-256 fingerprint of a DER certificate, `AB:CD:…` (the form
+/// `openssl x509 -fingerprint -sha256` prints).
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Whether two fingerprints are the same, ignoring case and separators.
+pub fn same_fingerprint(a: &str, b: &str) -> bool {
+    let digits = |s: &str| -> String {
+        s.chars()
+            .filter(char::is_ascii_hexdigit)
+            .map(|c| c.to_ascii_uppercase())
+            .collect()
+    };
+    digits(a) == digits(b)
+}
+
+/// The pinned fingerprint of server `url`, if any.
+pub fn pin_of(url: &str) -> Option<String> {
+    attached()?.get_config().cert_pins.get(url.trim()).cloned()
+}
+
+/// What a handshake does with a certificate CA validation rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fallback {
+    /// Accept it when it matches the pin; pin it when nothing is pinned.
+    Pin,
+    /// Accept it when it matches the pin or nothing is pinned — a probe of
+    /// a server that may never be added records nothing.
+    Probe,
+    /// Accept anything: only the presented certificate is of interest.
+    Inspect,
+}
+
+impl Fallback {
+    fn policy(self) -> &'static str {
+        match self {
+            Fallback::Pin => "pinned",
+            Fallback::Probe => "probe",
+            Fallback::Inspect => "inspect",
+        }
+    }
+}
+
+/// What the server presented in the last handshake of a verifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presented {
+    /// SHA-256 fingerprint of the leaf certificate.
+    pub fingerprint: String,
+    /// Whether it chains to the webpki roots for the host name.
+    pub ca_valid: bool,
+}
+
+/// CA validation first; a rejected certificate is checked against (or
+/// recorded as) the pin of `url` per `fallback`.
+#[derive(Debug)]
+struct PinVerifier {
+    url: String,
+    fallback: Fallback,
+    webpki: Arc<WebPkiServerVerifier>,
+    presented: Mutex<Option<Presented>>,
+}
+
+/// The crypto provider every wallet TLS session uses.
+fn provider() -> Arc<CryptoProvider> {
+    static PROVIDER: OnceLock<Arc<CryptoProvider>> = OnceLock::new();
+    PROVIDER
+        .get_or_init(|| Arc::new(rustls::crypto::ring::default_provider()))
+        .clone()
+}
+
+/// The webpki-roots chain verifier (built once).
+fn webpki() -> Result<Arc<WebPkiServerVerifier>, String> {
+    static VERIFIER: OnceLock<Arc<WebPkiServerVerifier>> = OnceLock::new();
+    if let Some(verifier) = VERIFIER.get() {
+        return Ok(verifier.clone());
+    }
+    let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider())
+        .build()
+        .map_err(|e| format!("TLS setup: {e}"))?;
+    Ok(VERIFIER.get_or_init(|| verifier).clone())
+}
+
+impl PinVerifier {
+    /// The CA-rejected certificate `presented` of `self.url`: accepted per
+    /// the fallback against the pins of `state`, `reason` being why CA
+    /// validation refused it.
+    fn fall_back(
+        &self,
+        state: Option<SharedBtcxWalletState>,
+        presented: &str,
+        reason: rustls::Error,
+    ) -> Result<(), String> {
+        let url = self.url.as_str();
+        if self.fallback == Fallback::Inspect {
+            return Ok(());
+        }
+        if url == DEFAULT_MAINNET_ELECTRUM {
+            return Err(format!(
+                "{url}: the certificate is not valid for this server ({reason})"
+            ));
+        }
+        let pin = state
+            .as_ref()
+            .and_then(|s| s.get_config().cert_pins.get(url).cloned());
+        match pin {
+            Some(pin) if same_fingerprint(&pin, presented) => Ok(()),
+            Some(pin) => Err(format!(
+                "{url}: certificate changed — the server now presents {presented}, \
+                 pinned is {pin}. Re-pin the server only if its operator rotated \
+                 the certificate."
+            )),
+            None if self.fallback == Fallback::Probe => Ok(()),
+            None => {
+                let state = state.ok_or_else(|| {
+                    format!("{url}: untrusted certificate ({reason}) and no pin store")
+                })?;
+                // First connect: trust what the server presents now (a pin a
+                // concurrent dial recorded meanwhile wins, and is checked).
+                let config = state.update_config(|c| {
+                    c.cert_pins
+                        .entry(url.to_string())
+                        .or_insert_with(|| presented.to_string());
+                })?;
+                match config.cert_pins.get(url) {
+                    Some(pin) if same_fingerprint(pin, presented) => {
+                        log::info!("btcx wallet: pinned {url} certificate {presented}");
+                        Ok(())
+                    }
+                    _ => Err(format!("{url}: certificate changed while pinning")),
+                }
+            }
+        }
+    }
+}
+
+impl ServerCertVerifier for PinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let fingerprint = fingerprint(end_entity.as_ref());
+        let checked = self.webpki.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        );
+        if let Ok(mut slot) = self.presented.lock() {
+            *slot = Some(Presented {
+                fingerprint: fingerprint.clone(),
+                ca_valid: checked.is_ok(),
+            });
+        }
+        match checked {
+            Ok(verified) => Ok(verified),
+            Err(reason) => self
+                .fall_back(attached(), &fingerprint, reason)
+                .map(|()| ServerCertVerified::assertion())
+                .map_err(rustls::Error::General),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.webpki.supported_verify_schemes()
+    }
+}
+
+/// A TLS client config verifying with `verifier`.
+fn client_config(verifier: Arc<PinVerifier>) -> Result<Arc<ClientConfig>, String> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS setup: {e}"))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+fn verifier(url: &str, fallback: Fallback) -> Result<Arc<PinVerifier>, String> {
+    Ok(Arc::new(PinVerifier {
+        url: url.to_string(),
+        fallback,
+        webpki: webpki()?,
+        presented: Mutex::new(None),
+    }))
+}
+
+/// The certificate `ssl://` server `url` presents right now (a fresh TLS
+/// handshake; nothing is pinned).
+pub fn presented(url: &str) -> Result<Presented, String> {
+    let url = url.trim();
+    let (_, host, port) = crate::proxy::split_url(url)?;
+    let verifier = verifier(url, Fallback::Inspect)?;
+    let sock = crate::proxy::open_stream(host, port)
+        .map_err(|e| format!("connecting to {host}:{port}: {e}"))?;
+    let mut tls = forward::handshake(client_config(verifier.clone())?, host, port, sock)?;
+    tls.conn.send_close_notify();
+    let _ = tls.conn.complete_io(&mut tls.sock);
+    let presented = verifier.presented.lock().ok().and_then(|p| p.clone());
+    presented.ok_or_else(|| format!("{host}:{port} presented no certificate"))
+}
+
+/// The URL to dial for Electrum server `url`. `tcp://` servers go through
+/// the app-wide proxy routing unchanged; an `ssl://` server becomes the
+/// `tcp://` loopback forwarder that runs its TLS session — CA-validated,
+/// else pinned on first use and refused with a "certificate changed"
+/// error when it later presents a different one. No network I/O happens
+/// here: the forwarder verifies on each connection.
+pub(crate) fn dial_url(url: &str) -> Result<String, String> {
+    route(url, Fallback::Pin)
+}
+
+/// [`dial_url`], or `url` itself when it cannot be routed. For keying
+/// health cells and the pool.
+pub(crate) fn dialed_url(url: &str) -> String {
+    dial_url(url).unwrap_or_else(|_| url.to_string())
+}
+
+/// [`dial_url`] without recording a first-use pin — a probe of a server
+/// that may never be added.
+pub(crate) fn probe_dial_url(url: &str) -> Result<String, String> {
+    route(url, Fallback::Probe)
+}
+
+fn route(url: &str, fallback: Fallback) -> Result<String, String> {
+    let url = url.trim();
+    let (scheme, host, port) = crate::proxy::split_url(url)?;
+    if scheme != "ssl" {
+        return crate::proxy::electrum_dial_url(url);
+    }
+    let upstream = Upstream::Tls {
+        policy: fallback.policy(),
+        config: client_config(verifier(url, fallback)?)?,
+    };
+    let local = forward::forwarder(host, port, upstream)?;
+    Ok(format!("tcp://127.0.0.1:{local}"))
+}
+
+/// Replace the pin of `url` with the certificate it presents now.
+/// `expected`, when given, must match it (a fingerprint confirmed out of
+/// band with the operator). Returns the new pin.
+pub fn repin(state: &BtcxWalletState, url: &str, expected: Option<&str>) -> Result<String, String> {
+    let url = url.trim();
+    if !url.starts_with("ssl://") {
+        return Err(format!(
+            "{url}: only ssl:// servers have a certificate to pin"
+        ));
+    }
+    if url == DEFAULT_MAINNET_ELECTRUM {
+        return Err(format!(
+            "{url}: the built-in server is trusted by its CA certificate, never pinned"
+        ));
+    }
+    let presented = presented(url)?.fingerprint;
+    if let Some(expected) = expected.filter(|e| !e.trim().is_empty()) {
+        if !same_fingerprint(expected, &presented) {
+            return Err(format!(
+                "{url} presents {presented}, not the expected {expected} — not re-pinned"
+            ));
+        }
+    }
+    state.update_config(|c| {
+        c.cert_pins.insert(url.to_string(), presented.clone());
+    })?;
+    log::info!("btcx wallet: re-pinned {url} certificate {presented}");
+    Ok(presented)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_compare_by_digits() {
+        let fp = fingerprint(b"certificate");
+        assert_eq!(fp.len(), 32 * 3 - 1);
+        assert!(fp.chars().all(|c| c == ':' || c.is_ascii_hexdigit()));
+        assert!(same_fingerprint(&fp, &fp.replace(':', "").to_lowercase()));
+        assert!(!same_fingerprint(&fp, &fingerprint(b"other certificate")));
+    }
+
+    #[test]
+    fn ca_rejected_certificates_fall_back_per_mode() {
+        // Without a pin store nothing can be pinned.
+        let fall_back = |url: &str, fallback| {
+            verifier(url, fallback).unwrap().fall_back(
+                None,
+                "AB:CD",
+                rustls::Error::General("self-signed".into()),
+            )
+        };
+        let custom = "ssl://electrum.example.org:50002";
+        assert!(fall_back(custom, Fallback::Inspect).is_ok());
+        assert!(fall_back(custom, Fallback::Probe).is_ok());
+        assert!(fall_back(custom, Fallback::Pin).is_err());
+        // The built-in server must pass CA validation, even for a probe.
+        let err = fall_back(DEFAULT_MAINNET_ELECTRUM, Fallback::Probe).unwrap_err();
+        assert!(err.contains("not valid"), "{err}");
+    }
+}
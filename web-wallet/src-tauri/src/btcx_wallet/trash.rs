@@ -0,0 +1,450 @@
+//! The per-network wallet trash: list, restore, purge.
+//!
+//! `btcx_wallet_delete` / `btcx_wallet_delete_group` MOVE a wallet dir to
+//! `<network>/.trash/<name>-<ts>[-n]/` and drop its registry entry. Since
+//! the registry no longer knows the wallet, the delete writes its entry
+//! into the moved dir as `trash.json` ([`TrashManifest`]) — that is what
+//! the trash browser lists and what a restore re-registers. Dirs trashed
+//! before the manifest existed still list: name and deletion time come
+//! from the dir name, the source from the key file present, and the policy
+//! is unknown (a restore falls back to the fresh-wallet default).
+//!
+//! A trash entry's id is its dir name. Purging is the ONLY path that ever
+//! removes wallet data from disk; the optional retention policy
+//! (`trashRetentionDays` in the config) purges entries older than that on
+//! startup.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{
+    BalanceSnapshot, BtcxWalletConfig, DescriptorPolicy, MultisigShape, WalletMeta, WalletNetwork,
+    WalletSourceCfg, TRASH_SUBDIR,
+};
+use super::descstore;
+use super::state::SharedBtcxWalletState;
+
+/// File name of the registry entry a delete leaves in the trashed dir.
+pub const TRASH_MANIFEST_FILE: &str = "trash.json";
+
+/// The registry entry of a trashed wallet, as of its deletion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashManifest {
+    pub name: String,
+    /// Unix seconds of the delete — shared by every member of one group
+    /// delete, which is how a group restore finds its batch.
+    pub deleted_at: u64,
+    pub meta: WalletMeta,
+}
+
+/// One trashed wallet, as the trash browser lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedWallet {
+    /// The trash dir name — the handle restore/purge take.
+    pub id: String,
+    /// The name the wallet had when deleted.
+    pub name: String,
+    pub deleted_at: Option<u64>,
+    pub source: WalletSourceCfg,
+    /// `None` for dirs trashed before manifests were written.
+    pub policy: Option<DescriptorPolicy>,
+    /// The wallet-selector group it belonged to (manifest only).
+    pub group: Option<String>,
+    pub single_address: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigShape>,
+    /// Last known balance (display only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance_snapshot: Option<BalanceSnapshot>,
+}
+
+/// The trash dir of `network`.
+pub fn trash_dir(network: WalletNetwork) -> PathBuf {
+    BtcxWalletConfig::wallet_dir()
+        .join(network.as_str())
+        .join(TRASH_SUBDIR)
+}
+
+/// Write the manifest into a freshly trashed dir.
+pub fn write_manifest(dir: &Path, manifest: &TrashManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    descstore::write_atomic(&dir.join(TRASH_MANIFEST_FILE), &json)
+}
+
+/// Split a trash id into the wallet name and deletion time:
+/// `<name>-<ts>` or `<name>-<ts>-<n>`.
+fn parse_id(id: &str) -> (String, Option<u64>) {
+    let mut parts: Vec<&str> = id.rsplitn(3, '-').collect();
+    parts.reverse();
+    match parts.as_slice() {
+        // `-<n>` is the small same-second disambiguator, never a timestamp.
+        [name, ts, n] if matches!((ts.parse::<u64>(), n.parse::<u64>()), (Ok(t), Ok(n)) if n < t) => {
+            (name.to_string(), ts.parse().ok())
+        }
+        [.., ts] if ts.parse::<u64>().is_ok() => {
+            let name = &id[..id.len() - ts.len() - 1];
+            (name.to_string(), ts.parse().ok())
+        }
+        _ => (id.to_string(), None),
+    }
+}
+
+/// Refuse ids that are not a plain trash entry name (no separators, no
+/// dot-dirs) — ids come from the frontend.
+fn validate_id(id: &str) -> Result<(), String> {
+    let ok = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("Invalid trash entry '{id}'"))
+    }
+}
+
+/// Key-material source of a manifest-less dir, from the file present.
+fn infer_source(dir: &Path) -> WalletSourceCfg {
+    if dir.join(descstore::DESCRIPTOR_FILE).exists() {
+        WalletSourceCfg::Descriptor
+    } else if dir.join(descstore::PUBLIC_DESCRIPTOR_FILE).exists() {
+        WalletSourceCfg::WatchOnly
+    } else {
+        WalletSourceCfg::Seed
+    }
+}
+
+/// Read one trash entry of `trash`.
+fn read_entry_in(trash: &Path, id: &str) -> Result<(TrashedWallet, Option<TrashManifest>), String> {
+    validate_id(id)?;
+    let dir = trash.join(id);
+    if !dir.is_dir() {
+        return Err(format!("No trashed wallet '{id}'"));
+    }
+    let manifest = match std::fs::read_to_string(dir.join(TRASH_MANIFEST_FILE)) {
+        Ok(text) => Some(
+            serde_json::from_str::<TrashManifest>(&text)
+                .map_err(|e| format!("{}: {e}", dir.join(TRASH_MANIFEST_FILE).display()))?,
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("reading trash entry '{id}': {e}")),
+    };
+    let entry = match &manifest {
+        Some(m) => TrashedWallet {
+            id: id.to_string(),
+            name: m.name.clone(),
+            deleted_at: Some(m.deleted_at),
+            source: m.meta.source,
+            policy: Some(m.meta.policy),
+            group: Some(m.meta.group.clone()).filter(|g| !g.is_empty()),
+            single_address: m.meta.single_address,
+            multisig: m.meta.multisig,
+            balance_snapshot: m.meta.balance_snapshot,
+        },
+        None => {
+            let (name, deleted_at) = parse_id(id);
+            TrashedWallet {
+                id: id.to_string(),
+                name,
+                deleted_at,
+                source: infer_source(&dir),
+                policy: None,
+                group: None,
+                single_address: false,
+                multisig: None,
+                balance_snapshot: None,
+            }
+        }
+    };
+    Ok((entry, manifest))
+}
+
+/// Every entry of `trash`, newest first (unknown times last). A missing
+/// trash dir is an empty list; unreadable entries are logged and skipped.
+pub fn list_in(trash: &Path) -> Result<Vec<TrashedWallet>, String> {
+    let read = match std::fs::read_dir(trash) {
+        Ok(read) => read,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("reading {}: {e}", trash.display())),
+    };
+    let mut out = Vec::new();
+    for dirent in read.flatten() {
+        if !dirent.path().is_dir() {
+            continue;
+        }
+        let id = dirent.file_name().to_string_lossy().to_string();
+        match read_entry_in(trash, &id) {
+            Ok((entry, _)) => out.push(entry),
+            Err(e) => log::warn!("btcx wallet: skipping trash entry '{id}': {e}"),
+        }
+    }
+    out.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(a.id.cmp(&b.id)));
+    Ok(out)
+}
+
+/// Permanently remove one entry of `trash`. `confirm_name` must repeat
+/// the wallet's name.
+pub fn purge_in(trash: &Path, id: &str, confirm_name: &str) -> Result<(), String> {
+    let (entry, _) = read_entry_in(trash, id)?;
+    if entry.name != confirm_name {
+        return Err("Confirmation does not match the wallet name".into());
+    }
+    let dir = trash.join(id);
+    std::fs::remove_dir_all(&dir).map_err(|e| format!("removing {}: {e}", dir.display()))?;
+    log::info!("btcx wallet: purged trashed wallet '{id}'");
+    Ok(())
+}
+
+/// Purge every entry of `trash` deleted more than `days` days before
+/// `now`. Entries without a known deletion time are kept. Returns the
+/// purged ids.
+pub fn purge_expired_in(trash: &Path, days: u32, now: u64) -> Result<Vec<String>, String> {
+    let cutoff = now.saturating_sub(u64::from(days) * 86_400);
+    let mut purged = Vec::new();
+    for entry in list_in(trash)? {
+        if entry.deleted_at.is_some_and(|at| at < cutoff) {
+            purge_in(trash, &entry.id, &entry.name)?;
+            purged.push(entry.id);
+        }
+    }
+    Ok(purged)
+}
+
+/// Apply the configured retention to every network's trash (startup).
+/// Failures are logged — cleanup never blocks the wallet from loading.
+pub fn apply_retention(config: &BtcxWalletConfig) {
+    let Some(days) = config.trash_retention_days else {
+        return;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    for network in [
+        WalletNetwork::Mainnet,
+        WalletNetwork::Testnet,
+        WalletNetwork::Regtest,
+    ] {
+        if let Err(e) = purge_expired_in(&trash_dir(network), days, now) {
+            log::warn!(
+                "btcx wallet: trash retention on {} failed: {e}",
+                network.as_str()
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Restore (registry side)
+// ============================================================================
+
+/// The registry entry a restore re-creates: the manifest's, or — for a
+/// manifest-less dir — the fresh-wallet default policy with the inferred
+/// source.
+fn restored_meta(entry: &TrashedWallet, manifest: Option<TrashManifest>) -> WalletMeta {
+    match manifest {
+        Some(m) => m.meta,
+        None => WalletMeta {
+            policy: DescriptorPolicy::default(),
+            created_at: None,
+            source: entry.source,
+            single_address: false,
+            v30_migrated: true,
+            group: String::new(),
+            balance_snapshot: None,
+            multisig: None,
+            backup_verified_at: None,
+        },
+    }
+}
+
+/// Move one trash entry back into the registry as `name` (already
+/// resolved free) inside `group`.
+fn restore_one(
+    state: &SharedBtcxWalletState,
+    network: WalletNetwork,
+    entry: &TrashedWallet,
+    manifest: Option<TrashManifest>,
+    name: &str,
+    group: &str,
+) -> Result<(), String> {
+    let from = trash_dir(network).join(&entry.id);
+    let to = BtcxWalletConfig::wallet_root(network, name);
+    super::commands::move_wallet_dir(&from, &to)?;
+    if let Err(e) = std::fs::remove_file(to.join(TRASH_MANIFEST_FILE)) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("btcx wallet: removing the trash manifest of '{name}' failed: {e}");
+        }
+    }
+    let mut meta = restored_meta(entry, manifest);
+    meta.group = group.to_string();
+    state.update_config(|c| c.set_wallet_meta(network, name, meta))?;
+    log::info!(
+        "btcx wallet: '{}' restored from trash as '{name}' (group '{group}')",
+        entry.id
+    );
+    Ok(())
+}
+
+/// Restore ONE trashed wallet under `name` (default: its old name),
+/// disambiguated with `-2`, `-3`, … when taken. It comes back as its own
+/// group — siblings that stayed registered may have been reused since.
+/// Returns the name it was restored under.
+pub fn restore_wallet(
+    state: &SharedBtcxWalletState,
+    id: &str,
+    name: Option<&str>,
+) -> Result<String, String> {
+    let network = state.get_config().network;
+    let (entry, manifest) = read_entry_in(&trash_dir(network), id)?;
+    let base = name.unwrap_or(&entry.name);
+    super::config::validate_wallet_name(base)?;
+    let name = super::commands::resolve_counterpart_name(state, network, base, "")?;
+    restore_one(state, network, &entry, manifest, &name, &name)?;
+    Ok(name)
+}
+
+/// Restore the whole group one trashed wallet was deleted with: every
+/// entry of the same group and delete batch. When the group id is taken
+/// it moves to a free `<group>-<n>` and the members' `<group>` prefix
+/// follows (the tandem-rename mapping); each member name is then
+/// disambiguated like a single restore. Returns the restored names.
+pub fn restore_group(state: &SharedBtcxWalletState, id: &str) -> Result<Vec<String>, String> {
+    let config = state.get_config();
+    let network = config.network;
+    let trash = trash_dir(network);
+    let (entry, _) = read_entry_in(&trash, id)?;
+    let (Some(group), Some(deleted_at)) = (entry.group.clone(), entry.deleted_at) else {
+        return Err(format!(
+            "'{}' was trashed without its group — restore it on its own",
+            entry.name
+        ));
+    };
+    let batch: Vec<TrashedWallet> = list_in(&trash)?
+        .into_iter()
+        .filter(|e| e.group.as_deref() == Some(group.as_str()) && e.deleted_at == Some(deleted_at))
+        .collect();
+
+    let group_taken = |candidate: &str| {
+        config
+            .wallet_names(network)
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(candidate) || config.group_of(network, n) == candidate)
+    };
+    let new_group = if !group_taken(&group) {
+        group.clone()
+    } else {
+        (2..=99)
+            .map(|n| {
+                let tail = format!("-{n}");
+                let base: String = group
+                    .chars()
+                    .take(32usize.saturating_sub(tail.len()))
+                    .collect();
+                format!("{base}{tail}")
+            })
+            .find(|candidate| !group_taken(candidate))
+            .ok_or("Could not find a free group name")?
+    };
+
+    let mut restored = Vec::new();
+    for member in &batch {
+        let (member, manifest) = read_entry_in(&trash, &member.id)?;
+        let mapped = super::commands::renamed_member(&member.name, &group, &new_group);
+        let name = super::commands::resolve_counterpart_name(state, network, &mapped, "")?;
+        restore_one(state, network, &member, manifest, &name, &new_group)?;
+        restored.push(name);
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btcx_wallet::config::DescriptorKindCfg;
+
+    fn manifest(name: &str, deleted_at: u64) -> TrashManifest {
+        TrashManifest {
+            name: name.to_string(),
+            deleted_at,
+            meta: WalletMeta {
+                policy: DescriptorPolicy {
+                    kind: DescriptorKindCfg::Bip86,
+                    coin_type: 0,
+                },
+                created_at: Some(1),
+                source: WalletSourceCfg::Seed,
+                single_address: false,
+                v30_migrated: true,
+                group: "acct".to_string(),
+                balance_snapshot: Some(BalanceSnapshot {
+                    sat: 42,
+                    height: 7,
+                    at: 1,
+                }),
+                multisig: None,
+                backup_verified_at: None,
+            },
+        }
+    }
+
+    #[test]
+    fn ids_split_into_name_and_time() {
+        assert_eq!(
+            parse_id("acct-1700000000"),
+            ("acct".into(), Some(1_700_000_000))
+        );
+        assert_eq!(
+            parse_id("acct-v30-1700000000-2"),
+            ("acct-v30".into(), Some(1_700_000_000))
+        );
+        assert_eq!(
+            parse_id("pay-2-1700000000"),
+            ("pay-2".into(), Some(1_700_000_000))
+        );
+        assert_eq!(parse_id("odd"), ("odd".into(), None));
+        assert!(validate_id("acct-1700000000").is_ok());
+        assert!(validate_id("../acct").is_err());
+        assert!(validate_id("").is_err());
+    }
+
+    #[test]
+    fn list_reads_manifests_and_legacy_dirs_then_retention_purges() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path();
+        assert!(list_in(&trash.join("missing")).unwrap().is_empty());
+
+        // A manifest entry and a pre-manifest watch-only dir.
+        let fresh = trash.join("acct-taproot-2000000");
+        std::fs::create_dir_all(&fresh).unwrap();
+        write_manifest(&fresh, &manifest("acct-taproot", 2_000_000)).unwrap();
+        let legacy = trash.join("watch-1000000");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join(descstore::PUBLIC_DESCRIPTOR_FILE), "x").unwrap();
+
+        let listed = list_in(trash).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].name, "acct-taproot", "newest first");
+        assert_eq!(listed[0].group.as_deref(), Some("acct"));
+        assert_eq!(listed[0].balance_snapshot.map(|b| b.sat), Some(42));
+        assert_eq!(listed[1].name, "watch");
+        assert_eq!(listed[1].source, WalletSourceCfg::WatchOnly);
+        assert_eq!(listed[1].policy, None);
+
+        // Purge demands the name typed back.
+        assert!(purge_in(trash, "watch-1000000", "wtch").is_err());
+        // Retention: 10 days before "now" drops the older entry only.
+        let now = 1_000_000 + 10 * 86_400 + 1;
+        assert_eq!(
+            purge_expired_in(trash, 10, now).unwrap(),
+            vec!["watch-1000000".to_string()]
+        );
+        assert!(!legacy.exists());
+        assert!(fresh.exists());
+        purge_in(trash, "acct-taproot-2000000", "acct-taproot").unwrap();
+        assert!(list_in(trash).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,797 @@
+//! BC-UR animated QR codes for PSBTs (`ur:crypto-psbt`).
+//!
+//! An air-gapped signer and the wallet exchange PSBTs by camera: a PSBT is
+//! too large for one QR code, so it travels as an endless sequence of
+//! `ur:crypto-psbt/<seq>-<len>/<bytewords>` frames. The first `len` frames
+//! carry the fragments in order; every later one XORs a pseudo-random
+//! subset of them (a fountain code), so a scanner that missed any frames
+//! completes from whichever it catches next, in any order.
+//!
+//! Only the codec lives here — the Blockchain Commons reference
+//! (BCR-2020-005 UR, BCR-2020-012 bytewords): minimal bytewords with a
+//! CRC-32 checksum, the CBOR subset the parts use, and the fountain code's
+//! Xoshiro256** fragment chooser and Walker-alias degree sampler, which
+//! must match the reference bit for bit so other wallets can read our
+//! frames and we theirs. The message is the `crypto-psbt` CBOR byte string
+//! holding the serialized PSBT; the newer `ur:psbt` name is read too.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::Psbt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// The UR type frames are written with.
+pub const UR_TYPE: &str = "crypto-psbt";
+/// UR types read as a PSBT.
+const PSBT_TYPES: [&str; 2] = ["crypto-psbt", "psbt"];
+/// Default fragment size, bytes — a frame of ~450 bytewords letters, which
+/// phone cameras read reliably as an alphanumeric QR.
+pub const DEFAULT_MAX_FRAGMENT_LEN: usize = 200;
+/// Smallest fragment the encoder splits into (the reference's default).
+const MIN_FRAGMENT_LEN: usize = 10;
+/// Largest message a scan assembles — far above any standard PSBT, far
+/// below what a hostile frame could make us allocate.
+const MAX_MESSAGE_LEN: usize = 4_000_000;
+/// Most fragments one message may be split into. Every mixed part costs
+/// work and memory linear in the count, so a hostile frame must not be able
+/// to name millions; a few thousand still carries any PSBT a camera could
+/// reasonably scan.
+const MAX_SEQ_LEN: usize = 4_096;
+/// Most frames `encode_psbt` produces, whatever `frame_count` asks for
+/// (never fewer than two loops' worth of fragments).
+const MAX_FRAMES: usize = 4_096;
+
+/// The bytewords list; minimal bytewords keep each word's first and last
+/// letter.
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+// ============================================================================
+// CRC-32, bytewords, CBOR
+// ============================================================================
+
+/// CRC-32 (IEEE, as zlib) of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// `data` plus its CRC-32 as minimal bytewords.
+fn bytewords_encode(data: &[u8]) -> String {
+    data.iter()
+        .chain(&crc32(data).to_be_bytes())
+        .flat_map(|&b| {
+            let word = BYTEWORDS[b as usize].as_bytes();
+            [word[0] as char, word[3] as char]
+        })
+        .collect()
+}
+
+/// Minimal bytewords (any case) back to the bytes, checksum verified.
+fn bytewords_decode(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.to_ascii_lowercase();
+    let letters = text.as_bytes();
+    if !letters.len().is_multiple_of(2) || letters.len() < 8 {
+        return Err("Malformed UR frame: truncated bytewords".to_string());
+    }
+    let mut bytes = letters
+        .chunks(2)
+        .map(|pair| {
+            BYTEWORDS
+                .iter()
+                .position(|w| w.as_bytes()[0] == pair[0] && w.as_bytes()[3] == pair[1])
+                .map(|b| b as u8)
+                .ok_or_else(|| "Malformed UR frame: not a byteword".to_string())
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if crc32(&bytes).to_be_bytes() != checksum[..] {
+        return Err("Malformed UR frame: checksum mismatch (misread QR?)".to_string());
+    }
+    Ok(bytes)
+}
+
+/// A CBOR head: major type and argument, shortest form.
+fn cbor_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend([major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+/// `bytes` as a CBOR byte string.
+fn cbor_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + 5);
+    cbor_head(&mut out, 2, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Reads the CBOR subset UR parts use.
+struct CborReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CborReader<'a> {
+    fn head(&mut self, major: u8) -> Result<u64, String> {
+        let malformed = || "Malformed UR frame: bad CBOR".to_string();
+        let (&first, rest) = self.data.split_first().ok_or_else(malformed)?;
+        if first >> 5 != major {
+            return Err(malformed());
+        }
+        let width = match first & 0x1f {
+            n @ 0..=23 => {
+                self.data = rest;
+                return Ok(u64::from(n));
+            }
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(malformed()),
+        };
+        if rest.len() < width {
+            return Err(malformed());
+        }
+        let value = rest[..width]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+        self.data = &rest[width..];
+        Ok(value)
+    }
+
+    fn uint(&mut self) -> Result<u64, String> {
+        self.head(0)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.head(2)? as usize;
+        if self.data.len() < len {
+            return Err("Malformed UR frame: bad CBOR".to_string());
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+}
+
+/// The content of a CBOR byte string that is the whole of `data`.
+fn cbor_unwrap_bytes(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = CborReader { data };
+    let bytes = reader.bytes()?.to_vec();
+    if !reader.data.is_empty() {
+        return Err("Malformed UR message: trailing data".to_string());
+    }
+    Ok(bytes)
+}
+
+// ============================================================================
+// Fountain code
+// ============================================================================
+
+/// Xoshiro256**, seeded the reference's way: SHA-256 of the seed bytes,
+/// read as four big-endian words.
+struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    fn new(seed: &[u8]) -> Self {
+        let digest = Sha256::digest(seed);
+        let mut s = [0u64; 4];
+        for (word, chunk) in s.iter_mut().zip(digest.chunks(8)) {
+            *word = u64::from_be_bytes(chunk.try_into().expect("8-byte chunk"));
+        }
+        Self { s }
+    }
+
+    fn next(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    fn next_int(&mut self, low: u64, high: u64) -> u64 {
+        (self.next_double() * (high - low + 1) as f64) as u64 + low
+    }
+}
+
+/// Walker's alias method, built exactly as the reference builds it.
+struct RandomSampler {
+    probs: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl RandomSampler {
+    fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut p: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+        let (mut small, mut large) = (Vec::new(), Vec::new());
+        for i in (0..n).rev() {
+            if p[i] < 1.0 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+        let mut probs = vec![0.0; n];
+        let mut aliases = vec![0; n];
+        while !small.is_empty() && !large.is_empty() {
+            let a = small.pop().expect("non-empty");
+            let g = large.pop().expect("non-empty");
+            probs[a] = p[a];
+            aliases[a] = g;
+            p[g] += p[a] - 1.0;
+            if p[g] < 1.0 {
+                small.push(g);
+            } else {
+                large.push(g);
+            }
+        }
+        for i in large.into_iter().chain(small) {
+            probs[i] = 1.0;
+        }
+        Self { probs, aliases }
+    }
+
+    fn next(&self, rng: &mut Xoshiro256) -> usize {
+        let r1 = rng.next_double();
+        let r2 = rng.next_double();
+        let i = (self.probs.len() as f64 * r1) as usize;
+        if r2 < self.probs[i] {
+            i
+        } else {
+            self.aliases[i]
+        }
+    }
+}
+
+/// The fragment indexes part `seq_num` of a `seq_len`-fragment message
+/// XORs: fragment `seq_num - 1` for the first `seq_len` parts, then a
+/// subset of degree ~1/d drawn from the seeded generator.
+fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> BTreeSet<usize> {
+    if seq_num as usize <= seq_len {
+        return BTreeSet::from([seq_num as usize - 1]);
+    }
+    let mut seed = seq_num.to_be_bytes().to_vec();
+    seed.extend(checksum.to_be_bytes());
+    let mut rng = Xoshiro256::new(&seed);
+    let weights: Vec<f64> = (1..=seq_len).map(|i| 1.0 / i as f64).collect();
+    let degree = RandomSampler::new(&weights).next(&mut rng) + 1;
+    let mut remaining: Vec<usize> = (0..seq_len).collect();
+    let mut chosen = BTreeSet::new();
+    while chosen.len() < degree {
+        let index = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+        chosen.insert(remaining.remove(index));
+    }
+    chosen
+}
+
+/// The fragment length the reference picks: the fewest fragments (of at
+/// least `MIN_FRAGMENT_LEN` bytes) none of which exceeds `max_len`.
+fn fragment_len(message_len: usize, max_len: usize) -> usize {
+    let max_count = (message_len / MIN_FRAGMENT_LEN).max(1);
+    let mut len = message_len;
+    for count in 1..=max_count {
+        len = message_len.div_ceil(count);
+        if len <= max_len {
+            break;
+        }
+    }
+    len.max(1)
+}
+
+fn xor_into(target: &mut [u8], other: &[u8]) {
+    for (t, o) in target.iter_mut().zip(other) {
+        *t ^= o;
+    }
+}
+
+/// The first `count` frames of `message` as `ur:<ur_type>` strings; a
+/// message that fits one fragment is a single frame.
+fn encode(ur_type: &str, message: &[u8], max_fragment_len: usize, count: usize) -> Vec<String> {
+    let len = fragment_len(message.len(), max_fragment_len);
+    let seq_len = message.len().div_ceil(len).max(1);
+    if seq_len == 1 {
+        return vec![format!("ur:{ur_type}/{}", bytewords_encode(message))];
+    }
+    let mut padded = message.to_vec();
+    padded.resize(seq_len * len, 0);
+    let fragments: Vec<&[u8]> = padded.chunks(len).collect();
+    let checksum = crc32(message);
+    (1..=count.max(1) as u32)
+        .map(|seq_num| {
+            let mut data = vec![0u8; len];
+            for i in choose_fragments(seq_num, seq_len, checksum) {
+                xor_into(&mut data, fragments[i]);
+            }
+            let mut part = Vec::with_capacity(len + 20);
+            cbor_head(&mut part, 4, 5);
+            cbor_head(&mut part, 0, u64::from(seq_num));
+            cbor_head(&mut part, 0, seq_len as u64);
+            cbor_head(&mut part, 0, message.len() as u64);
+            cbor_head(&mut part, 0, u64::from(checksum));
+            cbor_head(&mut part, 2, len as u64);
+            part.extend(data);
+            format!(
+                "ur:{ur_type}/{seq_num}-{seq_len}/{}",
+                bytewords_encode(&part)
+            )
+        })
+        .collect()
+}
+
+/// What every part of one message agrees on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Shape {
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    fragment_len: usize,
+}
+
+/// One decoded multi-part frame.
+struct Part {
+    seq_num: u32,
+    shape: Shape,
+    data: Vec<u8>,
+}
+
+fn parse_part(cbor: &[u8]) -> Result<Part, String> {
+    let mut reader = CborReader { data: cbor };
+    if reader.head(4)? != 5 {
+        return Err("Malformed UR frame: not a fountain part".to_string());
+    }
+    let seq_num = reader.uint()?;
+    let seq_len = reader.uint()? as usize;
+    let message_len = reader.uint()? as usize;
+    let checksum = reader.uint()?;
+    let data = reader.bytes()?.to_vec();
+    let valid = seq_num >= 1
+        && seq_num <= u64::from(u32::MAX)
+        && checksum <= u64::from(u32::MAX)
+        && seq_len >= 1
+        && seq_len <= MAX_SEQ_LEN
+        && !data.is_empty()
+        && message_len <= MAX_MESSAGE_LEN
+        && message_len <= seq_len.saturating_mul(data.len())
+        && message_len > (seq_len - 1).saturating_mul(data.len());
+    if !valid || !reader.data.is_empty() {
+        return Err("Malformed UR frame: inconsistent fountain part".to_string());
+    }
+    Ok(Part {
+        seq_num: seq_num as u32,
+        shape: Shape {
+            seq_len,
+            message_len,
+            checksum: checksum as u32,
+            fragment_len: data.len(),
+        },
+        data,
+    })
+}
+
+/// Reassembles one message from fountain parts in any order: single-
+/// fragment parts are kept, mixed ones reduced by what is known until they
+/// peel down to a single fragment.
+struct FountainDecoder {
+    shape: Shape,
+    seen: BTreeSet<u32>,
+    simple: BTreeMap<usize, Vec<u8>>,
+    mixed: Vec<(BTreeSet<usize>, Vec<u8>)>,
+}
+
+impl FountainDecoder {
+    fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            seen: BTreeSet::new(),
+            simple: BTreeMap::new(),
+            mixed: Vec::new(),
+        }
+    }
+
+    /// Take in a part of this decoder's shape; false for a duplicate.
+    fn receive(&mut self, part: Part) -> bool {
+        if !self.seen.insert(part.seq_num) {
+            return false;
+        }
+        let indexes = choose_fragments(part.seq_num, self.shape.seq_len, self.shape.checksum);
+        let mut queue = vec![(indexes, part.data)];
+        while let Some((mut indexes, mut data)) = queue.pop() {
+            for (i, fragment) in &self.simple {
+                if indexes.remove(i) {
+                    xor_into(&mut data, fragment);
+                }
+            }
+            match indexes.len() {
+                0 => {}
+                1 => {
+                    let index = *indexes.first().expect("one index");
+                    self.simple.insert(index, data);
+                    // Mixed parts holding the new fragment may peel now.
+                    let (peel, keep) = std::mem::take(&mut self.mixed)
+                        .into_iter()
+                        .partition(|(mixed, _)| mixed.contains(&index));
+                    self.mixed = keep;
+                    queue.extend::<Vec<_>>(peel);
+                }
+                _ => {
+                    if !self.mixed.iter().any(|(mixed, _)| *mixed == indexes) {
+                        self.mixed.push((indexes, data));
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// The message, once every fragment is known; an error when it fails
+    /// its checksum.
+    fn message(&self) -> Option<Result<Vec<u8>, String>> {
+        if self.simple.len() < self.shape.seq_len {
+            return None;
+        }
+        let mut message: Vec<u8> = self.simple.values().flatten().copied().collect();
+        message.truncate(self.shape.message_len);
+        Some(if crc32(&message) == self.shape.checksum {
+            Ok(message)
+        } else {
+            Err("The reassembled PSBT fails its checksum — scan again".to_string())
+        })
+    }
+}
+
+// ============================================================================
+// PSBT frames
+// ============================================================================
+
+/// `btcx_ur_encode_psbt` result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrFrames {
+    /// Frames to show in a loop, uppercase (QR alphanumeric mode).
+    pub frames: Vec<String>,
+    /// Fragments the PSBT is split into (1: a single static QR).
+    pub fragment_count: u32,
+}
+
+/// Encode a PSBT as `ur:crypto-psbt` frames. `frame_count` defaults to
+/// twice the fragment count: the fragments, then as many mixed frames, so a
+/// scanner that missed some catches up within one loop; it is capped at
+/// [`MAX_FRAMES`]. A PSBT needing more than [`MAX_SEQ_LEN`] fragments is
+/// refused — no scanner would accept its frames.
+pub fn encode_psbt(
+    psbt_base64: &str,
+    max_fragment_len: Option<usize>,
+    frame_count: Option<usize>,
+) -> Result<UrFrames, String> {
+    let psbt = super::psbt::parse_psbt(psbt_base64)?;
+    let message = cbor_bytes(&psbt.serialize());
+    let max_len = max_fragment_len
+        .unwrap_or(DEFAULT_MAX_FRAGMENT_LEN)
+        .max(MIN_FRAGMENT_LEN);
+    let fragment_count = message
+        .len()
+        .div_ceil(fragment_len(message.len(), max_len))
+        .max(1);
+    if fragment_count > MAX_SEQ_LEN {
+        return Err(format!(
+            "The PSBT needs {fragment_count} QR fragments at this size — at most \
+             {MAX_SEQ_LEN} are allowed; use a larger fragment size or a file"
+        ));
+    }
+    let count = frame_count
+        .unwrap_or(fragment_count * 2)
+        .max(fragment_count)
+        .min(MAX_FRAMES.max(fragment_count * 2));
+    let frames = encode(UR_TYPE, &message, max_len, count)
+        .into_iter()
+        .map(|frame| frame.to_ascii_uppercase())
+        .collect();
+    Ok(UrFrames {
+        frames,
+        fragment_count: fragment_count as u32,
+    })
+}
+
+/// Progress of an animated-QR scan (`btcx_ur_scan_frame`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrScanProgress {
+    /// The PSBT (base64) is complete.
+    pub complete: bool,
+    /// The PSBT, base64 — once complete.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psbt: Option<String>,
+    /// Fragments recovered so far, of `fragmentCount`.
+    pub fragments_known: u32,
+    pub fragment_count: u32,
+    /// `fragmentsKnown / fragmentCount`, 0..1.
+    pub progress: f64,
+    /// Distinct frames taken in for this PSBT.
+    pub frames_received: u32,
+    /// This frame was seen before (nothing changed).
+    pub duplicate: bool,
+    /// This frame belongs to another PSBT: the scan started over with it.
+    pub restarted: bool,
+}
+
+/// An animated-QR scan in progress, fed one frame at a time.
+#[derive(Default)]
+pub struct UrScan {
+    decoder: Option<FountainDecoder>,
+    done: Option<String>,
+}
+
+impl UrScan {
+    /// Take in one scanned frame. Frames may come in any order and repeat;
+    /// a frame of a different PSBT starts a new scan.
+    pub fn receive(&mut self, frame: &str) -> Result<UrScanProgress, String> {
+        let lower = frame.trim().to_ascii_lowercase();
+        let body = lower
+            .strip_prefix("ur:")
+            .ok_or("Not a UR frame — expected ur:crypto-psbt/…")?;
+        let segments: Vec<&str> = body.split('/').collect();
+        let ur_type = segments[0];
+        if !PSBT_TYPES.contains(&ur_type) {
+            return Err(format!(
+                "This QR holds a ur:{ur_type}, not a PSBT (ur:crypto-psbt)"
+            ));
+        }
+        match segments[..] {
+            [_, payload] => {
+                let psbt = psbt_from_message(&bytewords_decode(payload)?)?;
+                *self = Self::default();
+                Ok(UrScanProgress {
+                    complete: true,
+                    psbt: Some(psbt),
+                    fragments_known: 1,
+                    fragment_count: 1,
+                    progress: 1.0,
+                    frames_received: 1,
+                    duplicate: false,
+                    restarted: false,
+                })
+            }
+            [_, sequence, payload] => {
+                let part = parse_part(&bytewords_decode(payload)?)?;
+                if sequence != format!("{}-{}", part.seq_num, part.shape.seq_len) {
+                    return Err("Malformed UR frame: sequence does not match its part".into());
+                }
+                self.receive_part(part)
+            }
+            _ => Err("Malformed UR frame: expected ur:type/[seq-len/]payload".to_string()),
+        }
+    }
+
+    fn receive_part(&mut self, part: Part) -> Result<UrScanProgress, String> {
+        let restarted = self
+            .decoder
+            .as_ref()
+            .is_some_and(|decoder| decoder.shape != part.shape);
+        if restarted || self.decoder.is_none() {
+            self.decoder = Some(FountainDecoder::new(part.shape));
+            self.done = None;
+        }
+        let decoder = self.decoder.as_mut().expect("decoder set above");
+        let duplicate = !decoder.receive(part);
+        if self.done.is_none() {
+            match decoder.message() {
+                Some(Ok(message)) => match psbt_from_message(&message) {
+                    Ok(psbt) => self.done = Some(psbt),
+                    Err(e) => {
+                        *self = Self::default();
+                        return Err(e);
+                    }
+                },
+                Some(Err(e)) => {
+                    *self = Self::default();
+                    return Err(e);
+                }
+                None => {}
+            }
+        }
+        let decoder = self.decoder.as_ref().expect("decoder set above");
+        let known = decoder.simple.len() as u32;
+        let total = decoder.shape.seq_len as u32;
+        Ok(UrScanProgress {
+            complete: self.done.is_some(),
+            psbt: self.done.clone(),
+            fragments_known: known,
+            fragment_count: total,
+            progress: f64::from(known) / f64::from(total),
+            frames_received: decoder.seen.len() as u32,
+            duplicate,
+            restarted,
+        })
+    }
+}
+
+/// The PSBT (base64) of a `crypto-psbt` message.
+fn psbt_from_message(message: &[u8]) -> Result<String, String> {
+    let bytes = cbor_unwrap_bytes(message)?;
+    Psbt::deserialize(&bytes)
+        .map(|psbt| psbt.to_string())
+        .map_err(|e| format!("The scanned QR holds no valid PSBT: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The reference test message: 256 bytes from `Xoshiro256("Wolf")`.
+    fn wolf_message() -> Vec<u8> {
+        let mut rng = Xoshiro256::new(b"Wolf");
+        (0..256).map(|_| rng.next_int(0, 255) as u8).collect()
+    }
+
+    #[test]
+    fn codec_matches_the_reference_vectors() {
+        let mut rng = Xoshiro256::new(b"Wolf");
+        let first: Vec<u64> = (0..5).map(|_| rng.next() % 100).collect();
+        assert_eq!(first, [42, 81, 85, 8, 82]);
+
+        // bytewords: [0, 1, 2, 128, 255] is "able acid also lava zoom" plus
+        // the checksum "jade need echo taxi".
+        let words = bytewords_encode(&[0, 1, 2, 128, 255]);
+        assert_eq!(words, "aeadaolazmjendeoti");
+        assert_eq!(
+            bytewords_decode(&words.to_uppercase()).unwrap(),
+            [0, 1, 2, 128, 255]
+        );
+        assert!(bytewords_decode("aeadaolazmjendeotj").is_err());
+
+        let frames = encode("bytes", &cbor_bytes(&wolf_message()), 30, 12);
+        assert_eq!(
+            frames[0],
+            "ur:bytes/1-9/lpadascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtdkgslpgh"
+        );
+        assert_eq!(
+            frames[9],
+            "ur:bytes/10-9/lpbkascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtwdkiplzs"
+        );
+        assert_eq!(
+            frames[11],
+            "ur:bytes/12-9/lpbnascfadaxcywenbpljkhdcarllaluzmdmgstospeyiefmwejlwtpedamktksrvlcygmzemovovllarodtmtbnptrs"
+        );
+    }
+
+    #[test]
+    fn fountain_decodes_out_of_order_with_losses_and_duplicates() {
+        let message = cbor_bytes(&wolf_message());
+        let parts: Vec<Part> = encode("bytes", &message, 30, 40)
+            .iter()
+            .map(|frame| parse_part(&bytewords_decode(frame.rsplit('/').next().unwrap()).unwrap()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let mut decoder = FountainDecoder::new(parts[0].shape);
+        // Lose fragments 1, 4 and 7, take the rest backwards, each twice.
+        for part in parts
+            .into_iter()
+            .rev()
+            .filter(|p| p.seq_num > 9 || p.seq_num % 3 != 1)
+        {
+            let again = Part {
+                seq_num: part.seq_num,
+                shape: part.shape,
+                data: part.data.clone(),
+            };
+            assert!(decoder.receive(part));
+            assert!(!decoder.receive(again));
+        }
+        assert_eq!(decoder.message().unwrap().unwrap(), message);
+
+        // A part claiming more fragments than any scan assembles is refused
+        // before anything is sized from it.
+        let oversized = |seq_len: u64| {
+            let mut part = Vec::new();
+            cbor_head(&mut part, 4, 5);
+            for value in [1, seq_len, seq_len, 0] {
+                cbor_head(&mut part, 0, value);
+            }
+            part.extend(cbor_bytes(&[0]));
+            parse_part(&part)
+        };
+        assert!(oversized(MAX_SEQ_LEN as u64).is_ok());
+        assert!(oversized(MAX_SEQ_LEN as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn scan_reassembles_a_psbt() {
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: (0..8)
+                .map(|vout| bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint::new("11".repeat(32).parse().unwrap(), vout),
+                    ..Default::default()
+                })
+                .collect(),
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(1_000),
+                script_pubkey: bitcoin::ScriptBuf::new(),
+            }],
+        };
+        let psbt = Psbt::from_unsigned_tx(tx).unwrap().to_string();
+        let encoded = encode_psbt(&psbt, Some(40), None).unwrap();
+        assert!(encoded.fragment_count > 1);
+        assert_eq!(encoded.frames.len(), encoded.fragment_count as usize * 2);
+        let capped = encode_psbt(&psbt, Some(40), Some(usize::MAX)).unwrap();
+        assert_eq!(capped.frames.len(), MAX_FRAMES);
+
+        // Mixed frames first, every frame twice.
+        let mut scan = UrScan::default();
+        for frame in encoded.frames.iter().rev().skip(1) {
+            assert!(!scan.receive(frame).unwrap().duplicate);
+            assert!(scan.receive(&frame.to_lowercase()).unwrap().duplicate);
+        }
+        let last = scan.receive(&encoded.frames[0]).unwrap();
+        assert!(last.complete);
+        assert_eq!(last.fragments_known, last.fragment_count);
+        assert_eq!(last.psbt.as_deref(), Some(psbt.as_str()));
+
+        // A frame of another message starts over; a non-PSBT type is refused.
+        let other = encode_psbt(&psbt, Some(60), None).unwrap();
+        let progress = scan.receive(&other.frames[0]).unwrap();
+        assert!(progress.restarted && !progress.complete);
+        assert!(scan.receive("ur:bytes/aeadaolazmjendeoti").is_err());
+    }
+}
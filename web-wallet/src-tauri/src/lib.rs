@@ -31,6 +31,9 @@ pub mod update;
 // Clock-drift / NTP module
 pub mod time;
 
+// App-wide SOCKS5 / Tor proxy setting
+pub mod proxy;
+
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 /// Include database migrations for the mining database
@@ -789,6 +792,15 @@ pub fn run() {
             });
         }
 
+        // Electrum peer discovery runs whether a wallet is open or not, so
+        // the server list recovers before the first open. Wallet flavor only.
+        #[cfg(feature = "wallet")]
+        btcx_wallet::discovery::spawn_background(
+            app.state::<btcx_wallet::SharedBtcxWalletState>()
+                .inner()
+                .clone(),
+        );
+
         // Set window title based on launch mode (desktop only)
         #[cfg(desktop)]
         {
@@ -1090,9 +1102,31 @@ pub fn run() {
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_validate_import,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_multisig_xpub,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_create_multisig,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_multisig_backup,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_export_descriptors,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_unlock,
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_lock,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_change_passphrase,
+            // Seed backup reveal / verification
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_reveal_mnemonic,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_backup_challenge,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_verify_backup,
+            // Split-seed backups (codex32)
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_split_seed,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_restore_from_shares,
             // Named-wallet registry
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_list,
@@ -1112,6 +1146,17 @@ pub fn run() {
             btcx_wallet::commands::btcx_wallet_rename,
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_rename_group,
+            // Trash
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_trash_list,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_trash_restore,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_trash_restore_group,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_trash_purge,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_set_trash_retention,
             // Operations
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_new_address,
@@ -1130,11 +1175,66 @@ pub fn run() {
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_send,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_preview_send,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_commit_preview,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_discard_preview,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_send_batch,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_parse_payout_csv,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_bumpfee,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_cpfp,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_cancel_tx,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_fee_estimates,
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_broadcast_tx,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_test_mempool_accept,
+            // Labels (BIP-329)
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_labels,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_get_label,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_set_label,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_delete_label,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_export_labels,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_import_labels,
+            // Coin control
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_frozen_utxos,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_set_frozen,
+            // Payment URIs & requests (BIP-21)
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_parse_payment_uri,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_build_payment_uri,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_create_payment_request,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_payment_requests,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_delete_payment_request,
+            // Message signing
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_sign_message,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_verify_message,
+            // Backup bundles
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_export_backup,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_import_backup,
             // Config & Sync
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_get_config,
@@ -1148,6 +1248,10 @@ pub fn run() {
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_revoke_assignment,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_preview_create_assignment,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_preview_revoke_assignment,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_get_assignment,
             // PSBT operations
             #[cfg(feature = "wallet")]
@@ -1161,6 +1265,22 @@ pub fn run() {
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_psbt_combine,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_ur_encode_psbt,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_ur_scan_frame,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_ur_scan_reset,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_psbt_utxo_update,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_psbt_to_rawtx,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_rawtx_decode,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_rawtx_create,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_rawtx_to_psbt,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_create_funded_psbt,
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_wallet_utxos,
@@ -1168,8 +1288,18 @@ pub fn run() {
             #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_electrum_health,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_electrum_check_consensus,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_set_header_consensus,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_electrum_probe,
             #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_electrum_repin_certificate,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_electrum_discover,
+            #[cfg(feature = "wallet")]
+            btcx_wallet::commands::btcx_wallet_set_server_discovery,
+            #[cfg(feature = "wallet")]
             btcx_wallet::commands::btcx_chain_info,
             write_text_file,
             write_binary_file,
@@ -1178,6 +1308,9 @@ pub fn run() {
             update::check_wallet_update,
             // Clock-drift command
             time::commands::check_clock_drift,
+            // Proxy commands
+            proxy::commands::get_proxy_config,
+            proxy::commands::set_proxy_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
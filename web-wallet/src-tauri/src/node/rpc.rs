@@ -83,7 +83,7 @@ impl NodeRpcClient {
             params,
         };
 
-        let client = reqwest::Client::builder()
+        let client = crate::proxy::http_client_builder()?
             .timeout(Duration::from_secs(5))
             .build()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
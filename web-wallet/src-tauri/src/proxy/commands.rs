@@ -0,0 +1,37 @@
+//! Tauri commands for the proxy setting.
+
+use tauri::AppHandle;
+#[cfg(feature = "wallet")]
+use tauri::Manager;
+
+use super::ProxyConfig;
+
+/// The current proxy setting, without its password (see
+/// [`ProxyConfig::redacted`]).
+#[tauri::command]
+pub fn get_proxy_config() -> Result<ProxyConfig, String> {
+    super::current().map(|c| c.redacted())
+}
+
+/// Save and apply the proxy setting. An open nodeless wallet is reopened
+/// so its Electrum connections are re-dialed under the new setting — the
+/// pooled ones were opened under the old one. Returns the applied setting,
+/// redacted.
+#[tauri::command]
+pub async fn set_proxy_config(config: ProxyConfig, app: AppHandle) -> Result<ProxyConfig, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        super::set(config)?;
+        #[cfg(feature = "wallet")]
+        if let Some(state) = app.try_state::<crate::btcx_wallet::SharedBtcxWalletState>() {
+            if state.is_open() {
+                state.close_runtime();
+                state.open_runtime(Some(app.clone()))?;
+            }
+        }
+        #[cfg(not(feature = "wallet"))]
+        let _ = app;
+        super::current().map(|c| c.redacted())
+    })
+    .await
+    .map_err(|e| format!("Proxy task failed: {e}"))?
+}
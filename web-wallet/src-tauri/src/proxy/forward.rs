@@ -0,0 +1,275 @@
+//! Loopback forwarders the Electrum connections are routed through.
+//!
+//! The Electrum stack dials servers from a URL and offers no proxy hook,
+//! so a proxied Electrum URL is rewritten to a loopback listener that
+//! tunnels every accepted connection to the real host (see
+//! [`super::open_stream`]).
+//!
+//! An `ssl://` server cannot simply be tunneled: the client would run TLS
+//! against `127.0.0.1` and the certificate check would be against the
+//! wrong name. Its forwarder TERMINATES TLS instead — the session to the
+//! real host carries the real host name (SNI) and validates the
+//! certificate against the webpki roots — and hands the client plain TCP.
+//!
+//! A proxy change shuts every forwarder down ([`shutdown_all`]): its
+//! listener is woken, exits and releases the port, so dials made under
+//! the old setting cannot linger.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+/// TLS handshake timeout — generous, the proxy may route over Tor.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Read poll of the TLS forwarding loop (one thread serves both
+/// directions of a TLS session, which cannot be split).
+const POLL: Duration = Duration::from_millis(20);
+
+/// How a forwarder talks to its target.
+#[derive(Clone)]
+pub enum Upstream {
+    /// Bytes as they are (`tcp://` servers).
+    Plain,
+    /// A TLS session to the target host name. `policy` names the
+    /// certificate policy of `config` — forwarders of the same target
+    /// under different policies are different forwarders.
+    Tls {
+        policy: &'static str,
+        config: Arc<ClientConfig>,
+    },
+}
+
+impl Upstream {
+    /// TLS validating the server certificate against the webpki roots.
+    pub fn webpki() -> Self {
+        static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+        let config = CONFIG.get_or_init(|| {
+            let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            let config = ClientConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default TLS versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+            Arc::new(config)
+        });
+        Upstream::Tls {
+            policy: "webpki",
+            config: config.clone(),
+        }
+    }
+
+    fn policy(&self) -> &'static str {
+        match self {
+            Upstream::Plain => "",
+            Upstream::Tls { policy, .. } => policy,
+        }
+    }
+}
+
+/// A forwarder's target host, port, TLS policy and proxy generation.
+type ForwardKey = (String, u16, &'static str, u64);
+
+/// A live forwarder: its loopback port and the flag its listener checks
+/// after every accept.
+struct Forwarder {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl Forwarder {
+    /// Stop the listener: flag it, then wake its blocking accept with a
+    /// throwaway connection.
+    fn shut_down(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+    }
+}
+
+/// Live forwarders by target.
+static FORWARDERS: Mutex<BTreeMap<ForwardKey, Forwarder>> = Mutex::new(BTreeMap::new());
+
+/// Stop every forwarder — the proxy setting changed.
+pub fn shutdown_all() {
+    let stale = match FORWARDERS.lock() {
+        Ok(mut forwarders) => std::mem::take(&mut *forwarders),
+        Err(_) => return,
+    };
+    for (_, forwarder) in stale {
+        forwarder.shut_down();
+    }
+}
+
+pub(crate) type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// Complete a TLS handshake with `host:port` over `sock` under `config`,
+/// with `host` as the server name.
+pub(crate) fn handshake(
+    config: Arc<ClientConfig>,
+    host: &str,
+    port: u16,
+    sock: TcpStream,
+) -> Result<TlsStream, String> {
+    let name = ServerName::try_from(host.to_string())
+        .map_err(|e| format!("{host}: not a valid TLS server name: {e}"))?;
+    let conn = ClientConnection::new(config, name)
+        .map_err(|e| format!("TLS setup for {host}:{port}: {e}"))?;
+    let _ = sock.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+    let _ = sock.set_write_timeout(Some(HANDSHAKE_TIMEOUT));
+    let mut tls = StreamOwned::new(conn, sock);
+    while tls.conn.is_handshaking() {
+        tls.conn
+            .complete_io(&mut tls.sock)
+            .map_err(|e| format!("TLS handshake with {host}:{port}: {e}"))?;
+    }
+    Ok(tls)
+}
+
+/// Pump bytes between `client` and `upstream` until either side closes.
+fn pipe(client: TcpStream, upstream: TcpStream) -> io::Result<()> {
+    let (mut client_rx, mut upstream_tx) = (client.try_clone()?, upstream.try_clone()?);
+    let up = std::thread::spawn(move || {
+        let _ = io::copy(&mut client_rx, &mut upstream_tx);
+        let _ = upstream_tx.shutdown(Shutdown::Write);
+    });
+    let (mut upstream_rx, mut client_tx) = (upstream, client);
+    let _ = io::copy(&mut upstream_rx, &mut client_tx);
+    let _ = client_tx.shutdown(Shutdown::Write);
+    let _ = up.join();
+    Ok(())
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// Pump bytes between the plain `client` and the TLS session until either
+/// side closes.
+fn pump(mut client: TcpStream, mut tls: TlsStream) -> io::Result<()> {
+    client.set_read_timeout(Some(POLL))?;
+    tls.sock.set_read_timeout(Some(POLL))?;
+    tls.sock.set_write_timeout(None)?;
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        match client.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                tls.write_all(&buf[..n])?;
+                tls.flush()?;
+            }
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+        match tls.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => client.write_all(&buf[..n])?,
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    tls.conn.send_close_notify();
+    let _ = tls.conn.complete_io(&mut tls.sock);
+    let _ = client.shutdown(Shutdown::Both);
+    Ok(())
+}
+
+/// Serve one accepted client: dial the target and forward until either
+/// side closes.
+fn serve(client: TcpStream, host: &str, port: u16, upstream: &Upstream) -> Result<(), String> {
+    let sock =
+        super::open_stream(host, port).map_err(|e| format!("connecting to {host}:{port}: {e}"))?;
+    match upstream {
+        Upstream::Plain => pipe(client, sock),
+        Upstream::Tls { config, .. } => pump(client, handshake(config.clone(), host, port, sock)?),
+    }
+    .map_err(|e| format!("forwarding {host}:{port}: {e}"))
+}
+
+/// The loopback port forwarding to `host:port` under `upstream`, spawning
+/// the listener on first use. Forwarders of an older proxy generation are
+/// shut down; a connection accepted after the setting changed is dropped
+/// (fail closed).
+pub fn forwarder(host: &str, port: u16, upstream: Upstream) -> Result<u16, String> {
+    let generation = super::generation();
+    let mut forwarders = FORWARDERS.lock().map_err(|_| "forwarder lock poisoned")?;
+    let stale: Vec<ForwardKey> = forwarders
+        .keys()
+        .filter(|(.., g)| *g != generation)
+        .cloned()
+        .collect();
+    for key in stale {
+        if let Some(forwarder) = forwarders.remove(&key) {
+            forwarder.shut_down();
+        }
+    }
+    let key = (host.to_string(), port, upstream.policy(), generation);
+    if let Some(forwarder) = forwarders.get(&key) {
+        return Ok(forwarder.port);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("binding the proxy forwarder: {e}"))?;
+    let local = listener
+        .local_addr()
+        .map_err(|e| format!("proxy forwarder address: {e}"))?
+        .port();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    let target = host.to_string();
+    std::thread::Builder::new()
+        .name(format!("proxy-forward-{target}:{port}"))
+        .spawn(move || {
+            for client in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) || super::generation() != generation {
+                    // Shut down, or the setting changed: never dial under a
+                    // stale proxy. Returning releases the port.
+                    return;
+                }
+                let Ok(client) = client else { continue };
+                let (target, upstream) = (target.clone(), upstream.clone());
+                std::thread::spawn(move || {
+                    if let Err(e) = serve(client, &target, port, &upstream) {
+                        log::warn!("proxy: {e}");
+                    }
+                });
+            }
+        })
+        .map_err(|e| format!("spawning the proxy forwarder: {e}"))?;
+    forwarders.insert(key, Forwarder { port: local, stop });
+    Ok(local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shut_down_forwarders_release_their_port() {
+        let local = forwarder("electrum.example.org", 50001, Upstream::Plain).unwrap();
+        let tls = forwarder("electrum.example.org", 50001, Upstream::webpki()).unwrap();
+        assert_ne!(local, tls, "the TLS policy is part of the identity");
+        assert_eq!(
+            forwarder("electrum.example.org", 50001, Upstream::Plain).unwrap(),
+            local
+        );
+
+        shutdown_all();
+        let released = |port| {
+            (0..50).any(|_| {
+                std::thread::sleep(Duration::from_millis(20));
+                TcpStream::connect(("127.0.0.1", port)).is_err()
+            })
+        };
+        assert!(released(local) && released(tls));
+    }
+}
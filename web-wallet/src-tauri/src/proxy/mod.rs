@@ -0,0 +1,374 @@
+//! App-wide SOCKS5 / Tor proxy setting.
+//!
+//! One setting (`proxy_config.json`) routes every outbound connection the
+//! backend makes that leaves the machine:
+//! - HTTP (GitHub release checks and node downloads, node RPC to a remote
+//!   node) through reqwest's `socks5h` proxy — see [`http_client_builder`];
+//! - Electrum (the wallet pool, sync worker, probes, consensus and SPV
+//!   checks) through loopback forwarders — see [`electrum_dial_url`]; an
+//!   `ssl://` server's TLS is terminated in its forwarder, against the
+//!   real host name;
+//! - NTP is UDP and cannot be proxied: it is refused while the proxy is on.
+//!
+//! Everything fails CLOSED: with the proxy on, a connection that cannot go
+//! through it errors instead of going direct, and a setting file that
+//! cannot be read refuses connections until it is saved again. Loopback
+//! targets (a local node, a regtest electrs) stay direct. The password
+//! never goes back to the UI ([`ProxyConfig::redacted`]). Tor mode additionally moves
+//! `.onion` Electrum servers to the front of the server list.
+
+pub mod commands;
+#[cfg(feature = "wallet")]
+pub(crate) mod forward;
+mod socks;
+
+use std::fs;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+/// Hosts that never go through the proxy.
+const LOOPBACK_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+/// The persisted proxy setting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyConfig {
+    /// Route outbound traffic through the proxy.
+    #[serde(default)]
+    pub enabled: bool,
+    /// SOCKS5 proxy host (Tor's default listener by default).
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Optional RFC 1929 credentials (Tor uses them for stream isolation).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// A password is stored — set on [`ProxyConfig::redacted`] copies, which
+    /// carry no password themselves.
+    #[serde(
+        skip_deserializing,
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub password_set: bool,
+    /// Tor mode: prefer `.onion` Electrum servers (NTP is off whenever the
+    /// proxy is on).
+    #[serde(default)]
+    pub tor_mode: bool,
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    9050
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_host(),
+            port: default_port(),
+            username: None,
+            password: None,
+            password_set: false,
+            tor_mode: false,
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Get the path to the proxy config file
+    pub fn config_path() -> PathBuf {
+        crate::app_data_dir().join("proxy_config.json")
+    }
+
+    /// Load config from disk, or return default if not found. A file that
+    /// cannot be read or parsed is an error, never the default — that
+    /// would silently turn the proxy off.
+    pub fn load() -> Result<Self, String> {
+        let path = Self::config_path();
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Proxy config {} is invalid: {e}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+        }
+    }
+
+    /// This setting as the UI sees it: no password, only whether one is
+    /// stored.
+    pub fn redacted(&self) -> Self {
+        Self {
+            password: None,
+            password_set: self.password.as_deref().is_some_and(|p| !p.is_empty()),
+            ..self.clone()
+        }
+    }
+
+    /// Save config to disk
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {e}"))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {e}"))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write config: {e}"))?;
+        log::info!("Proxy config saved to {}", path.display());
+        Ok(())
+    }
+
+    /// Reject settings the SOCKS5 handshake cannot carry.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.host.trim().is_empty() || self.port == 0 {
+            return Err("Enter the proxy host and port".into());
+        }
+        let user = self.username.as_deref().unwrap_or("");
+        let pass = self.password.as_deref().unwrap_or("");
+        if user.is_empty() && !pass.is_empty() {
+            return Err("A proxy password needs a username".into());
+        }
+        if user.len() > 255 || pass.len() > 255 {
+            return Err("Proxy username and password are limited to 255 bytes".into());
+        }
+        Ok(())
+    }
+}
+
+/// The loaded setting (`None` until first loaded successfully — a failed
+/// load is retried on the next use).
+static CURRENT: RwLock<Option<ProxyConfig>> = RwLock::new(None);
+/// Bumped on every change — Electrum forwarders of an older generation
+/// stop dialing.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The current proxy setting; an error while the setting file is broken.
+pub fn current() -> Result<ProxyConfig, String> {
+    if let Some(config) = CURRENT.read().ok().and_then(|c| c.clone()) {
+        return Ok(config);
+    }
+    let config = ProxyConfig::load()?;
+    if let Ok(mut slot) = CURRENT.write() {
+        slot.get_or_insert_with(|| config.clone());
+    }
+    Ok(config)
+}
+
+/// The current setting when the proxy is on. An unreadable setting is an
+/// error — callers refuse to connect rather than go direct.
+pub fn active() -> Result<Option<ProxyConfig>, String> {
+    Ok(Some(current()?).filter(|c| c.enabled))
+}
+
+#[cfg(feature = "wallet")]
+fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Validate, persist and apply a new setting. The Electrum forwarders of
+/// the old setting are shut down. No password (as a [`redacted`] copy
+/// sends back) keeps the stored one for the same username; an empty one
+/// clears it.
+///
+/// [`redacted`]: ProxyConfig::redacted
+pub fn set(mut config: ProxyConfig) -> Result<(), String> {
+    if config.password.is_none() {
+        if let Ok(stored) = current() {
+            if stored.username == config.username {
+                config.password = stored.password;
+            }
+        }
+    }
+    config.password = config.password.filter(|p| !p.is_empty());
+    config.password_set = false;
+    config.validate()?;
+    config.save()?;
+    *CURRENT.write().map_err(|_| "proxy config lock poisoned")? = Some(config);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    #[cfg(feature = "wallet")]
+    forward::shutdown_all();
+    Ok(())
+}
+
+fn is_loopback(host: &str) -> bool {
+    LOOPBACK_HOSTS.contains(&host.trim_start_matches('[').trim_end_matches(']'))
+}
+
+/// A reqwest client builder honoring the proxy: all traffic goes through
+/// `socks5h://` (remote DNS) except loopback.
+pub fn http_client_builder() -> Result<reqwest::ClientBuilder, String> {
+    let builder = reqwest::Client::builder();
+    let Some(proxy) = active()? else {
+        return Ok(builder);
+    };
+    let host = if proxy.host.contains(':') {
+        format!("[{}]", proxy.host.trim())
+    } else {
+        proxy.host.trim().to_string()
+    };
+    let mut url = reqwest::Url::parse(&format!("socks5h://{host}:{}", proxy.port))
+        .map_err(|e| format!("Invalid proxy address: {e}"))?;
+    if let Some(user) = proxy.username.as_deref().filter(|u| !u.is_empty()) {
+        url.set_username(user)
+            .and_then(|_| url.set_password(proxy.password.as_deref()))
+            .map_err(|_| "Invalid proxy credentials".to_string())?;
+    }
+    let socks = reqwest::Proxy::all(url)
+        .map_err(|e| format!("Invalid proxy address: {e}"))?
+        .no_proxy(reqwest::NoProxy::from_string(&LOOPBACK_HOSTS.join(",")));
+    Ok(builder.proxy(socks))
+}
+
+/// Split an Electrum URL into scheme, host (brackets stripped) and port.
+#[cfg(feature = "wallet")]
+pub(crate) fn split_url(url: &str) -> Result<(&str, &str, u16), String> {
+    let malformed = || format!("{url}: expected tcp://host:port or ssl://host:port");
+    let (scheme, rest) = url.split_once("://").ok_or_else(malformed)?;
+    let (host, port) = rest
+        .trim_end_matches('/')
+        .rsplit_once(':')
+        .and_then(|(h, p)| Some((h, p.parse::<u16>().ok()?)))
+        .ok_or_else(malformed)?;
+    Ok((
+        scheme,
+        host.trim_start_matches('[').trim_end_matches(']'),
+        port,
+    ))
+}
+
+/// A TCP stream to `host:port`: through the proxy when it is on, direct
+/// for loopback or without a proxy.
+pub fn open_stream(host: &str, port: u16) -> io::Result<TcpStream> {
+    if let Some(proxy) = active()
+        .map_err(io::Error::other)?
+        .filter(|_| !is_loopback(host))
+    {
+        return socks::connect(&proxy, host, port);
+    }
+    let mut last = io::Error::other(format!("{host}:{port} did not resolve"));
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, socks::CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+/// The URL to dial for Electrum server `url`: unchanged without a proxy
+/// (or for a loopback server), else `tcp://127.0.0.1:<port>` of a
+/// forwarder tunneling to the real host through the proxy. An `ssl://`
+/// server's forwarder runs the TLS session itself (see `forward`) — a
+/// client doing TLS against the loopback address would check the
+/// certificate against the wrong name.
+#[cfg(feature = "wallet")]
+pub fn electrum_dial_url(url: &str) -> Result<String, String> {
+    if active()?.is_none() {
+        return Ok(url.to_string());
+    }
+    let (scheme, host, port) = split_url(url)?;
+    if is_loopback(host) {
+        return Ok(url.to_string());
+    }
+    let upstream = match scheme {
+        "tcp" => forward::Upstream::Plain,
+        "ssl" => forward::Upstream::webpki(),
+        _ => {
+            return Err(format!(
+                "{url}: expected tcp://host:port or ssl://host:port"
+            ))
+        }
+    };
+    let local = forward::forwarder(host, port, upstream)?;
+    Ok(format!("tcp://127.0.0.1:{local}"))
+}
+
+/// Whether `url` points at a Tor onion service.
+pub fn is_onion(url: &str) -> bool {
+    url.split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .rsplit_once(':')
+        .is_some_and(|(host, _)| host.ends_with(".onion"))
+}
+
+/// Reorder an Electrum server list for Tor mode: `.onion` servers first,
+/// otherwise in configured order. Unchanged outside Tor mode (or with an
+/// unreadable setting — dialing refuses then anyway).
+pub fn order_servers(mut servers: Vec<String>) -> Vec<String> {
+    if active().is_ok_and(|p| p.is_some_and(|p| p.tor_mode)) {
+        servers.sort_by_key(|url| !is_onion(url));
+    }
+    servers
+}
+
+/// NTP goes out over UDP, which a SOCKS5 proxy cannot carry — refused
+/// while the proxy is on (or its setting is unreadable).
+pub fn ntp_allowed() -> bool {
+    matches!(active(), Ok(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn onion_urls_and_settings_are_recognized() {
+        assert!(is_onion("tcp://abcdefg.onion:50001"));
+        assert!(is_onion("ssl://abcdefg.onion:50002"));
+        assert!(!is_onion("ssl://electrum.example.org:50002"));
+        assert!(is_loopback("localhost") && is_loopback("[::1]"));
+        assert!(!is_loopback("10.0.0.1"));
+        #[cfg(feature = "wallet")]
+        {
+            assert_eq!(
+                split_url("ssl://[::1]:50002/").unwrap(),
+                ("ssl", "::1", 50002)
+            );
+            assert!(split_url("electrum.example.org:50002").is_err());
+        }
+
+        let mut config = ProxyConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok(), "defaults point at Tor");
+        config.password = Some("secret".into());
+        assert!(config.validate().is_err(), "password without username");
+        config.username = Some("miner".into());
+        assert!(config.validate().is_ok());
+        config.port = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn the_password_never_goes_back_to_the_ui() {
+        let config = ProxyConfig {
+            enabled: true,
+            username: Some("miner".into()),
+            password: Some("secret".into()),
+            ..Default::default()
+        };
+        let shown = config.redacted();
+        assert!(shown.password.is_none() && shown.password_set);
+        let json = serde_json::to_string(&shown).unwrap();
+        assert!(!json.contains("secret") && json.contains("\"passwordSet\":true"));
+        // The flag is output only: it never comes back in.
+        let back: ProxyConfig = serde_json::from_str(&json).unwrap();
+        assert!(!back.password_set);
+        assert!(!ProxyConfig::default().redacted().password_set);
+    }
+}
@@ -0,0 +1,173 @@
+//! Minimal SOCKS5 client (RFC 1928 CONNECT + RFC 1929 username/password).
+//!
+//! The target is sent as a DOMAIN name, so the proxy resolves it — no
+//! local DNS leak, and `.onion` hosts work through Tor.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use super::ProxyConfig;
+
+/// Connect/handshake timeout — generous, Tor circuits take a while.
+pub(super) const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn socks_error(msg: impl Into<String>) -> io::Error {
+    io::Error::other(msg.into())
+}
+
+/// Open a TCP stream to `host:port` through the SOCKS5 proxy in `proxy`.
+pub fn connect(proxy: &ProxyConfig, host: &str, port: u16) -> io::Result<TcpStream> {
+    let addr = (proxy.host.as_str(), proxy.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| socks_error("proxy address did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+
+    // Greeting: offer no-auth, or username/password when configured.
+    let credentials = proxy.username.as_deref().filter(|u| !u.is_empty());
+    if credentials.is_some() {
+        stream.write_all(&[5, 1, 2])?;
+    } else {
+        stream.write_all(&[5, 1, 0])?;
+    }
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply)?;
+    match (reply, credentials) {
+        ([5, 0], _) => {}
+        ([5, 2], Some(user)) => {
+            let pass = proxy.password.as_deref().unwrap_or("");
+            let mut auth = vec![1, user.len() as u8];
+            auth.extend_from_slice(user.as_bytes());
+            auth.push(pass.len() as u8);
+            auth.extend_from_slice(pass.as_bytes());
+            stream.write_all(&auth)?;
+            stream.read_exact(&mut reply)?;
+            if reply[1] != 0 {
+                return Err(socks_error("proxy rejected the credentials"));
+            }
+        }
+        _ => return Err(socks_error("proxy refused every offered auth method")),
+    }
+
+    // CONNECT by domain name: the proxy resolves it.
+    if host.len() > 255 {
+        return Err(socks_error("host name too long for SOCKS5"));
+    }
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head)?;
+    if head[1] != 0 {
+        return Err(socks_error(format!(
+            "proxy could not reach {host}:{port} ({})",
+            match head[1] {
+                1 => "general failure",
+                2 => "not allowed by ruleset",
+                3 => "network unreachable",
+                4 => "host unreachable",
+                5 => "connection refused",
+                6 => "TTL expired",
+                _ => "unsupported request",
+            }
+        )));
+    }
+    // Skip the bound address.
+    let skip = match head[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len)?;
+            usize::from(len[0])
+        }
+        _ => return Err(socks_error("malformed proxy reply")),
+    };
+    let mut bound = vec![0u8; skip + 2];
+    stream.read_exact(&mut bound)?;
+
+    stream.set_read_timeout(None)?;
+    stream.set_write_timeout(None)?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    /// A one-shot fake SOCKS5 proxy: checks the handshake, answers the
+    /// CONNECT with `rep` and returns the requested target bytes.
+    fn fake_proxy(auth: bool, rep: u8) -> (u16, std::thread::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (mut s, _) = listener.accept().unwrap();
+            let mut greeting = [0u8; 3];
+            s.read_exact(&mut greeting).unwrap();
+            if auth {
+                assert_eq!(greeting, [5, 1, 2]);
+                s.write_all(&[5, 2]).unwrap();
+                let mut head = [0u8; 2];
+                s.read_exact(&mut head).unwrap();
+                let mut user = vec![0u8; head[1] as usize];
+                s.read_exact(&mut user).unwrap();
+                let mut plen = [0u8; 1];
+                s.read_exact(&mut plen).unwrap();
+                let mut pass = vec![0u8; plen[0] as usize];
+                s.read_exact(&mut pass).unwrap();
+                assert_eq!((&user[..], &pass[..]), (&b"miner"[..], &b"secret"[..]));
+                s.write_all(&[1, 0]).unwrap();
+            } else {
+                assert_eq!(greeting, [5, 1, 0]);
+                s.write_all(&[5, 0]).unwrap();
+            }
+            let mut head = [0u8; 5];
+            s.read_exact(&mut head).unwrap();
+            assert_eq!(&head[..4], &[5, 1, 0, 3]);
+            let mut target = vec![0u8; head[4] as usize + 2];
+            s.read_exact(&mut target).unwrap();
+            s.write_all(&[5, rep, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+            target
+        });
+        (port, handle)
+    }
+
+    fn proxy(port: u16, username: Option<&str>) -> ProxyConfig {
+        ProxyConfig {
+            enabled: true,
+            host: "127.0.0.1".into(),
+            port,
+            username: username.map(str::to_string),
+            password: username.map(|_| "secret".to_string()),
+            password_set: false,
+            tor_mode: false,
+        }
+    }
+
+    #[test]
+    fn connect_sends_the_target_as_a_domain_name() {
+        let (port, handle) = fake_proxy(false, 0);
+        connect(&proxy(port, None), "abc.onion", 50001).expect("tunnel");
+        let target = handle.join().unwrap();
+        assert_eq!(&target[..9], b"abc.onion");
+        assert_eq!(&target[9..], &50001u16.to_be_bytes());
+
+        let (port, handle) = fake_proxy(true, 0);
+        connect(&proxy(port, Some("miner")), "example.org", 443).expect("auth tunnel");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn a_refused_connect_is_an_error() {
+        let (port, handle) = fake_proxy(false, 5);
+        let err = connect(&proxy(port, None), "example.org", 50002).unwrap_err();
+        assert!(err.to_string().contains("connection refused"), "{err}");
+        handle.join().unwrap();
+    }
+}
@@ -140,6 +140,10 @@ fn duration_to_ns(d: Duration) -> i128 {
 /// the caller can silently skip (avoids spurious warnings on flaky links).
 #[tauri::command]
 pub async fn check_clock_drift() -> Result<ClockDriftReport, String> {
+    // UDP cannot go through the SOCKS5 proxy: fail closed.
+    if !crate::proxy::ntp_allowed() {
+        return Err("ntp_disabled: NTP is off while the proxy is enabled".to_string());
+    }
     let mut handles = Vec::with_capacity(NTP_SERVERS.len());
     for server in NTP_SERVERS {
         handles.push(tokio::spawn(query_ntp(server)));
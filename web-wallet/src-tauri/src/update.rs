@@ -43,7 +43,7 @@ pub async fn check_wallet_update() -> Result<WalletUpdateInfo, String> {
     let current_version = env!("CARGO_PKG_VERSION");
 
     // Fetch latest release from GitHub API
-    let client = reqwest::Client::builder()
+    let client = crate::proxy::http_client_builder()?
         .user_agent("Phoenix-Wallet")
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -1456,3 +1456,866 @@ fn regtest_rename_wallet_preserves_funds() {
     std::env::remove_var("PACT_DISABLE_KEYRING");
     println!("wallet rename smoke: OK (payroll -> salaries -> salaries-2026, funds intact)");
 }
+
+/// Persisted coin freezing, live: of two funded coins the frozen one is
+/// never auto-selected (a send needing it fails, a smaller one spends the
+/// other coin), an explicit input list naming it is refused, and thawing
+/// makes it spendable again.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_frozen_coins_are_never_selected() {
+    use phoenix_pocx_lib::btcx_wallet::{labels, psbt, spend};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("coins".into()),
+        None,
+    )
+    .expect("create coins");
+
+    // 1. Two coins: 0.3 (to be frozen — the "plot ownership" coin) and 0.2.
+    let big_addr = state.backend().unwrap().wallet_new_address().unwrap();
+    let small_addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&big_addr, 0.3);
+    fund_and_mine(&small_addr, 0.2);
+    wait_for_balance(&state, 50_000_000, "after funding both coins");
+    let big = psbt::wallet_utxos(&state)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.amount_sat == 30_000_000)
+        .expect("the 0.3 coin");
+    let big_outpoint = format!("{}:{}", big.txid, big.vout);
+    labels::update_active(&state, |l| l.set_frozen(&big_outpoint, true)).unwrap();
+    assert!(psbt::wallet_utxos(&state)
+        .unwrap()
+        .iter()
+        .any(|u| u.frozen && u.amount_sat == 30_000_000));
+
+    // 2. Auto selection never touches the frozen coin.
+    let fee = || electrum_btcx::SendFee::RatePerKvb(2000);
+    let dest = state.backend().unwrap().wallet_new_address().unwrap();
+    assert!(
+        spend::send(&state, &dest, Some(25_000_000), fee(), None).is_err(),
+        "0.25 needs the frozen coin — the build must fail"
+    );
+    let txid = spend::send(&state, &dest, Some(10_000_000), fee(), None).expect("small send");
+    let tx = state
+        .with_entry(|entry| {
+            Ok(entry
+                .wallet
+                .get_tx(txid.parse().unwrap())
+                .map(|w| w.tx_node.tx.clone()))
+        })
+        .unwrap()
+        .expect("our own send is in the graph");
+    assert!(
+        tx.input
+            .iter()
+            .all(|i| i.previous_output.to_string() != big_outpoint),
+        "the frozen coin must not be an input"
+    );
+
+    // 3. Naming the frozen coin explicitly is refused; thawing frees it.
+    let pinned = [big_outpoint.clone()];
+    let err = spend::send(&state, &dest, None, fee(), Some(&pinned)).unwrap_err();
+    assert!(err.contains("frozen"), "{err}");
+    labels::update_active(&state, |l| l.set_frozen(&big_outpoint, false)).unwrap();
+    spend::send(&state, &dest, None, fee(), Some(&pinned)).expect("sweep the thawed coin");
+
+    rpc(None, "setmocktime", serde_json::json!([0]));
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("frozen coins smoke: OK");
+}
+
+/// A wallet with ONE Electrum server has no second opinion for SPV and
+/// trusts its home server: a fresh confirmed receive is spendable at once
+/// and a send may select it.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_single_server_wallet_spends_fresh_receives() {
+    use phoenix_pocx_lib::btcx_wallet::{spend, spv};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("single".into()),
+        None,
+    )
+    .expect("create single");
+
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.3);
+    wait_for_balance(&state, 30_000_000, "after funding");
+    assert!(!spv::holds_back(&state), "one server: nothing is held back");
+    let control = spend::coin_control(&state).unwrap();
+    assert!(control.unverified.is_empty());
+
+    let fee = || electrum_btcx::SendFee::RatePerKvb(2000);
+    let dest = state.backend().unwrap().wallet_new_address().unwrap();
+    spend::send(&state, &dest, Some(10_000_000), fee(), None)
+        .expect("the fresh receive is spendable");
+
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("single-server spend smoke: OK");
+}
+
+/// The coin-control send path against the one it replaced: at the same
+/// explicit feerate, `spend::send` pays what wallet-btcx's `wallet_send`
+/// pays (both RBF-signaling), and a sweep leaves no change output and an
+/// empty wallet — the fee and `sendAll` behaviour `btcx_wallet_send` had.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_coin_control_send_matches_wallet_send() {
+    use phoenix_pocx_lib::btcx_wallet::spend;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(&state, None, &mnemonic, None, "", Some("fees".into()), None)
+        .expect("create fees");
+
+    let funded = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&funded, 0.5);
+    wait_for_balance(&state, 50_000_000, "after funding");
+
+    let wallets = rpc(None, "listwallets", serde_json::json!([]));
+    let miner = wallets[0].as_str().unwrap().to_string();
+    let dest = rpc(Some(&miner), "getnewaddress", serde_json::json!([]))
+        .as_str()
+        .unwrap()
+        .to_string();
+    // (fee rate in sat/vB, output count, RBF) of a wallet tx.
+    let shape = |txid: &str| {
+        state
+            .with_entry(|entry| {
+                let tx = entry
+                    .wallet
+                    .get_tx(txid.parse().unwrap())
+                    .map(|w| w.tx_node.tx.clone())
+                    .expect("our own send is in the graph");
+                let fee = entry.wallet.calculate_fee(&tx).unwrap().to_sat();
+                Ok((
+                    fee as f64 / tx.vsize() as f64,
+                    tx.output.len(),
+                    tx.is_explicitly_rbf(),
+                ))
+            })
+            .unwrap()
+    };
+    let fee = || electrum_btcx::SendFee::RatePerKvb(3000);
+
+    // 1. Same amount, same explicit rate: the old and the new path agree.
+    let old = state
+        .backend()
+        .unwrap()
+        .wallet_send(&dest, 5_000_000, fee())
+        .expect("wallet_send");
+    let new = spend::send(&state, &dest, Some(5_000_000), fee(), None).expect("spend::send");
+    let (old_rate, old_outputs, old_rbf) = shape(&old);
+    let (new_rate, new_outputs, new_rbf) = shape(&new);
+    assert!(
+        (old_rate - new_rate).abs() < 0.1,
+        "feerates differ: wallet_send {old_rate:.3} vs spend::send {new_rate:.3} sat/vB"
+    );
+    assert!(
+        (new_rate - 3.0).abs() < 0.1,
+        "asked 3 sat/vB, paid {new_rate:.3}"
+    );
+    assert_eq!((old_outputs, new_outputs), (2, 2), "payment + change each");
+    assert!(old_rbf && new_rbf, "both paths signal RBF");
+
+    // 2. Sweep: one output, the fee out of the swept amount, nothing left.
+    let sweep = spend::send(&state, &dest, None, fee(), None).expect("sweep");
+    let (sweep_rate, sweep_outputs, _) = shape(&sweep);
+    assert_eq!(sweep_outputs, 1, "a sweep has no change output");
+    assert!(
+        (sweep_rate - 3.0).abs() < 0.1,
+        "sweep paid {sweep_rate:.3} sat/vB"
+    );
+    wait_for_balance(&state, 0, "after the sweep");
+
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("coin-control send parity smoke: OK");
+}
+
+/// Preview/commit, live: the preview's summary matches what the commit
+/// broadcasts (same txid), its coins are kept out of other builds' coin
+/// selection, a handle commits once, and a preview whose coins another
+/// commit spent is refused instead of re-selecting.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_preview_commits_exactly_the_previewed_tx() {
+    use phoenix_pocx_lib::btcx_wallet::preview::{self, PreviewKind};
+    use phoenix_pocx_lib::btcx_wallet::spend;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("preview".into()),
+        None,
+    )
+    .expect("create preview");
+
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.5);
+    wait_for_balance(&state, 50_000_000, "after funding");
+
+    // 1. A send preview: the summary is the final transaction.
+    let fee = || electrum_btcx::SendFee::RatePerKvb(2000);
+    let dest = state.backend().unwrap().wallet_new_address().unwrap();
+    let (tx, _) = spend::build_send(&state, &dest, Some(10_000_000), fee(), None).unwrap();
+    let summary = preview::stage(&state, PreviewKind::Send, tx).unwrap();
+    assert_eq!(summary.inputs.len(), 1);
+    assert_eq!(summary.inputs[0].value_sat, 50_000_000);
+    assert_eq!(
+        summary.inputs[0].value_sat,
+        summary.outputs.iter().map(|o| o.value_sat).sum::<u64>() + summary.fee_sat
+    );
+    assert!(summary.outputs.iter().any(|o| o.change));
+
+    // 2. The coin is reserved for that preview: selection no longer sees
+    // it, but naming it explicitly parks a competing sweep alongside.
+    let err = spend::build_send(&state, &dest, Some(1_000_000), fee(), None).unwrap_err();
+    assert!(err.contains("building the transaction"), "{err}");
+    let coin = vec![summary.inputs[0].outpoint.clone()];
+    let (sweep, _) = spend::build_send(&state, &dest, None, fee(), Some(&coin)).unwrap();
+    let stale = preview::stage(&state, PreviewKind::Sweep, sweep).unwrap();
+
+    // 3. The commit broadcasts exactly the previewed tx, once.
+    let txid = preview::commit(&state, &summary.handle).expect("commit the send");
+    assert_eq!(txid, summary.txid);
+    let err = preview::commit(&state, &summary.handle).unwrap_err();
+    assert!(err.contains("preview the transaction again"), "{err}");
+
+    // 4. The sweep's coin is spent now: refused, not rebuilt.
+    let err = preview::commit(&state, &stale.handle).unwrap_err();
+    assert!(err.contains("was spent since the preview"), "{err}");
+
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("preview/commit smoke: OK");
+}
+
+/// The local policy checks: a wallet send passes, a tampered signature and
+/// a non-paying double-spend are rejected with their reasons.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_policy_checks_flag_bad_transactions() {
+    use phoenix_pocx_lib::btcx_wallet::policy::{self, RejectReason};
+    use phoenix_pocx_lib::btcx_wallet::spend;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("policy".into()),
+        None,
+    )
+    .expect("create policy");
+
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.5);
+    wait_for_balance(&state, 50_000_000, "after funding");
+
+    let fee = || electrum_btcx::SendFee::RatePerKvb(2000);
+    let dest = state.backend().unwrap().wallet_new_address().unwrap();
+    let hex = |tx: &bitcoin::Transaction| bitcoin::consensus::encode::serialize_hex(tx);
+
+    // 1. A wallet send passes, with its fee computed from the prevouts.
+    let (tx, _) = spend::build_send(&state, &dest, Some(10_000_000), fee(), None).unwrap();
+    let check = policy::test_accept(&state, &hex(&tx), None).unwrap();
+    assert!(check.allowed, "{}", check.summary());
+    assert!(check.fee_sat.is_some_and(|f| f > 0));
+
+    // 2. A flipped signature byte is an invalid signature on input 0.
+    let mut tampered = tx.clone();
+    let mut witness = tampered.input[0].witness.to_vec();
+    witness[0][10] ^= 1;
+    tampered.input[0].witness = bitcoin::Witness::from_slice(&witness);
+    let check = policy::test_accept(&state, &hex(&tampered), None).unwrap();
+    assert!(!check.allowed);
+    assert!(check
+        .rejections
+        .iter()
+        .any(|r| r.reason == RejectReason::InvalidSignature && r.input == Some(0)));
+
+    // 3. Once the send is out, a sweep of the same coin at the same rate
+    //    pays less than the tx it would replace: a double-spend.
+    let (sweep, _) = spend::build_send(&state, &dest, None, fee(), None).unwrap();
+    spend::send(&state, &dest, Some(10_000_000), fee(), None).expect("send");
+    let check = policy::test_accept(&state, &hex(&sweep), None).unwrap();
+    assert!(check
+        .rejections
+        .iter()
+        .any(|r| r.reason == RejectReason::DoubleSpend));
+    let err = spend::broadcast_and_record(&state, &state.backend().unwrap(), &sweep).unwrap_err();
+    assert!(err.contains("local policy checks"), "{err}");
+
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("policy checks smoke: OK");
+}
+
+/// The raw-transaction tools: create a tx over a wallet coin, enrich its
+/// PSBT from the server, sign that PSBT with the wallet and broadcast it.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_raw_tx_create_sign_broadcast() {
+    use phoenix_pocx_lib::btcx_wallet::psbt;
+    use phoenix_pocx_lib::btcx_wallet::rawtx::{self, RawTxInput, RawTxOutput};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("rawtx".into()),
+        None,
+    )
+    .expect("create rawtx");
+
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.5);
+    wait_for_balance(&state, 50_000_000, "after funding");
+
+    let coin = psbt::wallet_utxos(&state).unwrap().remove(0);
+    let dest = state.backend().unwrap().wallet_new_address().unwrap();
+    let hex = rawtx::create(
+        WalletNetwork::Regtest,
+        &[RawTxInput {
+            txid: coin.txid.clone(),
+            vout: coin.vout,
+            sequence: None,
+        }],
+        &[RawTxOutput {
+            address: Some(dest.clone()),
+            amount_sat: coin.amount_sat - 10_000,
+            data_hex: None,
+        }],
+        None,
+        None,
+    )
+    .unwrap();
+    let decoded = rawtx::decode(WalletNetwork::Regtest, &hex).unwrap();
+    assert_eq!(decoded.vout[0].address.as_deref(), Some(dest.as_str()));
+
+    // 1. The server fills the spent output in; analyze sees the fee.
+    let empty = rawtx::to_psbt(&hex, false).unwrap();
+    let updated = rawtx::utxo_update(&state, &empty, None).unwrap();
+    assert_eq!(
+        psbt::decode(WalletNetwork::Regtest, &updated)
+            .unwrap()
+            .fee_sat,
+        Some(10_000)
+    );
+
+    // 2. The wallet signs its own coin through the PSBT; the tx relays.
+    let signed = psbt::wallet_process(&state, &updated).unwrap();
+    assert!(signed.complete);
+    let raw = rawtx::from_psbt(&signed.psbt).unwrap();
+    assert!(raw.complete);
+    let params = WalletNetwork::Regtest.params();
+    let txid = broadcast_tx_over_electrum(params, &[ELECTRUM_URL.to_string()], &raw.hex)
+        .expect("broadcast the signed raw tx");
+    assert_eq!(txid, decoded.txid);
+
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("raw tx smoke: OK");
+}
+
+/// Cancel an unconfirmed send and check both replacement links.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_cancel_tx_links_both_directions() {
+    use phoenix_pocx_lib::btcx_wallet::{replacements, spend};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("cancel".into()),
+        None,
+    )
+    .expect("create cancel");
+
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.5);
+    wait_for_balance(&state, 50_000_000, "after funding");
+
+    // A send out of the wallet, then cancelled before it confirms.
+    let wallets = rpc(None, "listwallets", serde_json::json!([]));
+    let miner = wallets[0].as_str().expect("a loaded miner wallet");
+    let dest = rpc(Some(miner), "getnewaddress", serde_json::json!([]));
+    let dest = dest.as_str().unwrap();
+    let fee = electrum_btcx::SendFee::RatePerKvb(2000);
+    let sent = spend::send(&state, dest, Some(10_000_000), fee, None).expect("send");
+    let cancel = spend::cancel(&state, &sent).expect("cancel");
+    assert_eq!(cancel.replaced_txid, sent);
+    assert!(
+        cancel.fee_rate_sat_vb > 2.0,
+        "the cancel must outbid the original's feerate"
+    );
+    assert_eq!(
+        cancel.amount_sat + cancel.fee_sat,
+        50_000_000,
+        "everything comes back minus the fee"
+    );
+
+    let links = replacements::read_active(&state);
+    assert_eq!(links.replaced_by(&sent), Some(cancel.txid.as_str()));
+    assert_eq!(links.replaces(&cancel.txid), Some(sent.as_str()));
+
+    // The cancel itself still signals RBF: cancelling it again outbids it
+    // and extends the chain of links.
+    let again = spend::cancel(&state, &cancel.txid).expect("cancel the cancel");
+    assert_eq!(
+        replacements::read_active(&state).replaces(&again.txid),
+        Some(cancel.txid.as_str())
+    );
+
+    rpc(None, "setmocktime", serde_json::json!([0]));
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("cancel tx smoke: OK");
+}
+
+/// Export a funded, labelled wallet group as a sealed bundle, restore it
+/// into a fresh data dir, and check the group, labels and balance return
+/// without a restore probe.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_backup_bundle_moves_a_group() {
+    use phoenix_pocx_lib::btcx_wallet::backup;
+    use phoenix_pocx_lib::btcx_wallet::labels::{self, LabelType};
+
+    let old_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", old_dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("vault".into()),
+        None,
+    )
+    .expect("create vault");
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.4);
+    wait_for_balance(&state, 40_000_000, "before export");
+    labels::update_active(&state, |l| l.set(LabelType::Addr, &addr, "savings")).unwrap();
+
+    let sealed = backup::export_backup(&state, Some("vault"), "bundle-pass", None)
+        .expect("export the group");
+    assert!(!sealed.contains(mnemonic.split_whitespace().next().unwrap()));
+    let members = state
+        .get_config()
+        .group_members(WalletNetwork::Regtest, "vault");
+    state.close_runtime();
+
+    // A different machine: fresh data dir, same server.
+    let new_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", new_dir.path());
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    assert!(backup::import_backup(&state, None, &sealed, "wrong", None).is_err());
+    let restored =
+        backup::import_backup(&state, None, &sealed, "bundle-pass", None).expect("restore");
+    assert_eq!(restored.active, "vault");
+    assert_eq!(restored.restored, members);
+    let config: BtcxWalletConfig = state.get_config();
+    assert_eq!(
+        config.group_members(WalletNetwork::Regtest, "vault"),
+        members
+    );
+    wait_for_balance(&state, 40_000_000, "after restore");
+    assert_eq!(
+        labels::read_active(&state)
+            .unwrap()
+            .label(LabelType::Addr, &addr),
+        Some("savings")
+    );
+    assert!(
+        backup::import_backup(&state, None, &sealed, "bundle-pass", None).is_err(),
+        "restoring over the same names is refused"
+    );
+
+    rpc(None, "setmocktime", serde_json::json!([0]));
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("backup bundle smoke: OK");
+}
+
+/// Split a funded wallet's seed 2-of-3 into codex32 shares and restore a
+/// second wallet from two of them: the restore probe finds the funded
+/// branch and the group's compartments materialize as for a typed phrase.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_codex32_shares_restore_the_wallet() {
+    use phoenix_pocx_lib::btcx_wallet::commands::{restore_from_shares_impl, split_seed_impl};
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("treasury".into()),
+        None,
+    )
+    .expect("create treasury");
+    let addr = state.backend().unwrap().wallet_new_address().unwrap();
+    fund_and_mine(&addr, 0.25);
+    wait_for_balance(&state, 25_000_000, "before split");
+
+    let split = split_seed_impl(&state, None, true, 2, 3).expect("split the seed");
+    assert_eq!(split.shares.len(), 3);
+    assert!(
+        restore_from_shares_impl(
+            &state,
+            None,
+            &split.shares[..1],
+            None,
+            "",
+            Some("vault".into()),
+            None,
+        )
+        .is_err(),
+        "one share is below the threshold"
+    );
+    let result = restore_from_shares_impl(
+        &state,
+        None,
+        &[split.shares[2].clone(), split.shares[0].clone()],
+        None,
+        "",
+        Some("vault".into()),
+        None,
+    )
+    .expect("restore from two shares");
+    assert!(!result.fresh);
+    assert_eq!(result.status.wallet_name, "vault");
+    let config = state.get_config();
+    assert!(config
+        .group_members(WalletNetwork::Regtest, "vault")
+        .contains(&"vault-taproot".to_string()));
+    wait_for_balance(&state, 25_000_000, "after the share restore");
+
+    rpc(None, "setmocktime", serde_json::json!([0]));
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("codex32 share restore smoke: OK");
+}
+
+/// Header consensus (btcx_wallet::consensus), live: the regtest electrs
+/// listed twice agrees with itself over a freshly mined tip — both lines
+/// carry the node's height and best hash — while a dead port is reported
+/// unreachable and, as the home server, does not count as a minority.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_header_consensus_agrees_with_the_node() {
+    use phoenix_pocx_lib::btcx_wallet::consensus::{check_servers, ServerVerdict};
+
+    let params = &params_btcx::params::BTCX_REGTEST;
+    let miner = rpc(None, "listwallets", serde_json::json!([]))[0]
+        .as_str()
+        .expect("a loaded miner wallet on the regtest node")
+        .to_string();
+    let addr = rpc(Some(&miner), "getnewaddress", serde_json::json!([]))
+        .as_str()
+        .unwrap()
+        .to_string();
+    mine_block(&addr);
+    rpc(None, "setmocktime", serde_json::json!([0]));
+    let height = rpc(None, "getblockcount", serde_json::json!([]))
+        .as_u64()
+        .unwrap();
+    let best = rpc(None, "getbestblockhash", serde_json::json!([]))
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // electrs indexes the new block asynchronously; retry until it serves it.
+    let servers = vec![
+        ELECTRUM_URL.to_string(),
+        ELECTRUM_URL.to_string(),
+        "tcp://127.0.0.1:1".to_string(),
+    ];
+    let deadline = SystemTime::now() + Duration::from_secs(30);
+    let report = loop {
+        let report = check_servers(params, "regtest", &servers, Some("tcp://127.0.0.1:1"));
+        if report.height == Some(height) || SystemTime::now() > deadline {
+            break report;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    };
+    assert_eq!(report.height, Some(height), "{report:?}");
+    assert_eq!((report.agreeing, report.verified), (2, 2));
+    for line in &report.servers[..2] {
+        assert_eq!(line.verdict, ServerVerdict::Agrees, "{line:?}");
+        assert_eq!(line.tip_hash.as_deref(), Some(best.as_str()));
+    }
+    assert_eq!(report.servers[2].verdict, ServerVerdict::Unreachable);
+    assert!(!report.home_on_minority);
+    println!("header consensus smoke: OK (height {height})");
+}
+
+/// Peer discovery (btcx_wallet::discovery), live: the regtest electrs
+/// answers `server.peers.subscribe` (with no peers — it is alone) while a
+/// dead port does not, and the shared probe genesis-checks and times it.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_peer_discovery_asks_the_servers() {
+    use phoenix_pocx_lib::btcx_wallet::discovery::{discover, probe};
+
+    let params = &params_btcx::params::BTCX_REGTEST;
+    let servers = vec![ELECTRUM_URL.to_string(), "tcp://127.0.0.1:1".to_string()];
+    let report = discover(params, "regtest", &servers, &servers);
+    assert_eq!(report.sources, 1, "{report:?}");
+    assert!(report.kept.iter().all(|k| !servers.contains(&k.url)));
+
+    let height = rpc(None, "getblockcount", serde_json::json!([]))
+        .as_u64()
+        .unwrap();
+    let (probed, latency_ms) = probe(params, ELECTRUM_URL).expect("probe the regtest electrs");
+    assert!(
+        probed + 1 >= height,
+        "electrs at {probed}, node at {height}"
+    );
+    assert!(latency_ms >= 0.0);
+    println!(
+        "peer discovery smoke: OK ({} candidates)",
+        report.candidates.len()
+    );
+}
+
+/// Descriptor export (btcx_wallet::descriptors::descriptor_export), live:
+/// the exported public descriptors of a seed wallet derive the wallet's
+/// own first address on the node, and the Core `importdescriptors` JSON
+/// loads into a fresh watch-only node wallet as-is.
+#[test]
+#[ignore = "needs a running regtest bitcoind (127.0.0.1:18443) + electrs (127.0.0.1:60401)"]
+fn regtest_exported_descriptors_load_into_the_node() {
+    use phoenix_pocx_lib::btcx_wallet::commands::export_descriptors_impl;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("PHOENIX_DATA_DIR", dir.path());
+    std::env::set_var("PACT_DISABLE_KEYRING", "1");
+
+    let state = phoenix_pocx_lib::btcx_wallet::create_btcx_wallet_state();
+    state
+        .update_config(|c| {
+            c.network = WalletNetwork::Regtest;
+            c.set_servers(WalletNetwork::Regtest, vec![ELECTRUM_URL.to_string()]);
+        })
+        .unwrap();
+    let seed_dir = tempfile::tempdir().unwrap();
+    let mut scratch = seedstore::SeedStore::open(seed_dir.path(), None).unwrap();
+    let mnemonic = scratch.create_seed(None, 24).unwrap();
+    create_wallet_impl(
+        &state,
+        None,
+        &mnemonic,
+        None,
+        "",
+        Some("export".into()),
+        None,
+    )
+    .expect("create wallet");
+    let first = state.backend().unwrap().wallet_new_address().unwrap();
+
+    let export = export_descriptors_impl(&state, false, None, false).expect("public export");
+    assert!(export.external_private.is_none());
+    let account = export.account.as_ref().expect("single-key account");
+    assert!(account.xpub.starts_with("tpub"), "{}", account.xpub);
+    assert!(export.external.contains(&account.key_origin));
+    let derived = rpc(
+        None,
+        "deriveaddresses",
+        serde_json::json!([export.external, [0, 0]]),
+    );
+    assert_eq!(derived[0], first.as_str(), "node derives our first address");
+
+    let node_wallet = format!("export-{}", now_secs());
+    rpc(
+        None,
+        "createwallet",
+        serde_json::json!([node_wallet, true, true, "", false, true]),
+    );
+    let requests: serde_json::Value = serde_json::from_str(&export.core_import).unwrap();
+    let results = rpc(Some(&node_wallet), "importdescriptors", requests);
+    assert!(
+        results
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|r| r["success"] == true),
+        "{results}"
+    );
+
+    // No passphrase on this seed: the private export needs the confirm.
+    let err = export_descriptors_impl(&state, true, None, false).unwrap_err();
+    assert!(err.contains("confirm"), "{err}");
+    let private = export_descriptors_impl(&state, true, None, true).expect("private export");
+    assert!(private.external_private.unwrap().contains("tprv"));
+
+    rpc(None, "unloadwallet", serde_json::json!([node_wallet]));
+    state.close_runtime();
+    std::env::remove_var("PHOENIX_DATA_DIR");
+    std::env::remove_var("PACT_DISABLE_KEYRING");
+    println!("descriptor export smoke: OK");
+}
@@ -24,12 +24,16 @@ interface BtcxUtxoDto {
   isChange: boolean;
   /** The address' pubkey is on-chain (it has been spent from before). */
   exposed: boolean;
+  /** BIP-329 label of the coin (or of its address). */
+  label?: string;
+  /** Frozen by the user — never coin-selected. */
+  frozen: boolean;
 }
 
 /**
  * Map one BDK wallet transaction onto the Core-style list entry the UI
- * renders. Fields with no Electrum equivalent (`label`, `blockhash`) stay
- * absent — templates null-guard them. Exported for unit testing.
+ * renders. `label` is the wallet's BIP-329 tx label; `blockhash` has no
+ * Electrum equivalent and stays absent — templates null-guard it. Exported for unit testing.
  */
 export function mapBtcxTxToWalletTransaction(tx: BtcxWalletTx): WalletTransaction {
   const sign = tx.direction === 'sent' ? -1 : 1;
@@ -43,6 +47,7 @@ export function mapBtcxTxToWalletTransaction(tx: BtcxWalletTx): WalletTransactio
     timereceived: tx.timestamp ?? 0,
     // Display address derived backend-side from the tx outputs.
     address: tx.address ?? undefined,
+    label: tx.label ?? undefined,
     // BDK sends always signal RBF (ENABLE_RBF_NO_LOCKTIME).
     bip125_replaceable: tx.direction === 'sent' && tx.confirmations === 0 ? 'yes' : 'no',
   };
@@ -54,6 +59,7 @@ export function mapBtcxUtxo(utxo: BtcxUtxoDto): UTXO {
     txid: utxo.txid,
     vout: utxo.vout,
     address: utxo.address ?? '',
+    label: utxo.label,
     scriptPubKey: '',
     amount: utxo.amountSat / SATS_PER_BTC,
     confirmations: utxo.confirmations,
@@ -131,7 +137,8 @@ export class ElectrumWalletBackend implements WalletBackend {
       amount: u.amountSat / SATS_PER_BTC,
       confirmations: u.confirmations,
       isChange: u.isChange,
-      spendable: true,
+      // Frozen coins are excluded from every coin selection until thawed.
+      spendable: !u.frozen,
       exposed: u.exposed,
     }));
   }
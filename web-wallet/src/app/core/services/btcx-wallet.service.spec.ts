@@ -11,6 +11,7 @@ describe('mapWalletTx', () => {
       confirmations: 3,
       timestamp: 1_752_000_000,
       address: 'pocx1qcounterparty',
+      label: 'rent',
     };
 
     const tx = mapWalletTx(dto);
@@ -23,6 +24,7 @@ describe('mapWalletTx', () => {
       confirmations: 3,
       timestamp: 1_752_000_000,
       address: 'pocx1qcounterparty',
+      label: 'rent',
     });
   });
 
@@ -44,6 +46,7 @@ describe('mapWalletTx', () => {
     expect(tx.confirmations).toBe(0);
     // No address on the wire (older backend / underivable) maps to null.
     expect(tx.address).toBeNull();
+    expect(tx.label).toBeNull();
   });
 
   it('normalizes unknown directions to received (net-in default)', () => {
@@ -48,6 +48,11 @@ export interface BtcxWalletStatus {
    * address, change returns to it; the receive page hides "new address".
    */
   singleAddress: boolean;
+  /**
+   * The selected wallet is watch-only (public descriptors, no keys) — it
+   * composes unsigned PSBTs but never signs or sends.
+   */
+  watchOnly: boolean;
   /** Wallet-cache chain height, once open. */
   syncedHeight?: number;
   /** Seconds since the last completed sync pass. */
@@ -60,6 +65,12 @@ export interface BtcxBalance {
   trustedPendingSat: number;
   untrustedPendingSat: number;
   immatureSat: number;
+  /**
+   * Reported confirmed but not yet SPV-verified — counted as pending.
+   * Always 0 without an independent second server (the home server is
+   * trusted).
+   */
+  unverifiedSat: number;
   /** confirmed + trusted pending — what a send can use. */
   spendableSat: number;
   totalSat: number;
@@ -76,6 +87,8 @@ export interface BtcxWalletTxDto {
   timestamp: number | null;
   /** Display address derived from the outputs; absent when underivable. */
   address?: string;
+  /** The user's BIP-329 `tx` label; absent when unlabelled. */
+  label?: string;
 }
 
 /** Wire shape of one `btcx_wallet_transactions` page (items + total). */
@@ -104,6 +117,8 @@ export interface BtcxWalletTx {
    * address on receives; null when underivable (e.g. OP_RETURN-only).
    */
   address: string | null;
+  /** The user's label (BIP-329), null when unlabelled. */
+  label: string | null;
 }
 
 /** Wire shape of `btcx_wallet_tx_detail` (camelCase from serde). */
@@ -143,41 +158,125 @@ export interface BtcxTxDetail {
   timestamp: number | null;
   blockHeight: number | null;
   blockHash: string | null;
+  /** The confirmation is SPV-verified against its block header. */
+  verified: boolean;
   rbf: boolean;
+  /** Txid of the replacement (fee bump or cancel) that took this tx's place. */
+  replacedBy: string | null;
+  /** Txid of the transaction this one replaced. */
+  replaces: string | null;
   inputs: BtcxTxDetailInput[];
   outputs: BtcxTxDetailOutput[];
 }
 
+/**
+ * Which estimator answers a confirmation target: the server's estimator,
+ * the mempool fee histogram projection, or 'auto' (the server's estimator,
+ * the projection where it has no data).
+ */
+export type BtcxFeeEstimateMode = 'auto' | 'server' | 'mempool';
+
+/** Where a fee estimate came from; 'floor' = the mempool leaves room at the coin's floor. */
+export type BtcxFeeSource = 'server' | 'mempool' | 'floor';
+
+/** One confirmation target of `BtcxFeeEstimates.targets`. */
+export interface BtcxFeeTarget {
+  blocks: number;
+  satPerVb: number | null;
+  source: BtcxFeeSource | null;
+  /** Expected wait at the PoCX block spacing. */
+  etaSecs: number;
+}
+
 /** Fee estimates in decimal sat/vB (`btcx_wallet_fee_estimates`). */
 export interface BtcxFeeEstimates {
   /** Coin feerate floor (the custom field's minimum/default). */
   minSatPerVb: number;
-  /** 1-block target; null when the estimator has no data. */
+  /** 1-block target; null when the mode has no estimate. */
   fast: number | null;
   /** 6-block target. */
   normal: number | null;
   /** 144-block target. */
   slow: number | null;
+  fastSource: BtcxFeeSource | null;
+  normalSource: BtcxFeeSource | null;
+  slowSource: BtcxFeeSource | null;
+  /** The 1/3/6/12-block targets. */
+  targets: BtcxFeeTarget[];
+  mode: BtcxFeeEstimateMode;
 }
 
 /**
- * Descriptor branch recorded at create/restore/import time. 'legacy' only
- * ever occurs on descriptor-IMPORTED wallets (pkh / sh(wpkh) scripts) —
- * accepted for fund visibility and spending, gated from mining/assignments
- * like taproot.
+ * Descriptor branch recorded at create/restore/import time. 'legacy' and
+ * 'multisig' only ever occur on descriptor-IMPORTED wallets (pkh /
+ * sh(wpkh) and wsh(sortedmulti) scripts) — accepted for fund visibility and
+ * spending, gated from mining/assignments like taproot.
  */
 export interface BtcxDescriptorPolicy {
-  kind: 'bip84' | 'bip86' | 'legacy';
+  kind: 'bip84' | 'bip86' | 'legacy' | 'multisig';
   coinType: number;
 }
 
+/** k-of-n shape of a multisig wallet. */
+export interface BtcxMultisigShape {
+  threshold: number;
+  cosigners: number;
+}
+
+/** One cosigner of a multisig backup. */
+export interface BtcxMultisigCosigner {
+  fingerprint: string;
+  derivationPath: string;
+  xpub: string;
+}
+
+/** Public multisig wallet backup (`btcx_wallet_multisig_backup`). */
+export interface BtcxMultisigBackup {
+  threshold: number;
+  cosigners: BtcxMultisigCosigner[];
+  /** Public receive descriptor, checksummed. */
+  external: string;
+  /** Public change descriptor, checksummed. */
+  internal: string;
+  /** Coldcard/Sparrow-style multisig setup file. */
+  text: string;
+}
+
+/** Account key of a single-key wallet's descriptor export. */
+export interface BtcxAccountKeyExport {
+  fingerprint: string;
+  /** `m/84'/0'/0'`. */
+  derivationPath: string;
+  /** `[fingerprint/84'/coin'/0']`. */
+  keyOrigin: string;
+  /** Account xpub (tpub off mainnet). */
+  xpub: string;
+  /** SLIP-132 zpub/vpub — BIP-84 wallets only. */
+  slip132: string | null;
+}
+
+/** Descriptor export of the active wallet (`btcx_wallet_export_descriptors`). */
+export interface BtcxDescriptorExport {
+  /** Public receive descriptor, checksummed. */
+  external: string;
+  /** Public change descriptor; null for a single-address wallet. */
+  internal: string | null;
+  /** Private descriptors — only when requested. */
+  externalPrivate: string | null;
+  internalPrivate: string | null;
+  /** Null for multisig and single-address wallets. */
+  account: BtcxAccountKeyExport | null;
+  /** Bitcoin Core `importdescriptors` request JSON. */
+  coreImport: string;
+}
+
 /** Key-material source of a registered wallet. */
-export type BtcxWalletSource = 'seed' | 'descriptor';
+export type BtcxWalletSource = 'seed' | 'descriptor' | 'watchonly';
 
 /** Structured error codes of the descriptor-import validation. */
 export type BtcxImportErrorCode =
   | 'empty'
-  | 'watch_only'
+  | 'bare_xpub'
   | 'bare_key'
   | 'bare_wif'
   | 'wif_not_segwit'
@@ -186,6 +285,8 @@ export type BtcxImportErrorCode =
   | 'unsupported_type'
   | 'not_ranged'
   | 'pair_mismatch'
+  | 'mixed_keys'
+  | 'multisig_shape'
   | 'multipath_nonstandard'
   | 'too_many'
   | 'parse';
@@ -210,6 +311,10 @@ export interface BtcxImportValidation {
    * same address (the verdict line notes it).
    */
   singleAddress: boolean;
+  /** Public descriptors — imports as a watch-only wallet (no keys). */
+  watchOnly: boolean;
+  /** k-of-n of a wsh(sortedmulti) paste. */
+  multisig?: BtcxMultisigShape;
 }
 
 /** What `btcx_wallet_import_descriptor` did. */
@@ -221,6 +326,8 @@ export interface BtcxImportResult {
   fromMultipath: boolean;
   /** Single-address wpkh(WIF) wallet — the success screen notes it. */
   singleAddress: boolean;
+  /** k-of-n of a multisig import. */
+  multisig?: BtcxMultisigShape;
 }
 
 /** One probed derivation branch with history (`btcx_wallet_restore`). */
@@ -251,6 +358,8 @@ export interface BtcxWalletMeta {
   policy: BtcxDescriptorPolicy;
   /** Unix seconds at create/restore/migration time (display only). */
   createdAt?: number | null;
+  /** Unix seconds of the last successful mnemonic backup check. */
+  backupVerifiedAt?: number;
 }
 
 /** One registered wallet as listed by `btcx_wallet_list`. */
@@ -280,6 +389,10 @@ export interface BtcxWalletSummary {
   balanceSat?: number;
   /** Wallet-selector group this wallet belongs to (one group = one seed). */
   group: string;
+  /** k-of-n of a multisig wallet (the "2-of-3" badge). */
+  multisig?: BtcxMultisigShape;
+  /** Unix seconds of the last successful mnemonic backup check. */
+  backupVerifiedAt?: number;
 }
 
 /**
@@ -351,6 +464,87 @@ export interface BtcxWalletConfig {
   wallets: Record<string, Record<string, BtcxWalletMeta>>;
   /** The selected wallet per network name (missing = 'default'). */
   activeWallet: Record<string, string>;
+  /** Trash retention in days; absent = keep trashed wallets forever. */
+  trashRetentionDays?: number;
+  /** Cross-server header consensus mode (absent = 'warn'). */
+  headerConsensus?: BtcxHeaderConsensusMode;
+  /** Pinned certificates of ssl:// servers: URL → SHA-256 fingerprint. */
+  certPins?: Record<string, string>;
+  /**
+   * Peer discovery mode as set; absent = 'views' while only built-in
+   * servers are configured, 'off' once the user added their own.
+   */
+  serverDiscovery?: BtcxServerDiscoveryMode;
+  /** Discovered servers may be home / failover views / broadcast targets (opt-in). */
+  discoveredFailover?: boolean;
+  /** Healthy discovered servers per network name, best first. */
+  discoveredServers?: Record<string, BtcxDiscoveredServer[]>;
+}
+
+/** Outcome of `btcx_wallet_change_passphrase`. */
+export interface BtcxPassphraseChange {
+  /** Every re-wrapped wallet (the active one and its group siblings). */
+  rewrapped: string[];
+  /** Whether the key files are now passphrase-encrypted. */
+  encrypted: boolean;
+}
+
+/** A re-displayed mnemonic (`btcx_wallet_reveal_mnemonic`). */
+export interface BtcxMnemonicReveal {
+  mnemonic: string;
+  /** A BIP39 25th word is set — the mnemonic alone does NOT recover funds. */
+  hasBip39Passphrase: boolean;
+  /** Re-reveals without the passphrase until `expiresAt`. */
+  token: string;
+  /** Unix seconds. */
+  expiresAt: number;
+}
+
+/** Word positions to fill in (`btcx_wallet_backup_challenge`). */
+export interface BtcxBackupChallenge {
+  /** 1-based, ascending. */
+  positions: number[];
+  /** Length of the mnemonic (12 or 24). */
+  wordCount: number;
+  /** Unix seconds. */
+  expiresAt: number;
+}
+
+/** Outcome of `btcx_wallet_verify_backup` — never the seed itself. */
+export interface BtcxBackupVerification {
+  valid: boolean;
+  /** The recorded timestamp on success. */
+  verifiedAt?: number;
+}
+
+/** A k-of-n codex32 split of the active seed (`btcx_wallet_split_seed`). */
+export interface BtcxSeedShares {
+  /** `ms1…` share strings, index order. */
+  shares: string[];
+  /** How many shares recover the seed. */
+  threshold: number;
+  /** The 4-character identifier every share of this split carries. */
+  identifier: string;
+  /** A BIP39 25th word is set — the shares alone do NOT recover funds. */
+  hasBip39Passphrase: boolean;
+}
+
+/** One trashed wallet (`btcx_wallet_trash_list`). */
+export interface BtcxTrashedWallet {
+  /** Trash directory name — the handle restore/purge take. */
+  id: string;
+  /** The name the wallet had when deleted. */
+  name: string;
+  /** Unix seconds of the delete; null when unknown. */
+  deletedAt: number | null;
+  source: BtcxWalletSource;
+  /** Null for wallets trashed before the registry entry was kept. */
+  policy: BtcxDescriptorPolicy | null;
+  group: string | null;
+  singleAddress: boolean;
+  multisig?: BtcxMultisigShape;
+  /** Last known balance (display only). */
+  balanceSnapshot?: { sat: number; height: number; at: number };
 }
 
 /** A send request (`btcx_wallet_send`). Give amountSat XOR sendAll. */
@@ -363,6 +557,141 @@ export interface BtcxSendRequest {
   feeTarget?: number;
   /** Explicit feerate in sat/vB; wins over feeTarget. */
   feeRateSatVb?: number;
+  /** Which estimator answers feeTarget (default 'auto'). */
+  feeMode?: BtcxFeeEstimateMode;
+  /** Coin control: spend exactly these outpoints ("txid:vout"). */
+  inputs?: string[];
+}
+
+/** One payee of a batch send (`btcx_wallet_send_batch`). */
+export interface BtcxBatchRecipient {
+  address: string;
+  amountSat: number;
+  /** Stored as the BIP-329 label of the paying output. */
+  label?: string;
+}
+
+/** A batch send: N recipients in one RBF transaction. */
+export interface BtcxBatchSendRequest {
+  recipients: BtcxBatchRecipient[];
+  feeTarget?: number;
+  feeRateSatVb?: number;
+  feeMode?: BtcxFeeEstimateMode;
+  inputs?: string[];
+  /** Build and sign, but do not broadcast. */
+  dryRun?: boolean;
+}
+
+/** Batch send outcome; `txid` is absent on a dry run. */
+export interface BtcxBatchSendResult {
+  txid?: string;
+  recipients: number;
+  amountSat: number;
+  feeSat: number;
+  changeSat: number;
+  vsize: number;
+  feeRateSatVb: number;
+}
+
+/** What a transaction preview spends for. */
+export type BtcxPreviewKind = 'send' | 'sweep' | 'assignment' | 'revocation';
+
+/**
+ * A signed, not yet broadcast transaction (`btcx_wallet_preview_*`).
+ * `btcx_wallet_commit_preview` broadcasts exactly it — the txid is final.
+ */
+export interface BtcxTxPreview {
+  /** Pass to commitPreview / discardPreview; short-lived. */
+  handle: string;
+  kind: BtcxPreviewKind;
+  txid: string;
+  feeSat: number;
+  vsize: number;
+  feeRateSatVb: number;
+  /** Sum paid to outputs other than our change. */
+  amountSat: number;
+  changeSat: number;
+  inputs: { outpoint: string; valueSat: number }[];
+  outputs: {
+    n: number;
+    address: string | null;
+    valueSat: number;
+    change: boolean;
+    opReturn: boolean;
+  }[];
+  /** Seconds left to commit. */
+  expiresInSecs: number;
+}
+
+/** Why the local policy checks reject a transaction. */
+export type BtcxRejectReason =
+  | 'empty'
+  | 'dust'
+  | 'min-relay-fee'
+  | 'op-return-size'
+  | 'multiple-op-return'
+  | 'tx-weight'
+  | 'missing-inputs'
+  | 'outputs-exceed-inputs'
+  | 'missing-signature'
+  | 'invalid-signature'
+  | 'double-spend';
+
+/** One failed local check; `input` / `output` index the offending part. */
+export interface BtcxPolicyRejection {
+  reason: BtcxRejectReason;
+  message: string;
+  input?: number;
+  output?: number;
+}
+
+/** Verdict of the local policy checks (`btcx_test_mempool_accept`). */
+export interface BtcxPolicyCheck {
+  txid: string;
+  allowed: boolean;
+  vsize: number;
+  weight: number;
+  /** Null when a prevout is missing. */
+  feeSat: number | null;
+  feeRateSatVb: number | null;
+  rejections: BtcxPolicyRejection[];
+  /** Inputs whose script is not verified locally (the server judges them). */
+  uncheckedInputs: number[];
+  /** Unconfirmed wallet transactions this one validly replaces. */
+  replaces: string[];
+}
+
+/** Outcome of a child-pays-for-parent bump (`btcx_wallet_cpfp`). */
+export interface BtcxCpfpResult {
+  /** The child's txid. */
+  txid: string;
+  parentTxid: string;
+  parentFeeSat: number;
+  parentVsize: number;
+  childFeeSat: number;
+  childVsize: number;
+  /** Effective (parent + child) feerate in sat/vB. */
+  packageFeeRateSatVb: number;
+}
+
+/** Outcome of cancelling an unconfirmed send (`btcx_wallet_cancel_tx`). */
+export interface BtcxCancelResult {
+  /** The replacement's txid. */
+  txid: string;
+  replacedTxid: string;
+  /** Value returned to the wallet. */
+  amountSat: number;
+  feeSat: number;
+  vsize: number;
+  feeRateSatVb: number;
+}
+
+/** A parsed payout CSV (`btcx_wallet_parse_payout_csv`). */
+export interface BtcxPayoutCsv {
+  recipients: BtcxBatchRecipient[];
+  /** Rejected rows (1-based line numbers); send nothing while non-empty. */
+  errors: { line: number; message: string }[];
+  totalSat: number;
 }
 
 /** Aggregate Electrum connectivity as the toolbar indicator shows it. */
@@ -394,6 +723,78 @@ export interface BtcxServerHealth {
   last_error_secs_ago?: number;
   requests: number;
   failures: number;
+  /** This server's line of the last header consensus check, if any. */
+  consensus?: BtcxServerConsensus;
+  /** Found by peer discovery rather than configured. */
+  discovered: boolean;
+}
+
+/** Peer discovery mode: 'views' lists discovered servers (wallet traffic
+ *  only with `discoveredFailover`), 'auto' also appends them to the
+ *  configured list. */
+export type BtcxServerDiscoveryMode = 'off' | 'views' | 'auto';
+
+/** A discovered server that passed the probe, ranked by latency. */
+export interface BtcxDiscoveredServer {
+  url: string;
+  height: number;
+  latencyMs: number;
+  /** Unix seconds. */
+  checkedAt: number;
+}
+
+/** One probed candidate of a discovery pass. */
+export interface BtcxDiscoveryCandidate {
+  url: string;
+  height?: number;
+  latencyMs?: number;
+  healthy: boolean;
+  /** Why the candidate was not kept. */
+  detail?: string;
+}
+
+/** Outcome of `btcx_electrum_discover`. */
+export interface BtcxDiscoveryReport {
+  network: BtcxNetwork;
+  /** Unix seconds. */
+  checkedAt: number;
+  /** Servers asked for peers that answered. */
+  sources: number;
+  candidates: BtcxDiscoveryCandidate[];
+  /** The kept discovered servers, best first. */
+  kept: BtcxDiscoveredServer[];
+  /** Servers appended to the configured list ('auto' mode). */
+  added: string[];
+}
+
+/** Header consensus mode: 'enforce' refuses broadcasts/balances while the
+ *  home server is on a minority chain. */
+export type BtcxHeaderConsensusMode = 'off' | 'warn' | 'enforce';
+
+/** One server's standing in a header consensus check. */
+export interface BtcxServerConsensus {
+  url: string;
+  verdict: 'agrees' | 'lagging' | 'ahead' | 'minority' | 'disputed' | 'invalid' | 'unreachable';
+  height?: number;
+  tipHash?: string;
+  /** Why the server is not 'agrees'. */
+  detail?: string;
+}
+
+/** Outcome of `btcx_electrum_check_consensus`. */
+export interface BtcxConsensusReport {
+  network: BtcxNetwork;
+  /** Unix seconds. */
+  checkedAt: number;
+  /** Median tip height of the consensus chain (absent without a majority). */
+  height?: number;
+  /** Servers on the consensus chain, out of `verified`. */
+  agreeing: number;
+  verified: number;
+  homeUrl?: string;
+  /** The home server is on a minority chain or served a broken one. */
+  homeOnMinority: boolean;
+  servers: BtcxServerConsensus[];
 }
 
 /** Result of a live server probe (`btcx_electrum_probe`). */
@@ -402,6 +803,12 @@ export interface BtcxElectrumProbeResult {
   height: number;
   /** Round-trip time of the tip fetch, milliseconds. */
   latencyMs: number;
+  /** SHA-256 fingerprint of the certificate an ssl:// server presented. */
+  certFingerprint: string | null;
+  /** That certificate chains to a trusted CA (such a certificate is never pinned). */
+  certCaValid: boolean;
+  /** That certificate is the pinned one (false: not pinned yet). */
+  certPinned: boolean;
 }
 
 /** Chain tip snapshot from Electrum (`btcx_chain_info`). */
@@ -457,6 +864,68 @@ export interface BtcxPsbtProcessResult {
   complete: boolean;
 }
 
+/** A PSBT as animated QR frames (`btcx_ur_encode_psbt`). */
+export interface BtcxUrFrames {
+  /** `UR:CRYPTO-PSBT/…` frames to show in a loop. */
+  frames: string[];
+  /** Fragments the PSBT is split into (1: a single static QR). */
+  fragmentCount: number;
+}
+
+/** Progress of an animated-QR PSBT scan (`btcx_ur_scan_frame`). */
+export interface BtcxUrScanProgress {
+  complete: boolean;
+  /** The PSBT (base64), once complete. */
+  psbt?: string;
+  fragmentsKnown: number;
+  fragmentCount: number;
+  /** 0..1. */
+  progress: number;
+  framesReceived: number;
+  /** The frame was seen before. */
+  duplicate: boolean;
+  /** The frame belongs to another PSBT — the scan started over. */
+  restarted: boolean;
+}
+
+/** Client-side raw transaction decode result (`btcx_rawtx_decode`). */
+export interface BtcxRawTxDecode {
+  txid: string;
+  wtxid: string;
+  version: number;
+  locktime: number;
+  size: number;
+  vsize: number;
+  weight: number;
+  /** Some input signals BIP-125 replaceability. */
+  rbf: boolean;
+  vin: {
+    txid: string;
+    vout: number;
+    sequence: number;
+    scriptSigHex: string;
+    witness: string[];
+  }[];
+  vout: BtcxPsbtDecode['vout'];
+}
+
+/** One input of `createRawTx`; the sequence defaults per `replaceable`. */
+export interface BtcxRawTxInput {
+  txid: string;
+  vout: number;
+  sequence?: number;
+}
+
+/** One output of `createRawTx`: an address and amount, or OP_RETURN data. */
+export type BtcxRawTxOutput = { address: string; amountSat: number } | { dataHex: string };
+
+/** A raw transaction from a PSBT (`btcx_psbt_to_rawtx`). */
+export interface BtcxRawTx {
+  /** Final inputs carry their signatures; the rest are bare. */
+  hex: string;
+  complete: boolean;
+}
+
 /** One spendable wallet UTXO (`btcx_wallet_utxos`). */
 export interface BtcxUtxo {
   txid: string;
@@ -465,6 +934,79 @@ export interface BtcxUtxo {
   address?: string;
   confirmations: number;
   isChange: boolean;
+  /** The coin's BIP-329 label, else its address' label. */
+  label?: string;
+  /** Frozen by the user — never coin-selected until thawed. */
+  frozen: boolean;
+}
+
+/** BIP-329 record type. */
+export type BtcxLabelType = 'tx' | 'addr' | 'pubkey' | 'input' | 'output' | 'xpub';
+
+/** One BIP-329 label record (spec field names). */
+export interface BtcxLabelRecord {
+  type: BtcxLabelType;
+  /** txid, address, `txid:vout` outpoint, or xpub/descriptor. */
+  ref: string;
+  label?: string;
+  origin?: string;
+  spendable?: boolean;
+}
+
+/** Outcome of a BIP-329 import (`btcx_wallet_import_labels`). */
+export interface BtcxLabelImportSummary {
+  imported: number;
+  skipped: number;
+}
+
+/** A BIP-21 payment URI's fields (`btcx_wallet_parse_payment_uri`). */
+export interface BtcxPaymentUri {
+  /** Address in the network's canonical encoding. */
+  address: string;
+  amountSat?: number | null;
+  label?: string | null;
+  message?: string | null;
+}
+
+/** Message signature format: legacy `signmessage` (BIP-137) or BIP-322. */
+export type BtcxMessageFormat = 'legacy' | 'bip322_simple' | 'bip322_full';
+
+/** Outcome of `btcx_verify_message`. */
+export interface BtcxMessageVerification {
+  valid: boolean;
+  /** The format the signature was recognised as. */
+  format: BtcxMessageFormat;
+}
+
+/** Outcome of `btcx_wallet_import_backup`. */
+export interface BtcxBackupRestoreResult {
+  status: BtcxWalletStatus;
+  /** Every restored wallet name, bundle order. */
+  restored: string[];
+  /** The wallet now active. */
+  active: string;
+}
+
+/** Derived state of a payment request. */
+export type BtcxPaymentStatus = 'pending' | 'partially_paid' | 'paid' | 'expired';
+
+/** A payment request (invoice) with its status from the synced graph. */
+export interface BtcxPaymentRequest {
+  address: string;
+  /** Expected amount; null = any amount settles it. */
+  amountSat: number | null;
+  label: string | null;
+  message: string | null;
+  /** Unix seconds. */
+  createdAt: number;
+  /** Unix seconds; null = never expires. */
+  expiresAt: number | null;
+  status: BtcxPaymentStatus;
+  /** Everything the address received, mempool included. */
+  receivedSat: number;
+  confirmedSat: number;
+  /** The request as a `btcx:` URI (QR / share). */
+  uri: string;
 }
 
 /** The BIP32 coin type new BTCX wallets derive at (0x504F4358, "POCX"). */
@@ -492,6 +1034,7 @@ export function mapWalletTx(dto: BtcxWalletTxDto): BtcxWalletTx {
     confirmations: dto.confirmations,
     timestamp: dto.timestamp ?? null,
     address: dto.address ?? null,
+    label: dto.label ?? null,
   };
 }
 
@@ -576,6 +1119,9 @@ export class BtcxWalletService {
    */
   readonly singleAddress = computed(() => this._status()?.singleAddress ?? false);
 
+  /** The selected wallet is watch-only — send/sign paths are refused. */
+  readonly watchOnly = computed(() => this._status()?.watchOnly ?? false);
+
   /**
    * Descriptor policy of the active network's selected wallet (null =
    * fresh default). Falls back to the legacy per-network map while the
@@ -765,6 +1311,50 @@ export class BtcxWalletService {
     return result;
   }
 
+  /**
+   * Split the active wallet's seed into `shareCount` codex32 shares, any
+   * `threshold` of which restore it. An encrypted seed needs `passphrase`,
+   * an unencrypted one `confirm`. Throws on failure.
+   */
+  async splitSeed(
+    threshold: number,
+    shareCount: number,
+    passphrase?: string,
+    confirm?: boolean
+  ): Promise<BtcxSeedShares> {
+    return invoke<BtcxSeedShares>('btcx_wallet_split_seed', {
+      threshold,
+      shareCount,
+      passphrase: passphrase || null,
+      confirm: confirm ?? null,
+    });
+  }
+
+  /**
+   * Restore a wallet from codex32 shares (at least the threshold). Same
+   * branch probe and arguments as `restore`. Throws on failure.
+   */
+  async restoreFromShares(
+    shares: string[],
+    passphrase?: string,
+    name?: string,
+    kind?: BtcxDescriptorPolicy['kind'],
+    bip39Passphrase?: string
+  ): Promise<BtcxRestoreResult> {
+    const result = await invoke<BtcxRestoreResult>('btcx_wallet_restore_from_shares', {
+      shares,
+      passphrase: passphrase || null,
+      // NFKD, as in `restore`.
+      bip39Passphrase: bip39Passphrase ? bip39Passphrase.normalize('NFKD') : null,
+      name: name ?? null,
+      kind: kind ?? null,
+    });
+    this._status.set(result.status);
+    await this.refreshConfig();
+    await this.refreshAll();
+    return result;
+  }
+
   /**
    * Import a wallet from one or two PRIVATE descriptors (single input,
    * whitespace/newline separated). A single standard descriptor infers its
@@ -800,6 +1390,59 @@ export class BtcxWalletService {
     return invoke<BtcxImportValidation>('btcx_wallet_validate_import', { input });
   }
 
+  /** Our multisig cosigner key ([fp/48'/coin'/0'/2']xpub) from the active seed wallet. */
+  async multisigXpub(): Promise<string> {
+    return invoke<string>('btcx_wallet_multisig_xpub');
+  }
+
+  /**
+   * Create a threshold-of-n wsh(sortedmulti) wallet from our key plus the
+   * cosigners' account xpubs; the new wallet becomes active. A
+   * passphrase-protected seed wallet needs `passphrase` for it too. Throws
+   * on failure.
+   */
+  async createMultisig(
+    threshold: number,
+    cosigners: string[],
+    passphrase?: string,
+    name?: string
+  ): Promise<BtcxImportResult> {
+    const result = await invoke<BtcxImportResult>('btcx_wallet_create_multisig', {
+      threshold,
+      cosigners,
+      passphrase: passphrase || null,
+      name: name ?? null,
+    });
+    this._status.set(result.status);
+    await this.refreshConfig();
+    await this.refreshAll();
+    return result;
+  }
+
+  /** Public backup of the active multisig wallet (descriptors + every cosigner). */
+  async multisigBackup(): Promise<BtcxMultisigBackup> {
+    return invoke<BtcxMultisigBackup>('btcx_wallet_multisig_backup');
+  }
+
+  /**
+   * Export the active wallet's descriptors, account xpub and key origin,
+   * plus a Core `importdescriptors` request. The public export needs the
+   * wallet open (a watch-only one works closed). `includePrivate` adds the
+   * private descriptors — an encrypted wallet needs its `passphrase`, an
+   * unencrypted one an explicit `confirm`.
+   */
+  async exportDescriptors(
+    includePrivate?: boolean,
+    passphrase?: string,
+    confirm?: boolean
+  ): Promise<BtcxDescriptorExport> {
+    return invoke<BtcxDescriptorExport>('btcx_wallet_export_descriptors', {
+      includePrivate: includePrivate ?? null,
+      passphrase: passphrase ?? null,
+      confirm: confirm ?? null,
+    });
+  }
+
   /**
    * Re-run the restore probe over the already-imported seed — the "scan
    * again" affordance behind a fresh-restore verdict (the server could
@@ -838,6 +1481,58 @@ export class BtcxWalletService {
     }
   }
 
+  /**
+   * Change, add, or remove the at-rest passphrase of the active wallet and
+   * its group siblings. An empty `newPassphrase` removes it and requires
+   * `confirmRemove`. Throws on failure (nothing is changed then).
+   */
+  async changePassphrase(opts: {
+    currentPassphrase?: string;
+    newPassphrase?: string;
+    confirmRemove?: boolean;
+  }): Promise<BtcxPassphraseChange> {
+    const change = await invoke<BtcxPassphraseChange>('btcx_wallet_change_passphrase', {
+      currentPassphrase: opts.currentPassphrase || null,
+      newPassphrase: opts.newPassphrase || null,
+      confirmRemove: opts.confirmRemove ?? false,
+    });
+    await this.refreshStatus();
+    return change;
+  }
+
+  /**
+   * Re-display the active seed wallet's mnemonic. An encrypted seed needs
+   * `passphrase`, an unencrypted one `confirm`; a live `token` from a
+   * previous reveal stands in for either. Throws on failure.
+   */
+  async revealMnemonic(opts: {
+    passphrase?: string;
+    token?: string;
+    confirm?: boolean;
+  }): Promise<BtcxMnemonicReveal> {
+    return invoke<BtcxMnemonicReveal>('btcx_wallet_reveal_mnemonic', {
+      passphrase: opts.passphrase || null,
+      token: opts.token || null,
+      confirm: opts.confirm ?? null,
+    });
+  }
+
+  /** Start a paper-backup check (random word positions). Throws on failure. */
+  async backupChallenge(count?: number): Promise<BtcxBackupChallenge> {
+    return invoke<BtcxBackupChallenge>('btcx_wallet_backup_challenge', {
+      count: count ?? null,
+    });
+  }
+
+  /**
+   * Answer the pending backup check — `words` in the challenge's position
+   * order. The challenge is single-use; repeated failures lock new
+   * challenges out for a while. Throws on failure.
+   */
+  async verifyBackup(words: string[]): Promise<BtcxBackupVerification> {
+    return invoke<BtcxBackupVerification>('btcx_wallet_verify_backup', { words });
+  }
+
   // ============================================================================
   // Named-Wallet Registry
   // ============================================================================
@@ -928,6 +1623,46 @@ export class BtcxWalletService {
     await this.refreshStatus();
   }
 
+  /** The active network's trashed wallets, newest first. */
+  async trashList(): Promise<BtcxTrashedWallet[]> {
+    return invoke<BtcxTrashedWallet[]>('btcx_wallet_trash_list');
+  }
+
+  /**
+   * Restore one trashed wallet under `name` (default: its old name, with a
+   * `-2`, `-3`, … suffix when taken). Resolves to the name it got.
+   */
+  async trashRestore(id: string, name?: string): Promise<string> {
+    const restored = await invoke<string>('btcx_wallet_trash_restore', { id, name: name ?? null });
+    await this.refreshConfig();
+    return restored;
+  }
+
+  /** Restore the whole group `id` was deleted with; resolves to the restored names. */
+  async trashRestoreGroup(id: string): Promise<string[]> {
+    const restored = await invoke<string[]>('btcx_wallet_trash_restore_group', { id });
+    await this.refreshConfig();
+    return restored;
+  }
+
+  /**
+   * PERMANENTLY delete a trashed wallet, key material included.
+   * `confirmName` must repeat the wallet's name. Throws on failure.
+   */
+  async trashPurge(id: string, confirmName: string): Promise<void> {
+    await invoke('btcx_wallet_trash_purge', { id, confirmName });
+  }
+
+  /**
+   * Set (or clear, `null`) the trash retention in days; expired entries are
+   * purged right away. Resolves to the purged ids.
+   */
+  async setTrashRetention(days: number | null): Promise<string[]> {
+    const purged = await invoke<string[]>('btcx_wallet_set_trash_retention', { days });
+    await this.refreshConfig();
+    return purged;
+  }
+
   /**
    * Select (and open, when possible) another registered wallet of the
    * active network. Closes the previous wallet's runtime. Throws on failure.
@@ -1046,7 +1781,9 @@ export class BtcxWalletService {
    */
   /** Every revealed external address with used/funded flags — the remote
    *  receive page's address enumeration. Pure local read. */
-  async addresses(): Promise<{ address: string; index: number; used: boolean; funded: boolean }[]> {
+  async addresses(): Promise<
+    { address: string; index: number; used: boolean; funded: boolean; label?: string }[]
+  > {
     return invoke('btcx_wallet_addresses');
   }
 
@@ -1105,6 +1842,56 @@ export class BtcxWalletService {
     return txid;
   }
 
+  /**
+   * Build and sign a send (or sweep) without broadcasting it: the exact fee,
+   * inputs, change and vsize for the confirmation screen. Throws on failure.
+   */
+  async previewSend(request: BtcxSendRequest): Promise<BtcxTxPreview> {
+    return invoke<BtcxTxPreview>('btcx_wallet_preview_send', { request });
+  }
+
+  /**
+   * Broadcast exactly the previewed transaction and return its txid. Throws
+   * when the preview expired or its coins changed — preview again.
+   */
+  async commitPreview(handle: string): Promise<string> {
+    const txid = await invoke<string>('btcx_wallet_commit_preview', { handle });
+    await this.refreshAll();
+    return txid;
+  }
+
+  /** Drop a preview the user cancelled. */
+  async discardPreview(handle: string): Promise<void> {
+    await invoke('btcx_wallet_discard_preview', { handle });
+  }
+
+  /**
+   * Pay N recipients in one RBF transaction. With `dryRun` nothing is
+   * broadcast — the result carries the exact fee, change and vsize.
+   * Throws on failure.
+   */
+  async sendBatch(request: BtcxBatchSendRequest): Promise<BtcxBatchSendResult> {
+    const result = await invoke<BtcxBatchSendResult>('btcx_wallet_send_batch', { request });
+    if (!request.dryRun) await this.refreshAll();
+    return result;
+  }
+
+  /** Validate a payout CSV (address,amount[,label]) against the active network. */
+  async parsePayoutCsv(csv: string): Promise<BtcxPayoutCsv> {
+    return invoke<BtcxPayoutCsv>('btcx_wallet_parse_payout_csv', { csv });
+  }
+
+  /**
+   * Child-pays-for-parent: speed up an unconfirmed incoming tx by spending
+   * our output of it so the package reaches `packageFeeRateSatVb`.
+   * Throws on failure.
+   */
+  async cpfp(txid: string, packageFeeRateSatVb: number): Promise<BtcxCpfpResult> {
+    const result = await invoke<BtcxCpfpResult>('btcx_wallet_cpfp', { txid, packageFeeRateSatVb });
+    await this.refreshAll();
+    return result;
+  }
+
   /** RBF-bump a wallet-owned transaction; returns the replacement txid. Throws on failure. */
   async bumpFee(txid: string, feeRateSatVb: number): Promise<string> {
     const newTxid = await invoke<string>('btcx_wallet_bumpfee', { txid, feeRateSatVb });
@@ -1112,9 +1899,23 @@ export class BtcxWalletService {
     return newTxid;
   }
 
-  /** Market fee estimates from the wallet's home Electrum server. Throws on failure. */
-  async fetchFeeEstimates(): Promise<BtcxFeeEstimates> {
-    return invoke<BtcxFeeEstimates>('btcx_wallet_fee_estimates');
+  /**
+   * Cancel an unconfirmed wallet send: replace it with a transaction paying
+   * the same inputs back to the wallet at the minimum replacement fee.
+   * Throws on failure.
+   */
+  async cancelTx(txid: string): Promise<BtcxCancelResult> {
+    const result = await invoke<BtcxCancelResult>('btcx_wallet_cancel_tx', { txid });
+    await this.refreshAll();
+    return result;
+  }
+
+  /**
+   * Fee estimates from the wallet's home Electrum server (`mode` default
+   * 'auto'). Throws on failure.
+   */
+  async fetchFeeEstimates(mode?: BtcxFeeEstimateMode): Promise<BtcxFeeEstimates> {
+    return invoke<BtcxFeeEstimates>('btcx_wallet_fee_estimates', { mode: mode ?? null });
   }
 
   /**
@@ -1128,6 +1929,16 @@ export class BtcxWalletService {
     return invoke<string>('btcx_broadcast_tx', { txHex, network: network ?? null });
   }
 
+  /**
+   * Run the local standardness and policy checks on a raw transaction (hex)
+   * without broadcasting it — a client-side `testmempoolaccept`. Chain-only
+   * like broadcastTx; with the wallet open on `network` its unconfirmed
+   * transactions are checked for double-spends too.
+   */
+  async testMempoolAccept(txHex: string, network?: BtcxNetwork): Promise<BtcxPolicyCheck> {
+    return invoke<BtcxPolicyCheck>('btcx_test_mempool_accept', { txHex, network: network ?? null });
+  }
+
   /**
    * Electrum servers configured for `network` per the last refreshed config
    * (empty until refreshConfig()/initialize() has run).
@@ -1207,6 +2018,35 @@ export class BtcxWalletService {
     return invoke<string>('btcx_psbt_combine', { psbts });
   }
 
+  /**
+   * Encode a PSBT as animated `ur:crypto-psbt` QR frames (BC-UR fountain
+   * code) for an air-gapped signer. Frames default to twice the fragments.
+   */
+  async urEncodePsbt(
+    psbtBase64: string,
+    maxFragmentLen?: number,
+    frameCount?: number
+  ): Promise<BtcxUrFrames> {
+    return invoke<BtcxUrFrames>('btcx_ur_encode_psbt', {
+      psbtBase64,
+      maxFragmentLen: maxFragmentLen ?? null,
+      frameCount: frameCount ?? null,
+    });
+  }
+
+  /**
+   * Feed one scanned UR frame to the scan in progress — any order, repeats
+   * fine; the PSBT comes back once complete.
+   */
+  async urScanFrame(frame: string): Promise<BtcxUrScanProgress> {
+    return invoke<BtcxUrScanProgress>('btcx_ur_scan_frame', { frame });
+  }
+
+  /** Abandon the animated-QR scan in progress. */
+  async urScanReset(): Promise<void> {
+    await invoke('btcx_ur_scan_reset');
+  }
+
   /** Compose a funded UNSIGNED PSBT — client-side `walletcreatefundedpsbt`. */
   async createFundedPsbt(
     outputs: { address: string; amountSat: number }[],
@@ -1229,11 +2069,228 @@ export class BtcxWalletService {
     });
   }
 
+  /**
+   * Add the spent outputs of a foreign PSBT's inputs from Electrum —
+   * client-side `utxoupdatepsbt`. Chain-only; `network` defaults to the
+   * active one.
+   */
+  async psbtUtxoUpdate(psbtBase64: string, network?: BtcxNetwork): Promise<string> {
+    return invoke<string>('btcx_psbt_utxo_update', { psbtBase64, network: network ?? null });
+  }
+
+  /** The raw transaction of a PSBT, complete or not. */
+  async psbtToRawTx(psbtBase64: string): Promise<BtcxRawTx> {
+    return invoke<BtcxRawTx>('btcx_psbt_to_rawtx', { psbtBase64 });
+  }
+
+  /** Decode a raw transaction — client-side `decoderawtransaction`. */
+  async rawTxDecode(txHex: string): Promise<BtcxRawTxDecode> {
+    return invoke<BtcxRawTxDecode>('btcx_rawtx_decode', { txHex });
+  }
+
+  /**
+   * Create an unsigned raw transaction from explicit inputs and outputs —
+   * client-side `createrawtransaction` (no coin selection, no change).
+   */
+  async createRawTx(
+    inputs: BtcxRawTxInput[],
+    outputs: BtcxRawTxOutput[],
+    locktime?: number,
+    replaceable?: boolean
+  ): Promise<string> {
+    return invoke<string>('btcx_rawtx_create', {
+      inputs,
+      outputs,
+      locktime: locktime ?? null,
+      replaceable: replaceable ?? null,
+    });
+  }
+
+  /** Convert a raw transaction into a PSBT — client-side `converttopsbt`. */
+  async rawTxToPsbt(txHex: string, permitSigData?: boolean): Promise<string> {
+    return invoke<string>('btcx_rawtx_to_psbt', { txHex, permitSigData: permitSigData ?? null });
+  }
+
   /** The open wallet's unspent outputs (cache read). Throws on failure. */
   async utxos(): Promise<BtcxUtxo[]> {
     return invoke<BtcxUtxo[]>('btcx_wallet_utxos');
   }
 
+  /** The active wallet's frozen outpoints ("txid:vout"). */
+  async frozenUtxos(): Promise<string[]> {
+    return invoke<string[]>('btcx_wallet_frozen_utxos');
+  }
+
+  /**
+   * Freeze or thaw outpoints (persisted; frozen coins are skipped by send,
+   * PSBT compose and assignments). Resolves to the resulting frozen set.
+   */
+  async setFrozen(outpoints: string[], frozen: boolean): Promise<string[]> {
+    return invoke<string[]>('btcx_wallet_set_frozen', { outpoints, frozen });
+  }
+
+  // ============================================================================
+  // Labels (BIP-329)
+  // ============================================================================
+
+  /** Every label record of the active wallet. Throws on failure. */
+  async labels(): Promise<BtcxLabelRecord[]> {
+    return invoke<BtcxLabelRecord[]>('btcx_wallet_labels');
+  }
+
+  /** The label of one txid / address / outpoint / xpub, null when none. */
+  async getLabel(kind: BtcxLabelType, reference: string): Promise<string | null> {
+    return invoke<string | null>('btcx_wallet_get_label', { kind, reference });
+  }
+
+  /** Set (or replace) a label; the transaction list shows it on next refresh. */
+  async setLabel(kind: BtcxLabelType, reference: string, label: string): Promise<BtcxLabelRecord> {
+    const record = await invoke<BtcxLabelRecord>('btcx_wallet_set_label', {
+      kind,
+      reference,
+      label,
+    });
+    if (kind === 'tx') {
+      await this.refreshTransactions(this._txWindow.limit, this._txWindow.offset);
+    }
+    return record;
+  }
+
+  /** Remove a label. Resolves to whether there was one. */
+  async deleteLabel(kind: BtcxLabelType, reference: string): Promise<boolean> {
+    const removed = await invoke<boolean>('btcx_wallet_delete_label', { kind, reference });
+    if (removed && kind === 'tx') {
+      await this.refreshTransactions(this._txWindow.limit, this._txWindow.offset);
+    }
+    return removed;
+  }
+
+  /** The active wallet's labels as a BIP-329 JSONL document. */
+  async exportLabels(): Promise<string> {
+    return invoke<string>('btcx_wallet_export_labels');
+  }
+
+  /** Merge a BIP-329 JSONL document into the active wallet's labels. */
+  async importLabels(jsonl: string): Promise<BtcxLabelImportSummary> {
+    const summary = await invoke<BtcxLabelImportSummary>('btcx_wallet_import_labels', { jsonl });
+    await this.refreshTransactions(this._txWindow.limit, this._txWindow.offset);
+    return summary;
+  }
+
+  // ============================================================================
+  // Payment URIs & Requests (BIP-21)
+  // ============================================================================
+
+  /** Parse a BIP-21 URI (or bare address) against the active network. Throws when invalid. */
+  async parsePaymentUri(uri: string): Promise<BtcxPaymentUri> {
+    return invoke<BtcxPaymentUri>('btcx_wallet_parse_payment_uri', { uri });
+  }
+
+  /** Build the canonical `btcx:` URI for an address of the active network. */
+  async buildPaymentUri(request: BtcxPaymentUri): Promise<string> {
+    return invoke<string>('btcx_wallet_build_payment_uri', { request });
+  }
+
+  /**
+   * Create a payment request on the current receive address (a fresh one
+   * when that address already backs a request). Throws on failure.
+   */
+  async createPaymentRequest(opts: {
+    amountSat?: number | null;
+    label?: string | null;
+    message?: string | null;
+    expiresInSecs?: number | null;
+  }): Promise<BtcxPaymentRequest> {
+    return invoke<BtcxPaymentRequest>('btcx_wallet_create_payment_request', {
+      amountSat: opts.amountSat ?? null,
+      label: opts.label ?? null,
+      message: opts.message ?? null,
+      expiresInSecs: opts.expiresInSecs ?? null,
+    });
+  }
+
+  /** Every payment request of the active wallet, newest first. */
+  async paymentRequests(): Promise<BtcxPaymentRequest[]> {
+    return invoke<BtcxPaymentRequest[]>('btcx_wallet_payment_requests');
+  }
+
+  /** Delete a payment request by its address. Resolves to whether there was one. */
+  async deletePaymentRequest(address: string): Promise<boolean> {
+    return invoke<boolean>('btcx_wallet_delete_payment_request', { address });
+  }
+
+  // ============================================================================
+  // Message Signing
+  // ============================================================================
+
+  /** Sign a message with the open wallet's key for `address`; resolves to the base64 signature. */
+  async signMessage(address: string, message: string, format: BtcxMessageFormat): Promise<string> {
+    return invoke<string>('btcx_wallet_sign_message', { address, message, format });
+  }
+
+  /**
+   * Verify a legacy or BIP-322 signature. Chain-only — works with no wallet
+   * open. Throws when the address or signature cannot be decoded.
+   */
+  async verifyMessage(
+    address: string,
+    message: string,
+    signature: string,
+    network?: BtcxNetwork
+  ): Promise<BtcxMessageVerification> {
+    return invoke<BtcxMessageVerification>('btcx_verify_message', {
+      address,
+      message,
+      signature,
+      network: network ?? null,
+    });
+  }
+
+  // ============================================================================
+  // Backup Bundles
+  // ============================================================================
+
+  /**
+   * Export one wallet group — or, without `group`, every wallet of the
+   * active network — as a passphrase-sealed backup file's contents.
+   * `walletPassphrase` unlocks encrypted members other than the open one.
+   */
+  async exportBackup(opts: {
+    group?: string;
+    backupPassphrase: string;
+    walletPassphrase?: string;
+  }): Promise<string> {
+    return invoke<string>('btcx_wallet_export_backup', {
+      group: opts.group ?? null,
+      backupPassphrase: opts.backupPassphrase,
+      walletPassphrase: opts.walletPassphrase || null,
+    });
+  }
+
+  /**
+   * Restore a backup file: its wallets, groups and key files are recreated
+   * and its active wallet opened (no restore probe). Throws — before
+   * anything is written — when a wallet or group name is already taken.
+   */
+  async importBackup(opts: {
+    bundle: string;
+    backupPassphrase: string;
+    walletPassphrase?: string;
+  }): Promise<BtcxBackupRestoreResult> {
+    const result = await invoke<BtcxBackupRestoreResult>('btcx_wallet_import_backup', {
+      bundle: opts.bundle,
+      backupPassphrase: opts.backupPassphrase,
+      walletPassphrase: opts.walletPassphrase || null,
+    });
+    this._status.set(result.status);
+    this._balance.set(null);
+    this.resetTransactionWindow();
+    this._lastSync.set(null);
+    await this.refreshConfig();
+    await this.refreshAll();
+    return result;
+  }
+
   // ============================================================================
   // Forging Assignments (remote node mode)
   // ============================================================================
@@ -1263,6 +2320,30 @@ export class BtcxWalletService {
     return result;
   }
 
+  /** Preview a forging assignment (commit with commitPreview). Throws on failure. */
+  async previewCreateAssignment(
+    plotAddress: string,
+    forgingAddress: string,
+    feeRateSatVb?: number
+  ): Promise<BtcxTxPreview> {
+    return invoke<BtcxTxPreview>('btcx_wallet_preview_create_assignment', {
+      plotAddress,
+      forgingAddress,
+      feeRateSatVb: feeRateSatVb ?? null,
+    });
+  }
+
+  /** Preview a revocation (commit with commitPreview). Throws on failure. */
+  async previewRevokeAssignment(
+    plotAddress: string,
+    feeRateSatVb?: number
+  ): Promise<BtcxTxPreview> {
+    return invoke<BtcxTxPreview>('btcx_wallet_preview_revoke_assignment', {
+      plotAddress,
+      feeRateSatVb: feeRateSatVb ?? null,
+    });
+  }
+
   /** Revoke a forging assignment client-side. Throws on failure. */
   async revokeAssignment(
     plotAddress: string,
@@ -1290,13 +2371,52 @@ export class BtcxWalletService {
   // ============================================================================
 
   /**
-   * Per-server health snapshots of the active network's configured servers
-   * (passive cells — no network I/O). Throws on failure.
+   * Per-server health snapshots of the active network's configured servers,
+   * then its discovered ones (passive cells — no network I/O). Throws on
+   * failure.
    */
   async electrumHealth(): Promise<BtcxServerHealth[]> {
     return invoke<BtcxServerHealth[]>('btcx_electrum_health');
   }
 
+  /**
+   * Check now that the active network's servers agree on the header chain
+   * (fresh connections); the report also feeds `electrumHealth`. Throws
+   * when no server is configured.
+   */
+  async checkHeaderConsensus(): Promise<BtcxConsensusReport> {
+    return invoke<BtcxConsensusReport>('btcx_electrum_check_consensus');
+  }
+
+  /** Set the header consensus mode. Throws on failure. */
+  async setHeaderConsensus(mode: BtcxHeaderConsensusMode): Promise<void> {
+    await invoke('btcx_wallet_set_header_consensus', { mode });
+    await this.refreshConfig();
+  }
+
+  /**
+   * Run a peer discovery pass now: ask the servers (configured and
+   * discovered; the built-in bootstrap ones only when none answers) for
+   * their peers, probe the candidates, keep the best healthy ones ('auto'
+   * also adds them to the server list).
+   * Needs no open wallet. Throws when discovery is off or there is no
+   * server to ask.
+   */
+  async discoverServers(): Promise<BtcxDiscoveryReport> {
+    const report = await invoke<BtcxDiscoveryReport>('btcx_electrum_discover');
+    await this.refreshConfig();
+    return report;
+  }
+
+  /**
+   * Set the peer discovery mode ('off' forgets discovered servers) and,
+   * when given, whether discovered servers may carry wallet traffic.
+   */
+  async setServerDiscovery(mode: BtcxServerDiscoveryMode, failover?: boolean): Promise<void> {
+    await invoke('btcx_wallet_set_server_discovery', { mode, failover: failover ?? null });
+    await this.refreshConfig();
+  }
+
   /**
    * Probe one Electrum server with a fresh connection: dial, genesis-check
    * the chain, time a tip fetch. Throws with a reason on failure — the
@@ -1309,6 +2429,22 @@ export class BtcxWalletService {
     });
   }
 
+  /**
+   * Re-pin an ssl:// server to the certificate it presents now — after a
+   * "certificate changed" refusal the user confirmed with the operator.
+   * `fingerprint`, when given, must match. Returns the new pin; throws on
+   * failure.
+   */
+  async repinCertificate(url: string, fingerprint?: string): Promise<string> {
+    const pin = await invoke<string>('btcx_electrum_repin_certificate', {
+      url,
+      fingerprint: fingerprint ?? null,
+    });
+    await this.refreshConfig();
+    await this.refreshStatus();
+    return pin;
+  }
+
   /**
    * Chain tip snapshot from the active network's first configured server —
    * the remote-mode replacement for getblockchaininfo. Chain-only: needs no
@@ -12,7 +12,12 @@ export type {
   BtcxWalletTx,
   BtcxWalletTxDto,
   BtcxFeeEstimates,
+  BtcxFeeEstimateMode,
+  BtcxFeeSource,
+  BtcxPolicyCheck,
+  BtcxRejectReason,
   BtcxDescriptorPolicy,
+  BtcxDescriptorExport,
   BtcxWalletConfig,
   BtcxSendRequest,
   BtcxSyncEvent,
@@ -25,3 +30,5 @@ export {
   CLOCK_DRIFT_CRITICAL_MS,
 } from './clock-drift.service';
 export type { ClockDriftReport, ClockDriftStatus, NtpSample } from './clock-drift.service';
+export { ProxyService } from './proxy.service';
+export type { ProxyConfig } from './proxy.service';
@@ -0,0 +1,54 @@
+import { Injectable, inject, signal } from '@angular/core';
+import { ElectronService } from './electron.service';
+
+/**
+ * App-wide SOCKS5 / Tor proxy setting from the Rust backend
+ * (`proxy_config.json`). While enabled, every outbound connection goes
+ * through the proxy or fails; NTP clock checks are off.
+ */
+export interface ProxyConfig {
+  enabled: boolean;
+  host: string;
+  port: number;
+  username?: string;
+  /**
+   * Never sent back by the backend (see `passwordSet`). On save, leave it
+   * out to keep the stored password for the same username; `''` clears it.
+   */
+  password?: string;
+  /** A password is stored (read-only). */
+  passwordSet?: boolean;
+  /** Prefer `.onion` Electrum servers. */
+  torMode: boolean;
+}
+
+/**
+ * Service for reading and changing the proxy setting.
+ */
+@Injectable({ providedIn: 'root' })
+export class ProxyService {
+  private readonly electronService = inject(ElectronService);
+
+  /** Last known setting (null until loaded). */
+  readonly config = signal<ProxyConfig | null>(null);
+
+  /** Load the current setting. */
+  async load(): Promise<ProxyConfig | null> {
+    if (!this.electronService.isTauri) return null;
+    const { invoke } = await import('@tauri-apps/api/core');
+    const config = await invoke<ProxyConfig>('get_proxy_config');
+    this.config.set(config);
+    return config;
+  }
+
+  /**
+   * Save and apply a setting — an open nodeless wallet reconnects through
+   * it. Throws with a reason on an invalid setting.
+   */
+  async save(config: ProxyConfig): Promise<ProxyConfig> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    const saved = await invoke<ProxyConfig>('set_proxy_config', { config });
+    this.config.set(saved);
+    return saved;
+  }
+}
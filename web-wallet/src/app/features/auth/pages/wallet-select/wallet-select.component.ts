@@ -1081,7 +1081,9 @@ export class WalletSelectComponent implements OnInit, OnDestroy {
         ? 'mwallet_kind_taproot'
         : c.policy.kind === 'legacy'
           ? 'mwallet_kind_legacy'
-          : 'mwallet_kind_segwit'
+          : c.policy.kind === 'multisig'
+            ? 'mwallet_kind_multisig'
+            : 'mwallet_kind_segwit'
     );
     return c.policy.coinType === 0 ? `${role} · ${this.i18n.get('wallet_legacy_badge')}` : role;
   }
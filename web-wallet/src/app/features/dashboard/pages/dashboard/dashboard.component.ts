@@ -1812,6 +1812,7 @@ export class DashboardComponent implements AfterViewInit {
       confirmations: tx.confirmations ?? 0,
       timestamp: tx.time ?? null,
       address: tx.address ?? null,
+      label: tx.label ?? null,
     };
   }
 
@@ -12,6 +12,7 @@ import {
   BtcxImportResult,
   BtcxImportValidation,
   BtcxImportErrorCode,
+  BtcxDescriptorPolicy,
 } from '../../../../core/services/btcx-wallet.service';
 import { isInvalidWalletName, isWalletNameTaken, suggestWalletName } from '../../wallet-name';
 import { PageHeaderComponent } from '../../components/page-header/page-header.component';
@@ -20,7 +21,7 @@ import { WalletNameSectionComponent } from '../../components/wallet-name-section
 /** Error code → translated message key (backend `descriptors::ImportError`). */
 const ERROR_KEYS: Record<BtcxImportErrorCode, string> = {
   empty: '',
-  watch_only: 'mwallet_import_watch_only',
+  bare_xpub: 'mwallet_import_bare_key',
   bare_key: 'mwallet_import_bare_key',
   bare_wif: 'mwallet_import_bare_wif',
   wif_not_segwit: 'mwallet_import_wif_not_segwit',
@@ -29,6 +30,8 @@ const ERROR_KEYS: Record<BtcxImportErrorCode, string> = {
   unsupported_type: 'mwallet_import_unsupported',
   not_ranged: 'mwallet_import_not_ranged',
   pair_mismatch: 'mwallet_import_pair_mismatch',
+  mixed_keys: 'mwallet_import_invalid',
+  multisig_shape: 'mwallet_import_invalid',
   multipath_nonstandard: 'mwallet_import_multipath_nonstandard',
   too_many: 'mwallet_import_too_many',
   parse: 'mwallet_import_invalid',
@@ -351,12 +354,14 @@ export class WalletImportDescriptorComponent implements OnInit, OnDestroy {
   });
 
   /** Human label of a script-type classification. */
-  kindLabel(kind: 'bip84' | 'bip86' | 'legacy' | null | undefined): string {
+  kindLabel(kind: BtcxDescriptorPolicy['kind'] | null | undefined): string {
     switch (kind) {
       case 'bip86':
         return this.i18n.get('mwallet_kind_taproot');
       case 'legacy':
         return this.i18n.get('mwallet_kind_legacy');
+      case 'multisig':
+        return this.i18n.get('mwallet_kind_multisig');
       default:
         return this.i18n.get('mwallet_kind_segwit');
     }
@@ -1212,6 +1212,7 @@ export class TransactionListComponent implements OnInit, OnDestroy {
       confirmations: tx.confirmations ?? 0,
       timestamp: tx.time ?? null,
       address: tx.address ?? null,
+      label: tx.label ?? null,
     };
   }
 